@@ -22,29 +22,258 @@ impl MenuAnimation {
         1.0 + 0.03 * (self.t * 1.5).sin()
     }
 
+    // 0..1 on the same beat as scale(), for fading a prompt in and out
+    pub fn pulse(&self) -> f32 {
+        0.5 + 0.5 * (self.t * 1.5).sin()
+    }
+
     // small vertical bob (pixels)
     pub fn bob(&self) -> f32 {
         6.0 * (self.t * 0.7).sin()
     }
 }
 
-// Coin animation helpers
+// Frame of a `num_frames` looping animation played at `fps` frames per unit of `time`
+// (negative times show the first frame).
+pub fn frame_at(time: f32, fps: f32, num_frames: usize) -> usize {
+    ((time * fps).max(0.0) as usize) % num_frames.max(1)
+}
+
+// How an Animation behaves past its last frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AnimMode {
+    // back to the first frame
+    Loop,
+    // stays on the last frame (is_finished() from then on)
+    Once,
+    // runs backwards to the first frame, then forwards again (0 1 2 1 0 1 ...)
+    PingPong,
+}
+
+// A frame-based animation advanced by real time: `t` seconds into it at `fps` frames per
+// second over `frame_count` frames.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Animation {
+    pub frame_count: usize,
+    pub fps: f32,
+    pub mode: AnimMode,
+    pub t: f32,
+}
+
+impl Animation {
+    pub fn new(frame_count: usize, fps: f32, mode: AnimMode) -> Self {
+        Self { frame_count: frame_count.max(1), fps: fps.max(0.0), mode, t: 0.0 }
+    }
+
+    // Frames shown in one cycle: PingPong doesn't repeat its end frames on the way back.
+    fn cycle_frames(&self) -> usize {
+        match self.mode {
+            AnimMode::PingPong if self.frame_count > 1 => 2 * self.frame_count - 2,
+            _ => self.frame_count,
+        }
+    }
+
+    // Seconds in one cycle (the whole animation for Once); infinite when it never advances.
+    pub fn duration(&self) -> f32 {
+        self.cycle_frames() as f32 / self.fps
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        self.t = (self.t + dt.max(0.0)).max(0.0);
+        let duration = self.duration();
+        if !duration.is_finite() {
+            return;
+        }
+        match self.mode {
+            AnimMode::Once => self.t = self.t.min(duration),
+            // wrap so long runs keep their precision
+            AnimMode::Loop | AnimMode::PingPong => self.t %= duration,
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.t = 0.0;
+    }
+
+    pub fn frame(&self) -> usize {
+        let step = (self.t * self.fps) as usize;
+        match self.mode {
+            AnimMode::Loop => step % self.frame_count,
+            AnimMode::Once => step.min(self.frame_count - 1),
+            AnimMode::PingPong => {
+                let i = step % self.cycle_frames();
+                if i < self.frame_count { i } else { self.cycle_frames() - i }
+            }
+        }
+    }
+
+    // How far through the current cycle (0..1); Once reaches 1 when it finishes.
+    pub fn progress(&self) -> f32 {
+        let duration = self.duration();
+        if !duration.is_finite() {
+            return 0.0;
+        }
+        match self.mode {
+            AnimMode::Once => (self.t / duration).min(1.0),
+            AnimMode::Loop | AnimMode::PingPong => (self.t / duration).fract(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.mode == AnimMode::Once && self.progress() >= 1.0
+    }
+}
+
+// The coin spin: 12 frames, one full turn every COIN_SPIN_SECS.
+pub const COIN_FRAMES: usize = 12;
+pub const COIN_SPIN_SECS: f32 = 0.7;
+
+pub fn coin_spin() -> Animation {
+    Animation::new(COIN_FRAMES, COIN_FRAMES as f32 / COIN_SPIN_SECS, AnimMode::Loop)
+}
+
+// Frames per second for a spritesheet without an fps of its own: one full cycle every
+// COIN_SPIN_SECS, like the coin.
+pub fn default_fps(num_frames: usize) -> f32 {
+    num_frames.max(1) as f32 / COIN_SPIN_SECS
+}
+
+// Vertical bob of a coin sprite in pixels, one rise and fall per spin.
+pub fn coin_float_offset(spin: &Animation) -> f32 {
+    8.0 * (spin.progress() * std::f32::consts::TAU).sin()
+}
+
+// Easing curves: each maps progress 0..1 (clamped) to 0..1, starting at 0 and ending at 1
+// (back and elastic overshoot in between).
+pub mod ease {
+    use std::f32::consts::PI;
+
+    pub type Easing = fn(f32) -> f32;
+
+    pub fn linear(t: f32) -> f32 {
+        t.clamp(0.0, 1.0)
+    }
+
+    pub fn in_quad(t: f32) -> f32 {
+        let t = linear(t);
+        t * t
+    }
+
+    pub fn out_quad(t: f32) -> f32 {
+        1.0 - in_quad(1.0 - linear(t))
+    }
+
+    pub fn in_out_quad(t: f32) -> f32 {
+        let t = linear(t);
+        if t < 0.5 { 2.0 * t * t } else { 1.0 - 2.0 * (1.0 - t) * (1.0 - t) }
+    }
+
+    pub fn in_cubic(t: f32) -> f32 {
+        let t = linear(t);
+        t * t * t
+    }
+
+    pub fn out_cubic(t: f32) -> f32 {
+        1.0 - in_cubic(1.0 - linear(t))
+    }
+
+    pub fn in_out_cubic(t: f32) -> f32 {
+        let t = linear(t);
+        if t < 0.5 { 4.0 * t * t * t } else { 1.0 - 4.0 * (1.0 - t).powi(3) }
+    }
+
+    pub fn in_sine(t: f32) -> f32 {
+        1.0 - (linear(t) * PI / 2.0).cos()
+    }
+
+    pub fn out_sine(t: f32) -> f32 {
+        (linear(t) * PI / 2.0).sin()
+    }
+
+    pub fn in_out_sine(t: f32) -> f32 {
+        0.5 - 0.5 * (linear(t) * PI).cos()
+    }
+
+    // pulls back below 0 before heading to 1
+    pub fn in_back(t: f32) -> f32 {
+        const C: f32 = 1.70158;
+        let t = linear(t);
+        t * t * ((C + 1.0) * t - C)
+    }
+
+    // overshoots 1, then settles
+    pub fn out_back(t: f32) -> f32 {
+        1.0 - in_back(1.0 - linear(t))
+    }
+
+    // overshoots and rings around 1 a few times, like a spring
+    pub fn out_elastic(t: f32) -> f32 {
+        let t = linear(t);
+        if t == 0.0 || t == 1.0 {
+            return t;
+        }
+        2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+    }
+}
+
+// A value eased from `from` to `to` over `duration` seconds.
+#[derive(Copy, Clone, Debug)]
+pub struct Tween {
+    pub from: f32,
+    pub to: f32,
+    pub duration: f32,
+    pub easing: ease::Easing,
+    pub t: f32,
+}
+
+impl Tween {
+    pub fn new(from: f32, to: f32, duration: f32, easing: ease::Easing) -> Self {
+        Self { from, to, duration: duration.max(0.0), easing, t: 0.0 }
+    }
+
+    // Already finished, resting at `value`.
+    pub fn settled(value: f32) -> Self {
+        Self::new(value, value, 0.0, ease::linear)
+    }
+
+    // Move `dt` seconds on and return the new value.
+    pub fn advance(&mut self, dt: f32) -> f32 {
+        self.t = (self.t + dt.max(0.0)).min(self.duration);
+        self.value()
+    }
+
+    pub fn progress(&self) -> f32 {
+        if self.duration > 0.0 { self.t / self.duration } else { 1.0 }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.from + (self.to - self.from) * (self.easing)(self.progress())
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.progress() >= 1.0
+    }
+
+    // The same tween played back from `to` to `from`.
+    pub fn reversed(&self) -> Self {
+        Self::new(self.to, self.from, self.duration, self.easing)
+    }
+}
+
+// Coin animation helpers from before Animation; coins now carry a coin_spin().
+#[deprecated(note = "use anim::coin_spin() and anim::coin_float_offset()")]
 pub struct CoinAnimation;
 
+#[allow(deprecated)]
 impl CoinAnimation {
-    // Calculate the current frame for sprite animation (12 frames total)
-    pub fn get_current_frame(animation_time: f32) -> usize {
-        let num_frames = 12;
-        let frame_time = (2.0 * std::f32::consts::PI) / num_frames as f32;
-        ((animation_time / frame_time) as usize) % num_frames
+    pub fn fps(num_frames: usize) -> f32 {
+        default_fps(num_frames)
     }
 
-    // Calculate floating motion offset in pixels
     pub fn get_float_offset(animation_time: f32) -> f32 {
         8.0 * (animation_time * 0.8).sin()
     }
 
-    // Update coin animation time with proper wrapping
     pub fn update_time(current_time: f32, delta: f32) -> f32 {
         let new_time = current_time + delta;
         if new_time > std::f32::consts::TAU {
@@ -53,10 +282,82 @@ impl CoinAnimation {
             new_time
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames_at(anim: &mut Animation, steps: usize, dt: f32) -> Vec<usize> {
+        (0..steps).map(|_| { let f = anim.frame(); anim.advance(dt); f }).collect()
+    }
+
+    #[test]
+    fn loop_wraps_and_once_holds_its_last_frame() {
+        let mut looping = Animation::new(4, 4.0, AnimMode::Loop);
+        assert_eq!(frames_at(&mut looping, 9, 0.25), [0, 1, 2, 3, 0, 1, 2, 3, 0]);
+        looping.advance(1000.125);
+        assert!(looping.t < 1.0 && (looping.progress() - 0.375).abs() < 1e-3);
+
+        let mut once = Animation::new(4, 4.0, AnimMode::Once);
+        assert_eq!(frames_at(&mut once, 3, 0.25), [0, 1, 2]);
+        assert!(!once.is_finished() && once.progress() == 0.75);
+        assert_eq!(frames_at(&mut once, 3, 0.25), [3, 3, 3]);
+        assert!(once.is_finished() && once.progress() == 1.0 && once.t == 1.0);
+        once.restart();
+        assert!(!once.is_finished() && once.frame() == 0);
+    }
+
+    #[test]
+    fn ping_pong_bounces_without_repeating_the_ends() {
+        let mut pp = Animation::new(4, 10.0, AnimMode::PingPong);
+        assert_eq!(frames_at(&mut pp, 10, 0.1), [0, 1, 2, 3, 2, 1, 0, 1, 2, 3]);
+        assert!((pp.duration() - 0.6).abs() < 1e-6);
+        // a single frame never moves, and a stopped animation stays at the start
+        let mut still = Animation::new(1, 10.0, AnimMode::PingPong);
+        assert_eq!(frames_at(&mut still, 3, 0.5), [0, 0, 0]);
+        let mut stopped = Animation::new(6, 0.0, AnimMode::Loop);
+        stopped.advance(3.0);
+        assert_eq!((stopped.frame(), stopped.progress()), (0, 0.0));
+        assert!(!Animation::new(6, 0.0, AnimMode::Once).is_finished());
+    }
+
+    #[test]
+    fn coin_spin_turns_once_per_spin_time() {
+        let mut spin = coin_spin();
+        spin.advance(COIN_SPIN_SECS / 2.0 + 0.01);
+        assert_eq!(spin.frame(), COIN_FRAMES / 2);
+        assert!(coin_float_offset(&spin) < 0.0);
+        spin.advance(COIN_SPIN_SECS / 2.0);
+        assert_eq!(spin.frame(), 0);
+    }
+
+    #[test]
+    fn easings_start_at_zero_end_at_one_and_pin_known_values() {
+        let all: [ease::Easing; 13] = [ease::linear, ease::in_quad, ease::out_quad, ease::in_out_quad, ease::in_cubic, ease::out_cubic, ease::in_out_cubic, ease::in_sine, ease::out_sine, ease::in_out_sine, ease::in_back, ease::out_back, ease::out_elastic];
+        for f in all {
+            assert!(f(0.0).abs() < 1e-6 && (f(1.0) - 1.0).abs() < 1e-6);
+            // clamped outside 0..1
+            assert_eq!((f(-2.0), f(3.0)), (f(0.0), f(1.0)));
+        }
+        let near = |a: f32, b: f32| (a - b).abs() < 1e-4;
+        assert!(near(ease::in_quad(0.5), 0.25) && near(ease::out_quad(0.5), 0.75) && near(ease::in_out_quad(0.25), 0.125));
+        assert!(near(ease::in_cubic(0.5), 0.125) && near(ease::out_cubic(0.5), 0.875) && near(ease::in_out_cubic(0.75), 0.9375));
+        assert!(near(ease::in_out_sine(0.5), 0.5) && near(ease::out_sine(1.0 / 3.0), 0.5) && near(ease::in_sine(2.0 / 3.0), 0.5));
+        assert!(ease::in_back(0.3) < 0.0 && ease::out_back(0.7) > 1.0);
+        assert!(near(ease::out_elastic(0.1), 1.25) && ease::out_elastic(0.5) > 0.98);
+    }
 
-    // Get frame offset for spritesheet sampling
-    pub fn get_frame_offset(animation_time: f32, frame_width: u32) -> u32 {
-        let current_frame = Self::get_current_frame(animation_time);
-        current_frame as u32 * frame_width
+    #[test]
+    fn tweens_ease_between_their_ends_and_stop() {
+        let mut fade = Tween::new(1.0, 0.0, 0.4, ease::in_out_sine);
+        assert_eq!(fade.value(), 1.0);
+        assert!((fade.advance(0.2) - 0.5).abs() < 1e-4);
+        assert_eq!(fade.advance(1.0), 0.0);
+        assert!(fade.is_done() && fade.t == 0.4);
+        let back = fade.reversed();
+        assert_eq!((back.value(), back.is_done()), (0.0, false));
+        let rest = Tween::settled(3.0);
+        assert_eq!((rest.value(), rest.is_done()), (3.0, true));
     }
 }
@@ -1,165 +1,1217 @@
+use std::cell::Cell;
 use std::ffi::CString;
 use std::path::Path;
+use std::time::Instant;
+use raylib::prelude::Vector2;
+use crate::events::GameEvent;
+use crate::player::Player;
+use crate::rng::Rng;
+use crate::settings::AudioSettings;
+use crate::sprite::CoinStreak;
+
+// seconds to crossfade between two music tracks (e.g. moving to the next level)
+const CROSSFADE_SECS: f32 = 1.5;
+
+const SOUNDS_DIR: &str = "sounds";
+const HEARTBEAT_STEM: &str = "heartbeat";
+
+// Audio formats raylib decodes, in preference order: when the same name exists in several
+// formats (coin_sound.ogg and coin_sound.wav) the earlier one is used. Matching is
+// case-insensitive (COIN.WAV counts).
+const AUDIO_EXTENSIONS: [&str; 3] = ["ogg", "wav", "mp3"];
+
+// Preference rank of a file's audio extension, or None when it isn't a supported format.
+fn audio_rank(path: &Path) -> Option<usize> {
+    let ext = path.extension()?.to_str()?;
+    AUDIO_EXTENSIONS.iter().position(|e| e.eq_ignore_ascii_case(ext))
+}
+
+// Audio files in `dir`, one per file stem (the best-ranked format wins). With `stems` only
+// those names are returned, in that order; with an empty list every audio file, by name.
+fn find_audio(dir: &str, stems: &[&str]) -> Vec<String> {
+    let mut best: Vec<(String, usize, String)> = Vec::new(); // (stem, rank, path)
+    let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+    for e in entries.flatten() {
+        let path = e.path();
+        let (Some(rank), Some(stem)) = (audio_rank(&path), path.file_stem().and_then(|s| s.to_str())) else { continue };
+        if !stems.is_empty() && !stems.contains(&stem) {
+            continue;
+        }
+        let path = format!("{}/{}", dir, path.file_name().and_then(|n| n.to_str()).unwrap_or(stem));
+        match best.iter_mut().find(|(s, _, _)| s == stem) {
+            Some(entry) if rank < entry.1 => *entry = (stem.to_string(), rank, path),
+            Some(_) => {}
+            None => best.push((stem.to_string(), rank, path)),
+        }
+    }
+    if stems.is_empty() {
+        best.sort_by(|a, b| a.2.cmp(&b.2));
+    } else {
+        best.sort_by_key(|(s, _, _)| stems.iter().position(|x| x == s));
+    }
+    best.into_iter().map(|(_, _, path)| path).collect()
+}
+
+// A configured audio path (e.g. level metadata music): used as-is when it exists, otherwise
+// the same name in another supported format ("level1.ogg" finds "level1.mp3").
+fn resolve_audio_path(path: &str) -> Option<String> {
+    let p = Path::new(path);
+    if p.exists() {
+        return Some(path.to_string());
+    }
+    let dir = p.parent().and_then(|d| d.to_str()).filter(|d| !d.is_empty()).unwrap_or(".");
+    let stem = p.file_stem()?.to_str()?;
+    find_audio(dir, &[stem]).into_iter().next()
+}
+
+// random ambience one-shots: folder, delay range between them (s) and volume relative to sfx
+const AMBIENT_DIR: &str = "sounds/ambient";
+const AMBIENT_MIN_DELAY: f32 = 20.0;
+const AMBIENT_MAX_DELAY: f32 = 60.0;
+const AMBIENT_GAIN: f32 = 0.35;
+
+// coin pickup pitch: random spread (fraction) and rise per streak step, capped at COIN_STREAK_MAX steps
+const COIN_PITCH_JITTER: f32 = 0.1;
+const COIN_STREAK_STEP: f32 = 0.04;
+const COIN_STREAK_MAX: u32 = 6;
+
+// Pitch for a coin pickup `streak` coins into a quick series, with `jitter` in -0.1..=0.1.
+fn coin_pitch(streak: u32, jitter: f32) -> f32 {
+    (1.0 + COIN_STREAK_STEP * streak.min(COIN_STREAK_MAX) as f32) * (1.0 + jitter)
+}
+
+// positional effects: how far off-center a source fully to one side pans (0..0.5 of raylib's
+// 0..1 range) and how much quieter a source straight behind the listener is
+const PAN_WIDTH: f32 = 0.4;
+const BEHIND_ATTENUATION: f32 = 0.3;
+// the door unlock must stay audible from across the maze
+const DOOR_MIN_GAIN: f32 = 0.4;
+// raylib's centered pan (1.0 = full left, 0.0 = full right)
+const PAN_CENTER: f32 = 0.5;
+
+// (gain, pan) for a sound at `source` heard from `listener` facing angle `facing`.
+// Gain fades linearly to 0 at `hearing_range` and drops by up to BEHIND_ATTENUATION for
+// sources behind; pan uses raylib's convention, so a source on the right gives pan < 0.5.
+fn spatialize(listener: Vector2, facing: f32, source: Vector2, hearing_range: f32) -> (f32, f32) {
+    let (dx, dy) = (source.x - listener.x, source.y - listener.y);
+    let dist = dx.hypot(dy);
+    let gain = (1.0 - dist / hearing_range.max(1.0)).clamp(0.0, 1.0);
+    if dist < 1e-3 {
+        return (gain, PAN_CENTER);
+    }
+    // angle relative to the view; positive = to the right on screen (y grows downwards)
+    let rel = dy.atan2(dx) - facing;
+    let behind = (-rel.cos()).max(0.0);
+    (gain * (1.0 - BEHIND_ATTENUATION * behind), PAN_CENTER - PAN_WIDTH * rel.sin())
+}
+
+// seconds between beats of the heartbeat sample at rate 1; the visual pulse in cues.rs counts
+// beats with it too
+pub const HEARTBEAT_BEAT_SECS: f32 = 1.0;
+
+// Heartbeat loop for a proximity level (0..1): its volume relative to the effects volume, and
+// its playback rate (1 = as recorded, faster as the NPC gets closer). Also drive the visual
+// heartbeat in cues.rs, so the two stay in step.
+pub fn heartbeat_strength(level: f32) -> f32 {
+    0.3 + 0.7 * level.clamp(0.0, 1.0)
+}
+
+pub fn heartbeat_rate(level: f32) -> f32 {
+    1.0 + 0.6 * level.clamp(0.0, 1.0)
+}
+
+// Effect played for a gameplay event (see AudioManager::handle).
+fn cue_for(event: &GameEvent) -> SfxId {
+    match event {
+        GameEvent::CoinCollected(_) => SfxId::Coin,
+        GameEvent::DoorOpened { .. } => SfxId::Door,
+        GameEvent::PlayerEscaped(_) => SfxId::Victory,
+        GameEvent::PlayerDied => SfxId::Hurt,
+        GameEvent::Footstep => SfxId::Footstep,
+        GameEvent::NpcAlerted { .. } => SfxId::Alert,
+        GameEvent::NpcWindup { .. } => SfxId::Growl,
+        GameEvent::NpcStep { .. } => SfxId::NpcStep,
+    }
+}
+
+// Random ambient sample index that differs from `last` whenever there is more than one.
+fn pick_ambient(rng: &mut Rng, count: usize, last: Option<usize>) -> usize {
+    match last {
+        Some(prev) if count > 1 => (prev + 1 + rng.below(count - 1)) % count,
+        _ => rng.below(count),
+    }
+}
+
+// Safe owners for raylib's raw audio handles. All the unsafe FFI lives in these wrappers;
+// dropping them stops/unloads the resource, so early returns and panics can't leak audio.
+
+// Keeps the audio device open; closes it when dropped.
+// Must outlive every MusicTrack/Sfx (AudioManager declares it last so it's dropped last).
+pub struct AudioDevice(());
+
+impl AudioDevice {
+    // None when no audio device could be opened (headless machines, CI)
+    pub fn init() -> Option<Self> {
+        unsafe {
+            raylib::ffi::InitAudioDevice();
+            if raylib::ffi::IsAudioDeviceReady() { Some(AudioDevice(())) } else { None }
+        }
+    }
+}
+
+impl AudioDevice {
+    pub fn set_master_volume(&self, volume: f32) {
+        unsafe { raylib::ffi::SetMasterVolume(volume); }
+    }
+}
+
+impl Drop for AudioDevice {
+    fn drop(&mut self) {
+        unsafe { raylib::ffi::CloseAudioDevice(); }
+    }
+}
+
+// A streamed music file; stopped and unloaded on drop.
+pub struct MusicTrack {
+    raw: raylib::ffi::Music,
+}
+
+impl MusicTrack {
+    pub fn load(path: &str) -> Option<Self> {
+        let Ok(cpath) = CString::new(path.to_string()) else {
+            eprintln!("[warn] invalid music path: {}", path);
+            return None;
+        };
+        unsafe {
+            let raw = raylib::ffi::LoadMusicStream(cpath.as_ptr());
+            if raylib::ffi::IsMusicValid(raw) {
+                Some(Self { raw })
+            } else {
+                eprintln!("[warn] failed to load music: {}", path);
+                None
+            }
+        }
+    }
+
+    pub fn play(&self) {
+        unsafe { raylib::ffi::PlayMusicStream(self.raw); }
+    }
+
+    pub fn set_volume(&self, volume: f32) {
+        unsafe { raylib::ffi::SetMusicVolume(self.raw, volume); }
+    }
+
+    pub fn set_pitch(&self, pitch: f32) {
+        unsafe { raylib::ffi::SetMusicPitch(self.raw, pitch); }
+    }
+
+    pub fn pause(&self) {
+        unsafe { raylib::ffi::PauseMusicStream(self.raw); }
+    }
+
+    pub fn resume(&self) {
+        unsafe { raylib::ffi::ResumeMusicStream(self.raw); }
+    }
+
+    // raylib loops streams by default; playlist tracks turn it off to detect their end
+    pub fn set_looping(&mut self, looping: bool) {
+        self.raw.looping = looping;
+    }
+
+    pub fn restart(&self) {
+        unsafe {
+            raylib::ffi::SeekMusicStream(self.raw, 0.0);
+            raylib::ffi::PlayMusicStream(self.raw);
+        }
+    }
+
+    // false once a non-looping stream reached its end (and while hard-paused)
+    pub fn is_playing(&self) -> bool {
+        unsafe { raylib::ffi::IsMusicStreamPlaying(self.raw) }
+    }
+
+    // seconds left until the end of the track
+    pub fn time_remaining(&self) -> f32 {
+        unsafe { raylib::ffi::GetMusicTimeLength(self.raw) - raylib::ffi::GetMusicTimePlayed(self.raw) }
+    }
+
+    pub fn length(&self) -> f32 {
+        unsafe { raylib::ffi::GetMusicTimeLength(self.raw) }
+    }
+
+    // feed the stream buffer; call once per frame
+    pub fn update(&self) {
+        unsafe { raylib::ffi::UpdateMusicStream(self.raw); }
+    }
+}
+
+impl Drop for MusicTrack {
+    fn drop(&mut self) {
+        unsafe {
+            raylib::ffi::StopMusicStream(self.raw);
+            raylib::ffi::UnloadMusicStream(self.raw);
+        }
+    }
+}
+
+// A fully loaded sound effect; unloaded on drop.
+pub struct Sfx {
+    raw: raylib::ffi::Sound,
+}
+
+impl Sfx {
+    pub fn load(path: &str) -> Option<Self> {
+        let Ok(cpath) = CString::new(path.to_string()) else {
+            eprintln!("[warn] invalid sound path: {}", path);
+            return None;
+        };
+        unsafe {
+            let raw = raylib::ffi::LoadSound(cpath.as_ptr());
+            if raylib::ffi::IsSoundValid(raw) {
+                Some(Self { raw })
+            } else {
+                eprintln!("[warn] failed to load sound: {}", path);
+                None
+            }
+        }
+    }
+
+    pub fn play(&self) {
+        unsafe { raylib::ffi::PlaySound(self.raw); }
+    }
+
+    pub fn set_volume(&self, volume: f32) {
+        unsafe { raylib::ffi::SetSoundVolume(self.raw, volume); }
+    }
+}
+
+impl Drop for Sfx {
+    fn drop(&mut self) {
+        unsafe { raylib::ffi::UnloadSound(self.raw); }
+    }
+}
+
+// An alias of a loaded Sfx: shares its sample data but plays independently.
+// Unloaded on drop; must be dropped before the Sfx it was made from.
+struct SfxAlias {
+    raw: raylib::ffi::Sound,
+}
+
+impl SfxAlias {
+    fn new(source: &Sfx) -> Self {
+        Self { raw: unsafe { raylib::ffi::LoadSoundAlias(source.raw) } }
+    }
+
+    fn is_playing(&self) -> bool {
+        unsafe { raylib::ffi::IsSoundPlaying(self.raw) }
+    }
+
+    fn play_at(&self, volume: f32, pitch: f32, pan: f32) {
+        unsafe {
+            raylib::ffi::SetSoundVolume(self.raw, volume);
+            raylib::ffi::SetSoundPitch(self.raw, pitch);
+            raylib::ffi::SetSoundPan(self.raw, pan);
+            raylib::ffi::PlaySound(self.raw);
+        }
+    }
+}
+
+impl Drop for SfxAlias {
+    fn drop(&mut self) {
+        unsafe { raylib::ffi::UnloadSoundAlias(self.raw); }
+    }
+}
+
+// A few voices of the same effect so rapid repeats overlap instead of restarting
+// (PlaySound on a single handle cuts off the previous play).
+pub struct SfxPool {
+    // declared before `source`: aliases must be unloaded before the sound they share data with
+    voices: Vec<SfxAlias>,
+    source: Sfx,
+    // round-robin position, used when every voice is busy (steals the oldest)
+    next: Cell<usize>,
+}
+
+impl SfxPool {
+    pub fn load(path: &str, voices: usize) -> Option<Self> {
+        let source = Sfx::load(path)?;
+        let voices = (0..voices.max(1)).map(|_| SfxAlias::new(&source)).collect();
+        Some(Self { voices, source, next: Cell::new(0) })
+    }
+
+    // play on the first idle voice, or restart the oldest one when all are busy;
+    // each voice keeps its own pitch and pan, so overlapping plays can differ
+    pub fn play(&self, volume: f32, pitch: f32, pan: f32) {
+        let n = self.voices.len();
+        let start = self.next.get();
+        let slot = (0..n).map(|i| (start + i) % n).find(|&i| !self.voices[i].is_playing()).unwrap_or(start);
+        self.voices[slot].play_at(volume, pitch, pan);
+        self.next.set((slot + 1) % n);
+    }
+}
+
+// Pooled one-shot effects, addressed by id.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SfxId {
+    Coin,
+    Door,
+    Victory,
+    Footstep,
+    Alert,
+    Growl,
+    Hurt,
+    NpcStep,
+}
+
+impl SfxId {
+    pub const ALL: [SfxId; 8] = [SfxId::Coin, SfxId::Door, SfxId::Victory, SfxId::Footstep, SfxId::Alert, SfxId::Growl, SfxId::Hurt, SfxId::NpcStep];
+
+    // file name in sounds/, without extension (see AUDIO_EXTENSIONS)
+    fn stem(self) -> &'static str {
+        match self {
+            SfxId::Coin => "coin_sound",
+            SfxId::Door => "door_open",
+            SfxId::Victory => "victory",
+            SfxId::Footstep => "footstep",
+            SfxId::Alert => "npc_alert",
+            SfxId::Growl => "npc_growl",
+            SfxId::NpcStep => "npc_step",
+            SfxId::Hurt => "hurt",
+        }
+    }
+
+    // file used when this effect's own is missing (NPC steps reuse the growl)
+    fn fallback_stem(self) -> Option<&'static str> {
+        match self {
+            SfxId::NpcStep => Some("npc_growl"),
+            _ => None,
+        }
+    }
+
+    // simultaneous voices; effects that repeat quickly get more
+    fn voices(self) -> usize {
+        match self {
+            SfxId::Coin | SfxId::Footstep => 4,
+            SfxId::Alert | SfxId::Growl => 2,
+            // matches the per-update cap on NPC footsteps (see sprite::update_npcs)
+            SfxId::NpcStep => 3,
+            SfxId::Door | SfxId::Victory | SfxId::Hurt => 1,
+        }
+    }
+}
+
+// What pause_music() does to the music: hard-pause the stream, or keep it playing quietly.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PauseMode {
+    Pause,
+    Duck,
+}
+
+impl PauseMode {
+    // fraction of the music volume kept while ducked
+    pub const DUCK_LEVEL: f32 = 0.3;
+
+    pub fn code(self) -> &'static str {
+        match self {
+            PauseMode::Pause => "pause",
+            PauseMode::Duck => "duck",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<PauseMode> {
+        match code.trim().to_lowercase().as_str() {
+            "pause" => Some(PauseMode::Pause),
+            "duck" => Some(PauseMode::Duck),
+            _ => None,
+        }
+    }
+}
+
+// What happens when the current music track ends.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MusicEndMode {
+    // start the same track again (menu, level-specific tracks)
+    Loop,
+    // crossfade into the next file from the sounds folder (default gameplay music)
+    Playlist,
+}
 
 pub struct AudioManager {
-    initialized: bool,
-    music: Option<raylib::ffi::Music>,
-    coin_sound: Option<raylib::ffi::Sound>,
+    // volumes in 0.0..=1.0; kept here so they can be set before init() and reapplied on load
+    master_volume: f32,
+    music_volume: f32,
+    sfx_volume: f32,
+    pause_mode: PauseMode,
+    // set by pause_music(); in Duck mode the stream keeps playing at DUCK_LEVEL volume
+    music_paused: bool,
+    // no audio device (or --no-audio): every method is a cheap no-op
+    disabled: bool,
+    // when muted the device volume is 0; master_volume keeps the value to restore
+    muted: bool,
+    music: Option<MusicTrack>,
+    music_path: Option<String>,
+    end_mode: MusicEndMode,
+    // gameplay playlist, scanned from disk when playlist mode starts rather than every frame
+    playlist: Vec<String>,
+    // previous track fading out while `music` fades in
+    fading_out: Option<MusicTrack>,
+    fade_elapsed: f32,
+    // temporary music ducking for danger cues: fraction of the music volume removed right
+    // now (0 = none) and how fast it recovers, per second
+    duck_level: f32,
+    duck_release_rate: f32,
+    // user setting scaling every duck (0 = music never ducks)
+    duck_scale: f32,
+    last_update: Option<Instant>,
+    // one pool per SfxId (index = position in SfxId::ALL); None when the file is missing
+    sfx: Vec<Option<SfxPool>>,
+    // looping heartbeat layered over the music while an NPC is close
+    heartbeat: Option<MusicTrack>,
+    heartbeat_started: bool,
+    heartbeat_playing: bool,
+    // ambient one-shots from sounds/ambient/, the last one played and seconds until the next
+    ambient: Vec<Sfx>,
+    ambient_enabled: bool,
+    last_ambient: Option<usize>,
+    ambient_timer: Option<f32>,
+    // random pitch variation for pickups, from the run seed
+    pitch_rng: Rng,
+    coin_streak: CoinStreak,
+    // where the player hears from and looks, and the distance at which positional cues fade out
+    listener: Vector2,
+    listener_facing: f32,
+    hearing_range: f32,
+    // (gain, pan) of the loudest NPC alert raised this frame; several alerts on one frame
+    // play a single sting
+    pending_alert: Option<(f32, f32)>,
+    // declared last: fields drop in order, so the device closes after all sounds are unloaded
+    device: Option<AudioDevice>,
 }
 
 impl AudioManager {
     pub fn new() -> Self {
         Self { 
-            initialized: false, 
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            pause_mode: PauseMode::Pause,
+            music_paused: false,
+            disabled: false,
+            muted: false,
             music: None,
-            coin_sound: None,
+            music_path: None,
+            end_mode: MusicEndMode::Loop,
+            playlist: Vec::new(),
+            fading_out: None,
+            fade_elapsed: 0.0,
+            duck_level: 0.0,
+            duck_release_rate: 0.0,
+            duck_scale: 1.0,
+            last_update: None,
+            sfx: Vec::new(),
+            heartbeat: None,
+            heartbeat_started: false,
+            heartbeat_playing: false,
+            ambient: Vec::new(),
+            ambient_enabled: true,
+            last_ambient: None,
+            ambient_timer: None,
+            pitch_rng: Rng::stream(crate::rng::STREAM_SFX_PITCH),
+            coin_streak: CoinStreak::new(),
+            listener: Vector2::zero(),
+            listener_facing: 0.0,
+            hearing_range: 1.0,
+            pending_alert: None,
+            device: None,
         }
     }
 
+    // Turn the manager into a no-op without touching the audio device (--no-audio).
+    pub fn disable(&mut self) {
+        self.cleanup();
+        self.disabled = true;
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.disabled
+    }
+
+    // true once init() opened a device; nothing is loaded or played before that
+    fn active(&self) -> bool {
+        !self.disabled && self.device.is_some()
+    }
+
     pub fn init(&mut self) {
-        if !self.initialized {
-            unsafe { raylib::ffi::InitAudioDevice(); }
-            self.initialized = true;
+        if self.disabled {
+            return;
+        }
+        if self.device.is_none() {
+            let Some(device) = AudioDevice::init() else {
+                eprintln!("[warn] no audio device available, audio disabled");
+                self.disabled = true;
+                return;
+            };
+            device.set_master_volume(self.effective_master_volume());
+            self.device = Some(device);
             
-            // Load coin collection sound
-            self.load_coin_sound();
+            // Load sound effects; only the coin sound is expected, the other cues are
+            // optional and silently skipped if the files aren't there
+            self.sfx = SfxId::ALL.iter().map(|&id| Self::load_sfx(id)).collect();
+            // optional: proximity heartbeat, streamed so it loops seamlessly
+            if let Some(path) = find_audio(SOUNDS_DIR, &[HEARTBEAT_STEM]).first() {
+                self.heartbeat = MusicTrack::load(path);
+            }
+            // sorted by name, so a seed picks the same samples on every machine
+            self.ambient = find_audio(AMBIENT_DIR, &[]).iter().filter_map(|p| Sfx::load(p)).collect();
         }
     }
+    // Every audio file in sounds/ (sorted), plus music.* next to the executable.
+    fn find_music() -> Vec<String> {
+        let mut files = find_audio(SOUNDS_DIR, &[]);
+        files.extend(find_audio(".", &["music"]).into_iter().map(|p| p.trim_start_matches("./").to_string()));
+        files
+    }
 
-    fn find_oggs() -> Vec<String> {
-        let mut oggs = Vec::new();
-        if let Ok(entries) = std::fs::read_dir("sounds") {
-            for e in entries.flatten() {
-                if let Some(name) = e.path().file_name().and_then(|n| n.to_str()) {
-                    if name.to_lowercase().ends_with(".ogg") {
-                        oggs.push(format!("sounds/{}", name));
-                    }
-                }
+    // Music files eligible for the gameplay playlist: every audio file except the sound effects.
+    fn scan_playlist() -> Vec<String> {
+        let is_effect = |path: &String| {
+            let stem = Path::new(path).file_stem().and_then(|s| s.to_str()).unwrap_or("");
+            stem == HEARTBEAT_STEM || SfxId::ALL.iter().any(|id| id.stem() == stem)
+        };
+        Self::find_music().into_iter().filter(|p| !is_effect(p)).collect()
+    }
+
+    // Track after the current one in the playlist (wrapping), or the first when the current
+    // one isn't part of it.
+    fn next_playlist_track(&self) -> Option<String> {
+        let list = &self.playlist;
+        let next = match list.iter().position(|p| Some(p.as_str()) == self.music_path.as_deref()) {
+            Some(i) => (i + 1) % list.len(),
+            None => 0,
+        };
+        list.get(next).cloned()
+    }
+
+    // Restart or advance the current track near its end, depending on end_mode.
+    fn handle_track_end(&mut self) {
+        let Some(m) = &self.music else { return };
+        let ended = !m.is_playing();
+        if self.end_mode == MusicEndMode::Playlist {
+            // start the next track early enough to crossfade into it
+            let lead = CROSSFADE_SECS.min(m.length() / 4.0);
+            if (ended || m.time_remaining() <= lead)
+                && let Some(next) = self.next_playlist_track().filter(|n| Some(n.as_str()) != self.music_path.as_deref())
+                && self.play_first(&[next], !ended)
+            {
+                return;
             }
-            oggs.sort();
         }
-        if Path::new("music.ogg").exists() {
-            oggs.push("music.ogg".to_string());
+        if let Some(m) = &self.music
+            && !m.is_playing()
+        {
+            m.restart();
         }
-        oggs
     }
 
-    fn load_and_play_internal(path: &str) -> Option<raylib::ffi::Music> {
-        unsafe {
-            if let Ok(cpath) = CString::new(path.to_string()) {
-                let m = raylib::ffi::LoadMusicStream(cpath.as_ptr());
-                if raylib::ffi::IsMusicValid(m) {
-                    raylib::ffi::PlayMusicStream(m);
-                    eprintln!("[info] playing music: {}", path);
-                    return Some(m);
-                } else {
-                    eprintln!("[warn] failed to load music: {}", path);
-                }
+    fn load_and_play_internal(&self, path: &str) -> Option<MusicTrack> {
+        let mut m = MusicTrack::load(path)?;
+        m.set_looping(self.end_mode == MusicEndMode::Loop);
+        m.set_volume(self.effective_music_volume());
+        m.play();
+        if self.music_paused && self.pause_mode == PauseMode::Pause {
+            m.pause();
+        }
+        eprintln!("[info] playing music: {}", path);
+        Some(m)
+    }
+
+    // Start the first candidate that loads. With `crossfade` the current track fades out
+    // while the new one fades in; otherwise the current track is replaced right away.
+    // Returns false (keeping the current track) when none of them load.
+    fn play_first(&mut self, candidates: &[String], crossfade: bool) -> bool {
+        if !self.active() {
+            return false;
+        }
+        for path in candidates {
+            if self.music.is_some() && self.music_path.as_deref() == Some(path.as_str()) {
+                // already playing: keep it going instead of restarting
+                return true;
+            }
+            let Some(m) = self.load_and_play_internal(path) else { continue };
+            if crossfade && self.music.is_some() {
+                m.set_volume(0.0);
+                self.fading_out = self.music.take();
+                self.fade_elapsed = 0.0;
             } else {
-                eprintln!("[warn] invalid music path: {}", path);
+                self.fading_out = None;
             }
+            self.music = Some(m);
+            self.music_path = Some(path.clone());
+            return true;
         }
-        None
+        false
     }
 
     pub fn play_menu_track(&mut self) {
-        // NOTE: swapped: menu should play the gameplay track (sounds/game.ogg) per user request
-        let oggs = Self::find_oggs();
-        if Path::new("sounds/game.ogg").exists() {
-            if let Some(m) = Self::load_and_play_internal("sounds/game.ogg") {
-                self.music = Some(m);
-                return;
-            }
-        }
-        // fallback: if there are any oggs, play the first one
-        if !oggs.is_empty() {
-            if let Some(m) = Self::load_and_play_internal(&oggs[0]) {
-                self.music = Some(m);
-            }
+        if !self.active() {
+            return;
         }
+        // NOTE: swapped: menu should play the gameplay track (sounds/game.*) per user request
+        let oggs = Self::find_music();
+        let mut candidates = find_audio(SOUNDS_DIR, &["game"]);
+        // fallback: if there are any music files, play the first one
+        candidates.extend(oggs.into_iter().take(1));
+        self.end_mode = MusicEndMode::Loop;
+        self.play_first(&candidates, false);
     }
 
     pub fn play_game_track(&mut self) {
-        // NOTE: swapped: gameplay should play the menu track (sounds/menu.ogg) per user request
-        let oggs = Self::find_oggs();
-        if Path::new("sounds/menu.ogg").exists() {
-            if let Some(m) = Self::load_and_play_internal("sounds/menu.ogg") {
-                self.music = Some(m);
-                return;
-            }
+        self.play_default_game_track(false);
+    }
+
+    fn play_default_game_track(&mut self, crossfade: bool) {
+        if !self.active() {
+            return;
         }
+        // NOTE: swapped: gameplay should play the menu track (sounds/menu.*) per user request
+        let oggs = Self::find_music();
+        let mut candidates = find_audio(SOUNDS_DIR, &["menu"]);
         // prefer second file if available, else first
         if oggs.len() >= 2 {
-            if let Some(m) = Self::load_and_play_internal(&oggs[1]) {
-                self.music = Some(m);
-                return;
+            candidates.push(oggs[1].clone());
+        } else if oggs.len() == 1 {
+            candidates.push(oggs[0].clone());
+        }
+        self.end_mode = MusicEndMode::Playlist;
+        self.playlist = Self::scan_playlist();
+        self.play_first(&candidates, crossfade);
+    }
+
+    // Crossfade to a specific music file (e.g. from level metadata; any of AUDIO_EXTENSIONS).
+    // A missing or broken file logs a warning and falls back to the default gameplay track.
+    pub fn play_track_path(&mut self, path: &str) {
+        if !self.active() {
+            return;
+        }
+        self.end_mode = MusicEndMode::Loop;
+        let resolved = resolve_audio_path(path);
+        if !resolved.is_some_and(|p| self.play_first(&[p], true)) {
+            eprintln!("[warn] couldn't play level music '{}', using default track", path);
+            self.play_default_game_track(true);
+        }
+    }
+
+    // Music for a level starting: its own track when the metadata names one, else the default.
+    pub fn play_level_track(&mut self, music: Option<&str>) {
+        match music {
+            Some(path) => self.play_track_path(path),
+            None => self.play_default_game_track(true),
+        }
+    }
+
+    pub fn stop_unload(&mut self) {
+        // dropping the tracks stops and unloads them
+        self.music = None;
+        self.music_path = None;
+        self.fading_out = None;
+    }
+
+    // Call once per frame: refills stream buffers and advances any crossfade.
+    pub fn update(&mut self) {
+        if !self.active() {
+            return;
+        }
+        let now = Instant::now();
+        let dt = self.last_update.map(|t| now.duration_since(t).as_secs_f32()).unwrap_or(0.0).min(0.1);
+        self.last_update = Some(now);
+        self.coin_streak.tick(dt);
+        if let Some((gain, pan)) = self.pending_alert.take() {
+            self.play_pool(SfxId::Alert, gain, 1.0, pan);
+            self.duck(0.6 * gain, 1.5);
+        }
+
+        // a hard-paused stream needs no buffer refills
+        let music_running = !(self.music_paused && self.pause_mode == PauseMode::Pause);
+        if music_running {
+            if let Some(m) = &self.music {
+                m.update();
+            }
+            if let Some(old) = &self.fading_out {
+                old.update();
+            }
+            if self.fading_out.is_none() {
+                self.handle_track_end();
             }
         }
-        if oggs.len() == 1 {
-            if let Some(m) = Self::load_and_play_internal(&oggs[0]) {
-                self.music = Some(m);
+        if let Some(old) = &self.fading_out {
+            if music_running {
+                self.fade_elapsed += dt;
+            }
+            let t = (self.fade_elapsed / CROSSFADE_SECS).min(1.0);
+            let volume = self.effective_music_volume();
+            if let Some(m) = &self.music {
+                m.set_volume(volume * t);
             }
+            old.set_volume(volume * (1.0 - t));
+            if t >= 1.0 {
+                self.fading_out = None;
+            }
+        }
+        let was_ducked = self.duck_level > 0.0;
+        self.advance_duck(dt);
+        if was_ducked && self.fading_out.is_none()
+            && let Some(m) = &self.music
+        {
+            m.set_volume(self.effective_music_volume());
+        }
+        if let (Some(h), true) = (&self.heartbeat, self.heartbeat_playing) {
+            h.update();
         }
     }
 
-    pub fn stop_unload(&mut self) {
-        if let Some(m) = self.music.take() {
-            unsafe {
-                raylib::ffi::StopMusicStream(m);
-                raylib::ffi::UnloadMusicStream(m);
+    // Lower the music by `amount` (0..1, a multiplier on the configured volume) and let it
+    // recover over `release_secs`. A weaker request doesn't cut a stronger duck short;
+    // calling it every frame holds the duck.
+    pub fn duck(&mut self, amount: f32, release_secs: f32) {
+        let amount = (amount * self.duck_scale).clamp(0.0, 1.0);
+        if amount >= self.duck_level {
+            self.duck_level = amount;
+            self.duck_release_rate = amount / release_secs.max(0.01);
+        }
+    }
+
+    fn advance_duck(&mut self, dt: f32) {
+        self.duck_level = (self.duck_level - self.duck_release_rate * dt).max(0.0);
+    }
+
+    // Call every gameplay frame (not from menus or while paused): counts down to the next
+    // random ambient sample and plays it quietly. Muting already silences it via the device volume.
+    pub fn tick_ambient(&mut self, dt: f32, rng: &mut Rng) {
+        if !self.active() || !self.ambient_enabled || self.ambient.is_empty() || self.music_paused {
+            return;
+        }
+        let timer = self.ambient_timer.get_or_insert_with(|| rng.range_f32(AMBIENT_MIN_DELAY, AMBIENT_MAX_DELAY));
+        *timer -= dt;
+        if *timer > 0.0 {
+            return;
+        }
+        *timer = rng.range_f32(AMBIENT_MIN_DELAY, AMBIENT_MAX_DELAY);
+        let idx = pick_ambient(rng, self.ambient.len(), self.last_ambient);
+        self.last_ambient = Some(idx);
+        let sample = &self.ambient[idx];
+        sample.set_volume(self.sfx_volume * AMBIENT_GAIN);
+        sample.play();
+    }
+
+    // Danger level from the nearest NPC: 0.0 = safe (heartbeat silent), 1.0 = touching.
+    // Call every frame; the loop is started/resumed only on the 0 -> >0 edge and paused (not
+    // stopped) on the way back, so the sample never restarts mid-beat. Passing 0.0 silences
+    // it immediately (e.g. when a pause or end screen opens).
+    pub fn set_proximity_level(&mut self, level: f32) {
+        let level = level.clamp(0.0, 1.0);
+        if self.heartbeat.is_none() {
+            return;
+        }
+        // keep the music down while the heartbeat is audible
+        self.duck(0.5 * level, 0.75);
+        let Some(h) = &self.heartbeat else { return };
+        if level <= 0.0 {
+            if self.heartbeat_playing {
+                h.pause();
+                self.heartbeat_playing = false;
             }
+            return;
+        }
+        h.set_volume(self.sfx_volume * heartbeat_strength(level));
+        // beats faster as the NPC gets closer
+        h.set_pitch(heartbeat_rate(level));
+        if !self.heartbeat_playing {
+            if self.heartbeat_started {
+                h.resume();
+            } else {
+                h.play();
+                self.heartbeat_started = true;
+            }
+            self.heartbeat_playing = true;
         }
     }
 
-    pub fn update(&self) {
-        if let Some(m) = self.music {
-            unsafe { raylib::ffi::UpdateMusicStream(m); }
-        }
-    }
-
-    fn load_coin_sound(&mut self) {
-        // Try to load the poker chip sound effect
-        let coin_sound_path = "sounds/coin_sound.ogg";
-        if Path::new(coin_sound_path).exists() {
-            unsafe {
-                if let Ok(cpath) = CString::new(coin_sound_path.to_string()) {
-                    let sound = raylib::ffi::LoadSound(cpath.as_ptr());
-                    if raylib::ffi::IsSoundValid(sound) {
-                        self.coin_sound = Some(sound);
-                        eprintln!("[info] loaded coin sound: {}", coin_sound_path);
-                    } else {
-                        eprintln!("[warn] failed to load coin sound: {}", coin_sound_path);
-                    }
-                } else {
-                    eprintln!("[warn] invalid coin sound path: {}", coin_sound_path);
+    fn load_sfx(id: SfxId) -> Option<SfxPool> {
+        let stems: Vec<&str> = std::iter::once(id.stem()).chain(id.fallback_stem()).collect();
+        let Some(path) = find_audio(SOUNDS_DIR, &stems).into_iter().next() else {
+            if id == SfxId::Coin {
+                eprintln!("[warn] coin sound file not found: {}/{}.{{ogg,wav,mp3}}", SOUNDS_DIR, id.stem());
+            }
+            return None;
+        };
+        let pool = SfxPool::load(&path, id.voices());
+        if pool.is_some() {
+            eprintln!("[info] loaded sound: {}", path);
+        }
+        pool
+    }
+
+    // Play a pooled effect at the current sfx volume; overlapping plays don't cut each other off.
+    pub fn play_sfx(&self, id: SfxId) {
+        self.play_pool(id, 1.0, 1.0, PAN_CENTER);
+    }
+
+    // Play a pooled effect from `source` (world position): panned left/right of `player`'s
+    // view and attenuated with distance, quieter from behind (see spatialize).
+    pub fn play_sfx_at(&self, id: SfxId, source: Vector2, player: &Player) {
+        let (gain, pan) = spatialize(player.pos, player.a, source, self.hearing_range);
+        self.play_pool(id, gain, 1.0, pan);
+    }
+
+    // volume scaled by `gain` (0..1, e.g. distance attenuation), with the given pitch and pan
+    fn play_pool(&self, id: SfxId, gain: f32, pitch: f32, pan: f32) {
+        let idx = SfxId::ALL.iter().position(|&i| i == id).unwrap_or(0);
+        if let Some(Some(pool)) = self.sfx.get(idx) {
+            pool.play(self.sfx_volume * gain.clamp(0.0, 1.0), pitch, pan);
+        }
+    }
+
+    // Where positional cues (NPC alerts, doors) are heard from; call each frame before handle().
+    pub fn set_listener(&mut self, player: &Player, hearing_range: f32) {
+        self.listener = player.pos;
+        self.listener_facing = player.a;
+        self.hearing_range = hearing_range.max(1.0);
+    }
+
+    // Turn a gameplay event into sound: main forwards every event of the frame here.
+    pub fn handle(&mut self, event: &GameEvent) {
+        if !self.active() {
+            return;
+        }
+        let cue = cue_for(event);
+        match *event {
+            GameEvent::CoinCollected(_) => {
+                // pitch jittered by up to ±10% so repeats don't sound identical, and raised
+                // a little for each coin of a quick pickup streak
+                let streak = self.coin_streak.collect();
+                let jitter = self.pitch_rng.range_f32(-COIN_PITCH_JITTER, COIN_PITCH_JITTER);
+                self.play_pool(cue, 1.0, coin_pitch(streak, jitter), PAN_CENTER);
+            }
+            GameEvent::PlayerEscaped(_) | GameEvent::PlayerDied => {
+                // silence the heartbeat before the transition/end screen
+                self.set_proximity_level(0.0);
+                self.play_sfx(cue);
+            }
+            GameEvent::NpcAlerted { pos } => {
+                // positional, played from update()
+                let (gain, pan) = spatialize(self.listener, self.listener_facing, pos, self.hearing_range);
+                if self.pending_alert.is_none_or(|(loudest, _)| gain > loudest) {
+                    self.pending_alert = Some((gain, pan));
                 }
             }
-        } else {
-            eprintln!("[warn] coin sound file not found: {}", coin_sound_path);
+            GameEvent::NpcWindup { pos } => {
+                // right next to the player: panned, but not faded with distance
+                let (_, pan) = spatialize(self.listener, self.listener_facing, pos, self.hearing_range);
+                self.play_pool(cue, 1.0, 1.0, pan);
+                self.duck(0.6, 1.0);
+            }
+            GameEvent::DoorOpened { pos } => {
+                // the unlock is heard from anywhere in the level: panned, with a floor on the gain
+                let (gain, pan) = spatialize(self.listener, self.listener_facing, pos, self.hearing_range);
+                self.play_pool(cue, gain.max(DOOR_MIN_GAIN), 1.0, pan);
+            }
+            GameEvent::NpcStep { pos } => {
+                let (gain, pan) = spatialize(self.listener, self.listener_facing, pos, self.hearing_range);
+                self.play_pool(cue, gain, 1.0, pan);
+            }
+            GameEvent::Footstep => self.play_sfx(cue),
         }
     }
 
     pub fn play_coin_sound(&self) {
-        if let Some(sound) = self.coin_sound {
-            unsafe {
-                raylib::ffi::PlaySound(sound);
+        self.play_sfx(SfxId::Coin);
+    }
+
+    // heavy door sound, played once when the last required coin unlocks the doors
+    pub fn play_door_sound(&self) {
+        self.play_sfx(SfxId::Door);
+    }
+
+    // short jingle when the player escapes through an exit
+    pub fn play_victory_jingle(&self) {
+        self.play_sfx(SfxId::Victory);
+    }
+
+    // volume actually sent to the device (0 while muted)
+    fn effective_master_volume(&self) -> f32 {
+        if self.muted { 0.0 } else { self.master_volume }
+    }
+
+    fn apply_master_volume(&self) {
+        if let Some(device) = &self.device {
+            device.set_master_volume(self.effective_master_volume());
+        }
+    }
+
+    // While muted this only updates the volume restored on unmute.
+    pub fn set_master_volume(&mut self, volume: f32) {
+        self.master_volume = volume.clamp(0.0, 1.0);
+        self.apply_master_volume();
+    }
+
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+        self.apply_master_volume();
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.set_muted(!self.muted);
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    pub fn set_music_volume(&mut self, volume: f32) {
+        self.music_volume = volume.clamp(0.0, 1.0);
+        if let Some(m) = &self.music {
+            m.set_volume(self.effective_music_volume());
+        }
+    }
+
+    // music volume after pause ducking and danger ducking
+    fn effective_music_volume(&self) -> f32 {
+        let volume = self.music_volume * (1.0 - self.duck_level);
+        if self.music_paused && self.pause_mode == PauseMode::Duck {
+            volume * PauseMode::DUCK_LEVEL
+        } else {
+            volume
+        }
+    }
+
+    pub fn set_pause_mode(&mut self, mode: PauseMode) {
+        self.pause_mode = mode;
+    }
+
+    // Called when the game pauses (pause key or window focus lost).
+    pub fn pause_music(&mut self) {
+        if self.music_paused {
+            return;
+        }
+        self.music_paused = true;
+        // both tracks during a crossfade (update() rescales the fade volumes while ducked)
+        for m in self.music.iter().chain(self.fading_out.iter()) {
+            match self.pause_mode {
+                PauseMode::Pause => m.pause(),
+                PauseMode::Duck => m.set_volume(self.effective_music_volume()),
+            }
+        }
+    }
+
+    pub fn resume_music(&mut self) {
+        if !self.music_paused {
+            return;
+        }
+        self.music_paused = false;
+        for m in self.music.iter().chain(self.fading_out.iter()) {
+            if self.pause_mode == PauseMode::Pause {
+                m.resume();
             }
         }
+        if let (Some(m), None) = (&self.music, &self.fading_out) {
+            m.set_volume(self.effective_music_volume());
+        }
+    }
+
+    // Take over the [audio] settings (at startup and whenever the options menu changes one).
+    // A disabled manager ignores them.
+    pub fn apply_settings(&mut self, settings: &AudioSettings) {
+        if self.disabled {
+            return;
+        }
+        self.set_master_volume(settings.master_volume);
+        self.set_music_volume(settings.music_volume);
+        self.set_sfx_volume(settings.sfx_volume);
+        self.set_muted(settings.muted);
+        self.set_pause_mode(settings.pause_mode);
+        self.duck_scale = settings.duck_amount.clamp(0.0, 1.0);
+        self.ambient_enabled = settings.ambient;
     }
 
+    // applied the next time an effect is played
+    pub fn set_sfx_volume(&mut self, volume: f32) {
+        self.sfx_volume = volume.clamp(0.0, 1.0);
+    }
+
+    pub fn master_volume(&self) -> f32 {
+        self.master_volume
+    }
+
+    pub fn music_volume(&self) -> f32 {
+        self.music_volume
+    }
+
+    pub fn sfx_volume(&self) -> f32 {
+        self.sfx_volume
+    }
+
+    // Release everything now (music, sounds, then the device). Dropping the manager does the
+    // same, so this is only needed to free audio before the manager goes out of scope.
     pub fn cleanup(&mut self) {
         self.stop_unload();
-        
-        // Unload coin sound
-        if let Some(sound) = self.coin_sound.take() {
-            unsafe {
-                raylib::ffi::UnloadSound(sound);
-            }
+        self.sfx.clear();
+        self.ambient.clear();
+        self.heartbeat = None;
+        self.heartbeat_playing = false;
+        self.device = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_manager_is_a_no_op() {
+        let mut audio = AudioManager::new();
+        audio.disable();
+        audio.set_master_volume(0.5);
+        audio.set_music_volume(2.0);
+        audio.init();
+        assert!(audio.is_disabled());
+        audio.play_menu_track();
+        audio.play_level_track(Some("sounds/level2.ogg"));
+        audio.play_sfx(SfxId::Coin);
+        audio.set_proximity_level(1.0);
+        audio.pause_music();
+        audio.resume_music();
+        audio.toggle_mute();
+        audio.update();
+        audio.cleanup();
+        assert_eq!(audio.music_volume(), 1.0);
+    }
+
+    // Needs a real audio device (and the sounds/ folder): cargo test -- --ignored
+    #[test]
+    #[ignore = "needs an audio device"]
+    fn rapid_sfx_plays_do_not_panic() {
+        let mut audio = AudioManager::new();
+        audio.init();
+        for _ in 0..10 {
+            audio.play_sfx(SfxId::Coin);
+            audio.play_sfx(SfxId::Footstep);
         }
-        
-        if self.initialized {
-            unsafe { raylib::ffi::CloseAudioDevice(); }
-            self.initialized = false;
+        audio.cleanup();
+    }
+
+    #[test]
+    fn ambient_never_repeats_back_to_back() {
+        let mut rng = Rng::new(7);
+        let mut last = None;
+        for _ in 0..200 {
+            let idx = pick_ambient(&mut rng, 3, last);
+            assert!(idx < 3);
+            assert_ne!(Some(idx), last);
+            last = Some(idx);
+        }
+        assert_eq!(pick_ambient(&mut rng, 1, Some(0)), 0);
+    }
+
+    #[test]
+    fn ducking_scales_music_volume_and_releases() {
+        let mut audio = AudioManager::new();
+        audio.set_music_volume(0.8);
+        audio.duck(0.5, 1.0);
+        assert!((audio.effective_music_volume() - 0.4).abs() < 1e-5);
+        // a weaker request doesn't cut a stronger duck short
+        audio.duck(0.2, 1.0);
+        assert!((audio.effective_music_volume() - 0.4).abs() < 1e-5);
+        audio.advance_duck(0.5);
+        assert!((audio.effective_music_volume() - 0.6).abs() < 1e-5);
+        audio.advance_duck(1.0);
+        assert!((audio.effective_music_volume() - 0.8).abs() < 1e-5);
+    }
+
+    #[test]
+    fn coin_pitch_rises_with_streak_and_caps() {
+        assert_eq!(coin_pitch(0, 0.0), 1.0);
+        assert!((coin_pitch(2, 0.0) - 1.08).abs() < 1e-5);
+        assert_eq!(coin_pitch(50, 0.0), coin_pitch(COIN_STREAK_MAX, 0.0));
+        assert!((coin_pitch(0, 0.1) - 1.1).abs() < 1e-5);
+        assert!((coin_pitch(0, -0.1) - 0.9).abs() < 1e-5);
+    }
+
+    #[test]
+    fn events_map_to_their_cues() {
+        let table = [
+            (GameEvent::CoinCollected(0), SfxId::Coin),
+            (GameEvent::DoorOpened { pos: Vector2::zero() }, SfxId::Door),
+            (GameEvent::PlayerEscaped((1, 1)), SfxId::Victory),
+            (GameEvent::PlayerDied, SfxId::Hurt),
+            (GameEvent::Footstep, SfxId::Footstep),
+            (GameEvent::NpcAlerted { pos: Vector2::zero() }, SfxId::Alert),
+            (GameEvent::NpcStep { pos: Vector2::zero() }, SfxId::NpcStep),
+            (GameEvent::NpcWindup { pos: Vector2::zero() }, SfxId::Growl),
+        ];
+        let mut audio = AudioManager::new();
+        audio.disable();
+        for (event, cue) in table {
+            assert_eq!(cue_for(&event), cue, "{:?}", event);
+            // no device: handling is a no-op and must not panic
+            audio.handle(&event);
+        }
+        audio.update();
+    }
+
+    #[test]
+    fn apply_settings_on_a_disabled_manager_is_a_no_op() {
+        let mut audio = AudioManager::new();
+        audio.disable();
+        let settings = AudioSettings { master_volume: 0.2, music_volume: 0.3, sfx_volume: 0.4, muted: true, pause_mode: PauseMode::Duck, duck_amount: 0.0, ambient: false };
+        audio.apply_settings(&settings);
+        assert_eq!((audio.master_volume(), audio.music_volume(), audio.sfx_volume()), (1.0, 1.0, 1.0));
+        assert!(!audio.is_muted());
+        assert!(audio.ambient_enabled);
+        assert_eq!(audio.duck_scale, 1.0);
+    }
+
+    #[test]
+    fn duck_amount_scales_ducking() {
+        let mut audio = AudioManager::new();
+        audio.apply_settings(&AudioSettings { duck_amount: 0.5, ..AudioSettings::default() });
+        audio.duck(0.8, 1.0);
+        assert!((audio.duck_level - 0.4).abs() < 1e-6);
+    }
+
+    #[test]
+    fn sources_pan_to_their_side_and_fade_with_distance() {
+        let at = |x: f32, y: f32| spatialize(Vector2::zero(), 0.0, Vector2::new(x, y), 800.0);
+        // facing +x; +y is to the right on screen
+        let (ahead_gain, ahead_pan) = at(400.0, 0.0);
+        assert!((ahead_gain - 0.5).abs() < 1e-4 && (ahead_pan - PAN_CENTER).abs() < 1e-4);
+        assert!(at(0.0, 400.0).1 < PAN_CENTER, "right side pans right");
+        assert!(at(0.0, -400.0).1 > PAN_CENTER, "left side pans left");
+        // behind is quieter than ahead at the same distance, and out of range is silent
+        let (behind_gain, behind_pan) = at(-400.0, 0.0);
+        assert!((behind_gain - 0.5 * (1.0 - BEHIND_ATTENUATION)).abs() < 1e-4);
+        assert!((behind_pan - PAN_CENTER).abs() < 1e-4);
+        assert_eq!(at(900.0, 0.0).0, 0.0);
+        // turning around swaps the sides
+        let (_, pan) = spatialize(Vector2::zero(), std::f32::consts::PI, Vector2::new(0.0, 400.0), 800.0);
+        assert!(pan > PAN_CENTER);
+    }
+
+    #[test]
+    fn audio_discovery_prefers_ogg_then_wav_then_mp3() {
+        let dir = std::env::temp_dir().join(format!("audio_discovery_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["coin.MP3", "coin.wav", "theme.mp3", "notes.txt", "door.OGG", "door.wav"] {
+            std::fs::write(dir.join(name), b"").unwrap();
         }
+        let d = dir.to_str().unwrap();
+        let name = |p: &String| Path::new(p).file_name().unwrap().to_str().unwrap().to_string();
+        let all: Vec<String> = find_audio(d, &[]).iter().map(name).collect();
+        assert_eq!(all, ["coin.wav", "door.OGG", "theme.mp3"]);
+        let picked: Vec<String> = find_audio(d, &["theme", "coin", "missing"]).iter().map(name).collect();
+        assert_eq!(picked, ["theme.mp3", "coin.wav"]);
+        // a configured .ogg that doesn't exist resolves to the same name in another format
+        let resolved = resolve_audio_path(&format!("{}/theme.ogg", d)).unwrap();
+        assert_eq!(name(&resolved), "theme.mp3");
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
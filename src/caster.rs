@@ -3,7 +3,7 @@
 use raylib::color::Color;
 
 use crate::framebuffer::Framebuffer;
-use crate::maze::Maze;
+use crate::maze::{CellKind, Maze};
 use crate::player::Player;
 
 pub struct Intersect {
@@ -12,6 +12,8 @@ pub struct Intersect {
   pub hit_x: f32,
   pub hit_y: f32,
   pub side: u8, // 0 = vertical (x-side), 1 = horizontal (y-side)
+  pub tex_u: f32, // 0..1 across the wall face, left-to-right as seen from the ray
+  pub steps: u32, // DDA cells stepped through (debug overlay)
 }
 
 pub fn cast_ray(
@@ -34,6 +36,12 @@ pub fn cast_ray(
   let mut map_x = pos_x.floor() as isize;
   let mut map_y = pos_y.floor() as isize;
 
+  // player outside every valid cell (empty maze, out of bounds, NaN position): nothing to hit
+  if !pos_x.is_finite() || !pos_y.is_finite() || map_x < 0 || map_y < 0
+    || (map_y as usize) >= maze.len() || (map_x as usize) >= maze[map_y as usize].len() {
+    return no_hit(player, 0);
+  }
+
   // length of ray from one x or y side to next x or y side
   let delta_dist_x = if ray_dir_x == 0.0 { f32::INFINITY } else { 1.0 / ray_dir_x.abs() };
   let delta_dist_y = if ray_dir_y == 0.0 { f32::INFINITY } else { 1.0 / ray_dir_y.abs() };
@@ -63,7 +71,9 @@ pub fn cast_ray(
   let mut hit = false;
   let mut side = 0; // 0 = hit on x-side (vertical wall), 1 = y-side (horizontal wall)
   let max_steps = 2000; // guard
+  let mut steps = 0;
   for _ in 0..max_steps {
+    steps += 1;
     if side_dist_x < side_dist_y {
       side_dist_x += delta_dist_x;
       map_x += step_x;
@@ -76,10 +86,9 @@ pub fn cast_ray(
 
     if map_y < 0 || map_x < 0 { break; }
     if (map_y as usize) < maze.len() && (map_x as usize) < maze[map_y as usize].len() {
-      // treat 'R' and 'C' as non-blocking so rays pass through
-      // 'G' (door) always stops rays for rendering, but collision is handled separately
-      let cell = maze[map_y as usize][map_x as usize];
-      if cell != ' ' && cell != 'R' && cell != 'C' {
+      // spawns are floor, so rays pass through; doors always stop rays for rendering, but
+      // collision is handled separately
+      if CellKind::of(maze[map_y as usize][map_x as usize]).blocks_sight() {
         hit = true;
         break;
       }
@@ -103,9 +112,104 @@ pub fn cast_ray(
     let hit_y = player.pos.y + distance * ray_dir_y;
 
     let impact = maze[map_y as usize][map_x as usize];
-  return Intersect { distance, impact, hit_x, hit_y, side: side as u8 };
+    let tex_u = face_u(pos_x, pos_y, perp_dist, ray_dir_x, ray_dir_y, side);
+    return Intersect { distance, impact, hit_x, hit_y, side: side as u8, tex_u, steps };
   }
 
-  // fallback: return large distance
-  Intersect { distance: 2000.0, impact: ' ', hit_x: player.pos.x, hit_y: player.pos.y, side: 0 }
+  no_hit(player, steps)
+}
+
+// Texture coordinate u for a wall hit, from the exact hit point in cell units. On an x-side
+// (side 0, vertical wall) u is the y fraction of the hit, on a y-side the x fraction. Faces
+// seen looking west (-x) or south (+y) would run right-to-left on screen, so u is mirrored
+// there and every face reads left-to-right like the texture image.
+fn face_u(pos_x: f32, pos_y: f32, perp_dist: f32, ray_dir_x: f32, ray_dir_y: f32, side: i32) -> f32 {
+  if side == 0 {
+    let frac = (pos_y + perp_dist * ray_dir_y).rem_euclid(1.0);
+    if ray_dir_x < 0.0 { 1.0 - frac } else { frac }
+  } else {
+    let frac = (pos_x + perp_dist * ray_dir_x).rem_euclid(1.0);
+    if ray_dir_y > 0.0 { 1.0 - frac } else { frac }
+  }
+}
+
+// fallback when the ray hits nothing: large distance, empty impact
+fn no_hit(player: &Player, steps: u32) -> Intersect {
+  Intersect { distance: 2000.0, impact: ' ', hit_x: player.pos.x, hit_y: player.pos.y, side: 0, tex_u: 0.0, steps }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use raylib::prelude::Vector2;
+
+  fn player_at(x: f32, y: f32) -> Player {
+    Player { pos: Vector2::new(x, y), a: 0.0, fov: std::f32::consts::PI / 3.0, vel: Vector2::zero() }
+  }
+
+  #[test]
+  fn empty_maze_returns_fallback() {
+    let mut fb = Framebuffer::new(8, 8);
+    let maze: Maze = Vec::new();
+    let hit = cast_ray(&mut fb, &maze, &player_at(150.0, 150.0), 0.0, 100, false, false);
+    assert_eq!(hit.distance, 2000.0);
+    assert_eq!(hit.impact, ' ');
+    assert_eq!(hit.steps, 0);
+  }
+
+  #[test]
+  fn one_by_one_maze_does_not_panic() {
+    let mut fb = Framebuffer::new(8, 8);
+    // player outside the only cell
+    let wall: Maze = vec![vec!['#']];
+    assert_eq!(cast_ray(&mut fb, &wall, &player_at(150.0, 150.0), 0.3, 100, false, false).distance, 2000.0);
+    // player inside an open 1x1 cell: the ray leaves the grid without hitting anything
+    let open: Maze = vec![vec![' ']];
+    assert_eq!(cast_ray(&mut fb, &open, &player_at(50.0, 50.0), 1.0, 100, false, false).impact, ' ');
+    // NaN position
+    assert_eq!(cast_ray(&mut fb, &open, &player_at(f32::NAN, 50.0), 1.0, 100, false, false).distance, 2000.0);
+  }
+
+  #[test]
+  fn tex_u_matches_known_hit_points() {
+    let maze: Maze = vec![
+      "+---+".chars().collect(),
+      "|   |".chars().collect(),
+      "|   |".chars().collect(),
+      "|   |".chars().collect(),
+      "+---+".chars().collect(),
+    ];
+    let mut fb = Framebuffer::new(8, 8);
+    // straight east from y = 2.25 cells: x-side hit a quarter of the way down the face
+    let hit = cast_ray(&mut fb, &maze, &player_at(250.0, 225.0), 0.0, 100, false, false);
+    assert_eq!(hit.side, 0);
+    // one floor cell, then the wall
+    assert_eq!(hit.steps, 2);
+    assert!((hit.tex_u - 0.25).abs() < 1e-4, "tex_u {}", hit.tex_u);
+    // straight west from the same spot: mirrored
+    let hit = cast_ray(&mut fb, &maze, &player_at(250.0, 225.0), std::f32::consts::PI, 100, false, false);
+    assert!((hit.tex_u - 0.75).abs() < 1e-4, "tex_u {}", hit.tex_u);
+  }
+
+  // Stands in for an asymmetric texture (a left-to-right gradient): on every wall face,
+  // a column further right on screen must sample further right in the texture.
+  #[test]
+  fn tex_u_is_not_mirrored_on_any_face() {
+    use std::f32::consts::PI;
+    let maze: Maze = vec![
+      "+---+".chars().collect(),
+      "|   |".chars().collect(),
+      "|   |".chars().collect(),
+      "|   |".chars().collect(),
+      "+---+".chars().collect(),
+    ];
+    let mut fb = Framebuffer::new(8, 8);
+    let player = player_at(250.0, 250.0);
+    // east, south, west, north (screen right = increasing angle)
+    for facing in [0.0, PI / 2.0, PI, -PI / 2.0] {
+      let left = cast_ray(&mut fb, &maze, &player, facing - 0.05, 100, false, false).tex_u;
+      let right = cast_ray(&mut fb, &maze, &player, facing + 0.05, 100, false, false).tex_u;
+      assert!(right > left, "mirrored u facing {}: left {} right {}", facing, left, right);
+    }
+  }
 }
@@ -0,0 +1,224 @@
+// cli.rs - command-line options
+//
+// Flags take their value as the next argument or after '=' (`--level 2`, `--level=2`). The old
+// form `<program> <width> <height>` (two positional integers) still sets the resolution.
+
+use crate::progress::LEVEL_COUNT;
+
+pub const USAGE: &str = "\
+usage: raycaster [options] [<width> <height>]
+
+options:
+  --width N, --height N  window size in pixels (default 1300x900, at least 201 each)
+  --level N              skip the menu and start at level N (1-3)
+  --maze PATH            skip the menu and play a single maze file
+  --render-scale N       window pixels per rendered pixel, 1-8 (default 2; higher is faster)
+  --fullscreen           start in fullscreen (F11 toggles)
+  --no-audio             don't open the audio device
+  --seed N               seed for every random system, to reproduce a run
+  --validate             check the level files (or --maze) and exit
+  --bench [FRAMES]       render FRAMES frames (default 300) along a fixed camera path without
+                         a window, print frame time statistics and exit
+  --bench-json           like --bench, printing the statistics as JSON
+  --no-embedded          ignore the textures built into the binary
+  --pack-textures        write textures/atlas.png + atlas.toml and exit
+  --texture-viewer       browse the loaded textures instead of playing
+  --help                 show this message";
+
+const MIN_WINDOW_SIDE: i32 = 201;
+pub const DEFAULT_BENCH_FRAMES: usize = 300;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CliOptions {
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub level: Option<i32>,
+    pub maze: Option<String>,
+    pub render_scale: Option<u32>,
+    pub fullscreen: bool,
+    pub no_audio: bool,
+    pub seed: Option<u64>,
+    pub validate: bool,
+    // frames to render in benchmark mode
+    pub bench: Option<usize>,
+    pub bench_json: bool,
+    pub no_embedded: bool,
+    pub pack_textures: bool,
+    pub texture_viewer: bool,
+    pub help: bool,
+}
+
+impl CliOptions {
+    // Parse the arguments after the program name.
+    pub fn parse<S: AsRef<str>>(args: &[S]) -> Result<Self, String> {
+        let mut o = Self::default();
+        let mut positional: Vec<&str> = Vec::new();
+        let mut i = 0;
+        while i < args.len() {
+            let arg = args[i].as_ref();
+            i += 1;
+            let Some(flag) = arg.strip_prefix("--") else {
+                positional.push(arg);
+                continue;
+            };
+            let (name, inline) = match flag.split_once('=') {
+                Some((n, v)) => (n, Some(v)),
+                None => (flag, None),
+            };
+            // value for flags that take one: after '=' or the next argument
+            let mut value = || -> Result<&str, String> {
+                if let Some(v) = inline {
+                    return Ok(v);
+                }
+                let v = args.get(i).map(|a| a.as_ref()).ok_or(format!("--{} needs a value", name))?;
+                i += 1;
+                Ok(v)
+            };
+            match name {
+                "width" => o.width = Some(number(name, value()?)?),
+                "height" => o.height = Some(number(name, value()?)?),
+                "level" => o.level = Some(number(name, value()?)?),
+                "maze" => o.maze = Some(value()?.to_string()),
+                "render-scale" => o.render_scale = Some(number(name, value()?)?),
+                "seed" => o.seed = Some(number(name, value()?)?),
+                // the frame count is optional: only a number right after --bench is taken
+                "bench" => {
+                    let frames = match inline {
+                        Some(v) => number(name, v)?,
+                        None => match args.get(i).and_then(|a| a.as_ref().parse::<usize>().ok()) {
+                            Some(n) => {
+                                i += 1;
+                                n
+                            }
+                            None => DEFAULT_BENCH_FRAMES,
+                        },
+                    };
+                    o.bench = Some(frames);
+                }
+                _ if inline.is_some() => return Err(format!("--{} doesn't take a value", name)),
+                "fullscreen" => o.fullscreen = true,
+                "no-audio" => o.no_audio = true,
+                "validate" => o.validate = true,
+                "bench-json" => {
+                    o.bench_json = true;
+                    o.bench.get_or_insert(DEFAULT_BENCH_FRAMES);
+                }
+                "no-embedded" => o.no_embedded = true,
+                "pack-textures" => o.pack_textures = true,
+                "texture-viewer" => o.texture_viewer = true,
+                "help" => o.help = true,
+                _ => return Err(format!("unknown option '{}'", arg)),
+            }
+        }
+        match positional[..] {
+            [] => {}
+            [w, h] => {
+                if o.width.is_some() || o.height.is_some() {
+                    return Err("give the resolution either as <width> <height> or with --width/--height, not both".to_string());
+                }
+                o.width = Some(number("width", w)?);
+                o.height = Some(number("height", h)?);
+            }
+            _ => return Err(format!("unexpected argument(s) {:?}, expected <width> <height>", positional)),
+        }
+        o.check()?;
+        Ok(o)
+    }
+
+    fn check(&self) -> Result<(), String> {
+        if self.level.is_some() && self.maze.is_some() {
+            return Err("--level and --maze can't be used together".to_string());
+        }
+        if let Some(level) = self.level
+            && !(1..=LEVEL_COUNT).contains(&level)
+        {
+            return Err(format!("--level must be 1-{}, got {}", LEVEL_COUNT, level));
+        }
+        for (name, side) in [("width", self.width), ("height", self.height)] {
+            if let Some(px) = side
+                && px < MIN_WINDOW_SIDE
+            {
+                return Err(format!("{} {} is too small, expected at least {}", name, px, MIN_WINDOW_SIDE));
+            }
+        }
+        if let Some(scale) = self.render_scale
+            && !(1..=8).contains(&scale)
+        {
+            return Err(format!("--render-scale must be 1-8, got {}", scale));
+        }
+        if self.bench == Some(0) {
+            return Err("--bench needs at least 1 frame".to_string());
+        }
+        if self.validate && self.bench.is_some() {
+            return Err("--validate and --bench can't be used together".to_string());
+        }
+        Ok(())
+    }
+}
+
+fn number<T: std::str::FromStr>(name: &str, text: &str) -> Result<T, String> {
+    text.parse::<T>().map_err(|_| format!("invalid {} '{}', expected a number", name, text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<CliOptions, String> {
+        CliOptions::parse(&args.split_whitespace().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn no_arguments_gives_defaults() {
+        assert_eq!(parse(""), Ok(CliOptions::default()));
+    }
+
+    #[test]
+    fn legacy_positional_resolution_still_works() {
+        let o = parse("800 600 --seed 7").unwrap();
+        assert_eq!((o.width, o.height, o.seed), (Some(800), Some(600), Some(7)));
+        assert!(parse("800").is_err());
+        assert!(parse("800 600 40").is_err());
+        assert!(parse("800 600 --width 900").is_err());
+        assert!(parse("100 600").is_err());
+    }
+
+    #[test]
+    fn every_value_flag_accepts_both_spellings() {
+        let o = parse("--width 1024 --height=768 --level 2 --render-scale=3 --seed=9").unwrap();
+        assert_eq!((o.width, o.height), (Some(1024), Some(768)));
+        assert_eq!(o.level, Some(2));
+        assert_eq!(o.render_scale, Some(3));
+        assert_eq!(o.seed, Some(9));
+        assert_eq!(parse("--maze levels/test.txt").unwrap().maze.as_deref(), Some("levels/test.txt"));
+        assert_eq!(parse("--maze=a.txt").unwrap().maze.as_deref(), Some("a.txt"));
+    }
+
+    #[test]
+    fn every_switch_is_recognized() {
+        let o = parse("--fullscreen --no-audio --validate --no-embedded --pack-textures --texture-viewer --help").unwrap();
+        assert!(o.fullscreen && o.no_audio && o.validate && o.no_embedded && o.pack_textures && o.texture_viewer && o.help);
+        assert_eq!(parse("--bench").unwrap().bench, Some(DEFAULT_BENCH_FRAMES));
+        assert_eq!(parse("--bench 50 --no-audio").unwrap().bench, Some(50));
+        assert_eq!(parse("--bench=50").unwrap().bench, Some(50));
+        let o = parse("--bench-json --bench 20").unwrap();
+        assert_eq!((o.bench, o.bench_json), (Some(20), true));
+        assert!(parse("--bench 0").is_err());
+        assert!(parse("--fullscreen=yes").is_err());
+    }
+
+    #[test]
+    fn invalid_values_and_combinations_are_errors() {
+        assert!(parse("--level 2 --maze a.txt").unwrap_err().contains("--level and --maze"));
+        assert!(parse("--level 0").is_err());
+        assert!(parse("--level 4").is_err());
+        assert!(parse("--level two").is_err());
+        assert!(parse("--render-scale 0").is_err());
+        assert!(parse("--render-scale 9").is_err());
+        assert!(parse("--seed").unwrap_err().contains("needs a value"));
+        assert!(parse("--seed -1").is_err());
+        assert!(parse("--height 150").is_err());
+        assert!(parse("--validate --bench").is_err());
+        assert!(parse("--frobnicate").unwrap_err().contains("unknown option"));
+    }
+}
@@ -0,0 +1,145 @@
+// clip.rs - clipping shared by the framebuffer drawing helpers
+//
+// Rectangles are in framebuffer pixels and half-open: Rect { x, y, w, h } covers columns
+// x..x+w and rows y..y+h, and may start off-screen. Lines are clipped with Cohen-Sutherland
+// against the pixels of a rectangle, so Bresenham only walks the visible part. A shape that is
+// already inside comes back unchanged.
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, w: i32, h: i32) -> Self {
+        Self { x, y, w, h }
+    }
+
+    pub fn right(&self) -> i32 {
+        self.x.saturating_add(self.w)
+    }
+
+    pub fn bottom(&self) -> i32 {
+        self.y.saturating_add(self.h)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.w <= 0 || self.h <= 0
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    // The overlap of two rectangles, None when they don't overlap.
+    pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+        let (x0, y0) = (self.x.max(other.x), self.y.max(other.y));
+        let (x1, y1) = (self.right().min(other.right()), self.bottom().min(other.bottom()));
+        let r = Rect::new(x0, y0, x1 - x0, y1 - y0);
+        (!r.is_empty()).then_some(r)
+    }
+}
+
+// Cohen-Sutherland outcode bits
+const LEFT: u8 = 1;
+const RIGHT: u8 = 2;
+const TOP: u8 = 4;
+const BOTTOM: u8 = 8;
+
+// The part of the line (x0, y0)-(x1, y1) on the pixels of `bounds`, None when it misses them.
+pub fn clip_line(x0: i32, y0: i32, x1: i32, y1: i32, bounds: &Rect) -> Option<(i32, i32, i32, i32)> {
+    if bounds.is_empty() {
+        return None;
+    }
+    // last pixel column/row inside, inclusive
+    let (xmin, ymin, xmax, ymax) = (bounds.x as f64, bounds.y as f64, (bounds.right() - 1) as f64, (bounds.bottom() - 1) as f64);
+    let code = |x: f64, y: f64| {
+        let mut c = 0;
+        if x < xmin { c |= LEFT } else if x > xmax { c |= RIGHT }
+        if y < ymin { c |= TOP } else if y > ymax { c |= BOTTOM }
+        c
+    };
+    let (mut ax, mut ay, mut bx, mut by) = (x0 as f64, y0 as f64, x1 as f64, y1 as f64);
+    let (mut code_a, mut code_b) = (code(ax, ay), code(bx, by));
+    loop {
+        if code_a | code_b == 0 {
+            return Some((ax.round() as i32, ay.round() as i32, bx.round() as i32, by.round() as i32));
+        }
+        if code_a & code_b != 0 {
+            return None;
+        }
+        // move the endpoint that is outside onto the edge it crosses
+        let out = if code_a != 0 { code_a } else { code_b };
+        let (x, y) = if out & TOP != 0 {
+            (ax + (bx - ax) * (ymin - ay) / (by - ay), ymin)
+        } else if out & BOTTOM != 0 {
+            (ax + (bx - ax) * (ymax - ay) / (by - ay), ymax)
+        } else if out & RIGHT != 0 {
+            (xmax, ay + (by - ay) * (xmax - ax) / (bx - ax))
+        } else {
+            (xmin, ay + (by - ay) * (xmin - ax) / (bx - ax))
+        };
+        if out == code_a {
+            (ax, ay) = (x, y);
+            code_a = code(ax, ay);
+        } else {
+            (bx, by) = (x, y);
+            code_b = code(bx, by);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn rect_intersection() {
+        let screen = Rect::new(0, 0, 100, 50);
+        assert_eq!(Rect::new(-10, 40, 30, 30).intersect(&screen), Some(Rect::new(0, 40, 20, 10)));
+        assert_eq!(Rect::new(100, 0, 5, 5).intersect(&screen), None);
+        assert_eq!(Rect::new(10, 10, 0, 5).intersect(&screen), None);
+        assert!(screen.contains(99, 49) && !screen.contains(100, 0) && !screen.contains(-1, 0));
+    }
+
+    #[test]
+    fn lines_are_trimmed_to_the_edges() {
+        let screen = Rect::new(0, 0, 10, 10);
+        assert_eq!(clip_line(-5, 5, 20, 5, &screen), Some((0, 5, 9, 5)));
+        assert_eq!(clip_line(5, -3, 5, 3, &screen), Some((5, 0, 5, 3)));
+        assert_eq!(clip_line(-5, -5, 15, 15, &screen), Some((0, 0, 9, 9)));
+        // crosses the corner's left and bottom extensions but misses the pixels
+        assert_eq!(clip_line(-3, 8, 2, 13, &screen), None);
+        assert_eq!(clip_line(-5, -5, -1, 20, &screen), None);
+        assert_eq!(clip_line(-4, 0, 0, -4, &screen), None);
+        assert_eq!(clip_line(1, 1, 1, 1, &Rect::default()), None);
+    }
+
+    #[test]
+    fn random_shapes_stay_inside_and_inside_shapes_are_unchanged() {
+        let mut rng = Rng::new(7);
+        let bounds = Rect::new(4, 6, 40, 25);
+        let mut coord = |lo: f32, hi: f32| rng.range_f32(lo, hi) as i32;
+        for _ in 0..2000 {
+            let (x0, y0, x1, y1) = (coord(-100.0, 150.0), coord(-100.0, 150.0), coord(-100.0, 150.0), coord(-100.0, 150.0));
+            if let Some((a, b, c, d)) = clip_line(x0, y0, x1, y1, &bounds) {
+                assert!(bounds.contains(a, b) && bounds.contains(c, d), "{:?} -> {:?}", (x0, y0, x1, y1), (a, b, c, d));
+            }
+            let r = Rect::new(x0, y0, coord(-5.0, 80.0), coord(-5.0, 80.0));
+            if let Some(clipped) = r.intersect(&bounds) {
+                assert!(bounds.contains(clipped.x, clipped.y) && bounds.contains(clipped.right() - 1, clipped.bottom() - 1));
+            }
+            // fully inside: returned as is
+            let (ix0, iy0, ix1, iy1) = (coord(4.0, 44.0), coord(6.0, 31.0), coord(4.0, 44.0), coord(6.0, 31.0));
+            assert_eq!(clip_line(ix0, iy0, ix1, iy1, &bounds), Some((ix0, iy0, ix1, iy1)));
+            let inside = Rect::new(ix0, iy0, (44 - ix0).min(7), (31 - iy0).min(5));
+            if !inside.is_empty() {
+                assert_eq!(inside.intersect(&bounds), Some(inside));
+            }
+        }
+    }
+}
@@ -0,0 +1,372 @@
+// console.rs - quake-style developer console, toggled with the ` / ~ key
+//
+// A line of text is parsed into a Command and executed against the World through the same
+// methods the game uses (teleport checks collision, given coins count like collected ones and
+// fire CoinCollected), so the console can't put a level into a state play couldn't reach,
+// except through noclip and open_doors. Reloading the level and taking a screenshot need the
+// game loop, so those come back as a Reply for main to carry out.
+
+use raylib::prelude::*;
+use crate::events::EventQueue;
+use crate::maze::Maze;
+use crate::rng;
+use crate::world::World;
+
+pub const TOGGLE_KEY: KeyboardKey = KeyboardKey::KEY_GRAVE;
+// lines kept in the scrollback and remembered in the history
+const LOG_LINES: usize = 8;
+const HISTORY_LEN: usize = 32;
+
+// The input line with the history of submitted lines (up/down to recall).
+#[derive(Clone, Debug, Default)]
+pub struct LineEditor {
+    text: String,
+    history: Vec<String>,
+    // index into history while browsing it, None while editing a fresh line
+    history_pos: Option<usize>,
+}
+
+impl LineEditor {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn insert(&mut self, c: char) {
+        // the toggle key types a backtick or tilde on the frame it closes the console
+        if c == '`' || c == '~' || c.is_control() {
+            return;
+        }
+        self.text.push(c);
+    }
+
+    pub fn backspace(&mut self) {
+        self.text.pop();
+    }
+
+    // Take the current line (remembered in the history unless blank).
+    pub fn submit(&mut self) -> String {
+        let line = std::mem::take(&mut self.text);
+        self.history_pos = None;
+        let trimmed = line.trim();
+        if !trimmed.is_empty() && self.history.last().map(String::as_str) != Some(trimmed) {
+            self.history.push(trimmed.to_string());
+            if self.history.len() > HISTORY_LEN {
+                self.history.remove(0);
+            }
+        }
+        line
+    }
+
+    pub fn history_prev(&mut self) {
+        if self.history.is_empty() {
+            return;
+        }
+        let pos = match self.history_pos {
+            Some(p) => p.saturating_sub(1),
+            None => self.history.len() - 1,
+        };
+        self.history_pos = Some(pos);
+        self.text = self.history[pos].clone();
+    }
+
+    pub fn history_next(&mut self) {
+        let Some(pos) = self.history_pos else { return };
+        if pos + 1 < self.history.len() {
+            self.history_pos = Some(pos + 1);
+            self.text = self.history[pos + 1].clone();
+        } else {
+            self.history_pos = None;
+            self.text.clear();
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Help(Option<String>),
+    Teleport(f32, f32),
+    GiveCoins(usize),
+    OpenDoors,
+    Noclip,
+    SetNpcSpeed(f32),
+    SpawnNpc,
+    KillNpcs,
+    Seed,
+    ReloadLevel,
+    Screenshot(Option<String>),
+}
+
+// name, usage, help
+const COMMANDS: [(&str, &str, &str); 11] = [
+    ("help", "help [command]", "list the commands, or explain one"),
+    ("tp", "tp <x> <y>", "teleport to cell (x, y); fractions allowed, 0.5 is a cell's middle"),
+    ("give", "give coins <n>", "collect n coins as if picked up"),
+    ("open_doors", "open_doors", "open the exit doors regardless of coins"),
+    ("noclip", "noclip", "toggle walking through walls"),
+    ("set", "set npc_speed <speed>", "set every NPC's speed (default 6)"),
+    ("spawn_npc", "spawn_npc", "spawn an NPC one cell ahead; it fades in before it can hurt"),
+    ("kill_npcs", "kill_npcs", "defeat every NPC; they fade out and disappear"),
+    ("seed", "seed", "print the random seed of this run (replay with --seed)"),
+    ("reload_level", "reload_level", "start the level over"),
+    ("screenshot", "screenshot [file]", "save the window to a PNG"),
+];
+
+fn usage(name: &str) -> String {
+    let (_, usage, _) = COMMANDS.iter().find(|(n, _, _)| *n == name).expect("command in COMMANDS");
+    format!("usage: {}", usage)
+}
+
+fn number<T: std::str::FromStr>(arg: Option<&str>, name: &str) -> Result<T, String> {
+    arg.and_then(|a| a.parse().ok()).ok_or_else(|| usage(name))
+}
+
+pub fn parse(line: &str) -> Result<Command, String> {
+    let mut words = line.split_whitespace();
+    let Some(name) = words.next() else {
+        return Err(String::new());
+    };
+    let args: Vec<&str> = words.collect();
+    let arg = |i: usize| args.get(i).copied();
+    let cmd = match name {
+        "help" => Command::Help(arg(0).map(str::to_string)),
+        "tp" => Command::Teleport(number(arg(0), name)?, number(arg(1), name)?),
+        "give" if arg(0) == Some("coins") => Command::GiveCoins(number(arg(1), name)?),
+        "give" => return Err(usage(name)),
+        "open_doors" => Command::OpenDoors,
+        "noclip" => Command::Noclip,
+        "set" if arg(0) == Some("npc_speed") => {
+            let speed: f32 = number(arg(1), name)?;
+            if !speed.is_finite() || speed < 0.0 {
+                return Err(usage(name));
+            }
+            Command::SetNpcSpeed(speed)
+        }
+        "set" => return Err(usage(name)),
+        "spawn_npc" => Command::SpawnNpc,
+        "kill_npcs" => Command::KillNpcs,
+        "seed" => Command::Seed,
+        "reload_level" => Command::ReloadLevel,
+        "screenshot" => Command::Screenshot(arg(0).map(str::to_string)),
+        _ => return Err(format!("unknown command '{}' (try help)", name)),
+    };
+    Ok(cmd)
+}
+
+// What running a command asks of the game loop.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Reply {
+    Done(String),
+    ReloadLevel,
+    Screenshot(String),
+}
+
+pub fn execute(cmd: Command, world: &mut World, maze: &Maze, block_size: usize, events: &mut EventQueue) -> Result<Reply, String> {
+    let done = |msg: String| Ok(Reply::Done(msg));
+    match cmd {
+        Command::Help(None) => done(COMMANDS.iter().map(|(name, _, _)| *name).collect::<Vec<_>>().join(" ")),
+        Command::Help(Some(name)) => match COMMANDS.iter().find(|(n, _, _)| *n == name) {
+            Some((_, usage, help)) => done(format!("{} - {}", usage, help)),
+            None => Err(format!("no help for '{}'", name)),
+        },
+        Command::Teleport(x, y) => {
+            let bs = block_size as f32;
+            let pos = Vector2::new(x * bs, y * bs);
+            if world.teleport(maze, block_size, pos) {
+                done(format!("teleported to {:.2} {:.2}", x, y))
+            } else {
+                Err(format!("can't stand at {:.2} {:.2}", x, y))
+            }
+        }
+        Command::GiveCoins(n) => {
+            let given = world.give_coins(n, events);
+            done(format!("gave {} coin(s), {} collected", given, world.total_coins_collected))
+        }
+        Command::OpenDoors => {
+            world.doors.force_open();
+            done("doors open".to_string())
+        }
+        Command::Noclip => {
+            world.noclip = !world.noclip;
+            done(format!("noclip {}", if world.noclip { "on" } else { "off" }))
+        }
+        Command::SetNpcSpeed(speed) => {
+            world.set_npc_speed(speed);
+            done(format!("npc_speed {}", speed))
+        }
+        Command::SpawnNpc => match world.spawn_npc(maze, block_size) {
+            Some(pos) => done(format!("npc at {:.2} {:.2}", pos.x / block_size as f32, pos.y / block_size as f32)),
+            None => Err("no room for an npc ahead".to_string()),
+        },
+        Command::KillNpcs => done(format!("defeated {} npc(s)", world.defeat_npcs())),
+        Command::Seed => done(format!("seed {}", rng::seed())),
+        Command::ReloadLevel => Ok(Reply::ReloadLevel),
+        Command::Screenshot(file) => {
+            let file = file.unwrap_or_else(|| {
+                let secs = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs());
+                format!("screenshot_{}.png", secs)
+            });
+            Ok(Reply::Screenshot(file))
+        }
+    }
+}
+
+// What the framebuffer draws while the console is open.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsoleView {
+    pub lines: Vec<String>,
+    pub input: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Console {
+    open: bool,
+    editor: LineEditor,
+    log: Vec<String>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    pub fn print(&mut self, line: impl Into<String>) {
+        self.log.push(line.into());
+        if self.log.len() > LOG_LINES {
+            self.log.remove(0);
+        }
+    }
+
+    // Feed this frame's typing to the input line; returns a submitted line. ESC closes.
+    pub fn poll(&mut self, window: &mut RaylibHandle) -> Option<String> {
+        while let Some(c) = window.get_char_pressed() {
+            self.editor.insert(c);
+        }
+        let pressed = |key| window.is_key_pressed(key) || window.is_key_pressed_repeat(key);
+        if pressed(KeyboardKey::KEY_BACKSPACE) {
+            self.editor.backspace();
+        }
+        if pressed(KeyboardKey::KEY_UP) {
+            self.editor.history_prev();
+        }
+        if pressed(KeyboardKey::KEY_DOWN) {
+            self.editor.history_next();
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            self.open = false;
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            let line = self.editor.submit();
+            if !line.trim().is_empty() {
+                self.print(format!("> {}", line.trim()));
+                return Some(line);
+            }
+        }
+        None
+    }
+
+    pub fn view(&self) -> Option<ConsoleView> {
+        self.open.then(|| ConsoleView { lines: self.log.clone(), input: self.editor.text().to_string() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::GameEvent;
+    use crate::player::Player;
+
+    fn world_in(rows: &[&str]) -> (World, Maze) {
+        let maze: Maze = rows.iter().map(|r| r.chars().collect()).collect();
+        let spawn = Player { pos: Vector2::new(150.0, 150.0), a: 0.0, fov: 1.0, vel: Vector2::zero() };
+        (World::new(&maze, 100, spawn, None), maze)
+    }
+
+    #[test]
+    fn editor_keeps_a_history_of_submitted_lines() {
+        let mut ed = LineEditor::default();
+        for c in "seed~".chars() {
+            ed.insert(c);
+        }
+        assert_eq!(ed.text(), "seed");
+        assert_eq!(ed.submit(), "seed");
+        "noclip".chars().for_each(|c| ed.insert(c));
+        ed.submit();
+        ed.history_prev();
+        assert_eq!(ed.text(), "noclip");
+        ed.history_prev();
+        ed.history_prev();
+        assert_eq!(ed.text(), "seed");
+        ed.history_next();
+        assert_eq!(ed.text(), "noclip");
+        ed.history_next();
+        assert_eq!(ed.text(), "");
+        ed.insert('x');
+        ed.backspace();
+        assert_eq!(ed.submit(), "");
+    }
+
+    #[test]
+    fn parses_commands_and_rejects_bad_arguments() {
+        assert_eq!(parse("tp 1.5 2"), Ok(Command::Teleport(1.5, 2.0)));
+        assert_eq!(parse("  give coins 3 "), Ok(Command::GiveCoins(3)));
+        assert_eq!(parse("set npc_speed 6"), Ok(Command::SetNpcSpeed(6.0)));
+        assert_eq!(parse("help tp"), Ok(Command::Help(Some("tp".to_string()))));
+        assert_eq!(parse("screenshot"), Ok(Command::Screenshot(None)));
+        assert_eq!(parse("tp 1"), Err("usage: tp <x> <y>".to_string()));
+        assert_eq!(parse("give gold 3"), Err("usage: give coins <n>".to_string()));
+        assert!(parse("set npc_speed -1").is_err());
+        assert!(parse("fly").unwrap_err().contains("unknown command 'fly'"));
+        // every command has help
+        for (name, _, _) in COMMANDS {
+            let (mut world, maze) = world_in(&["+-+", "| |", "+-+"]);
+            let reply = execute(Command::Help(Some(name.to_string())), &mut world, &maze, 100, &mut EventQueue::new());
+            assert!(matches!(reply, Ok(Reply::Done(_))), "{}", name);
+        }
+    }
+
+    #[test]
+    fn commands_go_through_the_world() {
+        let (mut world, maze) = world_in(&["+-----+", "| C  C|", "|  |  |", "+-----+"]);
+        let mut events = EventQueue::new();
+        let mut run = |line: &str, world: &mut World| execute(parse(line).unwrap(), world, &maze, 100, &mut events);
+        assert!(!world.doors_open());
+        assert!(run("give coins 1", &mut world).is_ok());
+        assert_eq!(world.total_coins_collected, 1);
+        run("give coins 5", &mut world).unwrap();
+        assert_eq!(world.total_coins_collected, 2);
+        assert!(world.doors_open());
+
+        let (mut world, maze) = world_in(&["+-----+", "| C  C|", "|  |  |", "+-----+"]);
+        let mut events = EventQueue::new();
+        execute(Command::OpenDoors, &mut world, &maze, 100, &mut events).unwrap();
+        assert!(world.doors_open());
+        // into a wall: refused unless noclip is on
+        assert!(execute(Command::Teleport(3.5, 2.5), &mut world, &maze, 100, &mut events).is_err());
+        assert_eq!(world.player.pos, Vector2::new(150.0, 150.0));
+        execute(Command::Noclip, &mut world, &maze, 100, &mut events).unwrap();
+        assert!(execute(Command::Teleport(3.5, 2.5), &mut world, &maze, 100, &mut events).is_ok());
+        assert_eq!(world.player.pos, Vector2::new(350.0, 250.0));
+        execute(Command::SetNpcSpeed(2.0), &mut world, &maze, 100, &mut events).unwrap();
+        // facing +x from (3.5, 2.5): (4.5, 2.5) is open
+        assert!(execute(Command::SpawnNpc, &mut world, &maze, 100, &mut events).is_ok());
+        assert_eq!(world.npcs.len(), 1);
+        assert_eq!(execute(Command::KillNpcs, &mut world, &maze, 100, &mut events), Ok(Reply::Done("defeated 1 npc(s)".to_string())));
+    }
+
+    #[test]
+    fn given_coins_fire_collection_events() {
+        let (mut world, _) = world_in(&["+----+", "|C C |", "+----+"]);
+        let mut events = EventQueue::new();
+        assert_eq!(world.give_coins(1, &mut events), 1);
+        assert_eq!(events.drain().collect::<Vec<_>>(), vec![GameEvent::CoinCollected(0)]);
+        assert!(world.coins[0].collected && !world.coins[1].collected);
+    }
+}
@@ -0,0 +1,226 @@
+// cues.rs - on-screen stand-ins for the sounds that carry gameplay information
+//
+// For deaf or muted players (settings `hud.visual_cues`). Fed from the same GameEvent stream
+// as the audio manager and the rumble, so a cue shows exactly when its sound plays:
+// - NPC alert sting: an arrow at the screen edge pointing toward the NPC, fading out
+// - door unlock: a door icon at the edge toward the exit, while that exit is out of view
+// - proximity heartbeat: a "lub-dub" pulse of the screen border, at the loop's rate and strength
+// Colors come from the framebuffer's palette (ui::Palette).
+
+use raylib::prelude::*;
+use crate::audio::{heartbeat_rate, heartbeat_strength, HEARTBEAT_BEAT_SECS};
+use crate::clip::Rect;
+use crate::events::GameEvent;
+use crate::framebuffer::Framebuffer;
+use crate::hud::{edge_point, fill_arrow};
+use crate::player::Player;
+use crate::shapes::fill_convex_polygon;
+
+// seconds an alert arrow and the door icon stay up (fading out)
+const ALERT_SECS: f32 = 2.0;
+const DOOR_ICON_SECS: f32 = 4.0;
+// alert arrows shown at once (the oldest goes first)
+const MAX_ALERTS: usize = 4;
+// border thickness in framebuffer pixels and its strongest alpha
+const BORDER_PX: i32 = 6;
+const BORDER_ALPHA: f32 = 170.0;
+// distance of the edge markers from the screen edge
+const EDGE_MARGIN: f32 = 36.0;
+
+// What an event shows on screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cue {
+    // arrow toward an NPC that just spotted the player
+    Alert(Vector2),
+    // icon toward the exit that just unlocked
+    DoorOpened(Vector2),
+}
+
+// The cue for an event, None for the ones with nothing to show (footsteps, pickups...).
+pub fn cue_for(event: &GameEvent) -> Option<Cue> {
+    match *event {
+        GameEvent::NpcAlerted { pos } => Some(Cue::Alert(pos)),
+        GameEvent::DoorOpened { pos } => Some(Cue::DoorOpened(pos)),
+        _ => None,
+    }
+}
+
+pub struct VisualCues {
+    // alert sources and seconds left
+    alerts: Vec<(Vector2, f32)>,
+    door: Option<(Vector2, f32)>,
+    // heartbeat: proximity level and position in the current beat (0..1)
+    level: f32,
+    beat_phase: f32,
+}
+
+impl VisualCues {
+    pub fn new() -> Self {
+        Self { alerts: Vec::new(), door: None, level: 0.0, beat_phase: 0.0 }
+    }
+
+    pub fn handle(&mut self, event: &GameEvent) {
+        match cue_for(event) {
+            Some(Cue::Alert(pos)) => {
+                if self.alerts.len() == MAX_ALERTS {
+                    self.alerts.remove(0);
+                }
+                self.alerts.push((pos, ALERT_SECS));
+            }
+            Some(Cue::DoorOpened(pos)) => self.door = Some((pos, DOOR_ICON_SECS)),
+            None => {}
+        }
+    }
+
+    // Once per frame with the heartbeat's proximity level (as given to the audio manager).
+    pub fn update(&mut self, dt: f32, proximity: f32) {
+        for alert in &mut self.alerts {
+            alert.1 -= dt;
+        }
+        self.alerts.retain(|&(_, left)| left > 0.0);
+        if let Some((_, left)) = &mut self.door {
+            *left -= dt;
+        }
+        self.door = self.door.filter(|&(_, left)| left > 0.0);
+
+        self.level = proximity.clamp(0.0, 1.0);
+        if self.level <= 0.0 {
+            // the next heartbeat starts on a beat
+            self.beat_phase = 0.0;
+        } else {
+            self.beat_phase = (self.beat_phase + dt * heartbeat_rate(self.level) / HEARTBEAT_BEAT_SECS).fract();
+        }
+    }
+
+    // (angle relative to the view, opacity 0..1) of each alert arrow.
+    pub fn alert_arrows(&self, player: &Player) -> Vec<(f32, f32)> {
+        self.alerts.iter().map(|&(pos, left)| (player.relative_angle(pos), left / ALERT_SECS)).collect()
+    }
+
+    // Angle of the door icon relative to the view, while the opened exit is out of view.
+    pub fn door_icon(&self, player: &Player) -> Option<f32> {
+        let (pos, _) = self.door?;
+        let rel = player.relative_angle(pos);
+        (rel.abs() > player.fov / 2.0).then_some(rel)
+    }
+
+    // Border opacity 0..1: a strong beat and a weaker one just after, scaled like the loop's volume.
+    pub fn border_pulse(&self) -> f32 {
+        if self.level <= 0.0 {
+            return 0.0;
+        }
+        let bump = |x: f32| if (0.0..0.15).contains(&x) { 1.0 - x / 0.15 } else { 0.0 };
+        bump(self.beat_phase).max(0.6 * bump(self.beat_phase - 0.3)) * heartbeat_strength(self.level)
+    }
+
+    pub fn draw(&self, fb: &mut Framebuffer, player: &Player) {
+        let palette = fb.palette();
+        let pulse = self.border_pulse();
+        if pulse > 0.0 {
+            let color = Color { a: (BORDER_ALPHA * pulse).round() as u8, ..palette.danger };
+            let (w, h, bounds) = (fb.width as i32, fb.height as i32, fb.bounds());
+            // full-width top and bottom bands, side bands between them (no pixel blended twice)
+            let bands = [
+                Rect::new(0, 0, w, BORDER_PX),
+                Rect::new(0, h - BORDER_PX, w, BORDER_PX),
+                Rect::new(0, BORDER_PX, BORDER_PX, h - 2 * BORDER_PX),
+                Rect::new(w - BORDER_PX, BORDER_PX, BORDER_PX, h - 2 * BORDER_PX),
+            ];
+            for band in bands.iter().filter_map(|b| b.intersect(&bounds)) {
+                for y in band.y..band.bottom() {
+                    for x in band.x..band.right() {
+                        fb.blend_pixel(x, y, color, 1.0);
+                    }
+                }
+            }
+        }
+        for (rel, alpha) in self.alert_arrows(player) {
+            let color = Color { a: (255.0 * alpha).round() as u8, ..palette.danger };
+            fill_arrow(fb, edge_point(fb.width, fb.height, rel, EDGE_MARGIN), rel, 12.0, color);
+        }
+        if let Some(rel) = self.door_icon(player) {
+            // a door: a frame with a darker panel and a knob
+            let (x, y) = edge_point(fb.width, fb.height, rel, EDGE_MARGIN);
+            let rect = |x0: f32, y0: f32, x1: f32, y1: f32| [(x + x0, y + y0), (x + x1, y + y0), (x + x1, y + y1), (x + x0, y + y1)];
+            fill_convex_polygon(fb, &rect(-7.0, -10.0, 7.0, 10.0), palette.objective);
+            fill_convex_polygon(fb, &rect(-4.0, -7.0, 4.0, 10.0), Color::new(20, 20, 24, 200));
+            fill_convex_polygon(fb, &rect(1.0, 0.0, 3.0, 2.0), palette.objective);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn player_facing_east() -> Player {
+        Player { pos: Vector2::new(0.0, 0.0), a: 0.0, fov: PI / 3.0, vel: Vector2::zero() }
+    }
+
+    #[test]
+    fn events_map_to_cues() {
+        let pos = Vector2::new(3.0, 4.0);
+        assert_eq!(cue_for(&GameEvent::NpcAlerted { pos }), Some(Cue::Alert(pos)));
+        assert_eq!(cue_for(&GameEvent::DoorOpened { pos }), Some(Cue::DoorOpened(pos)));
+        assert_eq!(cue_for(&GameEvent::Footstep), None);
+        assert_eq!(cue_for(&GameEvent::NpcStep { pos }), None);
+        assert_eq!(cue_for(&GameEvent::CoinCollected(0)), None);
+    }
+
+    #[test]
+    fn alert_arrows_point_at_the_npc_and_fade_out() {
+        let mut cues = VisualCues::new();
+        let player = player_facing_east();
+        // an NPC straight behind, then one to the right (+y when facing east)
+        cues.handle(&GameEvent::NpcAlerted { pos: Vector2::new(-100.0, 0.0) });
+        cues.update(ALERT_SECS / 2.0, 0.0);
+        cues.handle(&GameEvent::NpcAlerted { pos: Vector2::new(0.0, 100.0) });
+        let arrows = cues.alert_arrows(&player);
+        assert_eq!(arrows.len(), 2);
+        assert!((arrows[0].0.abs() - PI).abs() < 1e-4 && (arrows[0].1 - 0.5).abs() < 1e-4);
+        assert!((arrows[1].0 - PI / 2.0).abs() < 1e-4 && arrows[1].1 == 1.0);
+        cues.update(ALERT_SECS / 2.0, 0.0);
+        assert_eq!(cues.alert_arrows(&player).len(), 1);
+        // a burst keeps only the latest few
+        for _ in 0..10 {
+            cues.handle(&GameEvent::NpcAlerted { pos: Vector2::new(0.0, 100.0) });
+        }
+        assert_eq!(cues.alert_arrows(&player).len(), MAX_ALERTS);
+    }
+
+    #[test]
+    fn door_icon_only_while_the_exit_is_out_of_view() {
+        let mut cues = VisualCues::new();
+        let mut player = player_facing_east();
+        cues.handle(&GameEvent::DoorOpened { pos: Vector2::new(0.0, -500.0) });
+        assert!((cues.door_icon(&player).unwrap() + PI / 2.0).abs() < 1e-4);
+        // turning to face it hides the icon
+        player.a = -PI / 2.0;
+        assert_eq!(cues.door_icon(&player), None);
+        player.a = 0.0;
+        cues.update(DOOR_ICON_SECS, 0.0);
+        assert_eq!(cues.door_icon(&player), None);
+    }
+
+    #[test]
+    fn border_beats_with_the_heartbeat() {
+        let mut cues = VisualCues::new();
+        cues.update(0.5, 0.0);
+        assert_eq!(cues.border_pulse(), 0.0);
+        // close NPC: loud beat right away, quiet between beats, again one beat period later
+        let beat = HEARTBEAT_BEAT_SECS / heartbeat_rate(1.0);
+        cues.update(0.0, 1.0);
+        assert!((cues.border_pulse() - heartbeat_strength(1.0)).abs() < 1e-4);
+        cues.update(beat * 0.6, 1.0);
+        assert_eq!(cues.border_pulse(), 0.0);
+        cues.update(beat * 0.42, 1.0);
+        assert!(cues.border_pulse() > 0.5);
+        // a farther NPC beats slower and fainter
+        let mut far = VisualCues::new();
+        far.update(0.0, 0.2);
+        assert!(far.border_pulse() < heartbeat_strength(1.0) * 0.5);
+        far.update(beat, 0.2);
+        assert!(far.border_pulse() < 0.5 * heartbeat_strength(0.2));
+    }
+}
@@ -0,0 +1,507 @@
+// debug.rs - developer overlays (frame pacing stats), the texture viewer and --bench
+
+use raylib::prelude::*;
+use crate::clip::Rect;
+use crate::framebuffer::Framebuffer;
+use crate::input::MenuInput;
+use crate::maze::{CellKind, Maze};
+use crate::player::Player;
+use crate::profile::{self, ZoneTime};
+use crate::sprite::{NpcState, NPC};
+use crate::textures::{sample_rgba, FilterMode, TextureAtlas};
+
+// number of frames kept in the frame-time history
+pub const FRAME_SAMPLES: usize = 120;
+
+// Fixed-size ring buffer of recent frame times in milliseconds.
+// Backed by an array so recording and drawing never allocate.
+pub struct FrameTimes {
+    samples: [f32; FRAME_SAMPLES],
+    next: usize,
+    count: usize,
+}
+
+impl FrameTimes {
+    pub fn new() -> Self {
+        Self { samples: [0.0; FRAME_SAMPLES], next: 0, count: 0 }
+    }
+
+    pub fn push(&mut self, ms: f32) {
+        self.samples[self.next] = ms;
+        self.next = (self.next + 1) % FRAME_SAMPLES;
+        self.count = (self.count + 1).min(FRAME_SAMPLES);
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    // i-th sample counting from the oldest one kept
+    pub fn get(&self, i: usize) -> f32 {
+        let start = (self.next + FRAME_SAMPLES - self.count) % FRAME_SAMPLES;
+        self.samples[(start + i) % FRAME_SAMPLES]
+    }
+
+    // (min, avg, max) in ms over the kept samples; zeros when empty
+    pub fn stats(&self) -> (f32, f32, f32) {
+        if self.count == 0 {
+            return (0.0, 0.0, 0.0);
+        }
+        let mut min = f32::INFINITY;
+        let mut max = 0.0f32;
+        let mut sum = 0.0;
+        for i in 0..self.count {
+            let ms = self.get(i);
+            min = min.min(ms);
+            max = max.max(ms);
+            sum += ms;
+        }
+        (min, sum / self.count as f32, max)
+    }
+}
+
+// Frame graph scale: a full-height bar is this many ms.
+const GRAPH_MAX_MS: f32 = 50.0;
+// bar colors: under one 60 FPS frame, under two, anything slower
+const GRAPH_OK_MS: f32 = 16.7;
+const GRAPH_SLOW_MS: f32 = 33.0;
+
+// Area of the framebuffer in pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
+}
+
+// Bar color for one frame: green, yellow or red.
+pub fn frame_color(ms: f32) -> Color {
+    if ms < GRAPH_OK_MS {
+        Color::new(120, 220, 120, 255)
+    } else if ms < GRAPH_SLOW_MS {
+        Color::new(235, 210, 80, 255)
+    } else {
+        Color::new(230, 70, 60, 255)
+    }
+}
+
+// Bar height in pixels for a graph `h` pixels tall, proportional to ms up to GRAPH_MAX_MS.
+pub fn bar_height(ms: f32, h: u32) -> u32 {
+    ((ms / GRAPH_MAX_MS).clamp(0.0, 1.0) * h as f32) as u32
+}
+
+// Plot the frame history as a bar graph (one column per frame, newest on the right) inside
+// `rect`, colored by frame_color. Drawn into the framebuffer so screenshots keep it.
+pub fn draw_frame_graph(fb: &mut Framebuffer, frames: &FrameTimes, rect: PixelRect) {
+    let PixelRect { x, y, w, h } = rect;
+    let (x, y, w, h) = (x as i32, y as i32, w as i32, h as i32);
+    // dark background
+    fb.fill_rect(Rect::new(x, y, w, h), Color::new(0, 0, 0, 160));
+    // 16.7 ms (60 FPS) reference line
+    let ref_y = y + h - bar_height(GRAPH_OK_MS, h as u32) as i32;
+    fb.fill_rect(Rect::new(x, ref_y, w, 1), Color::new(90, 90, 90, 255));
+    // bars, right-aligned so the newest frame is always at the right edge
+    let n = frames.len().min(w as usize);
+    let first = frames.len() - n;
+    for i in 0..n {
+        let ms = frames.get(first + i);
+        let bar_h = bar_height(ms, h as u32) as i32;
+        fb.fill_rect(Rect::new(x + w - n as i32 + i as i32, y + h - bar_h, 1, bar_h), frame_color(ms));
+    }
+}
+
+// F3 overlay: what the game loop saw this frame, filled in as the frame is simulated and
+// rendered, then handed to the presenter (Framebuffer::set_debug_stats).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DebugStats {
+    pub pos: Vector2,
+    pub cell: (usize, usize),
+    // view angle in degrees, 0..360 (0 = +x, clockwise on screen)
+    pub angle_deg: f32,
+    pub fps: u32,
+    pub frame_ms: f32,
+    pub rays: u32,
+    pub dda_steps: u64,
+    // physical pixels per window unit (Framebuffer::dpi_scale)
+    pub dpi_scale: f32,
+    // NPCs per state: hunting, chasing, winding up
+    pub npc_states: [usize; 3],
+    pub coins_left: usize,
+    pub seed: u64,
+    // profiler zones of the previous frame (profile::take_frame)
+    pub zones: Vec<ZoneTime>,
+}
+
+impl DebugStats {
+    pub fn set_player(&mut self, player: &Player, block_size: usize) {
+        self.pos = player.pos;
+        let bs = block_size.max(1) as f32;
+        self.cell = ((player.pos.x.max(0.0) / bs) as usize, (player.pos.y.max(0.0) / bs) as usize);
+        self.angle_deg = player.a.to_degrees().rem_euclid(360.0);
+    }
+
+    pub fn count_npcs(&mut self, npcs: &[NPC]) {
+        self.npc_states = [0; 3];
+        for npc in npcs {
+            let slot = match npc.state {
+                NpcState::Hunt => 0,
+                NpcState::Chase => 1,
+                NpcState::Windup => 2,
+            };
+            self.npc_states[slot] += 1;
+        }
+    }
+
+    pub fn avg_dda_steps(&self) -> f32 {
+        if self.rays == 0 { 0.0 } else { self.dda_steps as f32 / self.rays as f32 }
+    }
+
+    // Overlay text, one line per row.
+    pub fn lines(&self) -> Vec<String> {
+        let [hunt, chase, windup] = self.npc_states;
+        vec![
+            format!("pos {:.1}, {:.1}  cell {}, {}", self.pos.x, self.pos.y, self.cell.0, self.cell.1),
+            format!("angle {:.1} deg", self.angle_deg),
+            format!("fps {}  frame {:.2} ms", self.fps, self.frame_ms),
+            format!("rays {}  avg dda steps {:.1}", self.rays, self.avg_dda_steps()),
+            format!("npcs {} (hunt {}, chase {}, windup {})", hunt + chase + windup, hunt, chase, windup),
+            format!("coins left {}", self.coins_left),
+            format!("dpi scale {:.2}", self.dpi_scale),
+            format!("seed {}", self.seed),
+        ]
+        .into_iter()
+        .chain(self.zones.iter().map(|z| format!("{}{} {:.2} ms (self {:.2})", "  ".repeat(z.depth), z.name, z.total_ms, z.self_ms)))
+        .collect()
+    }
+}
+
+// One --bench frame: total and per-stage times in ms.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BenchFrame {
+    pub total: f32,
+    pub walls: f32,
+    pub sprites: f32,
+    pub minimap: f32,
+}
+
+// Summary of a --bench run (all times in ms; stages are means).
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchReport {
+    pub frames: usize,
+    pub mean: f32,
+    pub median: f32,
+    pub p99: f32,
+    pub min: f32,
+    pub max: f32,
+    pub walls: f32,
+    pub sprites: f32,
+    pub minimap: f32,
+    // mean total time per frame of each profiler zone, in first-entered order
+    pub zones: Vec<(&'static str, f32)>,
+}
+
+// Nearest-rank percentile (0..=100) of an ascending slice; 0 when empty.
+fn percentile(sorted: &[f32], p: f32) -> f32 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f32).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+impl BenchReport {
+    pub fn from_frames(frames: &[BenchFrame]) -> Self {
+        let n = frames.len().max(1) as f32;
+        let mean_of = |f: fn(&BenchFrame) -> f32| frames.iter().map(f).sum::<f32>() / n;
+        let mut totals: Vec<f32> = frames.iter().map(|f| f.total).collect();
+        totals.sort_by(f32::total_cmp);
+        Self {
+            frames: frames.len(),
+            mean: mean_of(|f| f.total),
+            median: percentile(&totals, 50.0),
+            p99: percentile(&totals, 99.0),
+            min: totals.first().copied().unwrap_or(0.0),
+            max: totals.last().copied().unwrap_or(0.0),
+            walls: mean_of(|f| f.walls),
+            sprites: mean_of(|f| f.sprites),
+            minimap: mean_of(|f| f.minimap),
+            zones: Vec::new(),
+        }
+    }
+
+    // Average per-frame zone tables (profile::take_frame) over the run's frames.
+    fn set_zones(&mut self, frames: &[Vec<ZoneTime>]) {
+        let mut zones: Vec<(&'static str, f32)> = Vec::new();
+        for zone in frames.iter().flatten() {
+            match zones.iter_mut().find(|(name, _)| *name == zone.name) {
+                Some((_, ms)) => *ms += zone.total_ms as f32,
+                None => zones.push((zone.name, zone.total_ms as f32)),
+            }
+        }
+        let n = frames.len().max(1) as f32;
+        self.zones = zones.into_iter().map(|(name, ms)| (name, ms / n)).collect();
+    }
+
+    pub fn to_text(&self) -> String {
+        format!(
+            "bench: {} frames, mean {:.3} ms ({:.0} FPS), median {:.3} ms, p99 {:.3} ms, min {:.3} ms, max {:.3} ms\n  walls {:.3} ms, sprites {:.3} ms, minimap {:.3} ms",
+            self.frames, self.mean, 1000.0 / self.mean.max(0.001), self.median, self.p99, self.min, self.max, self.walls, self.sprites, self.minimap
+        )
+    }
+
+    // One JSON object (--bench-json), so runs can be diffed or collected by scripts.
+    pub fn to_json(&self) -> String {
+        let zones: Vec<String> = self.zones.iter().map(|(name, ms)| format!("\"{}\": {:.4}", name, ms)).collect();
+        format!(
+            "{{\"frames\": {}, \"mean_ms\": {:.4}, \"median_ms\": {:.4}, \"p99_ms\": {:.4}, \"min_ms\": {:.4}, \"max_ms\": {:.4}, \"stages_ms\": {{\"walls\": {:.4}, \"sprites\": {:.4}, \"minimap\": {:.4}}}, \"zones_ms\": {{{}}}}}",
+            self.frames, self.mean, self.median, self.p99, self.min, self.max, self.walls, self.sprites, self.minimap, zones.join(", ")
+        )
+    }
+}
+
+// Camera path for --bench, the same on every run: `frames` points on a circle around the
+// maze center (radius a third of the smaller side) looking along the direction of travel.
+// Points inside walls move to the nearest floor cell's center, so every frame sees corridors.
+pub fn bench_path(maze: &Maze, block_size: usize, frames: usize) -> Vec<(Vector2, f32)> {
+    let bs = block_size as f32;
+    let rows = maze.len() as f32;
+    let cols = maze.iter().map(|r| r.len()).max().unwrap_or(0) as f32;
+    let center = Vector2::new(cols * bs / 2.0, rows * bs / 2.0);
+    let radius = cols.min(rows) * bs / 3.0;
+    let floor: Vec<Vector2> = maze
+        .iter()
+        .enumerate()
+        .flat_map(|(j, row)| row.iter().enumerate().filter(|(_, c)| CellKind::of(**c).is_walkable(false)).map(move |(i, _)| (i, j)))
+        .map(|(i, j)| Vector2::new((i as f32 + 0.5) * bs, (j as f32 + 0.5) * bs))
+        .collect();
+    (0..frames)
+        .map(|k| {
+            let t = k as f32 / frames.max(1) as f32 * std::f32::consts::TAU;
+            let on_circle = center + Vector2::new(t.cos(), t.sin()) * radius;
+            let (ci, cj) = ((on_circle.x / bs) as usize, (on_circle.y / bs) as usize);
+            let walkable = maze.get(cj).and_then(|r| r.get(ci)).is_some_and(|&c| CellKind::of(c).is_walkable(false));
+            let pos = if walkable {
+                on_circle
+            } else {
+                floor.iter().copied().min_by(|a, b| (*a - on_circle).length().total_cmp(&(*b - on_circle).length())).unwrap_or(on_circle)
+            };
+            // tangent of the circle: the direction of travel
+            (pos, t + std::f32::consts::FRAC_PI_2)
+        })
+        .collect()
+}
+
+// Benchmark (--bench): once every texture has loaded, render `frames` frames with `render`
+// (which returns its stage times for frame index k) without sleeping, timing each one.
+pub fn run_bench(textures: &mut TextureAtlas, frames: usize, mut render: impl FnMut(&TextureAtlas, usize) -> BenchFrame) -> BenchReport {
+    while textures.is_loading() {
+        textures.poll_loaded();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    // the profiler runs during the bench; its zones are averaged into the report
+    profile::set_enabled(true);
+    profile::take_frame();
+    let mut zone_frames = Vec::with_capacity(frames);
+    let samples: Vec<BenchFrame> = (0..frames)
+        .map(|k| {
+            let start = std::time::Instant::now();
+            let mut frame = render(textures, k);
+            frame.total = start.elapsed().as_secs_f32() * 1000.0;
+            zone_frames.push(profile::take_frame());
+            frame
+        })
+        .collect();
+    profile::set_enabled(false);
+    let mut report = BenchReport::from_frames(&samples);
+    report.set_zones(&zone_frames);
+    report
+}
+
+// Largest (w, h) with the aspect of `img_w` x `img_h` that fits in `box_w` x `box_h`.
+fn fit(img_w: u32, img_h: u32, box_w: u32, box_h: u32) -> (u32, u32) {
+    let scale = (box_w as f32 / img_w.max(1) as f32).min(box_h as f32 / img_h.max(1) as f32);
+    (((img_w as f32 * scale) as u32).max(1), ((img_h as f32 * scale) as u32).max(1))
+}
+
+// Texture viewer (--texture-viewer): every texture the atlas knows with its source file, size
+// and memory, and a preview of the selected one over a checkerboard (to show its alpha).
+// Up/down picks a texture, left/right steps through a sprite sheet's frames, Esc/Q leaves.
+pub fn run_texture_viewer(window: &mut RaylibHandle, thread: &RaylibThread, framebuffer: &mut Framebuffer, textures: &mut TextureAtlas) {
+    const CHECKER: u32 = 8;
+    let mut input = MenuInput::new();
+    let mut selected = 0usize;
+    let mut frame = 0u32;
+    while !window.window_should_close() {
+        framebuffer.follow_window(window);
+        textures.poll_loaded();
+        input.poll(window);
+        if input.back || input.quit {
+            return;
+        }
+        let list = textures.inventory();
+        if list.is_empty() {
+            selected = 0;
+        } else {
+            let before = selected;
+            if input.down {
+                selected = (selected + 1) % list.len();
+            }
+            if input.up {
+                selected = (selected + list.len() - 1) % list.len();
+            }
+            selected = selected.min(list.len() - 1);
+            if selected != before {
+                frame = 0;
+            }
+        }
+        let info = list.get(selected);
+        if let Some(info) = info {
+            textures.prepare(&info.id);
+        }
+        let sheet = info.and_then(|i| textures.sheet(&i.id));
+        let frames = sheet.map_or(1, |s| s.frames);
+        if input.right {
+            frame = (frame + 1) % frames;
+        }
+        if input.left {
+            frame = (frame + frames - 1) % frames;
+        }
+        frame = frame.min(frames - 1);
+
+        // preview in the right 60% of the framebuffer
+        framebuffer.clear();
+        let (fb_w, fb_h) = (framebuffer.width, framebuffer.height);
+        let (box_x, box_w, box_h) = (fb_w * 2 / 5, fb_w * 3 / 5 - 8, fb_h - 16);
+        let image = info.and_then(|i| textures.get(&i.id));
+        if let Some(img) = image {
+            let src_w = sheet.map_or(img.w, |s| s.frame_width());
+            let (w, h) = fit(src_w, img.h, box_w, box_h);
+            let (x0, y0) = (box_x + (box_w - w) / 2, 8 + (box_h - h) / 2);
+            framebuffer.blit(Rect::new(x0 as i32, y0 as i32, w as i32, h as i32), |x, y| {
+                let (u, v) = ((x as f32 + 0.5) / w as f32, (y as f32 + 0.5) / h as f32);
+                let texel = match sheet {
+                    Some(s) => s.sample_frame(frame, u, v, FilterMode::Nearest),
+                    None => sample_rgba(img, u, v, FilterMode::Nearest),
+                };
+                let shade = if ((x / CHECKER) + (y / CHECKER)).is_multiple_of(2) { 160.0 } else { 100.0 };
+                let a = texel.a as f32 / 255.0;
+                let over = |c: u8| (c as f32 * a + shade * (1.0 - a)).round() as u8;
+                Color::new(over(texel.r), over(texel.g), over(texel.b), 255)
+            });
+        }
+
+        let screen_w = window.get_screen_width();
+        let screen_h = window.get_screen_height();
+        if let Ok(texture) = window.load_texture_from_image(thread, &framebuffer.color_buffer) {
+            let mut d = window.begin_drawing(thread);
+            let src = Rectangle::new(0.0, 0.0, fb_w as f32, fb_h as f32);
+            let dest = Rectangle::new(0.0, 0.0, screen_w as f32, screen_h as f32);
+            d.draw_texture_pro(&texture, src, dest, Vector2::new(0.0, 0.0), 0.0, Color::WHITE);
+            d.draw_text("Textures", 16, 12, 24, Color::RAYWHITE);
+            for (i, t) in list.iter().enumerate() {
+                let size = if t.frames == 0 { "not decoded".to_string() } else { format!("{}x{}  {} KB", t.w, t.h, t.bytes.div_ceil(1024)) };
+                let frames = if t.frames > 1 { format!("  {} frames", t.frames) } else { String::new() };
+                let color = if i == selected { Color::YELLOW } else { Color::WHITE };
+                d.draw_text(&format!("{:<12} {}{}", t.id.name(), size, frames), 16, 48 + 22 * i as i32, 18, color);
+            }
+            if let Some(info) = info {
+                let y = 56 + 22 * list.len() as i32;
+                d.draw_text(&info.source, 16, y, 16, Color::LIGHTGRAY);
+                if frames > 1 {
+                    d.draw_text(&format!("frame {} / {}", frame + 1, frames), 16, y + 22, 16, Color::LIGHTGRAY);
+                }
+            }
+            d.draw_text("Up/Down: texture   Left/Right: frame   Esc: quit", 16, screen_h - 28, 16, Color::GRAY);
+        }
+        std::thread::sleep(std::time::Duration::from_millis(16));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bench_report_percentiles() {
+        let frames: Vec<BenchFrame> = (1..=100).map(|ms| BenchFrame { total: ms as f32, walls: 2.0, sprites: 1.0, minimap: 0.5 }).collect();
+        let r = BenchReport::from_frames(&frames);
+        assert_eq!((r.frames, r.mean, r.median, r.p99, r.min, r.max), (100, 50.5, 50.0, 99.0, 1.0, 100.0));
+        assert_eq!((r.walls, r.sprites, r.minimap), (2.0, 1.0, 0.5));
+        assert!(r.to_json().starts_with("{\"frames\": 100, \"mean_ms\": 50.5000"));
+        assert_eq!(BenchReport::from_frames(&[]).p99, 0.0);
+    }
+
+    #[test]
+    fn bench_report_averages_profiler_zones() {
+        let zone = |name, total_ms| ZoneTime { name, total_ms, calls: 1, ..Default::default() };
+        let mut r = BenchReport::from_frames(&[BenchFrame::default(); 2]);
+        r.set_zones(&[vec![zone("walls", 3.0), zone("minimap", 1.0)], vec![zone("walls", 5.0)]]);
+        assert_eq!(r.zones, [("walls", 4.0), ("minimap", 0.5)]);
+        assert!(r.to_json().ends_with("\"zones_ms\": {\"walls\": 4.0000, \"minimap\": 0.5000}}"));
+    }
+
+    #[test]
+    fn frame_graph_buckets_and_colors() {
+        assert_eq!(frame_color(5.0), frame_color(16.6));
+        assert_eq!(frame_color(16.7), frame_color(32.9));
+        assert_eq!(frame_color(33.0), frame_color(400.0));
+        assert_ne!(frame_color(10.0), frame_color(20.0));
+        assert_ne!(frame_color(20.0), frame_color(40.0));
+        assert_eq!(bar_height(0.0, 40), 0);
+        assert_eq!(bar_height(25.0, 40), 20);
+        assert_eq!(bar_height(50.0, 40), 40);
+        assert_eq!(bar_height(200.0, 40), 40);
+        assert_eq!(bar_height(-1.0, 40), 0);
+    }
+
+    #[test]
+    fn frame_graph_draws_newest_on_the_right() {
+        let mut frames = FrameTimes::new();
+        for ms in [10.0, 25.0, 45.0] {
+            frames.push(ms);
+        }
+        let mut fb = Framebuffer::new(8, 12);
+        draw_frame_graph(&mut fb, &frames, PixelRect { x: 0, y: 0, w: 8, h: 10 });
+        // bottom row: the three bars fill the last three columns in push order
+        assert_eq!(fb.get_pixel(5, 9), frame_color(10.0));
+        assert_eq!(fb.get_pixel(6, 9), frame_color(25.0));
+        assert_eq!(fb.get_pixel(7, 9), frame_color(45.0));
+        // the 45 ms bar is 9 of 10 px tall, the 10 ms one only 2
+        assert_eq!(fb.get_pixel(7, 1), frame_color(45.0));
+        assert_ne!(fb.get_pixel(5, 7), frame_color(10.0));
+    }
+
+    #[test]
+    fn debug_stats_describe_the_frame() {
+        let mut stats = DebugStats { fps: 60, frame_ms: 16.7, rays: 4, dda_steps: 10, dpi_scale: 2.0, coins_left: 3, seed: 42, ..Default::default() };
+        let player = Player { pos: Vector2::new(250.0, 130.0), a: -std::f32::consts::FRAC_PI_2, fov: 1.0, vel: Vector2::zero() };
+        stats.set_player(&player, 100);
+        assert_eq!(stats.cell, (2, 1));
+        assert!((stats.angle_deg - 270.0).abs() < 1e-3);
+        let mut npcs = vec![NPC::new(0.0, 0.0, 1.0), NPC::new(0.0, 0.0, 1.0), NPC::new(0.0, 0.0, 1.0)];
+        npcs[1].state = NpcState::Chase;
+        npcs[2].state = NpcState::Chase;
+        stats.count_npcs(&npcs);
+        assert_eq!(stats.npc_states, [1, 2, 0]);
+        assert_eq!(stats.avg_dda_steps(), 2.5);
+        let lines = stats.lines();
+        assert_eq!(lines[0], "pos 250.0, 130.0  cell 2, 1");
+        assert_eq!(lines[4], "npcs 3 (hunt 1, chase 2, windup 0)");
+        assert!(lines.contains(&"dpi scale 2.00".to_string()));
+        assert_eq!(lines.last().map(String::as_str), Some("seed 42"));
+        assert_eq!(DebugStats::default().avg_dda_steps(), 0.0);
+    }
+
+    #[test]
+    fn bench_path_is_deterministic_and_stays_on_floor() {
+        let maze: Maze = ["+----+", "|    |", "| ++ |", "|    |", "+----+"].iter().map(|r| r.chars().collect()).collect();
+        let path = bench_path(&maze, 100, 16);
+        assert_eq!(path.len(), 16);
+        assert_eq!(path, bench_path(&maze, 100, 16));
+        for (pos, _) in path {
+            let cell = maze[(pos.y / 100.0) as usize][(pos.x / 100.0) as usize];
+            assert!(CellKind::of(cell).is_walkable(false), "camera inside {:?} at {:?}", cell, pos);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+// diagnostics.rs - startup check for the files the game can't run without
+//
+// Right after the window opens, before the texture atlas or the menu exist, make sure every
+// level file that can be played and at least one of the wall/floor textures can be found. When
+// something is missing, error_screen lists it with every path that was tried (drawn with plain
+// raylib text, since nothing else is loaded yet) until Q is pressed, instead of panicking later.
+
+use std::path::Path;
+use raylib::prelude::*;
+use crate::strings::{tr, trf, Str};
+use crate::textures;
+
+// A required file that wasn't found, and where the game looked for it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MissingAsset {
+    pub what: String,
+    pub looked_in: Vec<String>,
+}
+
+impl MissingAsset {
+    // A level file that couldn't be read (see maze::load_level).
+    pub fn level(file: &str) -> Self {
+        Self { what: trf(Str::MissingLevelFile, &[&file]), looked_in: vec![absolute(file)] }
+    }
+}
+
+// `path` resolved against the working directory, which is where the game reads from.
+fn absolute(path: &str) -> String {
+    match std::env::current_dir() {
+        Ok(cwd) => cwd.join(path).display().to_string(),
+        Err(_) => path.to_string(),
+    }
+}
+
+pub fn check_levels(files: &[String]) -> Vec<MissingAsset> {
+    files.iter().filter(|f| !Path::new(f).is_file()).map(|f| MissingAsset::level(f)).collect()
+}
+
+// The walls need a wall or floor image from disk, a packed atlas, or the built-in copies.
+fn check_wall_textures(candidates: &[String], exists: impl Fn(&str) -> bool, packed_atlas: bool, use_embedded: bool) -> Option<MissingAsset> {
+    if use_embedded || packed_atlas || candidates.iter().any(|c| exists(c)) {
+        return None;
+    }
+    Some(MissingAsset { what: tr(Str::MissingWallTextures).to_string(), looked_in: candidates.iter().map(|c| absolute(c)).collect() })
+}
+
+// Everything missing for a run that plays `level_files`; empty when the game can start.
+pub fn check_startup(level_files: &[String], use_embedded: bool) -> Vec<MissingAsset> {
+    let mut missing = check_levels(level_files);
+    let candidates = textures::wall_and_floor_candidates();
+    missing.extend(check_wall_textures(&candidates, |p| Path::new(p).is_file(), textures::packed_atlas_found(), use_embedded));
+    for asset in &missing {
+        eprintln!("[warn] missing {} (looked in: {})", asset.what, asset.looked_in.join(", "));
+    }
+    missing
+}
+
+// List `missing` until Q is pressed or the window is closed. The game quits afterwards, so the
+// screen just caps itself at 30 FPS instead of going through the frame pacer.
+pub fn error_screen(window: &mut RaylibHandle, thread: &RaylibThread, missing: &[MissingAsset]) {
+    window.set_target_fps(30);
+    while !window.window_should_close() && !window.is_key_pressed(KeyboardKey::KEY_Q) {
+        let mut d = window.begin_drawing(thread);
+        d.clear_background(Color::new(20, 16, 24, 255));
+        let (x, mut y) = (40, 40);
+        d.draw_text(tr(Str::StartupFailed), x, y, 30, Color::RED);
+        y += 56;
+        for asset in missing {
+            d.draw_text(&asset.what, x, y, 22, Color::RAYWHITE);
+            y += 28;
+            d.draw_text(tr(Str::LookedIn), x + 20, y, 18, Color::GRAY);
+            y += 24;
+            for path in &asset.looked_in {
+                d.draw_text(path, x + 40, y, 18, Color::LIGHTGRAY);
+                y += 22;
+            }
+            y += 12;
+        }
+        d.draw_text(tr(Str::QuitKeyHint), x, y + 12, 22, Color::GOLD);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_level_files_are_listed_with_their_full_path() {
+        let present = file!().to_string();
+        let missing = check_levels(&[present, "no_such_maze.txt".to_string()]);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].looked_in.len(), 1);
+        assert!(missing[0].looked_in[0].ends_with("no_such_maze.txt"));
+        assert!(Path::new(&missing[0].looked_in[0]).is_absolute());
+    }
+
+    #[test]
+    fn wall_textures_come_from_disk_the_atlas_or_the_built_in_copies() {
+        let candidates = vec!["textures/wall.png".to_string(), "../textures/wall.png".to_string()];
+        let nothing = |_: &str| false;
+        let missing = check_wall_textures(&candidates, nothing, false, false).expect("nothing to load the walls from");
+        assert_eq!(missing.looked_in.len(), 2);
+        assert!(check_wall_textures(&candidates, nothing, false, true).is_none());
+        assert!(check_wall_textures(&candidates, nothing, true, false).is_none());
+        assert!(check_wall_textures(&candidates, |p| p.starts_with(".."), false, false).is_none());
+    }
+}
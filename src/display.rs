@@ -0,0 +1,86 @@
+// display.rs - F11 fullscreen toggle, available in the game loop and the menus
+//
+// Fullscreen uses the monitor the window is currently on, at that monitor's resolution.
+// Going back restores the windowed size and position from before the switch. The choice is
+// saved as `fullscreen` in settings.toml, so the next start uses the same mode.
+
+use raylib::prelude::*;
+use crate::framebuffer::Framebuffer;
+use crate::settings::Settings;
+
+pub const FULLSCREEN_KEY: KeyboardKey = KeyboardKey::KEY_F11;
+
+// Windowed geometry remembered while fullscreen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct WindowedRect {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+#[derive(Default)]
+pub struct Display {
+    // Some while fullscreen
+    windowed: Option<WindowedRect>,
+}
+
+impl Display {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.windowed.is_some()
+    }
+
+    // Switch to the mode wanted at startup (settings or --fullscreen).
+    pub fn apply(&mut self, window: &mut RaylibHandle, framebuffer: &mut Framebuffer, fullscreen: bool) {
+        if fullscreen != self.is_fullscreen() {
+            self.toggle(window, framebuffer);
+        }
+    }
+
+    // Toggle on F11 and save the new mode. Call once per frame from any loop that draws.
+    pub fn handle_key(&mut self, window: &mut RaylibHandle, framebuffer: &mut Framebuffer, settings: &mut Settings) {
+        if !window.is_key_pressed(FULLSCREEN_KEY) {
+            return;
+        }
+        self.toggle(window, framebuffer);
+        settings.fullscreen = self.is_fullscreen();
+        settings.save();
+    }
+
+    fn toggle(&mut self, window: &mut RaylibHandle, framebuffer: &mut Framebuffer) {
+        let (width, height) = match self.windowed.take() {
+            Some(rect) => {
+                if window.is_window_fullscreen() {
+                    window.toggle_fullscreen();
+                }
+                window.set_window_size(rect.width, rect.height);
+                window.set_window_position(rect.x, rect.y);
+                (rect.width, rect.height)
+            }
+            None => {
+                let pos = window.get_window_position();
+                self.windowed = Some(WindowedRect {
+                    x: pos.x as i32,
+                    y: pos.y as i32,
+                    width: window.get_screen_width(),
+                    height: window.get_screen_height(),
+                });
+                // raylib keeps the window size as the video mode, so match the monitor first
+                let monitor = get_current_monitor();
+                let (width, height) = (get_monitor_width(monitor), get_monitor_height(monitor));
+                window.set_window_size(width, height);
+                if !window.is_window_fullscreen() {
+                    window.toggle_fullscreen();
+                }
+                (width, height)
+            }
+        };
+        // the resize event may only arrive next frame; size the framebuffer now
+        framebuffer.fit_window(width, height);
+        eprintln!("[info] {} at {}x{}", if self.is_fullscreen() { "fullscreen" } else { "windowed" }, width, height);
+    }
+}
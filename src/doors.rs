@@ -0,0 +1,178 @@
+// doors.rs - when the exit doors open, and when walking into one counts as escaping
+//
+// The doors open once `required` coins are collected: the level's `coins=` metadata when set
+// (never more than the coins in the grid), otherwise all of them. A level without coins has
+// nothing to collect, so its doors open as soon as the player steps off the spawn cell instead
+// of being open on the first frame. Escaping needs the player to walk into an exit cell from a
+// non-exit cell while the doors are open, so spawning on or next to a door never wins by itself.
+// Unlocked doors don't open at once: they slide up over DOOR_OPEN_SECS (open_progress) and can
+// only be walked through from PASSABLE_PROGRESS on.
+
+use raylib::prelude::*;
+use crate::anim::{ease, Tween};
+use crate::maze::{self, Maze};
+
+pub const DOOR_OPEN_SECS: f32 = 1.0;
+pub const PASSABLE_PROGRESS: f32 = 0.9;
+
+#[derive(Clone, Debug)]
+pub struct Doors {
+    required: usize,
+    spawn_cell: (usize, usize),
+    left_spawn: bool,
+    // the player was off every exit cell on the previous update
+    was_outside: bool,
+    // opened from the developer console regardless of coins
+    forced: bool,
+    // the opening slide, from the tick the doors unlocked (None while locked)
+    opening: Option<Tween>,
+}
+
+fn cell_of(pos: Vector2, block_size: usize) -> (usize, usize) {
+    let bs = block_size as f32;
+    ((pos.x.max(0.0) / bs) as usize, (pos.y.max(0.0) / bs) as usize)
+}
+
+impl Doors {
+    // `goal` is LevelMeta::required_coins, `coin_count` the coins in the grid.
+    pub fn new(maze: &Maze, goal: Option<usize>, coin_count: usize, spawn: Vector2, block_size: usize) -> Self {
+        Self {
+            required: goal.map_or(coin_count, |n| n.min(coin_count)),
+            spawn_cell: cell_of(spawn, block_size),
+            left_spawn: false,
+            was_outside: maze::exit_at(maze, spawn.x, spawn.y, block_size).is_none(),
+            forced: false,
+            opening: None,
+        }
+    }
+
+    // coins to collect before the doors open
+    pub fn required(&self) -> usize {
+        self.required
+    }
+
+    pub fn is_open(&self, collected: usize) -> bool {
+        if self.forced {
+            return true;
+        }
+        if self.required == 0 { self.left_spawn } else { collected >= self.required }
+    }
+
+    pub fn force_open(&mut self) {
+        self.forced = true;
+    }
+
+    // Start the opening slide once `open`, and move it `dt` seconds on.
+    pub fn animate(&mut self, open: bool, dt: f32) {
+        if open && self.opening.is_none() {
+            self.opening = Some(Tween::new(0.0, 1.0, DOOR_OPEN_SECS, ease::in_out_sine));
+        }
+        if let Some(opening) = &mut self.opening {
+            opening.advance(dt);
+        }
+    }
+
+    // Skip the slide (doors that were already open in a restored save).
+    pub fn finish_opening(&mut self) {
+        self.opening = Some(Tween::settled(1.0));
+    }
+
+    // 0 = closed, 1 = fully open
+    pub fn open_progress(&self) -> f32 {
+        self.opening.map_or(0.0, |t| t.value())
+    }
+
+    pub fn is_passable(&self) -> bool {
+        self.open_progress() >= PASSABLE_PROGRESS
+    }
+
+    // Follow the player after a simulation tick. `open` is whether the doors were open for
+    // that tick's movement; returns the exit the player just walked into, if any.
+    pub fn update(&mut self, maze: &Maze, pos: Vector2, block_size: usize, open: bool) -> Option<(usize, usize)> {
+        if cell_of(pos, block_size) != self.spawn_cell {
+            self.left_spawn = true;
+        }
+        let exit = maze::exit_at(maze, pos.x, pos.y, block_size);
+        let entered = open && self.was_outside;
+        self.was_outside = exit.is_none();
+        if entered { exit } else { None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze_of(rows: &[&str]) -> Maze {
+        rows.iter().map(|r| r.chars().collect()).collect()
+    }
+
+    // center of cell (col, row) with 100-unit blocks
+    fn at(col: usize, row: usize) -> Vector2 {
+        Vector2::new(col as f32 * 100.0 + 50.0, row as f32 * 100.0 + 50.0)
+    }
+
+    #[test]
+    fn zero_coin_level_opens_only_after_leaving_the_spawn() {
+        let maze = maze_of(&["+---+", "|  G|", "+---+"]);
+        let mut doors = Doors::new(&maze, None, 0, at(1, 1), 100);
+        assert_eq!(doors.required(), 0);
+        assert!(!doors.is_open(0), "open on the first frame");
+        assert_eq!(doors.update(&maze, at(1, 1), 100, false), None);
+        assert!(!doors.is_open(0));
+        assert_eq!(doors.update(&maze, at(2, 1), 100, false), None);
+        assert!(doors.is_open(0));
+        assert_eq!(doors.update(&maze, at(3, 1), 100, true), Some((3, 1)));
+    }
+
+    #[test]
+    fn spawning_next_to_a_door_needs_a_real_entry() {
+        let maze = maze_of(&["+---+", "|G  |", "|   |", "+---+"]);
+        // spawn on the exit itself, coins already collected: standing there doesn't count
+        let mut doors = Doors::new(&maze, None, 2, at(1, 1), 100);
+        assert!(doors.is_open(2));
+        assert_eq!(doors.update(&maze, at(1, 1), 100, true), None);
+        assert_eq!(doors.update(&maze, at(1, 1), 100, true), None);
+        // step off and back in
+        assert_eq!(doors.update(&maze, at(1, 2), 100, true), None);
+        assert_eq!(doors.update(&maze, at(1, 1), 100, true), Some((1, 1)));
+        // spawn beside the exit: walking in with the doors open escapes on that tick
+        let mut doors = Doors::new(&maze, None, 1, at(2, 1), 100);
+        assert_eq!(doors.update(&maze, at(1, 1), 100, false), None);
+        let mut doors2 = Doors::new(&maze, None, 1, at(2, 1), 100);
+        assert_eq!(doors2.update(&maze, at(1, 1), 100, true), Some((1, 1)));
+        // closed doors don't count, even once they open while standing inside
+        assert_eq!(doors.update(&maze, at(1, 1), 100, true), None);
+    }
+
+    #[test]
+    fn metadata_goal_is_capped_by_the_coins_in_the_grid() {
+        let maze = maze_of(&["+--+", "| G|", "+--+"]);
+        assert_eq!(Doors::new(&maze, Some(2), 5, at(1, 1), 100).required(), 2);
+        assert_eq!(Doors::new(&maze, Some(9), 5, at(1, 1), 100).required(), 5);
+        assert_eq!(Doors::new(&maze, None, 5, at(1, 1), 100).required(), 5);
+        let doors = Doors::new(&maze, Some(2), 5, at(1, 1), 100);
+        assert!(!doors.is_open(1) && doors.is_open(2));
+        // a goal of zero behaves like a coinless level
+        assert!(!Doors::new(&maze, Some(0), 5, at(1, 1), 100).is_open(0));
+    }
+
+    #[test]
+    fn unlocked_doors_slide_open_and_let_through_near_the_end() {
+        let maze = maze_of(&["+--+", "| G|", "+--+"]);
+        let mut doors = Doors::new(&maze, None, 1, at(1, 1), 100);
+        doors.animate(false, 5.0);
+        assert_eq!(doors.open_progress(), 0.0);
+        doors.animate(true, 0.0);
+        assert_eq!(doors.open_progress(), 0.0);
+        doors.animate(true, DOOR_OPEN_SECS / 2.0);
+        assert!((doors.open_progress() - 0.5).abs() < 1e-4 && !doors.is_passable());
+        doors.animate(true, DOOR_OPEN_SECS * 0.3);
+        assert!(doors.open_progress() > 0.9 && doors.is_passable());
+        doors.animate(true, 1.0);
+        assert_eq!(doors.open_progress(), 1.0);
+        let mut restored = Doors::new(&maze, None, 1, at(1, 1), 100);
+        restored.finish_opening();
+        assert!(restored.is_passable());
+    }
+}
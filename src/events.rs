@@ -0,0 +1,87 @@
+// events.rs - gameplay events raised during the update step
+//
+// Game logic pushes what happened this frame; main drains the queue afterwards and turns
+// events into feedback (sounds, banners). Keeps the update code free of audio/UI calls.
+
+use raylib::prelude::Vector2;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    // index into the level's coin list
+    CoinCollected(usize),
+    // the last required coin was collected and the doors unlocked; pos is the world position
+    // of the exit nearest to the player (where the sound comes from)
+    DoorOpened { pos: Vector2 },
+    // the player walked through an open exit at (col, row)
+    PlayerEscaped((usize, usize)),
+    PlayerDied,
+    // the player walked another stride
+    Footstep,
+    // an NPC just spotted the player (entered Chase); pos is the NPC's world position
+    NpcAlerted { pos: Vector2 },
+    // an NPC started winding up an attack next to the player
+    NpcWindup { pos: Vector2 },
+    // a walking NPC near the player took a step (heard through walls)
+    NpcStep { pos: Vector2 },
+}
+
+#[derive(Default)]
+pub struct EventQueue {
+    events: Vec<GameEvent>,
+}
+
+impl EventQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+
+    // take every event pushed since the last drain, in order
+    pub fn drain(&mut self) -> std::vec::Drain<'_, GameEvent> {
+        self.events.drain(..)
+    }
+}
+
+// Detects a false -> true transition of a condition that stays true for many frames,
+// so its event fires once per transition instead of every frame.
+pub struct RisingEdge {
+    prev: bool,
+}
+
+impl RisingEdge {
+    // `initial` is the state before the first frame (true suppresses an edge on frame one)
+    pub fn new(initial: bool) -> Self {
+        Self { prev: initial }
+    }
+
+    pub fn update(&mut self, now: bool) -> bool {
+        let rose = now && !self.prev;
+        self.prev = now;
+        rose
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rising_edge_fires_once_per_transition() {
+        let mut edge = RisingEdge::new(false);
+        let fired: Vec<bool> = [false, true, true, true, false, true].iter().map(|&b| edge.update(b)).collect();
+        assert_eq!(fired, vec![false, true, false, false, false, true]);
+        assert!(!RisingEdge::new(true).update(true));
+    }
+
+    #[test]
+    fn queue_drains_in_order() {
+        let mut q = EventQueue::new();
+        q.push(GameEvent::CoinCollected(2));
+        q.push(GameEvent::DoorOpened { pos: Vector2::zero() });
+        assert_eq!(q.drain().collect::<Vec<_>>(), vec![GameEvent::CoinCollected(2), GameEvent::DoorOpened { pos: Vector2::zero() }]);
+        assert_eq!(q.drain().count(), 0);
+    }
+}
@@ -0,0 +1,99 @@
+// exploration.rs - fog-of-war discovery saved per level between sessions
+//
+// One text file per level in saves/, one line per maze row: '#' = discovered, '.' = not.
+// Only used when `remember_exploration` is on in settings; a file whose dimensions don't
+// match the current maze (the level was edited) is ignored and exploration starts over.
+
+use crate::maze::Maze;
+
+const SAVE_DIR: &str = "saves";
+
+fn path_for(level: i32) -> String {
+    format!("{}/discovery_level{}.txt", SAVE_DIR, level)
+}
+
+// Nothing discovered yet, sized to the maze.
+pub fn fresh(maze: &Maze) -> Vec<Vec<bool>> {
+    maze.iter().map(|r| vec![false; r.len()]).collect()
+}
+
+fn to_text(discovered: &[Vec<bool>]) -> String {
+    let mut out = String::new();
+    for row in discovered {
+        out.extend(row.iter().map(|&d| if d { '#' } else { '.' }));
+        out.push('\n');
+    }
+    out
+}
+
+// Parse a saved grid; None when it doesn't fit the maze.
+fn from_text(text: &str, maze: &Maze) -> Option<Vec<Vec<bool>>> {
+    let grid: Vec<Vec<bool>> = text.lines().map(|line| line.chars().map(|c| c == '#').collect()).collect();
+    let same_shape = grid.len() == maze.len() && grid.iter().zip(maze.iter()).all(|(g, m)| g.len() == m.len());
+    same_shape.then_some(grid)
+}
+
+// Discovery saved for `level`, or a fresh grid when there is none or it is stale.
+pub fn load(level: i32, maze: &Maze) -> Vec<Vec<bool>> {
+    let path = path_for(level);
+    let Ok(text) = std::fs::read_to_string(&path) else {
+        return fresh(maze);
+    };
+    from_text(&text, maze).unwrap_or_else(|| {
+        eprintln!("[info] {} doesn't match the current maze, starting exploration over", path);
+        fresh(maze)
+    })
+}
+
+// Grid to start `level` with: the saved one when `remember` is on, else a fresh grid.
+pub fn begin_level(level: i32, maze: &Maze, remember: bool) -> Vec<Vec<bool>> {
+    if remember { load(level, maze) } else { fresh(maze) }
+}
+
+pub fn save(level: i32, discovered: &[Vec<bool>]) {
+    let path = path_for(level);
+    let result = std::fs::create_dir_all(SAVE_DIR).and_then(|_| std::fs::write(&path, to_text(discovered)));
+    if let Err(e) = result {
+        eprintln!("[warn] couldn't write {}: {}", path, e);
+    }
+}
+
+// Forget the saved exploration of every level ("reset exploration" in the options menu).
+pub fn reset_all() {
+    let Ok(entries) = std::fs::read_dir(SAVE_DIR) else { return };
+    for e in entries.flatten() {
+        let name = e.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with("discovery_level")
+            && name.ends_with(".txt")
+            && let Err(err) = std::fs::remove_file(e.path())
+        {
+            eprintln!("[warn] couldn't delete {}: {}", e.path().display(), err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze_of(rows: &[&str]) -> Maze {
+        rows.iter().map(|r| r.chars().collect()).collect()
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let maze = maze_of(&["+--+", "|  |", "+--+"]);
+        let mut grid = fresh(&maze);
+        grid[1][1] = true;
+        grid[1][2] = true;
+        assert_eq!(from_text(&to_text(&grid), &maze), Some(grid));
+    }
+
+    #[test]
+    fn mismatched_dimensions_are_discarded() {
+        let small = maze_of(&["+-+", "+-+"]);
+        let big = maze_of(&["+--+", "|  |", "+--+"]);
+        assert_eq!(from_text(&to_text(&fresh(&small)), &big), None);
+    }
+}
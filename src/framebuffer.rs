@@ -1,6 +1,12 @@
 // framebuffer.rs
 
 use raylib::prelude::*;
+use crate::clip::Rect;
+use crate::console::ConsoleView;
+use crate::debug::{DebugStats, FrameTimes};
+use crate::strings::{trf, Str};
+use crate::textures::{TextureAtlas, TextureId};
+use crate::ui::Palette;
 
 pub struct Framebuffer {
     pub width: u32,
@@ -8,8 +14,60 @@ pub struct Framebuffer {
     pub color_buffer: Image,
     background_color: Color,
     current_color: Color,
+    // user multiplier for HUD text/boxes on top of the automatic screen-height scaling
+    hud_scale: f32,
+    // framebuffer pixels at the top-left covered by overlays drawn into the image (the
+    // minimap); HUD text on screen starts to the right of it
+    hud_left_inset: u32,
+    // view angle for the compass strip, None = hidden
+    compass: Option<f32>,
+    // colors of the HUD text and the minimap markers
+    palette: Palette,
+    // level timer in seconds (hud::RunTimer), None = hidden
+    run_time: Option<f64>,
+    // progress of the held restart key (hud::HoldToConfirm), None = not held
+    restart_hold: Option<f32>,
+    // perpendicular wall distance per ray column from the last render_world, and the pixels
+    // per column; billboards drawn after it (particles) are depth-tested against it
+    wall_depth: Vec<f32>,
+    wall_depth_step: usize,
+    // coin pickup popup (hud::CoinTally::popup): coins gained and its rise 0..1, None = hidden
+    coin_popup: Option<(usize, f32)>,
+    // window pixels per framebuffer pixel, used to follow window resizes
+    render_scale: u32,
+    // physical pixels per logical window unit (2 on a retina display), see window_dpi_scale
+    dpi_scale: f32,
+    // frame cap shown after the FPS counter (None = unlimited)
+    fps_cap: Option<u32>,
+    // distance in cells under the objective arrow (hud::draw_objective_arrow), None = hidden
+    objective_distance: Option<f32>,
+    // F3 developer overlay, None = hidden
+    debug_stats: Option<DebugStats>,
+    // developer console, None = closed
+    console: Option<ConsoleView>,
 }
 
+// Per-channel lookup table for Framebuffer::apply_gamma.
+fn gamma_lut(gamma: f32) -> [u8; 256] {
+    let inv = 1.0 / gamma.max(0.01);
+    let mut lut = [0u8; 256];
+    for (i, v) in lut.iter_mut().enumerate() {
+        *v = ((i as f32 / 255.0).powf(inv) * 255.0).round() as u8;
+    }
+    lut
+}
+
+// Physical pixels per logical window unit: above 1 on retina and scaled displays (the window is
+// created with the HiDPI flag), 1 when raylib can't tell.
+pub fn window_dpi_scale(window: &RaylibHandle) -> f32 {
+    let dpi = window.get_window_scale_dpi();
+    let scale = dpi.x.max(dpi.y);
+    if scale.is_finite() && scale > 0.0 { scale } else { 1.0 }
+}
+
+// HUD sizes below are designed for a 720 px tall window
+const HUD_REFERENCE_HEIGHT: f32 = 720.0;
+
 impl Framebuffer {
     pub fn new(width: u32, height: u32) -> Self {
         let color_buffer = Image::gen_image_color(width as i32, height as i32, Color::BLACK);
@@ -19,7 +77,148 @@ impl Framebuffer {
             color_buffer,
             background_color: Color::BLACK,
             current_color: Color::WHITE,
+            hud_scale: 1.0,
+            hud_left_inset: 0,
+            compass: None,
+            palette: Palette::DEFAULT,
+            run_time: None,
+            restart_hold: None,
+            wall_depth: Vec::new(),
+            wall_depth_step: 1,
+            coin_popup: None,
+            render_scale: 1,
+            dpi_scale: 1.0,
+            fps_cap: None,
+            objective_distance: None,
+            debug_stats: None,
+            console: None,
+        }
+    }
+
+    // Framebuffer size for a window of `window_w` x `window_h` logical units shown at
+    // `dpi_scale` physical pixels per unit, rendered at 1/`render_scale` of the physical pixels.
+    pub fn size_for(window_w: i32, window_h: i32, render_scale: u32, dpi_scale: f32) -> (u32, u32) {
+        let scale = render_scale.max(1) as f32;
+        let side = |logical: i32| ((logical.max(1) as f32 * dpi_scale / scale) as u32).max(1);
+        (side(window_w), side(window_h))
+    }
+
+    pub fn set_fps_cap(&mut self, cap: Option<u32>) {
+        self.fps_cap = cap;
+    }
+
+    pub fn set_render_scale(&mut self, scale: u32) {
+        self.render_scale = scale.max(1);
+    }
+
+    pub fn set_dpi_scale(&mut self, scale: f32) {
+        self.dpi_scale = scale;
+    }
+
+    pub fn dpi_scale(&self) -> f32 {
+        self.dpi_scale
+    }
+
+    // "FPS: 58/60", or just "FPS: 240" when uncapped
+    fn fps_text(&self, fps: i32) -> String {
+        match self.fps_cap {
+            Some(cap) => format!("FPS: {}/{}", fps, cap),
+            None => format!("FPS: {}", fps),
+        }
+    }
+
+    // Reallocate the image at a new size. The last frame is stretched to fit, so screens that
+    // don't redraw every frame (the pause screen) stay readable until the next render.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if (width, height) == (self.width, self.height) {
+            return;
         }
+        self.width = width;
+        self.height = height;
+        self.color_buffer.resize_nn(width as i32, height as i32);
+    }
+
+    // Resize for a window of `window_w` x `window_h` at the current render and DPI scale.
+    pub fn fit_window(&mut self, window_w: i32, window_h: i32) {
+        let (w, h) = Self::size_for(window_w, window_h, self.render_scale, self.dpi_scale);
+        self.resize(w, h);
+    }
+
+    // Match the window after the user resized it or moved it to a monitor with another DPI
+    // scale, keeping the render scale; call once per frame in every loop that draws (game,
+    // menu, end screens). True if the size changed.
+    pub fn follow_window(&mut self, window: &RaylibHandle) -> bool {
+        let dpi = window_dpi_scale(window);
+        if !window.is_window_resized() && dpi == self.dpi_scale {
+            return false;
+        }
+        if dpi != self.dpi_scale {
+            eprintln!("[info] display scale {:.2}", dpi);
+            self.dpi_scale = dpi;
+        }
+        let before = (self.width, self.height);
+        self.fit_window(window.get_screen_width(), window.get_screen_height());
+        before != (self.width, self.height)
+    }
+
+    pub fn set_hud_scale(&mut self, scale: f32) {
+        self.hud_scale = scale;
+    }
+
+    pub fn set_hud_left_inset(&mut self, inset: u32) {
+        self.hud_left_inset = inset;
+    }
+
+    pub fn set_compass(&mut self, yaw: Option<f32>) {
+        self.compass = yaw;
+    }
+
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    pub fn palette(&self) -> Palette {
+        self.palette
+    }
+
+    pub fn set_run_time(&mut self, secs: Option<f64>) {
+        self.run_time = secs;
+    }
+
+    pub fn set_objective_distance(&mut self, cells: Option<f32>) {
+        self.objective_distance = cells;
+    }
+
+    pub fn set_debug_stats(&mut self, stats: Option<DebugStats>) {
+        self.debug_stats = stats;
+    }
+
+    pub fn set_console(&mut self, view: Option<ConsoleView>) {
+        self.console = view;
+    }
+
+    pub fn set_restart_hold(&mut self, progress: Option<f32>) {
+        self.restart_hold = progress;
+    }
+
+    pub fn set_wall_depth(&mut self, depth: Vec<f32>, column_step: usize) {
+        self.wall_depth = depth;
+        self.wall_depth_step = column_step.max(1);
+    }
+
+    // (depth per column, pixels per column); empty when no 3D view was rendered
+    pub fn wall_depth(&self) -> (&[f32], usize) {
+        (&self.wall_depth, self.wall_depth_step)
+    }
+
+    // The pixels alongside wall_depth, borrowed separately so billboards can be drawn into the
+    // image while depth-tested against it (see renderer::render_particles).
+    pub fn pixels_and_wall_depth(&mut self) -> (&mut Image, &[f32], usize) {
+        (&mut self.color_buffer, &self.wall_depth, self.wall_depth_step)
+    }
+
+    pub fn set_coin_popup(&mut self, popup: Option<(usize, f32)>) {
+        self.coin_popup = popup;
     }
 
     pub fn clear(&mut self) {
@@ -32,6 +231,105 @@ impl Framebuffer {
         }
     }
 
+    // The whole framebuffer, for clipping (see clip.rs).
+    pub fn bounds(&self) -> Rect {
+        Rect::new(0, 0, self.width as i32, self.height as i32)
+    }
+
+    // Fill `rect` with `color`; any part outside the framebuffer is skipped.
+    pub fn fill_rect(&mut self, rect: Rect, color: Color) {
+        let bounds = self.bounds();
+        self.fill_rect_in(&bounds, rect, color);
+    }
+
+    // fill_rect clipped to a viewport `clip` (and the framebuffer).
+    pub fn fill_rect_in(&mut self, clip: &Rect, rect: Rect, color: Color) {
+        let Some(r) = rect.intersect(clip).and_then(|r| r.intersect(&self.bounds())) else { return };
+        self.set_current_color(color);
+        for y in r.y..r.bottom() {
+            for x in r.x..r.right() {
+                self.set_pixel(x as u32, y as u32);
+            }
+        }
+    }
+
+    // Copy an image into `dest`: `sample(x, y)` gives the color at (x, y) from dest's top-left
+    // and is only asked for pixels inside the framebuffer.
+    pub fn blit(&mut self, dest: Rect, mut sample: impl FnMut(u32, u32) -> Color) {
+        let Some(r) = dest.intersect(&self.bounds()) else { return };
+        for y in r.y..r.bottom() {
+            for x in r.x..r.right() {
+                self.set_current_color(sample((x - dest.x) as u32, (y - dest.y) as u32));
+                self.set_pixel(x as u32, y as u32);
+            }
+        }
+    }
+
+    // Stretch the full-screen image `id` over the whole framebuffer (its fallback gradient while
+    // the image is missing); the background of the menu, game over and victory screens.
+    pub fn fill_fullscreen(&mut self, textures: &TextureAtlas, id: &TextureId) {
+        let (w, h) = (self.width as f32, self.height as f32);
+        let bounds = self.bounds();
+        self.blit(bounds, |x, y| textures.sample_fullscreen(id, x as f32 / w, y as f32 / h));
+    }
+
+    // Color already drawn at (x, y); used to blend translucent pixels over the scene.
+    pub fn get_pixel(&mut self, x: u32, y: u32) -> Color {
+        if x < self.width && y < self.height {
+            self.color_buffer.get_color(x as i32, y as i32)
+        } else {
+            self.background_color
+        }
+    }
+
+    // Mix `color` over the pixel at (x, y) by `coverage` (0..1) times its alpha; outside the
+    // framebuffer is ignored, so callers can pass unclipped coordinates.
+    pub fn blend_pixel(&mut self, x: i32, y: i32, color: Color, coverage: f32) {
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height {
+            return;
+        }
+        let t = coverage.clamp(0.0, 1.0) * color.a as f32 / 255.0;
+        if t <= 0.0 {
+            return;
+        }
+        let dst = self.color_buffer.get_color(x, y);
+        let mix = |d: u8, s: u8| (d as f32 + (s as f32 - d as f32) * t).round() as u8;
+        let out = Color::new(mix(dst.r, color.r), mix(dst.g, color.g), mix(dst.b, color.b), dst.a.max((t * 255.0) as u8));
+        self.color_buffer.draw_pixel(x, y, out);
+    }
+
+    // Darken the image toward black by `amount` (0 = unchanged, 1 = black), for fades.
+    pub fn fade(&mut self, amount: f32) {
+        let keep = 1.0 - amount.clamp(0.0, 1.0);
+        if keep >= 1.0 {
+            return;
+        }
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let c = self.get_pixel(x, y);
+                let s = |v: u8| (v as f32 * keep) as u8;
+                self.color_buffer.draw_pixel(x as i32, y as i32, Color::new(s(c.r), s(c.g), s(c.b), c.a));
+            }
+        }
+    }
+
+    // Brightness post-process: a per-pixel gamma curve, out = in^(1/gamma), through a lookup
+    // table. gamma > 1 lifts dark and mid tones, < 1 darkens them; pure black and white stay
+    // as they are (it is not a black-level lift). Runs on the CPU over the low-res framebuffer
+    // before the blit, so HUD text drawn by raylib afterwards keeps its colors.
+    pub fn apply_gamma(&mut self, gamma: f32) {
+        if (gamma - 1.0).abs() < 1e-3 {
+            return;
+        }
+        let lut = gamma_lut(gamma);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let c = self.get_pixel(x, y);
+                self.color_buffer.draw_pixel(x as i32, y as i32, Color::new(lut[c.r as usize], lut[c.g as usize], lut[c.b as usize], c.a));
+            }
+        }
+    }
+
     pub fn set_background_color(&mut self, color: Color) {
         self.background_color = color;
     }
@@ -82,14 +380,34 @@ impl Framebuffer {
 
             renderer.draw_texture_pro(&texture, src, dest, origin, 0.0, Color::WHITE);
             if let Some(f) = fps {
-                let txt = format!("FPS: {}", f);
+                let txt = self.fps_text(f);
                 // draw semi-transparent background for readability
-                renderer.draw_rectangle(10, 10, 90, 26, Color::new(0, 0, 0, 120));
+                let w = renderer.measure_text(&txt, 20);
+                renderer.draw_rectangle(10, 10, w + 12, 26, Color::new(0, 0, 0, 120));
                 renderer.draw_text(&txt, 16, 14, 20, Color::RAYWHITE);
             }
         }
     }
 
+    // Draw the framebuffer stretched over the whole window, then `overlay` on top in screen
+    // pixels (it gets the screen width and height); used by the full-screen menus.
+    pub fn present_stretched(
+        &self,
+        window: &mut RaylibHandle,
+        raylib_thread: &RaylibThread,
+        overlay: impl FnOnce(&mut RaylibDrawHandle, i32, i32),
+    ) {
+        if let Ok(texture) = window.load_texture_from_image(raylib_thread, &self.color_buffer) {
+            let screen_w = window.get_screen_width();
+            let screen_h = window.get_screen_height();
+            let mut d = window.begin_drawing(raylib_thread);
+            let src = Rectangle::new(0.0, 0.0, self.width as f32, self.height as f32);
+            let dest = Rectangle::new(0.0, 0.0, screen_w as f32, screen_h as f32);
+            d.draw_texture_pro(&texture, src, dest, Vector2::new(0.0, 0.0), 0.0, Color::WHITE);
+            overlay(&mut d, screen_w, screen_h);
+        }
+    }
+
     // Draw framebuffer and overlay with coin counter
     pub fn swap_buffers_with_coins(
         &self,
@@ -99,6 +417,9 @@ impl Framebuffer {
         coins_collected: usize,
         total_coins: usize,
         current_level: i32,
+        frame_stats: Option<&FrameTimes>,
+        banner: Option<&str>,
+        muted: bool,
     ) {
         if let Ok(texture) = window.load_texture_from_image(raylib_thread, &self.color_buffer) {
             // Preserve aspect ratio: compute destination rect that fits the window without stretching
@@ -129,23 +450,247 @@ impl Framebuffer {
             let origin = Vector2::new(0.0, 0.0);
 
             renderer.draw_texture_pro(&texture, src, dest, origin, 0.0, Color::WHITE);
-            
+
+            // sizes scale with the window height (and the user's hud_scale) so text stays readable on 4K;
+            // with the HiDPI flag raylib draws in logical units, so retina keeps the same layout
+            let hud = self.hud_scale * (screen_h as f32 / HUD_REFERENCE_HEIGHT).max(0.5);
+            let px = |v: i32| (v as f32 * hud).round() as i32;
+            let pad = px(10);
+            let box_bg = Color::new(0, 0, 0, 120);
+            // left column starts past the minimap, which is drawn into the framebuffer
+            let left_x = dest_x + (self.hud_left_inset as f32 * dest_w / fb_w) as i32 + pad;
+            let mut left_end = left_x;
+
             if let Some(f) = fps {
-                let txt = format!("FPS: {}", f);
+                let txt = self.fps_text(f);
                 // draw semi-transparent background for readability
-                renderer.draw_rectangle(10, 10, 90, 26, Color::new(0, 0, 0, 120));
-                renderer.draw_text(&txt, 16, 14, 20, Color::RAYWHITE);
+                let w = renderer.measure_text(&txt, px(20));
+                renderer.draw_rectangle(left_x, pad, w + px(12), px(26), box_bg);
+                renderer.draw_text(&txt, left_x + px(6), pad + px(4), px(20), Color::RAYWHITE);
+                left_end = left_x + w + px(12);
+            }
+
+            // Draw coin counter (right-aligned)
+            let coins_text = trf(Str::HudCoins, &[&coins_collected, &total_coins]);
+            let coins_w = renderer.measure_text(&coins_text, px(24)) + px(20);
+            let coins_x = screen_w - pad - coins_w;
+            renderer.draw_rectangle(coins_x, pad, coins_w, px(30), box_bg);
+            renderer.draw_text(&coins_text, coins_x + px(10), pad + px(4), px(24), self.palette.pickup);
+
+            // "+N" just left of the coin box, rising into line with it and fading out
+            if let Some((gained, rise)) = self.coin_popup {
+                let txt = format!("+{}", gained);
+                let w = renderer.measure_text(&txt, px(24));
+                let y = pad + px(4) + ((1.0 - rise) * px(24) as f32) as i32;
+                renderer.draw_text(&txt, coins_x - w - px(8), y, px(24), self.palette.pickup.fade(1.0 - rise * rise));
+            }
+
+            // crossed-out speaker next to the FPS box while audio is muted
+            if muted {
+                let (x, y) = (left_end + pad, pad);
+                let icon = |v: f32| v * hud;
+                renderer.draw_rectangle(x, y, px(34), px(26), box_bg);
+                renderer.draw_rectangle(x + px(5), y + px(9), px(6), px(8), Color::RAYWHITE);
+                renderer.draw_triangle(
+                    Vector2::new(x as f32 + icon(11.0), y as f32 + icon(13.0)),
+                    Vector2::new(x as f32 + icon(18.0), y as f32 + icon(20.0)),
+                    Vector2::new(x as f32 + icon(18.0), y as f32 + icon(6.0)),
+                    Color::RAYWHITE,
+                );
+                renderer.draw_line_ex(Vector2::new(x as f32 + icon(21.0), y as f32 + icon(8.0)), Vector2::new(x as f32 + icon(30.0), y as f32 + icon(18.0)), icon(2.0), self.palette.danger);
+                renderer.draw_line_ex(Vector2::new(x as f32 + icon(30.0), y as f32 + icon(8.0)), Vector2::new(x as f32 + icon(21.0), y as f32 + icon(18.0)), icon(2.0), self.palette.danger);
+                left_end = x + px(34);
+            }
+
+            // Draw level indicator: centered, but kept clear of the left column and the coin box;
+            // on narrow windows it drops below the coin counter instead
+            let level_text = trf(Str::HudLevel, &[&current_level]);
+            let level_w = renderer.measure_text(&level_text, px(24)) + px(20);
+            let centered_x = screen_w / 2 - level_w / 2;
+            let (level_x, level_y) = if centered_x.max(left_end + pad) + level_w + pad <= coins_x {
+                (centered_x.max(left_end + pad), pad)
+            } else {
+                (screen_w - pad - level_w, pad + px(30) + pad / 2)
+            };
+            renderer.draw_rectangle(level_x, level_y, level_w, px(30), box_bg);
+            renderer.draw_text(&level_text, level_x + px(10), level_y + px(4), px(24), self.palette.neutral);
+
+            // restart key held: a small "R" badge at the bottom center with a ring filling clockwise
+            if let Some(progress) = self.restart_hold {
+                let center = Vector2::new((screen_w / 2) as f32, (screen_h - pad - px(24)) as f32);
+                renderer.draw_circle(center.x as i32, center.y as i32, px(22) as f32, box_bg);
+                renderer.draw_ring(center, px(17) as f32, px(21) as f32, -90.0, -90.0 + 360.0 * progress, 36, Color::GOLD);
+                let w = renderer.measure_text("R", px(20));
+                renderer.draw_text("R", center.x as i32 - w / 2, center.y as i32 - px(10), px(20), Color::RAYWHITE);
+            }
+
+            // level timer right-aligned under the coin counter (or the level box when it dropped there)
+            if let Some(secs) = self.run_time {
+                let time_text = crate::hud::format_run_time(secs);
+                let time_w = renderer.measure_text(&time_text, px(22)) + px(20);
+                let time_y = if level_y == pad { pad + px(30) + pad / 2 } else { level_y + px(30) + pad / 2 };
+                renderer.draw_rectangle(screen_w - pad - time_w, time_y, time_w, px(28), box_bg);
+                renderer.draw_text(&time_text, screen_w - pad - time_w + px(10), time_y + px(4), px(22), Color::RAYWHITE);
+            }
+
+            // compass strip centered under the top row, ticks scrolling with the view angle
+            if let Some(yaw) = self.compass {
+                let strip_w = px(360).min(screen_w - 2 * pad);
+                let (strip_x, strip_y, strip_h) = (screen_w / 2 - strip_w / 2, level_y + px(30) + pad / 2, px(30));
+                renderer.draw_rectangle(strip_x, strip_y, strip_w, strip_h, box_bg);
+                for (offset, label) in crate::hud::compass_marks(yaw) {
+                    let x = strip_x + strip_w / 2 + (offset * strip_w as f32) as i32;
+                    match label {
+                        Some(text) => {
+                            let size = if text.len() == 1 { px(18) } else { px(12) };
+                            let color = if text == "N" { Color::RED } else { Color::RAYWHITE };
+                            let w = renderer.measure_text(text, size);
+                            renderer.draw_text(text, x - w / 2, strip_y + px(2), size, color);
+                            renderer.draw_line(x, strip_y + strip_h - px(8), x, strip_y + strip_h, color);
+                        }
+                        None => renderer.draw_line(x, strip_y + strip_h - px(5), x, strip_y + strip_h, Color::GRAY),
+                    }
+                }
+                // heading marker
+                let cx = screen_w / 2;
+                renderer.draw_triangle(
+                    Vector2::new(cx as f32, (strip_y + strip_h - px(6)) as f32),
+                    Vector2::new((cx - px(5)) as f32, (strip_y + strip_h + px(2)) as f32),
+                    Vector2::new((cx + px(5)) as f32, (strip_y + strip_h + px(2)) as f32),
+                    Color::GOLD,
+                );
+            }
+
+            // Frame pacing stats: the bar graph itself is drawn into the framebuffer
+            // (bottom-left, see debug::draw_frame_graph); label it with min/avg/max here
+            if let Some(frames) = frame_stats {
+                let (min, avg, max) = frames.stats();
+                let scale = dest_w / fb_w;
+                let label_x = dest_x + (8.0 * scale) as i32;
+                let label_y = dest_y + ((fb_h - 48.0) * scale) as i32 - px(22);
+                let txt = format!("ms min {:.1} / avg {:.1} / max {:.1}", min, avg, max);
+                let w = renderer.measure_text(&txt, px(18));
+                renderer.draw_rectangle(label_x - px(4), label_y - px(2), w + px(8), px(22), box_bg);
+                renderer.draw_text(&txt, label_x, label_y, px(18), Color::RAYWHITE);
+            }
+
+            // objective distance centered under the arrow drawn into the framebuffer
+            if let Some(cells) = self.objective_distance {
+                let scale = dest_h / fb_h;
+                let arrow_bottom = fb_h * crate::hud::OBJECTIVE_ARROW_Y + crate::hud::OBJECTIVE_ARROW_SIZE;
+                let y = dest_y + (arrow_bottom * scale) as i32 + px(4);
+                let txt = format!("{:.0}", cells);
+                let w = renderer.measure_text(&txt, px(16));
+                renderer.draw_rectangle(screen_w / 2 - w / 2 - px(4), y - px(2), w + px(8), px(20), box_bg);
+                renderer.draw_text(&txt, screen_w / 2 - w / 2, y, px(16), Color::SKYBLUE);
+            }
+
+            // F3 developer overlay in the bottom-right corner
+            if let Some(stats) = &self.debug_stats {
+                let lines = stats.lines();
+                let (size, line_h) = (px(16), px(20));
+                let w = lines.iter().map(|l| renderer.measure_text(l, size)).max().unwrap_or(0) + px(16);
+                let h = line_h * lines.len() as i32 + px(12);
+                let (x, y) = (screen_w - pad - w, screen_h - pad - h);
+                renderer.draw_rectangle(x, y, w, h, Color::new(0, 0, 0, 160));
+                for (i, line) in lines.iter().enumerate() {
+                    renderer.draw_text(line, x + px(8), y + px(6) + line_h * i as i32, size, Color::LIME);
+                }
+            }
+
+            // developer console across the top of the window, newest line just above the input
+            if let Some(view) = &self.console {
+                let (size, line_h) = (px(18), px(22));
+                let h = line_h * (view.lines.len() as i32 + 1) + px(12);
+                renderer.draw_rectangle(0, 0, screen_w, h, Color::new(0, 0, 0, 190));
+                for (i, line) in view.lines.iter().enumerate() {
+                    let color = if line.starts_with('>') { Color::LIGHTGRAY } else { Color::LIME };
+                    renderer.draw_text(line, pad, px(6) + line_h * i as i32, size, color);
+                }
+                let input = format!("] {}_", view.input);
+                renderer.draw_text(&input, pad, px(6) + line_h * view.lines.len() as i32, size, Color::RAYWHITE);
+            }
+
+            // short centered message (e.g. doors unlocked)
+            if let Some(msg) = banner {
+                let w = renderer.measure_text(msg, px(36));
+                let y = screen_h / 3;
+                renderer.draw_rectangle(screen_w / 2 - w / 2 - px(16), y - px(10), w + px(32), px(56), Color::new(0, 0, 0, 150));
+                renderer.draw_text(msg, screen_w / 2 - w / 2, y, px(36), Color::GOLD);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resize_follows_the_window_at_the_render_scale() {
+        assert_eq!(Framebuffer::size_for(1300, 900, 2, 1.0), (650, 450));
+        assert_eq!(Framebuffer::size_for(1, 1, 3, 1.0), (1, 1));
+        // a retina window has twice the pixels of its logical size
+        assert_eq!(Framebuffer::size_for(1300, 900, 2, 2.0), (1300, 900));
+        assert_eq!(Framebuffer::size_for(1000, 600, 1, 1.25), (1250, 750));
+        let mut fb = Framebuffer::new(4, 4);
+        fb.resize(8, 2);
+        assert_eq!((fb.width, fb.height), (8, 2));
+        assert_eq!((fb.color_buffer.width(), fb.color_buffer.height()), (8, 2));
+        fb.set_current_color(Color::RED);
+        fb.set_pixel(7, 1);
+        assert_eq!(fb.get_pixel(7, 1), Color::RED);
+    }
+
+    #[test]
+    fn gamma_lut_keeps_endpoints_and_lifts_midtones() {
+        let identity = gamma_lut(1.0);
+        assert!(identity.iter().enumerate().all(|(i, &v)| v as usize == i));
+        let bright = gamma_lut(2.0);
+        assert_eq!((bright[0], bright[255]), (0, 255));
+        assert!(bright[64] > 64);
+        assert!(gamma_lut(0.5)[64] < 64);
+    }
+
+    #[test]
+    fn clipped_drawing_stays_inside_its_viewport() {
+        let mut rng = crate::rng::Rng::new(11);
+        let mut fb = Framebuffer::new(24, 16);
+        let mut lines = Framebuffer::new(24, 16);
+        lines.set_current_color(Color::WHITE);
+        let viewport = Rect::new(4, 3, 12, 8);
+        for _ in 0..300 {
+            let mut n = |lo: f32, hi: f32| rng.range_f32(lo, hi) as i32;
+            let r = Rect::new(n(-30.0, 40.0), n(-30.0, 40.0), n(-5.0, 30.0), n(-5.0, 30.0));
+            fb.fill_rect_in(&viewport, r, Color::WHITE);
+            // one end around the buffer, the other far off-screen: clipped before walking, so
+            // no panic and no long loop
+            let a = Vector2::new(n(-10.0, 34.0) as f32, n(-10.0, 26.0) as f32);
+            let b = Vector2::new(n(-500.0, 500.0) as f32, n(-500.0, 500.0) as f32);
+            crate::line::line_in(&mut lines, &viewport, a, b);
+        }
+        let mut drawn = [0, 0];
+        for y in 0..16 {
+            for x in 0..24 {
+                for (i, buf) in [&mut fb, &mut lines].into_iter().enumerate() {
+                    let c = buf.get_pixel(x as u32, y as u32);
+                    if viewport.contains(x, y) {
+                        drawn[i] += (c == Color::WHITE) as usize;
+                    } else {
+                        assert_eq!(c, Color::BLACK, "({}, {}) in buffer {}", x, y, i);
+                    }
+                }
             }
-            
-            // Draw coin counter
-            let coins_text = format!("Monedas: {}/{}", coins_collected, total_coins);
-            renderer.draw_rectangle(screen_w - 210, 10, 200, 30, Color::new(0, 0, 0, 120));
-            renderer.draw_text(&coins_text, screen_w - 200, 20, 24, Color::GOLD);
-            
-            // Draw level indicator
-            let level_text = format!("Nivel: {}", current_level);
-            renderer.draw_rectangle(screen_w / 2 - 50, 10, 100, 30, Color::new(0, 0, 0, 120));
-            renderer.draw_text(&level_text, screen_w / 2 - 40, 20, 24, Color::CYAN);
         }
+        // both actually drew something inside the viewport
+        assert!(drawn[0] > 0 && drawn[1] > 0, "{:?}", drawn);
+        // blit only samples the visible part, relative to the destination's corner
+        let mut asked = Vec::new();
+        fb.blit(Rect::new(22, 14, 4, 4), |x, y| {
+            asked.push((x, y));
+            Color::RED
+        });
+        assert_eq!(asked, [(0, 0), (1, 0), (0, 1), (1, 1)]);
+        assert_eq!(fb.get_pixel(23, 15), Color::RED);
     }
 }
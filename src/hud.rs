@@ -0,0 +1,537 @@
+// hud.rs - small bits of state behind the HUD drawn in Framebuffer::swap_buffers_with_coins
+
+use std::f32::consts::PI;
+use raylib::prelude::*;
+use crate::anim::{ease, Tween};
+use crate::caster::cast_ray;
+use crate::framebuffer::Framebuffer;
+use crate::maze::{self, CellKind, Maze};
+use crate::player::Player;
+use crate::shapes::fill_triangle;
+use crate::sprite::Coin;
+
+// Coin counter that "counts up" toward the real total instead of jumping on pickup.
+// The shown value eases toward the target (fast at first, slowing down near it) and snaps
+// once it is close enough; a lower target (new level / restart) snaps immediately.
+// Each pickup also starts a "+1" popup next to the counter.
+pub struct CoinTally {
+    displayed: f32,
+    target: usize,
+    // coins gained since the popup (re)started, and its rise from 0 to 1
+    gained: usize,
+    popup: Tween,
+}
+
+impl CoinTally {
+    // how quickly the shown value closes the gap (1/s); ~0.35 s to reach a new total
+    const RATE: f32 = 12.0;
+    const SNAP: f32 = 0.05;
+    const POPUP_SECS: f32 = 0.8;
+
+    pub fn new() -> Self {
+        Self { displayed: 0.0, target: 0, gained: 0, popup: Tween::settled(1.0) }
+    }
+
+    pub fn update(&mut self, target: usize, dt: f32) {
+        self.popup.advance(dt);
+        if target > self.target {
+            // pickups while it is still up add to it
+            self.gained = if self.popup.is_done() { 0 } else { self.gained } + target - self.target;
+            self.popup = Tween::new(0.0, 1.0, Self::POPUP_SECS, ease::out_cubic);
+        }
+        self.target = target;
+        let target = target as f32;
+        if target < self.displayed {
+            self.displayed = target;
+            return;
+        }
+        let t = 1.0 - (-Self::RATE * dt.max(0.0)).exp();
+        self.displayed += (target - self.displayed) * t;
+        if (target - self.displayed).abs() < Self::SNAP {
+            self.displayed = target;
+        }
+    }
+
+    // integer value to draw
+    pub fn shown(&self) -> usize {
+        self.displayed.round() as usize
+    }
+
+    // The "+N" popup while it is up: coins gained and how far it has risen (0..1).
+    pub fn popup(&self) -> Option<(usize, f32)> {
+        (!self.popup.is_done() && self.gained > 0).then(|| (self.gained, self.popup.value()))
+    }
+}
+
+// Time spent on the current level, excluding pauses. Started on the player's first move and
+// stopped when the level ends; all methods take the current clock (window.get_time()).
+#[derive(Clone, Debug, Default)]
+pub struct RunTimer {
+    started_at: Option<f64>,
+    // when the current pause began, and the total of the finished pauses
+    paused_at: Option<f64>,
+    paused_total: f64,
+    stopped_at: Option<f64>,
+}
+
+impl RunTimer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // already running with `elapsed` seconds counted (a resumed savegame)
+    pub fn resumed(elapsed: f64, now: f64) -> Self {
+        Self { started_at: Some(now - elapsed.max(0.0)), ..Self::default() }
+    }
+
+    // start counting (only the first call counts)
+    pub fn start(&mut self, now: f64) {
+        if self.started_at.is_none() {
+            self.started_at = Some(now);
+        }
+    }
+
+    pub fn pause(&mut self, now: f64) {
+        if self.is_running() && self.paused_at.is_none() {
+            self.paused_at = Some(now);
+        }
+    }
+
+    pub fn resume(&mut self, now: f64) {
+        if let Some(at) = self.paused_at.take() {
+            self.paused_total += now - at;
+        }
+    }
+
+    // freeze the time (victory); a stop while paused doesn't count the pause
+    pub fn stop(&mut self, now: f64) {
+        if self.is_running() {
+            self.stopped_at = Some(self.paused_at.unwrap_or(now));
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started_at.is_some() && self.stopped_at.is_none()
+    }
+
+    // seconds counted so far (0 before the start)
+    pub fn elapsed(&self, now: f64) -> f64 {
+        let Some(start) = self.started_at else {
+            return 0.0;
+        };
+        let end = self.stopped_at.or(self.paused_at).unwrap_or(now);
+        (end - start - self.paused_total).max(0.0)
+    }
+}
+
+// "mm:ss.t" (tenths truncated, so the shown time never runs ahead)
+pub fn format_run_time(secs: f64) -> String {
+    let tenths = (secs.max(0.0) * 10.0) as u64;
+    format!("{:02}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
+
+// A key that has to be held for `duration` seconds to act (quick restart), so a tap does
+// nothing. Fires once per press; the key must be released before it can fire again.
+pub struct HoldToConfirm {
+    duration: f32,
+    held: f32,
+    fired: bool,
+}
+
+impl HoldToConfirm {
+    pub fn new(duration: f32) -> Self {
+        Self { duration, held: 0.0, fired: false }
+    }
+
+    // true on the frame the hold completes
+    pub fn update(&mut self, down: bool, dt: f32) -> bool {
+        if !down {
+            self.held = 0.0;
+            self.fired = false;
+            return false;
+        }
+        if self.fired {
+            return false;
+        }
+        self.held += dt;
+        self.fired = self.held >= self.duration;
+        self.fired
+    }
+
+    // 0..1 while the key is held toward firing, None otherwise (hides the HUD ring)
+    pub fn progress(&self) -> Option<f32> {
+        (self.held > 0.0 && !self.fired).then(|| (self.held / self.duration).min(1.0))
+    }
+}
+
+// Arrow at the screen edge pointing toward the nearest exit once the doors are open.
+// Shown while toggled on with the hint key, or automatically after wandering `delay`
+// seconds with the doors open (delay 0 = only with the key).
+pub struct ExitHint {
+    forced: bool,
+    wander: f32,
+}
+
+impl ExitHint {
+    pub fn new() -> Self {
+        Self { forced: false, wander: 0.0 }
+    }
+
+    pub fn toggle(&mut self) {
+        self.forced = !self.forced;
+    }
+
+    pub fn update(&mut self, doors_open: bool, dt: f32) {
+        self.wander = if doors_open { self.wander + dt } else { 0.0 };
+    }
+
+    pub fn active(&self, doors_open: bool, delay: f32) -> bool {
+        doors_open && (self.forced || (delay > 0.0 && self.wander >= delay))
+    }
+}
+
+// Angle of the nearest exit relative to the view direction (-PI..PI, positive = to the right),
+// or None when there is no exit or it is already on screen (inside the FOV and not hidden).
+pub fn exit_hint_angle(fb: &mut Framebuffer, maze: &Maze, player: &Player, block_size: usize) -> Option<f32> {
+    let exit = nearest_exit(maze, player, block_size)?;
+    let rel = player.relative_angle(exit);
+    if rel.abs() <= player.fov / 2.0 && CellKind::of(cast_ray(fb, maze, player, player.a + rel, block_size, false, true).impact).is_door() {
+        return None;
+    }
+    Some(rel)
+}
+
+fn nearest_exit(maze: &Maze, player: &Player, block_size: usize) -> Option<Vector2> {
+    let bs = block_size as f32;
+    maze::exits(maze)
+        .into_iter()
+        .map(|(i, j)| Vector2::new((i as f32 + 0.5) * bs, (j as f32 + 0.5) * bs))
+        .min_by(|a, b| player.distance_to(*a).total_cmp(&player.distance_to(*b)))
+}
+
+// Point `margin` pixels inside the edge of a `width` x `height` screen in the direction
+// `rel_angle` (clockwise from up) from its center; straight ahead = top, behind = bottom.
+pub fn edge_point(width: u32, height: u32, rel_angle: f32, margin: f32) -> (f32, f32) {
+    let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+    let (dx, dy) = (rel_angle.sin(), -rel_angle.cos());
+    // push the center out along (dx, dy) until it meets the inset screen rectangle
+    let reach_x = if dx.abs() > 1e-4 { (cx - margin).max(0.0) / dx.abs() } else { f32::INFINITY };
+    let reach_y = if dy.abs() > 1e-4 { (cy - margin).max(0.0) / dy.abs() } else { f32::INFINITY };
+    let reach = reach_x.min(reach_y);
+    (cx + dx * reach, cy + dy * reach)
+}
+
+// Gold triangle near the framebuffer edge pointing toward the exit.
+pub fn draw_exit_hint(fb: &mut Framebuffer, rel_angle: f32) {
+    fill_arrow(fb, edge_point(fb.width, fb.height, rel_angle, 28.0), rel_angle, 10.0, Color::GOLD);
+}
+
+// Triangle of half-size `size` centered on `center`, tip pointing `angle` clockwise from up.
+pub fn fill_arrow(fb: &mut Framebuffer, center: (f32, f32), angle: f32, size: f32, color: Color) {
+    let (px, py) = center;
+    let (dx, dy) = (angle.sin(), -angle.cos());
+    let tip = (px + dx * size, py + dy * size);
+    let left = (px - dx * size - dy * size, py - dy * size + dx * size);
+    let right = (px - dx * size + dy * size, py - dy * size - dx * size);
+
+    fill_triangle(fb, tip, left, right, color);
+}
+
+// When the objective arrow is shown. Auto leaves it off on DIFÍCIL, where finding the coins
+// is the challenge.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ObjectiveArrow {
+    Auto,
+    On,
+    Off,
+}
+
+// the DIFÍCIL level
+pub const HARD_LEVEL: i32 = 3;
+
+impl ObjectiveArrow {
+    pub fn code(self) -> &'static str {
+        match self {
+            ObjectiveArrow::Auto => "auto",
+            ObjectiveArrow::On => "on",
+            ObjectiveArrow::Off => "off",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<ObjectiveArrow> {
+        match code.trim().to_lowercase().as_str() {
+            "auto" => Some(ObjectiveArrow::Auto),
+            "on" => Some(ObjectiveArrow::On),
+            "off" => Some(ObjectiveArrow::Off),
+            _ => None,
+        }
+    }
+
+    pub fn shown_on(self, level: i32) -> bool {
+        match self {
+            ObjectiveArrow::Auto => level != HARD_LEVEL,
+            ObjectiveArrow::On => true,
+            ObjectiveArrow::Off => false,
+        }
+    }
+}
+
+// What the objective arrow points at: the nearest uncollected coin until the doors open,
+// then the nearest exit. Returns (angle relative to the view, distance in cells).
+pub fn objective_bearing(maze: &Maze, coins: &[Coin], doors_open: bool, player: &Player, block_size: usize) -> Option<(f32, f32)> {
+    let target = if doors_open {
+        nearest_exit(maze, player, block_size)?
+    } else {
+        coins
+            .iter()
+            .filter(|c| !c.collected)
+            .map(|c| c.pos)
+            .min_by(|a, b| player.distance_to(*a).total_cmp(&player.distance_to(*b)))?
+    };
+    Some((player.relative_angle(target), player.distance_to(target) / block_size as f32))
+}
+
+// framebuffer height fraction of the objective arrow's center, below the top HUD rows
+pub const OBJECTIVE_ARROW_Y: f32 = 0.2;
+pub const OBJECTIVE_ARROW_SIZE: f32 = 7.0;
+
+// Small triangle at the top center rotated toward the objective (up = straight ahead); the
+// distance is labeled under it on screen (Framebuffer::set_objective_distance).
+pub fn draw_objective_arrow(fb: &mut Framebuffer, rel_angle: f32) {
+    let center = (fb.width as f32 / 2.0, fb.height as f32 * OBJECTIVE_ARROW_Y);
+    fill_arrow(fb, center, rel_angle, OBJECTIVE_ARROW_SIZE, Color::SKYBLUE);
+}
+
+// Compass strip: degrees of heading visible across its width, and spacing of the ticks.
+pub const COMPASS_SPAN_DEG: f32 = 180.0;
+const COMPASS_TICK_DEG: i32 = 15;
+
+// Compass heading in degrees (0..360, 0 = north = up on the minimap, 90 = east) for a view
+// angle. Screen y grows downwards, so north is angle -PI/2 and angles increase clockwise.
+pub fn compass_bearing(yaw: f32) -> f32 {
+    (yaw.to_degrees() + 90.0).rem_euclid(360.0)
+}
+
+// Ticks visible on the compass strip for view angle `yaw`: (offset from the strip center as
+// a fraction of its width, -0.5..=0.5, label for the cardinal and intercardinal points).
+// Offsets come from the bearing difference wrapped to -180..180, so the strip scrolls
+// seamlessly past north.
+pub fn compass_marks(yaw: f32) -> Vec<(f32, Option<&'static str>)> {
+    const LABELS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+    let heading = compass_bearing(yaw);
+    (0..360)
+        .step_by(COMPASS_TICK_DEG as usize)
+        .filter_map(|b| {
+            let rel = (b as f32 - heading + 180.0).rem_euclid(360.0) - 180.0;
+            let offset = rel / COMPASS_SPAN_DEG;
+            let label = (b % 45 == 0).then(|| LABELS[(b / 45) as usize]);
+            (offset.abs() <= 0.5).then_some((offset, label))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player_at(x: f32, y: f32, a: f32) -> Player {
+        Player { pos: Vector2::new(x, y), a, fov: PI / 3.0, vel: Vector2::zero() }
+    }
+
+    #[test]
+    fn run_timer_excludes_pauses_and_freezes_on_stop() {
+        let mut t = RunTimer::new();
+        assert_eq!(t.elapsed(5.0), 0.0);
+        // pausing before the first move does nothing
+        t.pause(1.0);
+        t.resume(2.0);
+        t.start(10.0);
+        t.start(11.0);
+        assert_eq!(t.elapsed(12.0), 2.0);
+        t.pause(13.0);
+        assert_eq!(t.elapsed(20.0), 3.0);
+        t.pause(21.0);
+        t.resume(23.0);
+        assert_eq!(t.elapsed(24.0), 4.0);
+        t.stop(26.0);
+        assert!(!t.is_running());
+        assert_eq!(t.elapsed(100.0), 6.0);
+        // stopping while paused ends the run where the pause began
+        let mut t = RunTimer::new();
+        t.start(0.0);
+        t.pause(5.0);
+        t.stop(9.0);
+        assert_eq!(t.elapsed(9.0), 5.0);
+        // a resumed savegame keeps counting from the saved time
+        let mut t = RunTimer::resumed(40.0, 100.0);
+        t.start(101.0);
+        assert_eq!(t.elapsed(102.0), 42.0);
+    }
+
+    #[test]
+    fn hold_fires_once_after_the_duration() {
+        let mut hold = HoldToConfirm::new(0.5);
+        assert!(!hold.update(true, 0.3));
+        assert_eq!(hold.progress(), Some(0.6));
+        // releasing early starts over
+        assert!(!hold.update(false, 0.1));
+        assert_eq!(hold.progress(), None);
+        assert!(!hold.update(true, 0.3));
+        assert!(hold.update(true, 0.3));
+        // still held: no second restart, and the ring is gone
+        assert!(!hold.update(true, 1.0));
+        assert_eq!(hold.progress(), None);
+        hold.update(false, 0.1);
+        assert!(!hold.update(true, 0.1));
+    }
+
+    #[test]
+    fn run_time_format() {
+        assert_eq!(format_run_time(0.0), "00:00.0");
+        assert_eq!(format_run_time(65.37), "01:05.3");
+        assert_eq!(format_run_time(3599.99), "59:59.9");
+        assert_eq!(format_run_time(-1.0), "00:00.0");
+    }
+
+    #[test]
+    fn tally_counts_up_then_snaps() {
+        let mut tally = CoinTally::new();
+        tally.update(3, 1.0 / 60.0);
+        assert!(tally.shown() < 3);
+        for _ in 0..120 {
+            tally.update(3, 1.0 / 60.0);
+        }
+        assert_eq!(tally.shown(), 3);
+    }
+
+    #[test]
+    fn lower_target_snaps_immediately() {
+        let mut tally = CoinTally::new();
+        for _ in 0..120 {
+            tally.update(5, 1.0 / 60.0);
+        }
+        tally.update(0, 1.0 / 60.0);
+        assert_eq!(tally.shown(), 0);
+    }
+
+    #[test]
+    fn pickups_pop_up_and_add_up_until_it_settles() {
+        let mut tally = CoinTally::new();
+        assert_eq!(tally.popup(), None);
+        tally.update(1, 0.0);
+        assert_eq!(tally.popup(), Some((1, 0.0)));
+        tally.update(1, 0.2);
+        let (gained, rise) = tally.popup().unwrap();
+        // out-cubic: already most of the way up after a quarter of the time
+        assert!(gained == 1 && rise > 0.5);
+        tally.update(3, 0.1);
+        assert_eq!(tally.popup(), Some((3, 0.0)));
+        tally.update(3, 1.0);
+        assert_eq!(tally.popup(), None);
+        tally.update(4, 0.0);
+        assert_eq!(tally.popup().map(|p| p.0), Some(1));
+    }
+
+    #[test]
+    fn exit_hint_hides_when_exit_is_visible() {
+        let maze: Maze = vec![
+            "+---+".chars().collect(),
+            "|   G".chars().collect(),
+            "+---+".chars().collect(),
+        ];
+        let mut fb = Framebuffer::new(64, 48);
+        // facing the exit (east): on screen, no arrow
+        assert_eq!(exit_hint_angle(&mut fb, &maze, &player_at(150.0, 150.0, 0.0), 100), None);
+        // facing away: arrow points behind
+        let rel = exit_hint_angle(&mut fb, &maze, &player_at(150.0, 150.0, PI), 100).unwrap();
+        assert!((rel.abs() - PI).abs() < 1e-3);
+    }
+
+    #[test]
+    fn objective_is_the_nearest_coin_then_the_exit() {
+        let maze: Maze = vec![
+            "+-----+".chars().collect(),
+            "|    G".chars().collect(),
+            "+-----+".chars().collect(),
+        ];
+        let coin = |x: f32, collected: bool| Coin { collected, ..Coin::new(x, 150.0) };
+        let coins = [coin(450.0, false), coin(250.0, true), coin(50.0, false)];
+        let player = player_at(150.0, 150.0, 0.0);
+        // the collected coin is skipped; of the others the one behind is nearer
+        let (rel, cells) = objective_bearing(&maze, &coins, false, &player, 100).unwrap();
+        assert!((rel.abs() - PI).abs() < 1e-3 && (cells - 1.0).abs() < 1e-4);
+        // doors open: the exit, straight ahead
+        let (rel, cells) = objective_bearing(&maze, &coins, true, &player, 100).unwrap();
+        assert!(rel.abs() < 1e-3 && (cells - 4.0).abs() < 1e-4);
+        assert_eq!(objective_bearing(&maze, &[], false, &player, 100), None);
+    }
+
+    #[test]
+    fn objective_arrow_defaults_off_on_the_hard_level() {
+        assert!(ObjectiveArrow::Auto.shown_on(1) && !ObjectiveArrow::Auto.shown_on(HARD_LEVEL));
+        assert!(ObjectiveArrow::On.shown_on(HARD_LEVEL));
+        assert!(!ObjectiveArrow::Off.shown_on(1));
+        assert_eq!(ObjectiveArrow::from_code(" ON "), Some(ObjectiveArrow::On));
+        assert_eq!(ObjectiveArrow::from_code("maybe"), None);
+    }
+
+    fn mark_offset(yaw: f32, label: &str) -> Option<f32> {
+        compass_marks(yaw).into_iter().find(|(_, l)| *l == Some(label)).map(|(o, _)| o)
+    }
+
+    #[test]
+    fn compass_centers_the_facing_direction() {
+        // facing up the minimap (-y) is north, with east on the right half of the strip
+        assert!((compass_bearing(-PI / 2.0)).abs() < 1e-3);
+        assert!(mark_offset(-PI / 2.0, "N").unwrap().abs() < 1e-4);
+        assert!((mark_offset(-PI / 2.0, "E").unwrap() - 0.5).abs() < 1e-4);
+        assert!((mark_offset(-PI / 2.0, "W").unwrap() + 0.5).abs() < 1e-4);
+        assert_eq!(mark_offset(-PI / 2.0, "S"), None);
+        // facing +x is east
+        assert!(mark_offset(0.0, "E").unwrap().abs() < 1e-4);
+    }
+
+    #[test]
+    fn compass_wraps_seamlessly_past_north() {
+        let north = -PI / 2.0;
+        let step = 1f32.to_radians();
+        // one degree either side of north: N moves by the same small amount, no jump
+        let left = mark_offset(north - step, "N").unwrap();
+        let right = mark_offset(north + step, "N").unwrap();
+        assert!((left - 1.0 / COMPASS_SPAN_DEG).abs() < 1e-4);
+        assert!((right + 1.0 / COMPASS_SPAN_DEG).abs() < 1e-4);
+        // a full turn shows the same strip
+        let a = compass_marks(0.3);
+        let b = compass_marks(0.3 + 2.0 * PI);
+        assert_eq!(a.len(), b.len());
+        assert!(a.iter().zip(&b).all(|(x, y)| (x.0 - y.0).abs() < 1e-3 && x.1 == y.1));
+    }
+
+    #[test]
+    fn exit_hint_waits_for_wandering_delay() {
+        let mut hint = ExitHint::new();
+        hint.update(true, 10.0);
+        assert!(!hint.active(true, 30.0));
+        hint.update(true, 25.0);
+        assert!(hint.active(true, 30.0));
+        assert!(!hint.active(false, 30.0));
+        hint.update(false, 1.0);
+        assert!(!hint.active(true, 30.0));
+        hint.toggle();
+        assert!(hint.active(true, 0.0));
+    }
+
+    #[test]
+    fn edge_point_meets_the_inset_screen_edge() {
+        let close = |(x, y): (f32, f32), (ex, ey): (f32, f32)| (x - ex).abs() < 1e-3 && (y - ey).abs() < 1e-3;
+        // 200x100 screen, 10 px margin: ahead = top, right = right edge, behind = bottom
+        assert!(close(edge_point(200, 100, 0.0, 10.0), (100.0, 10.0)));
+        assert!(close(edge_point(200, 100, PI / 2.0, 10.0), (190.0, 50.0)));
+        assert!(close(edge_point(200, 100, PI, 10.0), (100.0, 90.0)));
+        assert!(close(edge_point(200, 100, -PI / 2.0, 10.0), (10.0, 50.0)));
+        // 45° to the right on a wide screen hits the top edge, right of center
+        assert!(close(edge_point(200, 100, PI / 4.0, 10.0), (140.0, 10.0)));
+        // a margin larger than the screen collapses to the center
+        assert!(close(edge_point(10, 10, 1.0, 40.0), (5.0, 5.0)));
+    }
+}
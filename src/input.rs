@@ -0,0 +1,262 @@
+// input.rs - keyboard + gamepad input helpers shared by menus and end screens
+
+use raylib::prelude::*;
+use crate::events::GameEvent;
+
+// gamepad slot used for menus (first connected pad)
+const MENU_GAMEPAD: i32 = 0;
+// left stick deflection needed to count as a direction
+const STICK_THRESHOLD: f32 = 0.5;
+// holding the stick: first repeat after STICK_REPEAT_DELAY, then every STICK_REPEAT_RATE seconds
+const STICK_REPEAT_DELAY: f32 = 0.40;
+const STICK_REPEAT_RATE: f32 = 0.12;
+
+// Per-frame menu actions merged from keyboard and gamepad.
+// Keep one instance alive for the whole menu loop so stick auto-repeat timing works.
+pub struct MenuInput {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub confirm: bool, // ENTER / gamepad A
+    pub back: bool,    // ESC / gamepad B
+    pub start: bool,   // gamepad Start
+    pub quit: bool,    // Q / gamepad Select
+    stick_dir: (i32, i32),
+    stick_held: f32,
+    stick_next_repeat: f32,
+}
+
+impl MenuInput {
+    pub fn new() -> Self {
+        Self {
+            up: false,
+            down: false,
+            left: false,
+            right: false,
+            confirm: false,
+            back: false,
+            start: false,
+            quit: false,
+            stick_dir: (0, 0),
+            stick_held: 0.0,
+            stick_next_repeat: 0.0,
+        }
+    }
+
+    // Read this frame's input. Call exactly once per frame before checking the flags.
+    pub fn poll(&mut self, window: &RaylibHandle) {
+        let dt = window.get_frame_time();
+        self.up = window.is_key_pressed(KeyboardKey::KEY_UP) || window.is_key_pressed(KeyboardKey::KEY_W);
+        self.down = window.is_key_pressed(KeyboardKey::KEY_DOWN) || window.is_key_pressed(KeyboardKey::KEY_S);
+        self.left = window.is_key_pressed(KeyboardKey::KEY_LEFT) || window.is_key_pressed(KeyboardKey::KEY_A);
+        self.right = window.is_key_pressed(KeyboardKey::KEY_RIGHT) || window.is_key_pressed(KeyboardKey::KEY_D);
+        self.confirm = window.is_key_pressed(KeyboardKey::KEY_ENTER);
+        self.back = window.is_key_pressed(KeyboardKey::KEY_ESCAPE);
+        self.start = false;
+        self.quit = window.is_key_pressed(KeyboardKey::KEY_Q);
+
+        if !window.is_gamepad_available(MENU_GAMEPAD) {
+            self.stick_dir = (0, 0);
+            return;
+        }
+
+        let pressed = |b: GamepadButton| window.is_gamepad_button_pressed(MENU_GAMEPAD, b);
+        self.up |= pressed(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP);
+        self.down |= pressed(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN);
+        self.left |= pressed(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT);
+        self.right |= pressed(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT);
+        self.confirm |= pressed(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN);
+        self.back |= pressed(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT);
+        self.start = pressed(GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT);
+        self.quit |= pressed(GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT);
+
+        // left stick as a d-pad with repeat-delay so holding it doesn't scroll uncontrollably
+        let ax = window.get_gamepad_axis_movement(MENU_GAMEPAD, GamepadAxis::GAMEPAD_AXIS_LEFT_X);
+        let ay = window.get_gamepad_axis_movement(MENU_GAMEPAD, GamepadAxis::GAMEPAD_AXIS_LEFT_Y);
+        let dir = if ay.abs() >= ax.abs() {
+            (0, if ay <= -STICK_THRESHOLD { -1 } else if ay >= STICK_THRESHOLD { 1 } else { 0 })
+        } else {
+            (if ax <= -STICK_THRESHOLD { -1 } else if ax >= STICK_THRESHOLD { 1 } else { 0 }, 0)
+        };
+        if self.stick_repeat(dir, dt) {
+            self.up |= dir.1 < 0;
+            self.down |= dir.1 > 0;
+            self.left |= dir.0 < 0;
+            self.right |= dir.0 > 0;
+        }
+    }
+
+    // true on the frame the stick enters a direction and then at the repeat rate while held
+    fn stick_repeat(&mut self, dir: (i32, i32), dt: f32) -> bool {
+        if dir == (0, 0) {
+            self.stick_dir = dir;
+            return false;
+        }
+        if dir != self.stick_dir {
+            self.stick_dir = dir;
+            self.stick_held = 0.0;
+            self.stick_next_repeat = STICK_REPEAT_DELAY;
+            return true;
+        }
+        self.stick_held += dt;
+        if self.stick_held >= self.stick_next_repeat {
+            self.stick_next_repeat += STICK_REPEAT_RATE;
+            return true;
+        }
+        false
+    }
+}
+
+// Parse a key name from settings.toml ("N", "7", "F3", "SPACE", "TAB") into a raylib key.
+// Letters and digits use their ASCII code, which is also raylib's key code.
+pub fn key_from_name(name: &str) -> Option<KeyboardKey> {
+    let name = name.trim().to_uppercase();
+    let code = match name.as_str() {
+        "SPACE" => 32,
+        "TAB" => 258,
+        "BACKSPACE" => 259,
+        _ => {
+            let mut chars = name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_alphanumeric() => c as i32,
+                (Some('F'), Some(_)) => match name[1..].parse::<i32>() {
+                    Ok(n @ 1..=12) => 289 + n,
+                    _ => return None,
+                },
+                _ => return None,
+            }
+        }
+    };
+    key_from_i32(code)
+}
+
+// Key bindings for in-game hotkeys, read from the `[keys]` section of settings.
+pub struct Bindings {
+    pub mute: KeyboardKey,
+    pub pause: KeyboardKey,
+    pub hint: KeyboardKey,
+    pub compass: KeyboardKey,
+    pub mirror: KeyboardKey,
+    // held (hud::HoldToConfirm) to restart the level
+    pub restart: KeyboardKey,
+    // while paused: save the run (savegame) and quit
+    pub save: KeyboardKey,
+}
+
+impl Bindings {
+    pub fn from_settings(settings: &crate::settings::Settings) -> Self {
+        Self {
+            mute: Self::key_or(&settings.key_mute, "mute", KeyboardKey::KEY_N),
+            pause: Self::key_or(&settings.key_pause, "pause", KeyboardKey::KEY_P),
+            hint: Self::key_or(&settings.key_hint, "hint", KeyboardKey::KEY_H),
+            compass: Self::key_or(&settings.key_compass, "compass", KeyboardKey::KEY_C),
+            mirror: Self::key_or(&settings.key_mirror, "mirror", KeyboardKey::KEY_V),
+            restart: Self::key_or(&settings.key_restart, "restart", KeyboardKey::KEY_R),
+            save: Self::key_or(&settings.key_save, "save", KeyboardKey::KEY_G),
+        }
+    }
+
+    fn key_or(name: &str, action: &str, default: KeyboardKey) -> KeyboardKey {
+        key_from_name(name).unwrap_or_else(|| {
+            eprintln!("[warn] settings: unknown key '{}' for {}, using {:?}", name, action, default);
+            default
+        })
+    }
+}
+
+// Gamepad vibration: (seconds, low-frequency motor, high-frequency motor), motors 0.0..=1.0.
+pub type RumblePattern = (f32, f32, f32);
+
+// caught by an NPC: strong and short
+pub const RUMBLE_DAMAGE: RumblePattern = (0.35, 1.0, 0.8);
+// coin pickup: a tiny tick on the light motor
+pub const RUMBLE_COIN: RumblePattern = (0.06, 0.0, 0.3);
+// heartbeat: the low motor ramps up to this as an NPC closes in, renewed every frame
+const HEARTBEAT_RUMBLE_MAX: f32 = 0.35;
+const HEARTBEAT_PULSE_SECS: f32 = 0.1;
+
+pub fn rumble_for(event: &GameEvent) -> Option<RumblePattern> {
+    match event {
+        GameEvent::PlayerDied => Some(RUMBLE_DAMAGE),
+        GameEvent::CoinCollected(_) => Some(RUMBLE_COIN),
+        _ => None,
+    }
+}
+
+// Low rumble for the heartbeat's proximity level (0 = nobody near, 1 = NPC adjacent).
+pub fn heartbeat_rumble(proximity: f32) -> Option<RumblePattern> {
+    let level = proximity.clamp(0.0, 1.0);
+    (level > 0.0).then_some((HEARTBEAT_PULSE_SECS, level * HEARTBEAT_RUMBLE_MAX, 0.0))
+}
+
+// Vibration on the first gamepad, fed from the GameEvent stream. Does nothing when turned off
+// in settings or with no gamepad connected. The heartbeat rumble waits for event patterns to end.
+pub struct Rumble {
+    enabled: bool,
+    // seconds left of the last event pattern
+    busy: f32,
+}
+
+impl Rumble {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled, busy: 0.0 }
+    }
+
+    pub fn play(&mut self, window: &mut RaylibHandle, pattern: RumblePattern) {
+        let (secs, low, high) = pattern;
+        if !self.enabled || !window.is_gamepad_available(MENU_GAMEPAD) {
+            return;
+        }
+        window.set_gamepad_vibration(MENU_GAMEPAD, low, high, secs);
+        self.busy = secs;
+    }
+
+    pub fn handle(&mut self, window: &mut RaylibHandle, event: &GameEvent) {
+        if let Some(pattern) = rumble_for(event) {
+            self.play(window, pattern);
+        }
+    }
+
+    // Once per frame while playing, with the heartbeat's proximity level.
+    pub fn heartbeat(&mut self, window: &mut RaylibHandle, proximity: f32) {
+        self.busy = (self.busy - window.get_frame_time()).max(0.0);
+        if self.busy > 0.0 {
+            return;
+        }
+        if let Some(pattern) = heartbeat_rumble(proximity) {
+            self.play(window, pattern);
+            // a heartbeat pulse never blocks the next frame's
+            self.busy = 0.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rumble_patterns_follow_the_events() {
+        assert_eq!(rumble_for(&GameEvent::PlayerDied), Some(RUMBLE_DAMAGE));
+        assert_eq!(rumble_for(&GameEvent::CoinCollected(3)), Some(RUMBLE_COIN));
+        assert_eq!(rumble_for(&GameEvent::Footstep), None);
+        // damage is the strongest and longest pattern
+        assert!(RUMBLE_DAMAGE.0 > RUMBLE_COIN.0 && RUMBLE_DAMAGE.1 > RUMBLE_COIN.1);
+        assert_eq!(heartbeat_rumble(0.0), None);
+        assert_eq!(heartbeat_rumble(-0.5), None);
+        let (_, near, _) = heartbeat_rumble(1.0).unwrap();
+        let (_, far, _) = heartbeat_rumble(0.2).unwrap();
+        assert!(near > far && near <= HEARTBEAT_RUMBLE_MAX);
+    }
+
+    #[test]
+    fn key_names_parse() {
+        assert_eq!(key_from_name("n"), Some(KeyboardKey::KEY_N));
+        assert_eq!(key_from_name("7"), Some(KeyboardKey::KEY_SEVEN));
+        assert_eq!(key_from_name("F3"), Some(KeyboardKey::KEY_F3));
+        assert_eq!(key_from_name("space"), Some(KeyboardKey::KEY_SPACE));
+        assert_eq!(key_from_name("F13"), None);
+        assert_eq!(key_from_name("??"), None);
+    }
+}
@@ -1,6 +1,7 @@
-// line.rs
+// line.rs - hard-edged Bresenham lines, and anti-aliased ones (Xiaolin Wu) for thin overlays
 
 use raylib::prelude::*;
+use crate::clip::{clip_line, Rect};
 use crate::framebuffer::Framebuffer;
 
 pub fn line(
@@ -8,10 +9,17 @@ pub fn line(
     start: Vector2,
     end: Vector2,
 ) {
-    let mut x0 = start.x as i32;
-    let mut y0 = start.y as i32;
-    let x1 = end.x as i32;
-    let y1 = end.y as i32;
+    let bounds = framebuffer.bounds();
+    line_in(framebuffer, &bounds, start, end);
+}
+
+// line clipped to a viewport `clip` (and the framebuffer).
+pub fn line_in(framebuffer: &mut Framebuffer, clip: &Rect, start: Vector2, end: Vector2) {
+    // only walk the part of the line inside the clip rect
+    let Some(clip) = clip.intersect(&framebuffer.bounds()) else { return };
+    let Some((mut x0, mut y0, x1, y1)) = clip_line(start.x as i32, start.y as i32, end.x as i32, end.y as i32, &clip) else {
+        return;
+    };
 
     let dx = (x1 - x0).abs();
     let dy = -(y1 - y0).abs();
@@ -20,13 +28,7 @@ pub fn line(
     let mut err = dx + dy;
 
     loop {
-        if x0 >= 0
-            && y0 >= 0
-            && (x0 as u32) < framebuffer.width
-            && (y0 as u32) < framebuffer.height
-        {
-            framebuffer.set_pixel(x0 as u32, y0 as u32);
-        }
+        framebuffer.set_pixel(x0 as u32, y0 as u32);
 
         if x0 == x1 && y0 == y1 {
             break;
@@ -42,3 +44,104 @@ pub fn line(
         }
     }
 }
+
+fn fpart(x: f32) -> f32 {
+    x - x.floor()
+}
+
+// Anti-aliased line from (x0, y0) to (x1, y1) in framebuffer pixels, pixel (x, y) covering
+// [x, x+1) x [y, y+1). Each step along the major axis splits `color` between the two pixels
+// the line passes between, by distance; the end pixels are weighted by how much of them the
+// line covers along the major axis.
+pub fn line_aa(framebuffer: &mut Framebuffer, x0: f32, y0: f32, x1: f32, y1: f32, color: Color) {
+    if !(x0.is_finite() && y0.is_finite() && x1.is_finite() && y1.is_finite()) {
+        return;
+    }
+    let length = (x1 - x0).hypot(y1 - y0);
+    // shorter than a pixel: one pixel at the midpoint, as faint as the line is short
+    if length < 1.0 {
+        let (mx, my) = ((x0 + x1) / 2.0, (y0 + y1) / 2.0);
+        framebuffer.blend_pixel(mx.floor() as i32, my.floor() as i32, color, length);
+        return;
+    }
+    // work in a frame where the major axis is x and the line runs left to right, with pixel
+    // centers on whole coordinates
+    let steep = (y1 - y0).abs() > (x1 - x0).abs();
+    let (mut a0, mut b0, mut a1, mut b1) = if steep { (y0, x0, y1, x1) } else { (x0, y0, x1, y1) };
+    if a0 > a1 {
+        std::mem::swap(&mut a0, &mut a1);
+        std::mem::swap(&mut b0, &mut b1);
+    }
+    let (a0, a1, b0, b1) = (a0 - 0.5, a1 - 0.5, b0 - 0.5, b1 - 0.5);
+    let mut plot = |a: i32, b: i32, coverage: f32| {
+        if steep { framebuffer.blend_pixel(b, a, color, coverage) } else { framebuffer.blend_pixel(a, b, color, coverage) }
+    };
+    let gradient = (b1 - b0) / (a1 - a0);
+    // the minor coordinate at pixel column `a`, and the coverage of column `a` along the major
+    // axis: 1 inside the line, partial at the ends
+    let b_at = |a: f32| b0 + gradient * (a - a0);
+    let (first, last) = (a0.round() as i32, a1.round() as i32);
+    let span = |a: i32| (a1.min(a as f32 + 0.5) - a0.max(a as f32 - 0.5)).clamp(0.0, 1.0);
+    for a in first..=last {
+        let b = b_at(a as f32);
+        let weight = span(a);
+        // horizontal and vertical lines on pixel centers: a single solid row or column
+        if gradient == 0.0 && fpart(b) == 0.0 {
+            plot(a, b as i32, weight);
+            continue;
+        }
+        let below = b.floor();
+        plot(a, below as i32, (1.0 - fpart(b)) * weight);
+        plot(a, below as i32 + 1, fpart(b) * weight);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alpha_at(fb: &mut Framebuffer, x: u32, y: u32) -> u8 {
+        // drawn white over black: the red channel is the coverage
+        fb.get_pixel(x, y).r
+    }
+
+    #[test]
+    fn diagonal_splits_coverage_between_neighbors() {
+        let mut fb = Framebuffer::new(12, 12);
+        // slope 1/2 through pixel centers: every other column lands exactly on a center, the
+        // ones between are split evenly across two rows
+        line_aa(&mut fb, 1.5, 1.5, 9.5, 5.5, Color::WHITE);
+        assert_eq!(alpha_at(&mut fb, 3, 2), 255);
+        assert_eq!(alpha_at(&mut fb, 4, 2), 128);
+        assert_eq!(alpha_at(&mut fb, 4, 3), 128);
+        assert_eq!(alpha_at(&mut fb, 5, 3), 255);
+        assert_eq!(alpha_at(&mut fb, 5, 2), 0);
+        // the end pixels are half covered along the line (it stops at their centers)
+        assert_eq!(alpha_at(&mut fb, 1, 1), 128);
+        assert_eq!(alpha_at(&mut fb, 9, 5), 128);
+        assert_eq!(alpha_at(&mut fb, 10, 5), 0);
+    }
+
+    #[test]
+    fn axis_aligned_lines_are_solid_on_pixel_centers() {
+        let mut fb = Framebuffer::new(8, 8);
+        line_aa(&mut fb, 1.0, 2.5, 6.0, 2.5, Color::WHITE);
+        assert!((1..6).all(|x| alpha_at(&mut fb, x, 2) == 255));
+        assert_eq!(alpha_at(&mut fb, 3, 1) as u32 + alpha_at(&mut fb, 3, 3) as u32, 0);
+        // vertical between two columns: half on each side
+        line_aa(&mut fb, 5.0, 4.0, 5.0, 7.0, Color::WHITE);
+        assert_eq!((alpha_at(&mut fb, 4, 5), alpha_at(&mut fb, 5, 5)), (128, 128));
+    }
+
+    #[test]
+    fn short_and_degenerate_lines() {
+        let mut fb = Framebuffer::new(4, 4);
+        line_aa(&mut fb, 1.2, 1.5, 1.7, 1.5, Color::WHITE);
+        assert_eq!(alpha_at(&mut fb, 1, 1), 128);
+        line_aa(&mut fb, 2.5, 2.5, 2.5, 2.5, Color::WHITE);
+        assert_eq!(alpha_at(&mut fb, 2, 2), 0);
+        // off-screen and NaN endpoints don't panic
+        line_aa(&mut fb, -10.0, -10.0, 20.0, 30.0, Color::WHITE);
+        line_aa(&mut fb, f32::NAN, 0.0, 3.0, 3.0, Color::WHITE);
+    }
+}
@@ -14,12 +14,41 @@ mod textures;
 mod menu;
 mod audio;
 mod anim;
+mod input;
+mod debug;
+mod settings;
+mod strings;
+mod rng;
+mod events;
+mod hud;
+mod exploration;
+mod timestep;
+mod progress;
+mod display;
+mod cli;
+mod savegame;
+mod doors;
+mod world;
+mod diagnostics;
+mod console;
+mod clip;
+mod profile;
+mod shapes;
+mod particles;
+mod victory;
+mod mirror;
+mod ui;
+mod cues;
 
 use line::line;
-use maze::{Maze,load_maze,load_maze_for_level};
+use maze::{Maze,load_maze};
 use caster::{cast_ray, Intersect};
 use framebuffer::Framebuffer;
-use player::{Player, process_events};
+use player::{Player, MoveInput, mouse_look};
+use events::GameEvent;
+use world::World;
+use anim::{ease, Tween};
+use strings::{tr, trf, Str};
 
 use raylib::prelude::*;
 use std::ffi::CString;
@@ -29,70 +58,291 @@ use std::env;
 use std::f32::consts::PI;
 
  
+// pixels per minimap cell (bigger minimap), and cells shown around the player; larger mazes scroll
+const MINIMAP_SCALE: usize = 14;
+const MINIMAP_VIEW_RADIUS: usize = 7;
+// seconds the view takes to fade back in after a quick restart
+const RESTART_FADE_SECS: f32 = 0.3;
 
-fn main() {
-    // Allow overriding resolution via command-line: cargo run -- <width> <height>
-    let args: Vec<String> = env::args().collect();
-    let mut window_width: i32 = 1300;
-    let mut window_height: i32 = 900;
-    if args.len() >= 3 {
-        match (args[1].parse::<i32>(), args[2].parse::<i32>()) {
-            (Ok(w), Ok(h)) => {
-                if w > 200 && h > 200 {
-                    window_width = w;
-                    window_height = h;
-                } else {
-                    eprintln!("[warn] provided resolution too small, using default {}x{}", window_width, window_height);
-                }
-            }
-            _ => {
-                eprintln!("[warn] invalid resolution arguments, expected two integers, using default {}x{}", window_width, window_height);
+// Per-level state that starts over whenever a level begins: on load, on the next level, after
+// the game over and victory screens, and with the quick-restart key.
+struct LevelStart {
+    // player, NPCs, coins and doors: the state simulated each tick
+    world: World,
+    // seconds left to show the "doors open" banner
+    doors_banner_timer: f32,
+    discovered: Vec<Vec<bool>>,
+    // level time shown in the HUD: from the first move to the exit, pauses excluded
+    run_timer: hud::RunTimer,
+}
+
+// `coin_goal` is the level's `coins=` metadata (LevelMeta::required_coins).
+fn start_level(maze: &Maze, block_size: usize, level: i32, coin_goal: Option<usize>, remember_exploration: bool) -> LevelStart {
+    let spawn = Player { pos: Vector2::new(150.0, 150.0), a: PI / 3.0, fov: PI / 3.0, vel: Vector2::zero() };
+    LevelStart {
+        world: World::new(maze, block_size, spawn, coin_goal),
+        doors_banner_timer: 0.0,
+        discovered: exploration::begin_level(level, maze, remember_exploration),
+        run_timer: hud::RunTimer::new(),
+    }
+}
+
+// The level files a run can play: `custom` (--maze) or every campaign level.
+fn level_files(custom: Option<&str>) -> Vec<String> {
+    match custom {
+        Some(path) => vec![path.to_string()],
+        None => (1..=progress::LEVEL_COUNT).map(|l| maze::level_filename(l).to_string()).collect(),
+    }
+}
+
+// Load a level, or show the missing-file screen when it can't be read; None means quit.
+fn load_level_or_report(window: &mut RaylibHandle, thread: &RaylibThread, file: &str) -> Option<(Maze, maze::LevelMeta)> {
+    match maze::load_level(file) {
+        Ok(level) => Some(level),
+        Err(e) => {
+            eprintln!("[warn] {}", e);
+            diagnostics::error_screen(window, thread, &[diagnostics::MissingAsset::level(file)]);
+            None
+        }
+    }
+}
+
+// Parse every level file (or just `custom`) and print what is wrong with each; true if all pass.
+fn validate_levels(custom: Option<&str>) -> bool {
+    let mut ok = true;
+    for file in level_files(custom) {
+        let problems = match std::fs::read_to_string(&file) {
+            Ok(text) => {
+                let (maze, meta) = maze::parse_level(&text);
+                maze::validate_level(&maze, &meta)
             }
+            Err(e) => vec![format!("couldn't read: {}", e)],
+        };
+        if problems.is_empty() {
+            eprintln!("[info] {}: ok", file);
         }
-    } else {
-        eprintln!("[info] run with \"<program> <width> <height>\" to override resolution. Using default {}x{}", window_width, window_height);
+        for problem in &problems {
+            eprintln!("[warn] {}: {}", file, problem);
+        }
+        ok &= problems.is_empty();
     }
-    let block_size = 100;
+    ok
+}
 
-    let (mut window, raylib_thread) = raylib::init()
-        .size(window_width, window_height)
-        .title("Raycaster Example")
-        .log_level(TraceLogLevel::LOG_WARNING)
-        .build();
+// --bench: render `frames` frames of the chosen level (--level or --maze, else level 1) into an
+// offscreen framebuffer of `size` = (width, height, render scale) along debug::bench_path,
+// with no window, audio or frame pacing, then print the statistics (JSON with --bench-json).
+fn run_bench(cli: &cli::CliOptions, settings: &settings::Settings, frames: usize, block_size: usize, size: (u32, u32, usize)) {
+    let (width, height, render_scale) = size;
+    let file = cli.maze.clone().unwrap_or_else(|| maze::level_filename(cli.level.unwrap_or(1)).to_string());
+    let (maze, meta) = match maze::load_level(&file) {
+        Ok(level) => level,
+        Err(e) => {
+            eprintln!("[warn] bench: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let mut textures = textures::TextureAtlas::load_in_background(!cli.no_embedded, settings.max_texture_size, settings.image_cache_mb);
+    textures.set_pixel_perfect(settings.pixel_perfect);
+    textures.apply_level(&meta.extra);
+    let mut framebuffer = Framebuffer::new(width, height);
+    let LevelStart { world, mut discovered, .. } = start_level(&maze, block_size, 1, meta.required_coins, false);
+    let path = debug::bench_path(&maze, block_size, frames);
+    // time stays at 0 so the torch flicker and sky are the same on every run
+    let opts = renderer::RenderSettings {
+        column_step: renderer::column_step_for(width, settings.ray_columns, render_scale),
+        sprite_threads: settings.sprite_threads,
+        time: 0.0,
+        sky_drift: settings.sky_drift,
+        lighting: meta.lighting,
+        day_cycle: meta.day_cycle,
+    };
+    let door_progress = world.doors.open_progress();
+    eprintln!("[info] bench: {} frames of {} at {}x{}", frames, file, width, height);
+    let report = debug::run_bench(&mut textures, frames, |textures, k| {
+        let mut view = world.player;
+        (view.pos, view.a) = path[k];
+        framebuffer.clear();
+        let timings = renderer::render_world(&mut framebuffer, &maze, block_size, &view, textures, &world.npcs, &world.coins, door_progress, &opts);
+        let minimap_start = std::time::Instant::now();
+        let _zone = profile::zone("minimap");
+        minimap::render_minimap(&mut framebuffer, &maze, MINIMAP_SCALE, &view, 12, 12, block_size, &world.npcs, &world.coins, &mut discovered, MINIMAP_VIEW_RADIUS);
+        debug::BenchFrame { total: 0.0, walls: timings.walls_ms, sprites: timings.sprites_ms, minimap: minimap_start.elapsed().as_secs_f32() * 1000.0 }
+    });
+    if cli.bench_json {
+        println!("{}", report.to_json());
+    } else {
+        println!("{}", report.to_text());
+    }
+}
 
+fn main() {
+    // command-line options (see cli::USAGE); the old `<program> <width> <height>` form still works
+    let args: Vec<String> = env::args().skip(1).collect();
+    let cli = match cli::CliOptions::parse(&args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("[warn] {} (run with --help for usage)", e);
+            std::process::exit(2);
+        }
+    };
+    if cli.help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+    // --pack-textures writes textures/atlas.png + atlas.toml from the manifest's files and exits
+    if cli.pack_textures {
+        match textures::pack_textures() {
+            Ok(msg) => eprintln!("[info] {}", msg),
+            Err(e) => {
+                eprintln!("[warn] packing textures failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(path) = &cli.maze
+        && !std::path::Path::new(path).is_file()
+    {
+        eprintln!("[warn] --maze: no such file '{}'", path);
+        std::process::exit(2);
+    }
+    // --validate checks the level files without opening a window
+    if cli.validate {
+        std::process::exit(if validate_levels(cli.maze.as_deref()) { 0 } else { 1 });
+    }
+    // every random system draws from this seed; print it so a run can be reproduced
+    let seed = cli.seed.unwrap_or_else(rng::clock_seed);
+    rng::set_seed(seed);
+    eprintln!("[info] rng seed = {} (run with --seed {} to reproduce)", seed, seed);
+    let window_width = cli.width.unwrap_or(1300);
+    let window_height = cli.height.unwrap_or(900);
+    if cli.width.is_none() && cli.height.is_none() {
+        eprintln!("[info] run with --width/--height to override resolution (--help lists every option). Using default {}x{}", window_width, window_height);
+    }
+    let block_size = 100;
     // render_scale reduces the internal framebuffer resolution to improve FPS.
     // e.g. render_scale = 2 renders to (width/2 x height/2) and scales up when drawing.
-    let render_scale: u32 = 2; // increase to 3/4 for better perf, set to 1 for native resolution
-    let fb_w = (window_width as u32).saturating_div(render_scale);
-    let fb_h = (window_height as u32).saturating_div(render_scale);
+    let render_scale: u32 = cli.render_scale.unwrap_or(2); // --render-scale 3/4 for better perf, 1 for native resolution
+    // --bench has no window to ask for a DPI scale; the game refits once the window is open
+    let (fb_w, fb_h) = Framebuffer::size_for(window_width, window_height, render_scale, 1.0);
+
+    // user preferences (language, ...) from settings.toml
+    let mut settings = settings::Settings::load();
+    strings::set_lang(settings.lang);
+
+    // --bench renders offscreen and exits before any window or audio device is opened
+    if let Some(frames) = cli.bench {
+        run_bench(&cli, &settings, frames, block_size, (fb_w, fb_h, render_scale as usize));
+        return;
+    }
+
+    let mut builder = raylib::init();
+    builder.size(window_width, window_height).title("Raycaster Example").log_level(TraceLogLevel::LOG_WARNING).resizable();
+    // the builder has no high-DPI switch; raylib ORs this into the flags build() sets
+    unsafe { raylib::ffi::SetConfigFlags(raylib::ffi::ConfigFlags::FLAG_WINDOW_HIGHDPI as u32); }
+    if settings.vsync {
+        builder.vsync();
+    }
+    let (mut window, raylib_thread) = builder.build();
+    // ESC is ours (mouse capture, closing the console), not raylib's default quit key
+    window.set_exit_key(None);
+    // with vsync the monitor's refresh rate caps the frame rate too (0 = unknown)
+    let vsync_hz = match get_monitor_refresh_rate(get_current_monitor()) {
+        hz if settings.vsync && hz > 0 => Some(hz as u32),
+        _ => None,
+    };
+
+    // missing level files or wall textures: say what and where we looked instead of failing later
+    let missing = diagnostics::check_startup(&level_files(cli.maze.as_deref()), !cli.no_embedded);
+    if !missing.is_empty() {
+        diagnostics::error_screen(&mut window, &raylib_thread, &missing);
+        return;
+    }
+
+    // the window is resizable; every loop calls framebuffer.follow_window to keep this ratio
     let mut framebuffer = Framebuffer::new(fb_w, fb_h);
+    framebuffer.set_render_scale(render_scale);
+    // on retina/scaled displays render from the window's physical pixels, not its logical size
+    framebuffer.set_dpi_scale(framebuffer::window_dpi_scale(&window));
+    framebuffer.fit_window(window.get_screen_width(), window.get_screen_height());
     framebuffer.set_background_color(Color::new(50, 50, 100, 255));
+    framebuffer.set_hud_scale(settings.hud_scale);
+    // fullscreen from the last session (or --fullscreen); F11 switches at any time
+    let mut display = display::Display::new();
+    display.apply(&mut window, &mut framebuffer, settings.fullscreen || cli.fullscreen);
 
-    // load textures atlas (optional - will fallback to procedural patterns)
-    let textures = textures::TextureAtlas::new();
-
+    // load textures atlas in the background (procedural patterns until each image arrives)
+    let mut textures = textures::TextureAtlas::load_in_background(!cli.no_embedded, settings.max_texture_size, settings.image_cache_mb);
+    textures.set_pixel_perfect(settings.pixel_perfect);
+    if cli.texture_viewer {
+        debug::run_texture_viewer(&mut window, &raylib_thread, &mut framebuffer, &mut textures);
+        return;
+    }
 
     // audio manager: encapsulates audio init/play/stop/update
     let mut audio = audio::AudioManager::new();
+    if cli.no_audio {
+        eprintln!("[info] --no-audio: audio disabled");
+        audio.disable();
+    }
+    audio.apply_settings(&settings.audio);
+    let bindings = input::Bindings::from_settings(&settings);
+    // gamepad vibration on damage, coins and the heartbeat (settings: general.rumble)
+    let mut rumble = input::Rumble::new(settings.rumble);
     audio.init();
-    audio.play_menu_track();
 
-    // show main menu and handle selection
-    let mut current_level = 1;
-    match menu::run_menu(&mut window, &raylib_thread, &mut framebuffer, &textures, &mut audio) {
-        menu::MenuAction::StartLevel(level) => {
-            current_level = level;
-            // stop menu music and start gameplay music
-            audio.stop_unload();
-            audio.play_game_track();
-        }
-        menu::MenuAction::Quit => {
-            audio.cleanup();
-            return;
+    // --maze plays that one file instead of the campaign: no next level, no saved progress
+    let custom_maze = cli.maze.clone();
+    let level_file = |level: i32| custom_maze.clone().unwrap_or_else(|| maze::level_filename(level).to_string());
+    // show main menu and handle selection, unless the command line already picked the level
+    let mut current_level = cli.level.unwrap_or(1);
+    // run saved with "guardar y salir", restored once the level is loaded ("CONTINUAR")
+    let mut resume = None;
+    // shown over the first seconds of play (a damaged save)
+    let mut startup_banner = None;
+    if cli.level.is_none() && custom_maze.is_none() {
+        audio.play_menu_track();
+        match menu::run_menu(&mut window, &raylib_thread, &mut framebuffer, &mut textures, &mut audio, &mut settings, &mut display) {
+            menu::MenuAction::StartLevel(level) => {
+                current_level = level;
+                // stop menu music; the level's own track starts once its metadata is loaded
+                audio.stop_unload();
+            }
+            menu::MenuAction::Continue => {
+                audio.stop_unload();
+                match savegame::SaveGame::load() {
+                    Some(Ok(save)) => {
+                        current_level = save.level;
+                        resume = Some(save);
+                    }
+                    Some(Err(e)) => {
+                        eprintln!("[warn] {}: {}, starting a new game", savegame::SAVE_PATH, e);
+                        startup_banner = Some(tr(Str::SaveDamaged).to_string());
+                    }
+                    None => {}
+                }
+                // a save is resumed once; saving again writes a new one
+                savegame::SaveGame::delete();
+            }
+            menu::MenuAction::Quit => {
+                audio.cleanup();
+                return;
+            }
         }
     }
+    // a custom maze would overwrite the campaign level's saved exploration
+    let remember_exploration = settings.remember_exploration && custom_maze.is_none();
 
-    let mut maze = load_maze_for_level(current_level);
+    let Some((mut maze, level_meta)) = load_level_or_report(&mut window, &raylib_thread, &level_file(current_level)) else {
+        audio.cleanup();
+        return;
+    };
+    textures.apply_level(&level_meta.extra);
+    let mut lighting = level_meta.lighting;
+    let mut day_cycle = level_meta.day_cycle;
+    let mut coin_goal = level_meta.required_coins;
+    audio.play_level_track(level_meta.music.as_deref());
 
         // DEBUG: print working directory and the resolved path of maze.txt so we know which file is loaded
         if let Ok(cwd) = env::current_dir() {
@@ -103,227 +353,536 @@ fn main() {
             Err(e) => eprintln!("[debug] couldn't canonicalize maze.txt: {}", e),
         }
         eprintln!("[debug] loaded maze rows = {}", maze.len());
-    let mut player = Player {
-        pos: Vector2::new(150.0, 150.0),
-        a: PI / 3.0,
-        fov: PI / 3.0,
-    };
+    // player at the spawn, NPCs and coins from the maze, fog-of-war discovered grid for the
+    // minimap (restored from saves/ when remember_exploration is on), level timer
+    let LevelStart { mut world, mut doors_banner_timer, mut discovered, mut run_timer } =
+        start_level(&maze, block_size, current_level, coin_goal, remember_exploration);
+    if let Some(save) = &resume {
+        match save.restore(&maze, block_size, &mut world.player, &mut world.npcs, &mut world.coins, &mut discovered) {
+            Ok(collected) => {
+                world.total_coins_collected = collected;
+                // learn whether the restored position is off the spawn or on an exit
+                world.doors.update(&maze, world.player.pos, block_size, false);
+                world.door_unlock = events::RisingEdge::new(world.doors_open());
+                if world.doors_open() {
+                    world.doors.finish_opening();
+                }
+                run_timer = hud::RunTimer::resumed(save.elapsed, window.get_time());
+            }
+            Err(e) => {
+                eprintln!("[warn] {}: {}, starting a new game", savegame::SAVE_PATH, e);
+                startup_banner = Some(tr(Str::SaveDamaged).to_string());
+            }
+        }
+    }
 
     // start with mouse capture enabled for better FPS-style controls
     let mut capture_mouse = true;
     window.hide_cursor(); // hide cursor initially
 
-    // load NPCs from maze
-    let mut npcs = sprite::load_npcs_from_maze(&maze, block_size);
-    // load coins from maze
-    let mut coins = sprite::load_coins_from_maze(&maze, block_size);
-    let mut total_coins_collected = 0;
-    // fog-of-war discovered grid for the minimap (initialized to false)
-    let mut discovered: Vec<Vec<bool>> = maze.iter().map(|r| vec![false; r.len()]).collect();
+    textures.report_missing();
+    // frame pacing stats overlay (F7), off by default
+    let mut frame_times = debug::FrameTimes::new();
+    let mut show_frame_stats = false;
+    // developer overlay (F3): position, rays, NPC states, seed; off by default
+    let mut show_debug_overlay = false;
+    // F10: top-down view of the maze, entities and this frame's rays instead of the 3D view
+    let mut top_down = false;
+    // gameplay events raised each frame, turned into sounds/banners after the update
+    let mut events = events::EventQueue::new();
+    // coin counter shown in the HUD, counting up toward total_coins_collected
+    let mut coin_tally = hud::CoinTally::new();
+    // result of the last texture reload (F6) and seconds left to show it
+    // (also used for the damaged-save message at startup)
+    let mut reload_banner = match startup_banner {
+        Some(text) => (text, 4.0),
+        None => (String::new(), 0.0f32),
+    };
+    // arrow toward the nearest exit (hint key, or after wandering with the doors open)
+    let mut exit_hint = hud::ExitHint::new();
+    let mut rear_mirror = mirror::RearMirror::new();
+    // on-screen versions of the alert, heartbeat and door sounds (settings.visual_cues)
+    let mut visual_cues = cues::VisualCues::new();
+    // random ambience (drips, rumbles) timing, reproducible with --seed
+    let mut ambient_rng = rng::Rng::stream(rng::STREAM_AMBIENT);
+    // fixed-rate simulation ticks, and positions before the latest tick for interpolated rendering
+    let mut timestep = timestep::FixedTimestep::new();
+    let mut prev_player_pos = world.player.pos;
+    let mut prev_npc_pos: Vec<Vector2> = world.npcs.iter().map(|n| n.pos).collect();
+    // time and coins over the levels escaped so far, for the victory screen
+    let mut run_secs = 0.0;
+    let mut run_coins = 0;
+    // paused with the pause key, or automatically while the window is unfocused
+    let mut manual_pause = false;
+    let mut was_paused = false;
+    // quick restart: hold the restart key half a second; the view then fades back in from black
+    let mut restart_hold = hud::HoldToConfirm::new(0.5);
+    let mut restart_fade = Tween::settled(0.0);
+    // frame cap (fps_limit), waited out at the end of every frame, paused or not
+    let mut pacer = timestep::FramePacer::new(settings.fps_limit);
+    // developer console (` or ~); while open it has the keyboard and the player stands still
+    let mut console = console::Console::new();
 
     while !window.window_should_close() {
+        frame_times.push(window.get_frame_time() * 1000.0);
+        // profiler zones of the last frame, for the F3 overlay; only timed while it's shown
+        let frame_zones = profile::take_frame();
+        profile::set_enabled(show_debug_overlay);
+        framebuffer.follow_window(&window);
+        // the coupled loop runs one update per frame, so it always renders at TICK_HZ
+        let limit = timestep::pacing_limit(settings.fps_limit, settings.fixed_timestep);
+        if limit != pacer.limit() && limit != settings.fps_limit {
+            eprintln!("[info] fps_limit {} ignored while fixed_timestep is off, running at {} FPS", settings.fps_limit, limit);
+        }
+        pacer.set_limit(limit);
+        framebuffer.set_fps_cap(timestep::effective_cap(pacer.limit(), vsync_hz));
+        display.handle_key(&mut window, &mut framebuffer, &mut settings);
+        // textures still arriving from the loader thread
+        textures.poll_loaded();
+
+        if !console.is_open() && window.is_key_pressed(bindings.pause) {
+            manual_pause = !manual_pause;
+        }
+        let paused = manual_pause || !window.is_window_focused();
+        if paused != was_paused {
+            if paused {
+                audio.pause_music();
+                audio.set_proximity_level(0.0);
+                run_timer.pause(window.get_time());
+            } else {
+                audio.resume_music();
+                run_timer.resume(window.get_time());
+            }
+            was_paused = paused;
+        }
+        if paused {
+            // keep showing the last frame; no gameplay update while paused
+            // "guardar y salir": only for campaign levels, a --maze run has nothing to resume into
+            let can_save = manual_pause && custom_maze.is_none();
+            if can_save && window.is_key_pressed(bindings.save) {
+                savegame::SaveGame::capture(current_level, &world.player, &world.npcs, &world.coins, run_timer.elapsed(window.get_time()), &discovered).write();
+                break;
+            }
+            let pause_text = if can_save { trf(Str::PausedSaveHint, &[&settings.key_save]) } else { tr(Str::Paused).to_string() };
+            let fps = window.get_fps();
+            framebuffer.swap_buffers_with_coins(&mut window, &raylib_thread, Some(fps as i32), coin_tally.shown(), world.coins.len(), current_level, None, Some(&pause_text), audio.is_muted());
+            audio.update();
+            pacer.wait();
+            continue;
+        }
+
         // 1. clear framebuffer
         framebuffer.clear();
 
-    // 2. move the player on user input (with collision checks)
-    // doors open when all coins are collected
-    let doors_open = total_coins_collected >= coins.len();
-    process_events(&mut player, &mut window, &maze, block_size, capture_mouse, doors_open);
-
-    // check if player has escaped (is standing on the door position when doors are open)
-    let player_escaped = doors_open && {
-        let player_grid_x = (player.pos.x / block_size as f32) as usize;
-        let player_grid_y = (player.pos.y / block_size as f32) as usize;
-        // Check if player is on a door position ('G' in the maze)
-        if player_grid_y < maze.len() && player_grid_x < maze[player_grid_y].len() {
-            maze[player_grid_y][player_grid_x] == 'G'
-        } else {
-            false
+    // 2. simulation: fixed 60 Hz ticks (or one per frame with the coupled loop), each moving
+    // the player on input, then NPCs and coins (World::tick). Ticks stop early once the level ends.
+    mouse_look(&mut world.player, &mut window, capture_mouse && !console.is_open());
+    let ticks = if settings.fixed_timestep { timestep.advance(window.get_frame_time()) } else { 1 };
+    let tick_secs = if settings.fixed_timestep { timestep::TICK_SECS } else { window.get_frame_time() };
+    let mut used_exit = None;
+    let mut player_dead = false;
+    for _ in 0..ticks {
+        prev_player_pos = world.player.pos;
+        prev_npc_pos = world.npcs.iter().map(|n| n.pos).collect();
+        let input = if console.is_open() { MoveInput::default() } else { MoveInput::read(&window) };
+        let result = world.tick(&maze, block_size, input, tick_secs, &mut events);
+        if world.player.vel != Vector2::zero() {
+            run_timer.start(window.get_time());
         }
-    };
+        // NPCs removed after fading out shift the list; don't lerp from someone else's position
+        if prev_npc_pos.len() != world.npcs.len() {
+            prev_npc_pos = world.npcs.iter().map(|n| n.pos).collect();
+        }
+        used_exit = result.escaped;
+        player_dead = result.died;
+        if result.level_over() {
+            run_timer.stop(window.get_time());
+            break;
+        }
+    }
+    let player_escaped = used_exit.is_some();
+
+        // heartbeat gets louder/faster as the nearest NPC comes within 3 cells
+        let danger_radius = 3.0 * block_size as f32;
+        let proximity = sprite::nearest_npc_distance(&world.npcs, &world.player)
+            .map(|d| 1.0 - d / danger_radius)
+            .unwrap_or(0.0);
+        audio.set_proximity_level(proximity);
+        rumble.heartbeat(&mut window, proximity);
+        visual_cues.update(window.get_frame_time(), proximity);
+        audio.tick_ambient(window.get_frame_time(), &mut ambient_rng);
 
-        // update NPCs and check for collision (player death)
-        let doors_open = total_coins_collected >= coins.len();
-        let player_dead = sprite::update_npcs(&mut npcs, &player, &maze, block_size, doors_open);
-        
-        // update coins and check for collection
-        let (coins_collected_this_frame, coin_collected) = sprite::update_coins(&mut coins, &player, block_size);
-        total_coins_collected += coins_collected_this_frame;
-        
-        // play coin sound if any coin was collected
-        if coin_collected {
-            audio.play_coin_sound();
+        // feedback for this frame's events: sounds from the audio manager, banners here
+        audio.set_listener(&world.player, 8.0 * block_size as f32);
+        for event in events.drain() {
+            audio.handle(&event);
+            rumble.handle(&mut window, &event);
+            visual_cues.handle(&event);
+            match event {
+                GameEvent::DoorOpened { .. } => doors_banner_timer = 2.0,
+                GameEvent::PlayerEscaped((col, row)) => eprintln!("[info] escaped through exit at ({}, {})", col, row),
+                _ => {}
+            }
         }
+        doors_banner_timer = (doors_banner_timer - window.get_frame_time()).max(0.0);
+        reload_banner.1 = (reload_banner.1 - window.get_frame_time()).max(0.0);
 
         // check for victory condition (player escaped through the door)
         if player_escaped {
-            if current_level < 3 {
+            run_secs += run_timer.elapsed(window.get_time());
+            run_coins += world.total_coins_collected;
+            if remember_exploration {
+                exploration::save(current_level, &discovered);
+            }
+            // first completion unlocks the next level in the level select
+            if custom_maze.is_none() {
+                let mut progress = progress::Progress::load();
+                if progress.complete(current_level) {
+                    progress.save();
+                }
+            }
+            if current_level < progress::LEVEL_COUNT && custom_maze.is_none() {
                 // Advance to next level
                 current_level += 1;
-                maze = load_maze_for_level(current_level);
-                
-                // Reset player, npcs, coins, discovered for next level
-                player.pos = Vector2::new(150.0, 150.0);
-                player.a = PI / 3.0;
-                npcs = sprite::load_npcs_from_maze(&maze, block_size);
-                coins = sprite::load_coins_from_maze(&maze, block_size);
-                total_coins_collected = 0;
-                discovered = maze.iter().map(|r| vec![false; r.len()]).collect();
+                let Some((next_maze, level_meta)) = load_level_or_report(&mut window, &raylib_thread, &level_file(current_level)) else {
+                    audio.cleanup();
+                    return;
+                };
+                textures.apply_level(&level_meta.extra);
+                lighting = level_meta.lighting;
+                day_cycle = level_meta.day_cycle;
+                coin_goal = level_meta.required_coins;
+                maze = next_maze;
+                audio.play_level_track(level_meta.music.as_deref());
                 
-                // Brief level transition screen
-                framebuffer.clear();
-                let screen_w = window.get_screen_width();
-                let screen_h = window.get_screen_height();
+                // Reset player, npcs, coins, discovered, timer for next level
+                LevelStart { world, doors_banner_timer, discovered, run_timer } = start_level(&maze, block_size, current_level, coin_goal, remember_exploration);
+                textures.report_missing();
                 
-                if let Ok(texture) = window.load_texture_from_image(&raylib_thread, &framebuffer.color_buffer) {
-                    let mut d = window.begin_drawing(&raylib_thread);
-                    d.clear_background(Color::BLACK);
-                    let level_text = format!("NIVEL {} - COMPLETADO!", current_level - 1);
-                    let next_text = format!("AVANZANDO AL NIVEL {}", current_level);
-                    d.draw_text(&level_text, screen_w / 2 - 200, screen_h / 2 - 50, 40, Color::GREEN);
-                    d.draw_text(&next_text, screen_w / 2 - 180, screen_h / 2 + 10, 30, Color::WHITE);
+                // Brief level transition screen: continues on its own after 2 seconds, or on ENTER
+                let mut input = input::MenuInput::new();
+                let shown_at = window.get_time();
+                while window.get_time() - shown_at < 2.0 && !window.window_should_close() {
+                    framebuffer.follow_window(&window);
+                    display.handle_key(&mut window, &mut framebuffer, &mut settings);
+                    input.poll(&window);
+                    if input.confirm || input.start {
+                        break;
+                    }
+                    let screen_w = window.get_screen_width();
+                    let screen_h = window.get_screen_height();
+                    {
+                        let mut d = window.begin_drawing(&raylib_thread);
+                        d.clear_background(Color::BLACK);
+                        let level_text = trf(Str::LevelCompleted, &[&(current_level - 1)]);
+                        let next_text = trf(Str::AdvancingToLevel, &[&current_level]);
+                        let level_w = d.measure_text(&level_text, 40);
+                        let next_w = d.measure_text(&next_text, 30);
+                        let hint_w = d.measure_text(tr(Str::ContinueHint), 20);
+                        d.draw_text(&level_text, screen_w / 2 - level_w / 2, screen_h / 2 - 50, 40, Color::GREEN);
+                        d.draw_text(&next_text, screen_w / 2 - next_w / 2, screen_h / 2 + 10, 30, Color::WHITE);
+                        d.draw_text(tr(Str::ContinueHint), screen_w / 2 - hint_w / 2, screen_h / 2 + 60, 20, Color::GRAY);
+                    }
+                    thread::sleep(Duration::from_millis(16));
                 }
-                thread::sleep(Duration::from_millis(2000)); // Show for 2 seconds
             } else {
-                // Completed all levels - Victory screen
+                // Completed all levels - Victory screen, with confetti falling over it
+                let mut input = input::MenuInput::new();
+                let stats = trf(Str::VictoryStats, &[&hud::format_run_time(run_secs), &run_coins]);
+                let mut victory = victory::VictoryScreen::new(stats);
                 loop {
+                    framebuffer.follow_window(&window);
+                    display.handle_key(&mut window, &mut framebuffer, &mut settings);
                     framebuffer.clear();
                     
                     // poll keys before drawing to avoid borrow conflicts
-                    if window.is_key_pressed(KeyboardKey::KEY_ENTER) {
+                    input.poll(&window);
+                    if input.confirm || input.start {
                         // reset to level 1
                         current_level = 1;
-                        maze = load_maze_for_level(current_level);
-                        player.pos = Vector2::new(150.0, 150.0);
-                        player.a = PI / 3.0;
-                        npcs = sprite::load_npcs_from_maze(&maze, block_size);
-                        coins = sprite::load_coins_from_maze(&maze, block_size);
-                        total_coins_collected = 0;
-                        discovered = maze.iter().map(|r| vec![false; r.len()]).collect();
+                        (run_secs, run_coins) = (0.0, 0);
+                        let Some((first_maze, level_meta)) = load_level_or_report(&mut window, &raylib_thread, &level_file(current_level)) else {
+                            audio.cleanup();
+                            return;
+                        };
+                        textures.apply_level(&level_meta.extra);
+                        lighting = level_meta.lighting;
+                        day_cycle = level_meta.day_cycle;
+                        coin_goal = level_meta.required_coins;
+                        maze = first_maze;
+                        audio.play_level_track(level_meta.music.as_deref());
+                        LevelStart { world, doors_banner_timer, discovered, run_timer } = start_level(&maze, block_size, current_level, coin_goal, remember_exploration);
+                        textures.report_missing();
                         break;
                     }
-                    if window.is_key_pressed(KeyboardKey::KEY_Q) {
+                    if input.quit {
                         // cleanup audio and quit
                         audio.cleanup();
                         return;
                     }
 
-                    // draw with raylib (query sizes first)
-                    let screen_w = window.get_screen_width();
-                    let screen_h = window.get_screen_height();
-                    
-                    // Clear framebuffer and draw victory background
-                    let fbw = framebuffer.width;
-                    let fbh = framebuffer.height;
-                    
-                    // If victoria texture exists, stretch it to cover the entire framebuffer
-                    for y in 0..fbh {
-                        for x in 0..fbw {
-                            let u = x as f32 / fbw as f32;
-                            let v = y as f32 / fbh as f32;
-                            let col = textures.sample_victoria(u, v);
-                            framebuffer.set_current_color(col);
-                            framebuffer.set_pixel(x, y);
-                        }
-                    }
-                    
-                    if let Ok(texture) = window.load_texture_from_image(&raylib_thread, &framebuffer.color_buffer) {
-                        let mut d = window.begin_drawing(&raylib_thread);
-                        let src = Rectangle::new(0.0,0.0,framebuffer.width as f32, framebuffer.height as f32);
-                        let dest = Rectangle::new(0.0,0.0,screen_w as f32, screen_h as f32);
-                        d.draw_texture_pro(&texture, src, dest, Vector2::new(0.0,0.0), 0.0, Color::WHITE);
-                        
-                        // Draw victory text
-                        d.draw_text("¡TODOS LOS NIVELES COMPLETADOS!", screen_w / 2 - 280, screen_h / 2 - 100, 40, Color::YELLOW);
-                        d.draw_text("ENTER = REINICIAR  Q = SALIR", screen_w / 2 - 140, screen_h / 2 + 50, 20, Color::WHITE);
-                    }
-                    
-                    thread::sleep(Duration::from_millis(16));
+                    // paced like the game (fps_limit) and advanced by the real frame time, so the
+                    // animations run at the same speed whatever the frame rate
+                    victory.update(window.get_frame_time().min(0.1), framebuffer.width);
+                    victory.draw_background(&mut framebuffer, &mut textures);
+                    framebuffer.present_stretched(&mut window, &raylib_thread, |d, screen_w, screen_h| victory.draw_text(d, screen_w, screen_h));
+                    pacer.wait();
                 }
             }
         }
 
     if player_dead {
-            // simple Game Over screen: Enter to restart, Q to quit
+            // simple Game Over screen: Enter (gamepad A) to restart, Q (gamepad Select) to quit
+            let mut input = input::MenuInput::new();
             loop {
+                framebuffer.follow_window(&window);
+                display.handle_key(&mut window, &mut framebuffer, &mut settings);
                 framebuffer.clear();
                 // draw current framebuffer scene briefly
-                let title = "GAME OVER";
+                let title = tr(Str::GameOver);
 
                 // poll keys before drawing to avoid borrow conflicts
-                if window.is_key_pressed(KeyboardKey::KEY_ENTER) {
+                input.poll(&window);
+                if input.confirm || input.start {
                     // reset player, npcs, coins, discovered and break to resume game
-                    player.pos = Vector2::new(150.0, 150.0);
-                    player.a = PI / 3.0;
-                    npcs = sprite::load_npcs_from_maze(&maze, block_size);
-                    coins = sprite::load_coins_from_maze(&maze, block_size);
-                    total_coins_collected = 0;
-                    discovered = maze.iter().map(|r| vec![false; r.len()]).collect();
+                    if remember_exploration {
+                        exploration::save(current_level, &discovered);
+                    }
+                    LevelStart { world, doors_banner_timer, discovered, run_timer } = start_level(&maze, block_size, current_level, coin_goal, remember_exploration);
+                    textures.report_missing();
                     break;
                 }
-                if window.is_key_pressed(KeyboardKey::KEY_Q) {
+                if input.quit {
                     // cleanup audio and quit
+                    if remember_exploration {
+                        exploration::save(current_level, &discovered);
+                    }
                     audio.cleanup();
                     return;
                 }
 
-                // draw with raylib (query sizes first)
-                let screen_w = window.get_screen_width();
-                let screen_h = window.get_screen_height();
-                    // If game over texture exists, stretch it to cover the entire framebuffer
-                    if textures.game_over.is_some() {
-                        // fill framebuffer by sampling the game_over texture stretched to fb size
-                        let fbw = framebuffer.width as u32;
-                        let fbh = framebuffer.height as u32;
-                        for y in 0..fbh {
-                            for x in 0..fbw {
-                                let u = x as f32 / fbw as f32;
-                                let v = y as f32 / fbh as f32;
-                                let col = textures.sample_gameover(u, v);
-                                framebuffer.set_current_color(col);
-                                framebuffer.set_pixel(x, y);
-                            }
-                        }
-                        // draw framebuffer to screen and overlay controls text
-                        if let Ok(texture) = window.load_texture_from_image(&raylib_thread, &framebuffer.color_buffer) {
-                            let mut d = window.begin_drawing(&raylib_thread);
-                            let src = Rectangle::new(0.0,0.0,framebuffer.width as f32, framebuffer.height as f32);
-                            let dest = Rectangle::new(0.0,0.0,screen_w as f32, screen_h as f32);
-                            d.draw_texture_pro(&texture, src, dest, Vector2::new(0.0,0.0), 0.0, Color::WHITE);
-                            d.draw_text("ENTER = REINICIAR  Q = SALIR", 24, 56, 16, Color::WHITE);
-                        }
-                    } else if let Ok(texture) = window.load_texture_from_image(&raylib_thread, &framebuffer.color_buffer) {
-                        let mut d = window.begin_drawing(&raylib_thread);
-                        let src = Rectangle::new(0.0,0.0,framebuffer.width as f32, framebuffer.height as f32);
-                        let dest = Rectangle::new(0.0,0.0,screen_w as f32, screen_h as f32);
-                        d.draw_texture_pro(&texture, src, dest, Vector2::new(0.0,0.0), 0.0, Color::WHITE);
+                // the game over art stretched over the screen, or the title over the last frame
+                if textures.prepare(&textures::TextureId::GameOver) {
+                    framebuffer.fill_fullscreen(&textures, &textures::TextureId::GameOver);
+                    framebuffer.present_stretched(&mut window, &raylib_thread, |d, _, _| {
+                        d.draw_text(tr(Str::RestartOrQuit), 24, 56, 16, Color::WHITE);
+                    });
+                } else {
+                    framebuffer.present_stretched(&mut window, &raylib_thread, |d, _, _| {
                         d.draw_rectangle(10, 10, 300, 80, Color::new(0,0,0,160));
                         d.draw_text(title, 24, 20, 40, Color::RAYWHITE);
-                        d.draw_text("ENTER = REINICIAR  Q = SALIR", 24, 56, 16, Color::WHITE);
-                    }
+                        d.draw_text(tr(Str::RestartOrQuit), 24, 56, 16, Color::WHITE);
+                    });
+                }
                 thread::sleep(Duration::from_millis(16));
             }
         }
 
+    // a level change or restart teleports everything: don't interpolate from the old positions
+    if player_escaped || player_dead {
+        prev_player_pos = world.player.pos;
+        prev_npc_pos = world.npcs.iter().map(|n| n.pos).collect();
+    }
+    // draw the world part of the way from the previous tick to the current one
+    let alpha = if settings.fixed_timestep { timestep.alpha() } else { 1.0 };
+    let lerp = |from: Vector2, to: Vector2| from + (to - from) * alpha;
+    let mut view = world.player;
+    view.pos = lerp(prev_player_pos, world.player.pos);
+    let view_npcs: Vec<sprite::NPC> = world.npcs.iter().enumerate().map(|(i, n)| {
+        let mut n = n.clone();
+        n.pos = lerp(prev_npc_pos.get(i).copied().unwrap_or(n.pos), n.pos);
+        n
+    }).collect();
+
     // 3. draw stuff: always render 3D world and a stylized minimap
-    // pass column_step derived from render_scale to the renderer (more aggressive when downscaling)
-    let column_step = render_scale as usize; 
-    // doors open when all coins are collected
-    let doors_open = total_coins_collected >= coins.len();
-    renderer::render_world(&mut framebuffer, &maze, block_size, &player, &textures, &npcs, &coins, column_step, doors_open);
-    let minimap_scale = 14; // increased pixels per cell for bigger minimap
-    // place minimap at 12,12 offset
-    minimap::render_minimap(&mut framebuffer, &maze, minimap_scale, &player, 12, 12, block_size, &npcs, &coins, &mut discovered);
+    // rays per frame come from settings (ray_columns); auto falls back to one ray per render_scale pixels
+    let column_step = renderer::column_step_for(framebuffer.width, settings.ray_columns, render_scale as usize);
+    // doors open once the level's coin goal is collected
+    let doors_open = world.doors_open();
+    let render_opts = renderer::RenderSettings {
+        column_step,
+        sprite_threads: settings.sprite_threads,
+        time: window.get_time() as f32,
+        sky_drift: settings.sky_drift,
+        lighting,
+        day_cycle,
+    };
+    let render_stats = {
+        let _zone = profile::zone("render");
+        if top_down {
+            // as many rays as render_world would cast at this resolution
+            let rays = (framebuffer.width as usize).div_ceil(render_opts.column_step.max(1));
+            let top_view = renderer::render_maze(&mut framebuffer, &maze, block_size, &view, doors_open, rays);
+            renderer::render_top_down_sprites(&mut framebuffer, &top_view, &view_npcs, &world.coins);
+            renderer::RenderStats { rays: rays as u32, ..Default::default() }
+        } else {
+            let stats = renderer::render_world(&mut framebuffer, &maze, block_size, &view, &textures, &view_npcs, &world.coins, world.doors.open_progress(), &render_opts);
+            renderer::render_particles(&mut framebuffer, &view, &textures, &world.particles, block_size);
+            renderer::render_coin_pops(&mut framebuffer, &view, &textures, &world.coin_pops, block_size);
+            stats
+        }
+    };
+    // rear-view mirror, skipped while frames take longer than the pacer's target allows
+    let frame_budget = pacer.target_secs(vsync_hz);
+    if settings.rear_mirror && !top_down && rear_mirror.within_budget(window.get_frame_time(), frame_budget) {
+        let _zone = profile::zone("mirror");
+        let behind = mirror::RearMirror::camera(&view);
+        let mirror_view = rear_mirror.view(framebuffer.width, framebuffer.height);
+        renderer::render_world(mirror_view, &maze, block_size, &behind, &textures, &view_npcs, &world.coins, world.doors.open_progress(), &mirror::RearMirror::settings(&render_opts));
+        renderer::render_particles(mirror_view, &behind, &textures, &world.particles, block_size);
+        rear_mirror.draw(&mut framebuffer);
+    }
+    // brightness post-process on the 3D view only (minimap and overlays keep their colors)
+    framebuffer.apply_gamma(settings.gamma);
+    // place minimap at 12,12 offset; the HUD text starts to its right
+    framebuffer.set_hud_left_inset((12 + minimap::MINIMAP_WIDTH) as u32);
+    framebuffer.set_palette(settings.palette.palette());
+    {
+        let _zone = profile::zone("minimap");
+        minimap::render_minimap(&mut framebuffer, &maze, MINIMAP_SCALE, &view, 12, 12, block_size, &view_npcs, &world.coins, &mut discovered, MINIMAP_VIEW_RADIUS);
+    }
+
+    exit_hint.update(doors_open, window.get_frame_time());
+    if exit_hint.active(doors_open, settings.exit_hint_delay)
+        && let Some(rel) = hud::exit_hint_angle(&mut framebuffer, &maze, &view, block_size)
+    {
+        hud::draw_exit_hint(&mut framebuffer, rel);
+    }
+    // objective arrow: nearest coin, then the exit once the doors are open
+    let objective = settings.objective_arrow.shown_on(current_level)
+        .then(|| hud::objective_bearing(&maze, &world.coins, doors_open, &view, block_size))
+        .flatten();
+    if let Some((rel, _)) = objective {
+        hud::draw_objective_arrow(&mut framebuffer, rel);
+    }
+    framebuffer.set_objective_distance(objective.map(|(_, cells)| cells));
+    if settings.visual_cues {
+        visual_cues.draw(&mut framebuffer, &view);
+    }
+    framebuffer.set_compass(settings.compass.then_some(view.a));
+    framebuffer.set_run_time(Some(run_timer.elapsed(window.get_time())));
+
+    // the frame graph shows with the F3 overlay or on its own with F7
+    let show_frame_graph = show_frame_stats || show_debug_overlay;
+    if show_frame_graph {
+        let graph_h = 40;
+        let graph_y = framebuffer.height.saturating_sub(graph_h + 8);
+        let rect = debug::PixelRect { x: 8, y: graph_y, w: debug::FRAME_SAMPLES as u32, h: graph_h };
+        debug::draw_frame_graph(&mut framebuffer, &frame_times, rect);
+    }
 
     // 4. swap buffers (draw framebuffer with coin counter and FPS)
     let fps = window.get_fps();
-    framebuffer.swap_buffers_with_coins(&mut window, &raylib_thread, Some(fps as i32), total_coins_collected, coins.len(), current_level);
-    
+    let debug_stats = show_debug_overlay.then(|| {
+        let mut stats = debug::DebugStats {
+            fps,
+            frame_ms: window.get_frame_time() * 1000.0,
+            rays: render_stats.rays,
+            dda_steps: render_stats.dda_steps,
+            dpi_scale: framebuffer.dpi_scale(),
+            coins_left: world.coins.iter().filter(|c| !c.collected).count(),
+            seed: rng::seed(),
+            zones: frame_zones,
+            ..Default::default()
+        };
+        stats.set_player(&world.player, block_size);
+        stats.count_npcs(&world.npcs);
+        stats
+    });
+    framebuffer.set_debug_stats(debug_stats);
+    let frame_stats = if show_frame_graph { Some(&frame_times) } else { None };
+    let banner = if maze.is_empty() {
+        Some(tr(Str::NoLevelLoaded))
+    } else if doors_banner_timer > 0.0 {
+        Some(tr(Str::DoorsOpen))
+    } else if reload_banner.1 > 0.0 {
+        Some(reload_banner.0.as_str())
+    } else {
+        None
+    };
+    coin_tally.update(world.total_coins_collected, window.get_frame_time());
+    if !restart_fade.is_done() {
+        framebuffer.fade(restart_fade.value());
+        restart_fade.advance(window.get_frame_time());
+    }
+    framebuffer.set_coin_popup(coin_tally.popup());
+    framebuffer.set_restart_hold(restart_hold.progress());
+    framebuffer.set_console(console.view());
+    {
+        let _zone = profile::zone("present");
+        framebuffer.swap_buffers_with_coins(&mut window, &raylib_thread, Some(fps as i32), coin_tally.shown(), world.coins.len(), current_level, frame_stats, banner, audio.is_muted());
+    }
+
     // update music streaming buffers each frame
-    audio.update();
+    {
+        let _zone = profile::zone("audio");
+        audio.update();
+    }
+        // the hotkeys below are off while typing in the console (it was open at the start of this frame)
+        let hotkeys = !console.is_open();
+        let mut reload_level = false;
+        if window.is_key_pressed(console::TOGGLE_KEY) {
+            console.toggle();
+        } else if console.is_open() && let Some(line) = console.poll(&mut window) {
+            match console::parse(&line).and_then(|cmd| console::execute(cmd, &mut world, &maze, block_size, &mut events)) {
+                Ok(console::Reply::Done(msg)) => console.print(msg),
+                Ok(console::Reply::ReloadLevel) => reload_level = true,
+                Ok(console::Reply::Screenshot(file)) => {
+                    window.take_screenshot(&raylib_thread, &file);
+                    console.print(format!("saved {}", file));
+                }
+                Err(msg) => console.print(msg),
+            }
+        }
+        // mute/unmute all audio (remembered in settings)
+        if hotkeys && window.is_key_pressed(bindings.mute) {
+            audio.toggle_mute();
+            settings.audio.muted = audio.is_muted();
+            settings.save();
+        }
+        // toggle the exit hint arrow
+        if hotkeys && window.is_key_pressed(bindings.hint) {
+            exit_hint.toggle();
+        }
+        // show/hide the compass strip (remembered in settings)
+        if hotkeys && window.is_key_pressed(bindings.compass) {
+            settings.compass = !settings.compass;
+            settings.save();
+        }
+        // show/hide the rear-view mirror (remembered in settings)
+        if hotkeys && window.is_key_pressed(bindings.mirror) {
+            settings.rear_mirror = !settings.rear_mirror;
+            settings.save();
+        }
+        // start the level over, exactly like the game over restart (or reload_level in the console)
+        if restart_hold.update(hotkeys && window.is_key_down(bindings.restart), window.get_frame_time()) || reload_level {
+            if remember_exploration {
+                exploration::save(current_level, &discovered);
+            }
+            LevelStart { world, doors_banner_timer, discovered, run_timer } = start_level(&maze, block_size, current_level, coin_goal, remember_exploration);
+            textures.report_missing();
+            restart_fade = Tween::new(1.0, 0.0, RESTART_FADE_SECS, ease::in_out_sine);
+        }
+        // toggle the developer overlay
+        if hotkeys && window.is_key_pressed(KeyboardKey::KEY_F3) {
+            show_debug_overlay = !show_debug_overlay;
+        }
+        // switch between the 3D view and the top-down debug view
+        if hotkeys && window.is_key_pressed(KeyboardKey::KEY_F10) {
+            top_down = !top_down;
+        }
+        // toggle frame pacing stats overlay
+        if hotkeys && window.is_key_pressed(KeyboardKey::KEY_F7) {
+            show_frame_stats = !show_frame_stats;
+        }
+        // reload textures from disk (debug, for iterating on the art without restarting)
+        if hotkeys && window.is_key_pressed(KeyboardKey::KEY_F6) {
+            let changed = textures.reload();
+            reload_banner = (trf(Str::TexturesReloaded, &[&changed]), 2.0);
+        }
         // toggle mouse capture with ESC key (currently only toggles state; we avoid forcing
         // SetMousePosition each frame since that can zero mouse delta on some platforms)
-        if window.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+        if hotkeys && window.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
             capture_mouse = !capture_mouse;
             if capture_mouse {
                 // hide cursor when capture is enabled
@@ -333,8 +892,9 @@ fn main() {
             }
         }
 
-        
-
-        thread::sleep(Duration::from_millis(16));
+        pacer.wait();
+    }
+    if remember_exploration {
+        exploration::save(current_level, &discovered);
     }
 }
@@ -1,26 +1,310 @@
 // maze.rs
 
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::collections::HashMap;
 
 pub type Maze = Vec<Vec<char>>;
 
-pub fn load_maze(filename: &str) -> Maze {
-    let file = File::open(filename).unwrap();
-    let reader = BufReader::new(file);
+// What a maze character means. The caster, collision, NPC pathing, the minimap and the sprite
+// loaders all classify cells through this instead of matching characters themselves.
+//   ' '           floor
+//   'G' / 'g'     exit door (blocks until the doors open; 'g' is the old lowercase spelling)
+//   'R' 'C' 'P'   NPC / coin / player spawn, floor otherwise
+//   anything else wall ('+' '-' '|' and unknown characters)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CellKind {
+    Floor,
+    Wall,
+    Door,
+    NpcSpawn,
+    CoinSpawn,
+    PlayerSpawn,
+}
+
+impl CellKind {
+    pub fn of(cell: char) -> Self {
+        match cell {
+            ' ' => CellKind::Floor,
+            'G' | 'g' => CellKind::Door,
+            'R' => CellKind::NpcSpawn,
+            'C' => CellKind::CoinSpawn,
+            'P' => CellKind::PlayerSpawn,
+            _ => CellKind::Wall,
+        }
+    }
+
+    pub fn is_wall(self) -> bool {
+        self == CellKind::Wall
+    }
+
+    pub fn is_door(self) -> bool {
+        self == CellKind::Door
+    }
+
+    pub fn is_spawn(self) -> bool {
+        matches!(self, CellKind::NpcSpawn | CellKind::CoinSpawn | CellKind::PlayerSpawn)
+    }
+
+    // Can be walked through: floor and spawns always, doors only once they are open.
+    pub fn is_walkable(self, doors_open: bool) -> bool {
+        match self {
+            CellKind::Wall => false,
+            CellKind::Door => doors_open,
+            _ => true,
+        }
+    }
+
+    // Stops rays: walls, and doors whether open or not (an open door is drawn as a texture).
+    pub fn blocks_sight(self) -> bool {
+        self.is_wall() || self.is_door()
+    }
+}
+
+// How a level is lit: evenly, or only by the torch the player carries (set per level with
+// `lighting=torch` in the maze metadata; the renderer turns it into brightness).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Lighting {
+    #[default]
+    Fullbright,
+    Torch,
+}
+
+impl Lighting {
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.to_ascii_lowercase().as_str() {
+            "fullbright" => Some(Lighting::Fullbright),
+            "torch" => Some(Lighting::Torch),
+            _ => None,
+        }
+    }
+}
+
+// Optional per-level metadata embedded in the maze file.
+// Written as `#key=value` lines (e.g. `#name=Nivel 1`, `#coins=2`, `#music=sounds/level1.ogg`)
+// anywhere before the grid, or as plain `key=value` lines after a blank line following the grid.
+#[derive(Clone, Debug, Default)]
+pub struct LevelMeta {
+    pub name: Option<String>,
+    pub required_coins: Option<usize>,
+    pub music: Option<String>,
+    // `lighting=torch` for a dark level lit only by the player's torch
+    pub lighting: Lighting,
+    // `day_cycle=300` for a day/night cycle of that many seconds; None (or `static`) = always day
+    pub day_cycle: Option<f32>,
+    // any other keys, kept for features that read them later
+    pub extra: HashMap<String, String>,
+}
+
+impl LevelMeta {
+    fn insert(&mut self, key: &str, value: &str) {
+        let key = key.trim().to_lowercase();
+        let value = value.trim().to_string();
+        match key.as_str() {
+            "name" => self.name = Some(value),
+            "coins" => match value.parse::<usize>() {
+                Ok(n) => self.required_coins = Some(n),
+                Err(_) => eprintln!("[warn] maze metadata: invalid coins value '{}'", value),
+            },
+            "music" => self.music = Some(value),
+            "lighting" => match Lighting::from_code(&value) {
+                Some(l) => self.lighting = l,
+                None => eprintln!("[warn] maze metadata: invalid lighting '{}', expected torch/fullbright", value),
+            },
+            "day_cycle" => match value.parse::<f32>() {
+                Ok(secs) if secs > 0.0 => self.day_cycle = Some(secs),
+                _ if value.eq_ignore_ascii_case("static") => self.day_cycle = None,
+                _ => eprintln!("[warn] maze metadata: invalid day_cycle '{}', expected seconds or static", value),
+            },
+            _ => { self.extra.insert(key, value); }
+        }
+    }
+}
+
+// Parse a maze file's text into its grid and metadata.
+// - lines starting with '#' are comments; `#key=value` comments are metadata
+// - blank lines before the grid are skipped
+// - the first blank line after the grid ends it; the rest of the file is a metadata section
+pub fn parse_level(text: &str) -> (Maze, LevelMeta) {
+    let mut maze: Maze = Vec::new();
+    let mut meta = LevelMeta::default();
+    let mut in_meta_section = false;
+
+    for raw in text.lines() {
+        let line = raw.trim_end_matches('\r');
+        if let Some(comment) = line.strip_prefix('#') {
+            if let Some((k, v)) = comment.split_once('=') {
+                meta.insert(k, v);
+            }
+            continue;
+        }
+        if line.trim().is_empty() {
+            if !maze.is_empty() {
+                in_meta_section = true;
+            }
+            continue;
+        }
+        if in_meta_section {
+            match line.split_once('=') {
+                Some((k, v)) => meta.insert(k, v),
+                None => eprintln!("[warn] maze metadata: ignoring line '{}'", line),
+            }
+            continue;
+        }
+        maze.push(line.chars().collect());
+    }
+
+    (maze, meta)
+}
+
+// Read and parse a level file; Err describes why it couldn't be read (missing file, ...).
+pub fn load_level(filename: &str) -> Result<(Maze, LevelMeta), String> {
+    let text = std::fs::read_to_string(filename).map_err(|e| format!("{}: {}", filename, e))?;
+    Ok(parse_level(&text))
+}
+
+pub fn load_maze(filename: &str) -> Result<Maze, String> {
+    load_level(filename).map(|(maze, _)| maze)
+}
 
-    reader
-        .lines()
-        .map(|line| line.unwrap().chars().collect())
-        .collect()
+// Grid coordinates (col, row) of every exit (door) cell, in reading order.
+pub fn exits(maze: &Maze) -> Vec<(usize, usize)> {
+    let mut out = Vec::new();
+    for (j, row) in maze.iter().enumerate() {
+        for (i, &cell) in row.iter().enumerate() {
+            if CellKind::of(cell).is_door() {
+                out.push((i, j));
+            }
+        }
+    }
+    out
 }
 
-pub fn load_maze_for_level(level: i32) -> Maze {
-    let filename = match level {
+// The exit cell (col, row) the world position (x, y) is standing on, if any.
+// Levels may have several exits; the caller can use which one was taken to pick the next level.
+pub fn exit_at(maze: &Maze, x: f32, y: f32, block_size: usize) -> Option<(usize, usize)> {
+    if x < 0.0 || y < 0.0 {
+        return None;
+    }
+    let i = (x / block_size as f32) as usize;
+    let j = (y / block_size as f32) as usize;
+    match maze.get(j).and_then(|row| row.get(i)) {
+        Some(&cell) if CellKind::of(cell).is_door() => Some((i, j)),
+        _ => None,
+    }
+}
+
+// Problems that make a level unplayable (checked by --validate); empty when it looks fine.
+// The player always starts in cell (1, 1), see main::start_level.
+pub fn validate_level(maze: &Maze, meta: &LevelMeta) -> Vec<String> {
+    let mut problems = Vec::new();
+    if maze.is_empty() {
+        problems.push("the grid is empty".to_string());
+        return problems;
+    }
+    if exits(maze).is_empty() {
+        problems.push("no exit ('G') cell".to_string());
+    }
+    match maze.get(1).and_then(|row| row.get(1)) {
+        Some(&cell) if CellKind::of(cell).is_walkable(false) => {}
+        _ => problems.push("the player start (1, 1) is not walkable".to_string()),
+    }
+    let coins = maze.iter().flatten().filter(|&&c| CellKind::of(c) == CellKind::CoinSpawn).count();
+    if let Some(required) = meta.required_coins
+        && required > coins
+    {
+        problems.push(format!("coins={} but only {} coin(s) in the grid", required, coins));
+    }
+    problems
+}
+
+pub fn level_filename(level: i32) -> &'static str {
+    match level {
         1 => "maze1.txt",
         2 => "maze2.txt",
         3 => "maze3.txt",
         _ => "maze1.txt", // fallback
-    };
-    load_maze(filename)
+    }
+}
+
+pub fn load_maze_for_level(level: i32) -> Result<Maze, String> {
+    load_maze(level_filename(level))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comments_and_leading_blank_lines_do_not_shift_rows() {
+        let text = "# a comment\n\n#name=Nivel 1\n+--+\n|RC|\n+--+\n";
+        let (maze, meta) = parse_level(text);
+        assert_eq!(maze.len(), 3);
+        assert_eq!(maze[1], vec!['|', 'R', 'C', '|']);
+        assert_eq!(meta.name.as_deref(), Some("Nivel 1"));
+    }
+
+    #[test]
+    fn blank_line_after_grid_starts_metadata_section() {
+        let text = "+--+\n|  G\n+--+\n\ncoins = 4\nmusic=sounds/level2.ogg\ntheme=blue\nlighting=torch\nday_cycle=300\n";
+        let (maze, meta) = parse_level(text);
+        assert_eq!(maze.len(), 3);
+        assert_eq!(meta.required_coins, Some(4));
+        assert_eq!(meta.music.as_deref(), Some("sounds/level2.ogg"));
+        assert_eq!(meta.extra.get("theme").map(String::as_str), Some("blue"));
+        assert_eq!(meta.lighting, Lighting::Torch);
+        assert_eq!(meta.day_cycle, Some(300.0));
+    }
+
+    #[test]
+    fn every_cell_character_has_one_classification() {
+        use CellKind::*;
+        // (char, kind, walkable closed, walkable open, blocks sight, spawn)
+        let table = [
+            (' ', Floor, true, true, false, false),
+            ('+', Wall, false, false, true, false),
+            ('-', Wall, false, false, true, false),
+            ('|', Wall, false, false, true, false),
+            ('#', Wall, false, false, true, false),
+            ('G', Door, false, true, true, false),
+            ('g', Door, false, true, true, false),
+            ('R', NpcSpawn, true, true, false, true),
+            ('C', CoinSpawn, true, true, false, true),
+            ('P', PlayerSpawn, true, true, false, true),
+            ('?', Wall, false, false, true, false),
+        ];
+        for (c, kind, closed, open, sight, spawn) in table {
+            let k = CellKind::of(c);
+            assert_eq!(k, kind, "{:?}", c);
+            assert_eq!(k.is_walkable(false), closed, "{:?} walkable with doors closed", c);
+            assert_eq!(k.is_walkable(true), open, "{:?} walkable with doors open", c);
+            assert_eq!(k.blocks_sight(), sight, "{:?} blocks sight", c);
+            assert_eq!(k.is_spawn(), spawn, "{:?} is a spawn", c);
+            assert_eq!(k.is_wall(), kind == Wall);
+            assert_eq!(k.is_door(), kind == Door);
+        }
+    }
+
+    #[test]
+    fn every_exit_is_detected() {
+        let (maze, _) = parse_level("+G--+\n|   G\n+---+\n");
+        assert_eq!(exits(&maze), vec![(1, 0), (4, 1)]);
+        assert_eq!(exit_at(&maze, 150.0, 50.0, 100), Some((1, 0)));
+        assert_eq!(exit_at(&maze, 450.0, 150.0, 100), Some((4, 1)));
+        assert_eq!(exit_at(&maze, 250.0, 150.0, 100), None);
+        assert_eq!(exit_at(&maze, -5.0, 50.0, 100), None);
+    }
+
+    #[test]
+    fn validation_reports_unplayable_levels() {
+        let (maze, meta) = parse_level("+--+\n| CG\n+--+\n");
+        assert!(validate_level(&maze, &meta).is_empty());
+        let (maze, meta) = parse_level("+--+\n|+C|\n+--+\n\ncoins=2\n");
+        assert_eq!(validate_level(&maze, &meta).len(), 3);
+        assert_eq!(validate_level(&Vec::new(), &meta), vec!["the grid is empty".to_string()]);
+    }
+
+    #[test]
+    fn crlf_line_endings_are_stripped() {
+        let (maze, _) = parse_level("+-+\r\n| |\r\n");
+        assert_eq!(maze[1], vec!['|', ' ', '|']);
+    }
 }
@@ -1,21 +1,75 @@
+use crate::anim::{ease, Tween};
+use crate::display::Display;
+use crate::clip::Rect;
 use crate::framebuffer::Framebuffer;
-use crate::textures::TextureAtlas;
+use crate::input::MenuInput;
+use crate::progress::{Progress, LEVEL_COUNT};
+use crate::savegame::SaveGame;
+use crate::settings::{Settings, GAMMA_MAX, GAMMA_MIN};
+use crate::strings::{self, tr, Str};
+use crate::textures::{TextureAtlas, TextureId};
+use crate::timestep::{next_fps_limit, pacing_limit};
 use raylib::prelude::*;
 
 pub enum MenuAction {
     StartLevel(i32),
+    // resume the run in saves/ (savegame)
+    Continue,
     Quit,
 }
 
 enum MenuState {
     Main,
     LevelSelect,
+    Options,
 }
 
-pub fn run_menu(window: &mut RaylibHandle, thread: &RaylibThread, framebuffer: &mut Framebuffer, textures: &TextureAtlas, audio: &mut crate::audio::AudioManager) -> MenuAction {
+// entries of the main menu, in display order; CONTINUAR goes first while a run is saved
+const MAIN_ITEMS: [Str; 3] = [Str::MenuPlay, Str::MenuOptions, Str::MenuQuit];
+// rows of the options screen (last row is always "back")
+const OPTIONS_ROWS: usize = 10;
+// vertical distance between option rows, in pixels
+const OPTIONS_ROW_PITCH: i32 = 44;
+const ROW_LANGUAGE: usize = 0;
+const ROW_MASTER: usize = 1;
+const ROW_MUSIC: usize = 2;
+const ROW_SFX: usize = 3;
+const ROW_BRIGHTNESS: usize = 4;
+const ROW_PIXEL_PERFECT: usize = 5;
+const ROW_PALETTE: usize = 6;
+const ROW_FPS_LIMIT: usize = 7;
+const ROW_RESET_EXPLORATION: usize = 8;
+// how much one left/right press changes a volume slider / the brightness (gamma)
+const VOLUME_STEP: f32 = 0.1;
+// the selected row bobs up and down by SELECTION_BOB_PX, one way every SELECTION_BOB_SECS
+const SELECTION_BOB_PX: f32 = 3.0;
+const SELECTION_BOB_SECS: f32 = 0.6;
+const GAMMA_STEP: f32 = 0.1;
+
+// "LABEL: [#####-----] 50%"
+fn volume_slider_text(label: &str, volume: f32) -> String {
+    let filled = (volume * 10.0).round() as usize;
+    format!("{}: [{}{}] {:>3}%", label, "#".repeat(filled), "-".repeat(10 - filled.min(10)), (volume * 100.0).round() as i32)
+}
+
+// draw `text` horizontally centered on `cx`
+fn draw_centered(d: &mut RaylibDrawHandle, text: &str, cx: i32, y: i32, size: i32, color: Color) {
+    let w = d.measure_text(text, size);
+    d.draw_text(text, cx - w / 2, y, size, color);
+}
+
+pub fn run_menu(window: &mut RaylibHandle, thread: &RaylibThread, framebuffer: &mut Framebuffer, textures: &mut TextureAtlas, audio: &mut crate::audio::AudioManager, settings: &mut Settings, display: &mut Display) -> MenuAction {
     let mut menu_state = MenuState::Main;
-    let mut main_selection: usize = 0; // 0 = Jugar, 1 = Salir
-    let mut level_selection: i32 = 1; // 1, 2, 3
+    let main_items: Vec<Str> = SaveGame::exists().then_some(Str::MenuContinue).into_iter().chain(MAIN_ITEMS).collect();
+    let mut main_selection: usize = 0; // index into main_items
+    let mut options_selection: usize = 0; // one of the ROW_* constants, or OPTIONS_ROWS - 1 = volver
+    let mut level_selection: i32 = 1; // 1..=LEVEL_COUNT, always an unlocked level
+    // which levels can be picked (re-read each time the level select opens)
+    let mut progress = Progress::load();
+    let mut input = MenuInput::new();
+    // saved exploration was deleted during this visit to the options screen
+    let mut exploration_reset = false;
+    let mut selection_bob = Tween::new(-SELECTION_BOB_PX, SELECTION_BOB_PX, SELECTION_BOB_SECS, ease::in_out_sine);
 
     loop {
         // Check if window should close
@@ -23,6 +77,12 @@ pub fn run_menu(window: &mut RaylibHandle, thread: &RaylibThread, framebuffer: &
             return MenuAction::Quit;
         }
         
+        framebuffer.follow_window(window);
+        display.handle_key(window, framebuffer, settings);
+        // other textures still arriving from the loader thread; the menu art is full-screen and
+        // lazy, so it is decoded here on the first menu frame (the gradient stands in if it's missing)
+        textures.poll_loaded();
+        textures.prepare(&TextureId::Menu);
         framebuffer.clear();
 
         // Draw background (same as before)
@@ -30,136 +90,234 @@ pub fn run_menu(window: &mut RaylibHandle, thread: &RaylibThread, framebuffer: &
         let fb_h = framebuffer.height as u32;
 
         // Determine if we have a menu image and its native size
-        let menu_dims = textures.menu.as_ref().map(|m| (m.w, m.h));
+        let menu_dims = textures.get(&TextureId::Menu).map(|m| (m.w, m.h));
 
         if let Some((mw, mh)) = menu_dims {
             // compute scale that fits menu inside framebuffer without stretching
             let scale = (fb_w as f32 / mw as f32).min(fb_h as f32 / mh as f32).max(1e-6);
             let tw = (mw as f32 * scale).floor() as u32;
             let th = (mh as f32 * scale).floor() as u32;
-            let ox = ((fb_w - tw) / 2) as isize;
-            let oy = ((fb_h - th) / 2) as isize;
+            let ox = (fb_w - tw) / 2;
+            let oy = (fb_h - th) / 2;
 
             // draw background dark
-            let bg = Color::new(8,8,16,255);
-            for y in 0..fb_h {
-                for x in 0..fb_w {
-                    framebuffer.set_current_color(bg);
-                    framebuffer.set_pixel(x, y);
-                }
-            }
+            let bounds = framebuffer.bounds();
+            framebuffer.fill_rect(bounds, Color::new(8,8,16,255));
 
             // sample menu texture only into centered rect to preserve aspect (animated)
-            for y in 0..th {
-                for x in 0..tw {
-                    let u = x as f32 / (tw as f32 - 1.0).max(1.0);
-                    let v = y as f32 / (th as f32 - 1.0).max(1.0);
-                    let col = textures.sample_menu(u, v);
-                    let px = ox + x as isize;
-                    let py = oy + y as isize;
-                    if px >= 0 && py >= 0 {
-                        let pxu = px as u32;
-                        let pyu = py as u32;
-                        if pxu < fb_w && pyu < fb_h {
-                            framebuffer.set_current_color(col);
-                            framebuffer.set_pixel(pxu, pyu);
-                        }
-                    }
-                }
-            }
+            framebuffer.blit(Rect::new(ox as i32, oy as i32, tw as i32, th as i32), |x, y| {
+                let u = x as f32 / (tw as f32 - 1.0).max(1.0);
+                let v = y as f32 / (th as f32 - 1.0).max(1.0);
+                textures.sample_fullscreen(&TextureId::Menu, u, v)
+            });
         } else {
             // no menu texture - fallback to full-screen sampling
-            for y in 0..fb_h {
-                for x in 0..fb_w {
-                    let u = x as f32 / fb_w as f32;
-                    let v = y as f32 / fb_h as f32;
-                    let col = textures.sample_menu(u, v);
-                    framebuffer.set_current_color(col);
-                    framebuffer.set_pixel(x, y);
-                }
-            }
+            framebuffer.fill_fullscreen(textures, &TextureId::Menu);
         }
 
-        // Input handling based on current menu state
+        // Input handling based on current menu state (keyboard and gamepad merged)
+        input.poll(window);
         match menu_state {
             MenuState::Main => {
-                if window.is_key_pressed(KeyboardKey::KEY_DOWN) || window.is_key_pressed(KeyboardKey::KEY_S) {
-                    main_selection = (main_selection + 1) % 2;
+                if input.down {
+                    main_selection = (main_selection + 1) % main_items.len();
                 }
-                if window.is_key_pressed(KeyboardKey::KEY_UP) || window.is_key_pressed(KeyboardKey::KEY_W) {
-                    main_selection = (main_selection + 2 - 1) % 2;
+                if input.up {
+                    main_selection = (main_selection + main_items.len() - 1) % main_items.len();
                 }
-                if window.is_key_pressed(KeyboardKey::KEY_ENTER) {
-                    if main_selection == 0 {
-                        menu_state = MenuState::LevelSelect;
-                    } else {
-                        return MenuAction::Quit;
+                if input.confirm || input.start {
+                    match main_items[main_selection] {
+                        Str::MenuContinue => return MenuAction::Continue,
+                        Str::MenuPlay => {
+                            progress = Progress::load();
+                            // start on the newest unlocked level
+                            level_selection = progress.unlocked;
+                            menu_state = MenuState::LevelSelect;
+                        }
+                        Str::MenuOptions => {
+                            options_selection = 0;
+                            exploration_reset = false;
+                            menu_state = MenuState::Options;
+                        }
+                        _ => return MenuAction::Quit,
                     }
                 }
-                if window.is_key_pressed(KeyboardKey::KEY_Q) {
+                if input.quit {
                     return MenuAction::Quit;
                 }
             }
             MenuState::LevelSelect => {
-                if window.is_key_pressed(KeyboardKey::KEY_DOWN) || window.is_key_pressed(KeyboardKey::KEY_S) {
-                    level_selection = if level_selection < 3 { level_selection + 1 } else { 1 };
+                // locked levels are skipped
+                if input.down {
+                    level_selection = if level_selection < progress.unlocked { level_selection + 1 } else { 1 };
                 }
-                if window.is_key_pressed(KeyboardKey::KEY_UP) || window.is_key_pressed(KeyboardKey::KEY_W) {
-                    level_selection = if level_selection > 1 { level_selection - 1 } else { 3 };
+                if input.up {
+                    level_selection = if level_selection > 1 { level_selection - 1 } else { progress.unlocked };
                 }
-                if window.is_key_pressed(KeyboardKey::KEY_ENTER) {
+                if (input.confirm || input.start) && progress.is_unlocked(level_selection) {
                     return MenuAction::StartLevel(level_selection);
                 }
-                if window.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+                if input.back {
                     menu_state = MenuState::Main;
                 }
-                if window.is_key_pressed(KeyboardKey::KEY_Q) {
+                if input.quit {
                     return MenuAction::Quit;
                 }
             }
+            MenuState::Options => {
+                if input.down {
+                    options_selection = (options_selection + 1) % OPTIONS_ROWS;
+                }
+                if input.up {
+                    options_selection = (options_selection + OPTIONS_ROWS - 1) % OPTIONS_ROWS;
+                }
+                let is_back_row = options_selection == OPTIONS_ROWS - 1;
+                if options_selection == ROW_LANGUAGE && (input.left || input.right || input.confirm) {
+                    settings.lang = settings.lang.next();
+                    strings::set_lang(settings.lang);
+                    settings.save();
+                }
+                if options_selection == ROW_PIXEL_PERFECT && (input.left || input.right || input.confirm) {
+                    settings.pixel_perfect = !settings.pixel_perfect;
+                    textures.set_pixel_perfect(settings.pixel_perfect);
+                    settings.save();
+                }
+                if options_selection == ROW_PALETTE && (input.left || input.right || input.confirm) {
+                    settings.palette = settings.palette.next();
+                    settings.save();
+                }
+                if options_selection == ROW_FPS_LIMIT && (input.left || input.right || input.confirm) {
+                    settings.fps_limit = next_fps_limit(settings.fps_limit, if input.left { -1 } else { 1 });
+                    settings.save();
+                }
+                if options_selection == ROW_RESET_EXPLORATION && input.confirm {
+                    crate::exploration::reset_all();
+                    exploration_reset = true;
+                }
+                let delta = if input.right { VOLUME_STEP } else if input.left { -VOLUME_STEP } else { 0.0 };
+                if delta != 0.0 {
+                    match options_selection {
+                        ROW_MASTER => settings.audio.master_volume = (settings.audio.master_volume + delta).clamp(0.0, 1.0),
+                        ROW_MUSIC => settings.audio.music_volume = (settings.audio.music_volume + delta).clamp(0.0, 1.0),
+                        ROW_SFX => settings.audio.sfx_volume = (settings.audio.sfx_volume + delta).clamp(0.0, 1.0),
+                        ROW_BRIGHTNESS => {
+                            settings.gamma = (settings.gamma + delta.signum() * GAMMA_STEP).clamp(GAMMA_MIN, GAMMA_MAX);
+                        }
+                        _ => {}
+                    }
+                    if matches!(options_selection, ROW_MASTER | ROW_MUSIC | ROW_SFX) {
+                        audio.apply_settings(&settings.audio);
+                    }
+                    if options_selection == ROW_SFX {
+                        // preview the new effects volume
+                        audio.play_coin_sound();
+                    }
+                    if matches!(options_selection, ROW_MASTER | ROW_MUSIC | ROW_SFX | ROW_BRIGHTNESS) {
+                        settings.save();
+                    }
+                }
+                if input.back || (is_back_row && input.confirm) {
+                    menu_state = MenuState::Main;
+                }
+            }
+        }
+
+        selection_bob.advance(window.get_frame_time());
+        if selection_bob.is_done() {
+            selection_bob = selection_bob.reversed();
         }
+        let bob = |selected: bool| if selected { selection_bob.value().round() as i32 } else { 0 };
+
+        // brightness preview: the menu background gets the same gamma as the game view
+        framebuffer.apply_gamma(settings.gamma);
 
         // Draw overlay text via raylib
-        let screen_w = window.get_screen_width();
-        let screen_h = window.get_screen_height();
-        if let Ok(texture) = window.load_texture_from_image(thread, &framebuffer.color_buffer) {
-            let mut d = window.begin_drawing(thread);
-            let src = Rectangle::new(0.0, 0.0, framebuffer.width as f32, framebuffer.height as f32);
-            let dest = Rectangle::new(0.0, 0.0, screen_w as f32, screen_h as f32);
-            let origin = Vector2::new(0.0,0.0);
-            d.draw_texture_pro(&texture, src, dest, origin, 0.0, Color::WHITE);
+        framebuffer.present_stretched(window, thread, |d, screen_w, screen_h| {
 
             let cx = screen_w / 2;
 
+            if textures.is_loading() {
+                let progress = strings::trf(Str::LoadingTextures, &[&textures.loaded_count(), &textures.total()]);
+                d.draw_text(&progress, 10, screen_h - 30, 20, Color::GRAY);
+            }
+
             match menu_state {
                 MenuState::Main => {
                     // Draw main menu
                     let opt_y = screen_h / 2 - 50;
-                    let play_color = if main_selection == 0 { Color::YELLOW } else { Color::WHITE };
-                    let quit_color = if main_selection == 1 { Color::YELLOW } else { Color::WHITE };
-                    d.draw_text("JUGAR", cx - 40, opt_y, 40, play_color);
-                    d.draw_text("SALIR", cx - 40, opt_y + 60, 40, quit_color);
+                    for (i, item) in main_items.iter().enumerate() {
+                        let color = if main_selection == i { Color::YELLOW } else { Color::WHITE };
+                        draw_centered(d, tr(*item), cx, opt_y + 60 * i as i32 + bob(main_selection == i), 40, color);
+                    }
                 }
                 MenuState::LevelSelect => {
                     // Draw level selection
                     let title_y = screen_h / 2 - 200;
-                    d.draw_text("SELECCIONAR NIVEL", cx - 150, title_y, 40, Color::WHITE);
+                    draw_centered(d, tr(Str::MenuSelectLevel), cx, title_y, 40, Color::WHITE);
 
                     let level_y = screen_h / 2 - 80;
-                    
-                    let level1_color = if level_selection == 1 { Color::YELLOW } else { Color::WHITE };
-                    let level2_color = if level_selection == 2 { Color::YELLOW } else { Color::WHITE };
-                    let level3_color = if level_selection == 3 { Color::YELLOW } else { Color::WHITE };
-
-                    d.draw_text("NIVEL 1 - FACIL (2 fichas)", cx - 140, level_y, 30, level1_color);
-                    d.draw_text("NIVEL 2 - MEDIO (4 fichas)", cx - 150, level_y + 60, 30, level2_color);
-                    d.draw_text("NIVEL 3 - DIFICIL (6 fichas)", cx - 160, level_y + 120, 30, level3_color);
+                    let levels = [Str::MenuLevel1, Str::MenuLevel2, Str::MenuLevel3];
+                    for (i, item) in levels.iter().enumerate().take(LEVEL_COUNT as usize) {
+                        let level = i as i32 + 1;
+                        let color = if !progress.is_unlocked(level) {
+                            Color::DARKGRAY
+                        } else if level_selection == level {
+                            Color::YELLOW
+                        } else {
+                            Color::WHITE
+                        };
+                        draw_centered(d, tr(*item), cx, level_y + 60 * i as i32 + bob(level_selection == level), 30, color);
+                    }
 
                     // Instructions
-                    d.draw_text("ESC = Volver | ENTER = Jugar", cx - 140, level_y + 200, 20, Color::GRAY);
+                    draw_centered(d, tr(Str::MenuLevelHint), cx, level_y + 200, 20, Color::GRAY);
+                }
+                MenuState::Options => {
+                    let title_y = screen_h / 2 - 200;
+                    draw_centered(d, tr(Str::MenuOptionsTitle), cx, title_y, 40, Color::WHITE);
+
+                    // rows start just under the title; with OPTIONS_ROW_PITCH the hint still fits a 900 px window
+                    let opt_y = screen_h / 2 - 80;
+                    let row_color = |row: usize| if options_selection == row { Color::YELLOW } else { Color::WHITE };
+                    let row_y = |row: usize| opt_y + OPTIONS_ROW_PITCH * row as i32 + bob(options_selection == row);
+                    let lang_text = format!("{}: < {} >", tr(Str::MenuLanguage), settings.lang.display_name());
+                    draw_centered(d, &lang_text, cx, row_y(ROW_LANGUAGE), 30, row_color(ROW_LANGUAGE));
+                    let sliders = [
+                        (ROW_MASTER, Str::MenuMasterVolume, settings.audio.master_volume),
+                        (ROW_MUSIC, Str::MenuMusicVolume, settings.audio.music_volume),
+                        (ROW_SFX, Str::MenuSfxVolume, settings.audio.sfx_volume),
+                    ];
+                    for (row, label, volume) in sliders {
+                        draw_centered(d, &volume_slider_text(tr(label), volume), cx, row_y(row), 30, row_color(row));
+                    }
+                    let brightness_text = format!("{}: < {:.1} >", tr(Str::MenuBrightness), settings.gamma);
+                    draw_centered(d, &brightness_text, cx, row_y(ROW_BRIGHTNESS), 30, row_color(ROW_BRIGHTNESS));
+                    let pixel_state = tr(if settings.pixel_perfect { Str::MenuOn } else { Str::MenuOff });
+                    let pixel_text = format!("{}: < {} >", tr(Str::MenuPixelPerfect), pixel_state);
+                    draw_centered(d, &pixel_text, cx, row_y(ROW_PIXEL_PERFECT), 30, row_color(ROW_PIXEL_PERFECT));
+                    let palette_text = format!("{}: < {} >", tr(Str::MenuPalette), tr(settings.palette.label()));
+                    draw_centered(d, &palette_text, cx, row_y(ROW_PALETTE), 30, row_color(ROW_PALETTE));
+                    let mut fps_value = if settings.fps_limit == 0 { tr(Str::MenuUnlimited).to_string() } else { settings.fps_limit.to_string() };
+                    // without fixed_timestep the limit is overridden; say so instead of ignoring it silently
+                    let paced = pacing_limit(settings.fps_limit, settings.fixed_timestep);
+                    if paced != settings.fps_limit {
+                        fps_value = strings::trf(Str::MenuFpsCoupled, &[&fps_value, &paced]);
+                    }
+                    let fps_text = format!("{}: < {} >", tr(Str::MenuFpsLimit), fps_value);
+                    draw_centered(d, &fps_text, cx, row_y(ROW_FPS_LIMIT), 30, row_color(ROW_FPS_LIMIT));
+                    let reset_text = if exploration_reset {
+                        format!("{} - {}", tr(Str::MenuResetExploration), tr(Str::MenuDone))
+                    } else {
+                        tr(Str::MenuResetExploration).to_string()
+                    };
+                    draw_centered(d, &reset_text, cx, row_y(ROW_RESET_EXPLORATION), 30, row_color(ROW_RESET_EXPLORATION));
+                    let back_row = OPTIONS_ROWS - 1;
+                    draw_centered(d, tr(Str::MenuBack), cx, row_y(back_row), 30, row_color(back_row));
+
+                    draw_centered(d, tr(Str::MenuOptionsHint), cx, opt_y + OPTIONS_ROW_PITCH * OPTIONS_ROWS as i32 + 30, 20, Color::GRAY);
                 }
             }
-        }
+        });
 
         // update audio streaming buffers for menu music
         audio.update();
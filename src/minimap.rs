@@ -1,13 +1,28 @@
+use crate::clip::Rect;
 use crate::framebuffer::Framebuffer;
-use crate::maze::Maze;
+use crate::line::line_aa;
+use crate::maze::{CellKind, Maze};
 use crate::player::Player;
+use crate::shapes::{fill_circle_in, fill_convex_polygon_in};
 use crate::sprite::{NPC, Coin};
+use crate::ui::Palette;
 use raylib::prelude::Color;
 
+// `base` moved `t` (0..1) of the way toward `tint`, opaque.
+fn tinted(base: Color, tint: Color, t: f32) -> Color {
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    Color::new(mix(base.r, tint.r), mix(base.g, tint.g), mix(base.b, tint.b), 255)
+}
+
+// Fixed minimap window size in framebuffer pixels (independent of maze size)
+pub const MINIMAP_WIDTH: usize = 200;
+pub const MINIMAP_HEIGHT: usize = 150;
+
 // Render a simple top-left minimap into the framebuffer.
 // - `scale` is pixels per maze cell in the minimap.
 // - `xo`, `yo` are pixel offsets inside the framebuffer where the minimap origin is drawn.
 // - `block_size` is the world pixels per maze cell (used to convert world coords -> maze cells).
+// - `view_radius` is how many cells around the player are shown; bigger mazes scroll inside the window.
 pub fn render_minimap(
     fb: &mut Framebuffer,
     maze: &Maze,
@@ -19,39 +34,64 @@ pub fn render_minimap(
     npcs: &Vec<NPC>,
     coins: &Vec<Coin>,
     discovered: &mut Vec<Vec<bool>>,
+    view_radius: usize,
 ) {
     if maze.is_empty() { return; }
     // ensure discovered grid matches maze dimensions
     if discovered.len() != maze.len() || discovered.iter().zip(maze.iter()).any(|(drow, mrow)| drow.len() != mrow.len()) {
         *discovered = maze.iter().map(|r| vec![false; r.len()]).collect();
     }
-    // helper to clip and draw a filled rect in framebuffer
-    let draw_filled_rect = |fb: &mut Framebuffer, x: isize, y: isize, w: usize, h: usize, col: Color| {
-        fb.set_current_color(col);
-        for iy in 0..h {
-            let py = y + iy as isize;
-            if py < 0 { continue; }
-            for ix in 0..w {
-                let px = x + ix as isize;
-                if px < 0 { continue; }
-                // clip to framebuffer bounds
-                if (px as u32) >= fb.width || (py as u32) >= fb.height { continue; }
-                fb.set_pixel(px as u32, py as u32);
-            }
+    // marker colors come from the framebuffer's palette; each kind also has its own shape
+    // (NPCs triangles, coins discs, doors squares) so color is never the only cue
+    let palette: Palette = fb.palette();
+    let fixed_minimap_width = MINIMAP_WIDTH;
+    let fixed_minimap_height = MINIMAP_HEIGHT;
+
+    // clip rectangle for everything drawn inside the minimap window (cells, markers, grid)
+    let window = Rect::new(xo as i32, yo as i32, fixed_minimap_width as i32, fixed_minimap_height as i32);
+    let rect = |x: isize, y: isize, w: usize, h: usize| Rect::new(x as i32, y as i32, w as i32, h as i32);
+    // filled rect clipped to the minimap window (and the framebuffer, by fill_rect)
+    let draw_clipped_rect = |fb: &mut Framebuffer, x: isize, y: isize, w: usize, h: usize, col: Color| {
+        if let Some(r) = rect(x, y, w, h).intersect(&window) {
+            fb.fill_rect(r, col);
         }
     };
 
     let rows = maze.len();
     let max_cols = maze.iter().map(|r| r.len()).max().unwrap_or(0);
 
-    // Fixed minimap size (independent of maze size)
-    let fixed_minimap_width = 200;
-    let fixed_minimap_height = 150;
-    
-    // Calculate scaling to fit maze into fixed minimap size
-    let scale_x = if max_cols > 0 { fixed_minimap_width / max_cols } else { scale };
-    let scale_y = if rows > 0 { fixed_minimap_height / rows } else { scale };
-    let adaptive_scale = scale_x.min(scale_y).max(1); // Use smaller scale, minimum 1
+    // Viewport: only a (2*view_radius+1)^2 window of cells around the player is shown.
+    // Small mazes that fit inside the window are shown whole, exactly as before.
+    let window_cells = view_radius * 2 + 1;
+    let vis_cols = max_cols.min(window_cells).max(1);
+    let vis_rows = rows.min(window_cells).max(1);
+
+    // Calculate scaling to fit the visible window into fixed minimap size
+    let scale_x = fixed_minimap_width / vis_cols;
+    let scale_y = fixed_minimap_height / vis_rows;
+    let adaptive_scale = if vis_cols > 0 && vis_rows > 0 { scale_x.min(scale_y).max(1) } else { scale }; // Use smaller scale, minimum 1
+
+    // top-left of the viewport in (fractional) cell units, centered on the player and clamped
+    // to the maze so the window scrolls smoothly and never shows space past the edges
+    let player_cx = player.pos.x / block_size as f32;
+    let player_cy = player.pos.y / block_size as f32;
+    let origin_x = (player_cx - vis_cols as f32 / 2.0).clamp(0.0, max_cols.saturating_sub(vis_cols) as f32);
+    let origin_y = (player_cy - vis_rows as f32 / 2.0).clamp(0.0, rows.saturating_sub(vis_rows) as f32);
+    let origin_px = (origin_x * adaptive_scale as f32).round() as isize;
+    let origin_py = (origin_y * adaptive_scale as f32).round() as isize;
+
+    // range of cells that intersect the viewport (one extra cell for the partially visible edge)
+    let first_col = origin_x.floor() as usize;
+    let first_row = origin_y.floor() as usize;
+    let last_col = (first_col + vis_cols + 1).min(max_cols);
+    let last_row = (first_row + vis_rows + 1).min(rows);
+
+    // world -> minimap pixel conversion honoring the scrolled viewport
+    let to_minimap = |wx: f32, wy: f32| -> (isize, isize) {
+        let mx = (wx / block_size as f32 - origin_x) * adaptive_scale as f32 + xo as f32;
+        let my = (wy / block_size as f32 - origin_y) * adaptive_scale as f32 + yo as f32;
+        (mx.round() as isize, my.round() as isize)
+    };
 
     // reveal cells around player (fog-of-war). radius in cells
     let pi = (player.pos.x / block_size as f32).floor() as isize;
@@ -70,67 +110,47 @@ pub fn render_minimap(
     }
 
     // background for minimap (fixed size with padding)
-    draw_filled_rect(fb, xo as isize - 6, yo as isize - 6, fixed_minimap_width + 12, fixed_minimap_height + 12, Color::new(8,8,16,200));
-    // outer border (fixed size)
-    fb.set_current_color(Color::new(220,220,220,200));
-    // top border
-    for x in (xo as isize - 6)..(xo as isize - 6 + (fixed_minimap_width + 12) as isize) {
-        if x >= 0 && (yo as isize - 6) >= 0 && (x as u32) < fb.width && ((yo as isize - 6) as u32) < fb.height {
-            fb.set_pixel(x as u32, (yo as isize - 6) as u32);
-        }
-    }
-    // left border
-    for y in (yo as isize - 6)..(yo as isize - 6 + (fixed_minimap_height + 12) as isize) {
-        if y >= 0 && (xo as isize - 6) >= 0 && (y as u32) < fb.height && ((xo as isize - 6) as u32) < fb.width {
-            fb.set_pixel((xo as isize - 6) as u32, y as u32);
-        }
-    }
+    fb.fill_rect(rect(xo as isize - 6, yo as isize - 6, fixed_minimap_width + 12, fixed_minimap_height + 12), Color::new(8,8,16,200));
+    // outer border (fixed size): top and left edges
+    let border = Color::new(220,220,220,200);
+    fb.fill_rect(rect(xo as isize - 6, yo as isize - 6, fixed_minimap_width + 12, 1), border);
+    fb.fill_rect(rect(xo as isize - 6, yo as isize - 6, 1, fixed_minimap_height + 12), border);
 
-    // draw cells with adaptive scaling to fit fixed minimap size
-    for (ry, row) in maze.iter().enumerate() {
-        for (rx, &cell) in row.iter().enumerate() {
-            let x = xo as isize + (rx * adaptive_scale) as isize;
-            let y = yo as isize + (ry * adaptive_scale) as isize;
+    // draw only the cells inside the viewport, with adaptive scaling to fit fixed minimap size
+    for (ry, row) in maze.iter().enumerate().take(last_row).skip(first_row) {
+        for (rx, &cell) in row.iter().enumerate().take(last_col).skip(first_col) {
+            let x = xo as isize + (rx * adaptive_scale) as isize - origin_px;
+            let y = yo as isize + (ry * adaptive_scale) as isize - origin_py;
             let discovered_cell = discovered.get(ry).and_then(|r| r.get(rx)).copied().unwrap_or(false);
             if !discovered_cell {
                 // draw fog for undiscovered cells
-                draw_filled_rect(fb, x, y, adaptive_scale, adaptive_scale, Color::new(10,10,20,220));
+                draw_clipped_rect(fb, x, y, adaptive_scale, adaptive_scale, Color::new(10,10,20,220));
                 continue;
             }
-            let col = match cell {
-                ' ' => Color::new(170,170,180,200), // floor (slightly bluish)
-                '+' | '|' | '-' => Color::new(32,32,48,255), // walls dark
-                'g' => Color::new(80,160,80,255),
-                'R' => Color::new(180,100,100,255),
-                _ => Color::new(140,140,140,200),
+            let floor = Color::new(170,170,180,200); // floor (slightly bluish)
+            let col = match CellKind::of(cell) {
+                CellKind::Floor | CellKind::CoinSpawn | CellKind::PlayerSpawn => floor,
+                CellKind::Wall => Color::new(32,32,48,255), // walls dark
+                // doors: a square inset in a floor cell, drawn below
+                CellKind::Door => floor,
+                CellKind::NpcSpawn => tinted(floor, palette.danger, 0.4),
             };
-            draw_filled_rect(fb, x, y, adaptive_scale, adaptive_scale, col);
+            draw_clipped_rect(fb, x, y, adaptive_scale, adaptive_scale, col);
+            if CellKind::of(cell) == CellKind::Door {
+                let inset = (adaptive_scale / 5) as isize;
+                let side = adaptive_scale.saturating_sub(2 * inset as usize).max(1);
+                draw_clipped_rect(fb, x + inset, y + inset, side, side, palette.objective);
+            }
             // subtle grid line on bottom and right edges (only if scale is big enough)
             if adaptive_scale > 3 {
-                fb.set_current_color(Color::new(20,20,30,120));
-                if (y as isize + adaptive_scale as isize) >= 0 {
-                    for gx in 0..adaptive_scale {
-                        let px = x + gx as isize;
-                        let py = y + adaptive_scale as isize - 1;
-                        if px >= 0 && py >= 0 && (px as u32) < fb.width && (py as u32) < fb.height {
-                            fb.set_pixel(px as u32, py as u32);
-                        }
-                    }
-                }
-                if (x as isize + adaptive_scale as isize) >= 0 {
-                    for gy in 0..adaptive_scale {
-                        let px = x + adaptive_scale as isize - 1;
-                        let py = y + gy as isize;
-                        if px >= 0 && py >= 0 && (px as u32) < fb.width && (py as u32) < fb.height {
-                            fb.set_pixel(px as u32, py as u32);
-                        }
-                    }
-                }
+                let grid = Color::new(20,20,30,120);
+                draw_clipped_rect(fb, x, y + adaptive_scale as isize - 1, adaptive_scale, 1, grid);
+                draw_clipped_rect(fb, x + adaptive_scale as isize - 1, y, 1, adaptive_scale, grid);
             }
         }
     }
 
-    // draw NPCs as small red squares only if their cell was discovered
+    // draw NPCs as small triangles only if their cell was discovered
     for npc in npcs.iter() {
         let cx_cell = (npc.pos.x / block_size as f32).floor() as isize;
         let cy_cell = (npc.pos.y / block_size as f32).floor() as isize;
@@ -140,15 +160,14 @@ pub fn render_minimap(
         if !discovered[cy_cell as usize][cx_cell as usize] { continue; }
         
         // Convert world position to minimap position using adaptive scale
-        let mx = (npc.pos.x / block_size as f32) * adaptive_scale as f32 + xo as f32;
-        let my = (npc.pos.y / block_size as f32) * adaptive_scale as f32 + yo as f32;
-        let cx = mx.round() as isize;
-        let cy = my.round() as isize;
-        let npc_size = (adaptive_scale / 3).max(2);
-        draw_filled_rect(fb, cx - npc_size as isize / 2, cy - npc_size as isize / 2, npc_size, npc_size, Color::RED);
+        let (cx, cy) = to_minimap(npc.pos.x, npc.pos.y);
+        let half = ((adaptive_scale / 3).max(2) as f32 / 2.0).max(1.5);
+        let (x, y) = (cx as f32 + 0.5, cy as f32 + 0.5);
+        let triangle = [(x, y - half * 1.2), (x + half, y + half * 0.8), (x - half, y + half * 0.8)];
+        fill_convex_polygon_in(fb, &window, &triangle, palette.danger);
     }
 
-    // draw coins as small gold squares only if their cell was discovered and not collected
+    // draw coins as small discs only if their cell was discovered and not collected
     for coin in coins.iter() {
         if coin.collected { continue; }
         
@@ -160,19 +179,28 @@ pub fn render_minimap(
         if !discovered[cy_cell as usize][cx_cell as usize] { continue; }
         
         // Convert world position to minimap position using adaptive scale
-        let mx = (coin.pos.x / block_size as f32) * adaptive_scale as f32 + xo as f32;
-        let my = (coin.pos.y / block_size as f32) * adaptive_scale as f32 + yo as f32;
-        let cx = mx.round() as isize;
-        let cy = my.round() as isize;
-        let coin_size = (adaptive_scale / 4).max(1);
-        draw_filled_rect(fb, cx - coin_size as isize / 2, cy - coin_size as isize / 2, coin_size, coin_size, Color::GOLD);
+        let (cx, cy) = to_minimap(coin.pos.x, coin.pos.y);
+        let coin_radius = (adaptive_scale / 8) as i32;
+        fill_circle_in(fb, &window, cx as i32, cy as i32, coin_radius, palette.pickup);
+    }
+
+    // FOV cone (faint fill, then its edges) and the heading line, anti-aliased so they stay
+    // smooth at minimap scale
+    let (px, py) = to_minimap(player.pos.x, player.pos.y);
+    let (cx, cy) = (px as f32 + 0.5, py as f32 + 0.5);
+    let cell = adaptive_scale as f32;
+    let reach = |a: f32, len: f32| (cx + a.cos() * len, cy + a.sin() * len);
+    let cone = [(cx, cy), reach(player.a - player.fov / 2.0, 2.0 * cell), reach(player.a + player.fov / 2.0, 2.0 * cell)];
+    let faint = |a: u8| Color { a, ..palette.player };
+    fill_convex_polygon_in(fb, &window, &cone, faint(40));
+    let edges = [(player.a - player.fov / 2.0, 2.0 * cell, faint(110)), (player.a + player.fov / 2.0, 2.0 * cell, faint(110)), (player.a, 1.2 * cell, palette.player)];
+    for (a, len, color) in edges {
+        line_aa(fb, cx, cy, cx + a.cos() * len, cy + a.sin() * len, color);
     }
 
-    // draw player as blue dot
-    let px_f = (player.pos.x / block_size as f32) * adaptive_scale as f32 + xo as f32;
-    let py_f = (player.pos.y / block_size as f32) * adaptive_scale as f32 + yo as f32;
-    let px = px_f.round() as isize;
-    let py = py_f.round() as isize;
-    let player_size = (adaptive_scale / 3).max(2);
-    draw_filled_rect(fb, px - player_size as isize / 2, py - player_size as isize / 2, player_size, player_size, Color::SKYBLUE);
+    // draw player as an arrow pointing along the view angle
+    let size = (cell / 3.0).max(3.0);
+    let (dx, dy) = (player.a.cos(), player.a.sin());
+    let arrow = [(cx + dx * size, cy + dy * size), (cx - dx * size * 0.6 - dy * size * 0.7, cy - dy * size * 0.6 + dx * size * 0.7), (cx - dx * size * 0.6 + dy * size * 0.7, cy - dy * size * 0.6 - dx * size * 0.7)];
+    fill_convex_polygon_in(fb, &window, &arrow, palette.player);
 }
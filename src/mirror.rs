@@ -0,0 +1,127 @@
+// mirror.rs - rear-view mirror: what's behind the player, in a strip near the top of the screen
+//
+// The view behind is rendered by renderer::render_world into a small framebuffer of its own,
+// with the camera turned 180° and half the rays of the main view, then copied onto the 3D view
+// flipped left to right, the way a real mirror shows it. RearMirror::within_budget drops it
+// while frames run over budget and brings it back once they have been on time for a while
+// (timestep::SlowFrameGuard).
+
+use std::f32::consts::PI;
+use raylib::prelude::*;
+use crate::clip::Rect;
+use crate::framebuffer::Framebuffer;
+use crate::player::Player;
+use crate::renderer::RenderSettings;
+use crate::timestep::SlowFrameGuard;
+
+// size of the strip and its top edge, as fractions of the framebuffer (below the level box
+// and the compass strip)
+const WIDTH_FRAC: f32 = 0.36;
+const HEIGHT_FRAC: f32 = 0.14;
+const TOP_FRAC: f32 = 0.18;
+// the mirror casts one ray per this many rays of the main view
+const COLUMN_STEP_FACTOR: usize = 2;
+const FRAME_COLOR: Color = Color::new(20, 20, 24, 255);
+
+pub struct RearMirror {
+    view: Framebuffer,
+    guard: SlowFrameGuard,
+}
+
+impl RearMirror {
+    pub fn new() -> Self {
+        Self { view: Framebuffer::new(1, 1), guard: SlowFrameGuard::new() }
+    }
+
+    // Where the mirror goes on a `fb_width` x `fb_height` framebuffer.
+    pub fn rect(fb_width: u32, fb_height: u32) -> Rect {
+        let w = (fb_width as f32 * WIDTH_FRAC) as i32;
+        let h = (fb_height as f32 * HEIGHT_FRAC) as i32;
+        Rect::new(fb_width as i32 / 2 - w / 2, (fb_height as f32 * TOP_FRAC) as i32, w, h)
+    }
+
+    // The camera looking back from `player`.
+    pub fn camera(player: &Player) -> Player {
+        Player { a: player.a + PI, ..*player }
+    }
+
+    // The main view's render settings with fewer rays.
+    pub fn settings(main: &RenderSettings) -> RenderSettings {
+        RenderSettings { column_step: main.column_step.max(1) * COLUMN_STEP_FACTOR, ..*main }
+    }
+
+    // Feed the last frame's duration; false while the mirror should be skipped.
+    pub fn within_budget(&mut self, frame_secs: f32, budget_secs: f32) -> bool {
+        self.guard.allow(frame_secs, budget_secs)
+    }
+
+    // The framebuffer to render the view behind into, sized for a `fb_width` x `fb_height` screen.
+    pub fn view(&mut self, fb_width: u32, fb_height: u32) -> &mut Framebuffer {
+        let rect = Self::rect(fb_width, fb_height);
+        let (w, h) = (rect.w.max(1) as u32, rect.h.max(1) as u32);
+        if self.view.width != w || self.view.height != h {
+            self.view = Framebuffer::new(w, h);
+        }
+        &mut self.view
+    }
+
+    // Copy the rendered view onto `fb`, flipped left to right, inside a one pixel frame.
+    pub fn draw(&mut self, fb: &mut Framebuffer) {
+        let rect = Self::rect(fb.width, fb.height);
+        if rect.is_empty() || (self.view.width, self.view.height) != (rect.w as u32, rect.h as u32) {
+            return;
+        }
+        fb.fill_rect(Rect::new(rect.x - 1, rect.y - 1, rect.w + 2, rect.h + 2), FRAME_COLOR);
+        let view = &mut self.view;
+        let last = view.width - 1;
+        fb.blit(rect, |x, y| view.get_pixel(last - x, y));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{Lighting, Maze};
+    use crate::renderer::render_world;
+    use crate::sprite::NPC;
+    use crate::textures::{ImageBuf, TextureAtlas, TextureId};
+
+    // The main framebuffer after drawing the mirror of `npcs` seen by a player facing east.
+    fn mirrored(npcs: &Vec<NPC>) -> Framebuffer {
+        let maze: Maze = ["+-------+", "|       |", "|       |", "|       |", "+-------+"]
+            .iter()
+            .map(|r| r.chars().collect())
+            .collect();
+        let mut textures = TextureAtlas::default();
+        let data = [200, 40, 40, 255].repeat(16 * 16);
+        textures.insert(TextureId::Npc, ImageBuf { w: 16, h: 16, data });
+        let player = Player { pos: Vector2::new(450.0, 250.0), a: 0.0, fov: PI / 3.0, vel: Vector2::zero() };
+        let opts = RenderSettings { column_step: 1, sprite_threads: 1, time: 0.0, sky_drift: false, lighting: Lighting::default(), day_cycle: None };
+        let mut fb = Framebuffer::new(200, 150);
+        let mut mirror = RearMirror::new();
+        let view = mirror.view(fb.width, fb.height);
+        render_world(view, &maze, 100, &RearMirror::camera(&player), &textures, npcs, &Vec::new(), 0.0, &RearMirror::settings(&opts));
+        mirror.draw(&mut fb);
+        fb
+    }
+
+    #[test]
+    fn npc_behind_on_the_right_shows_on_the_right_of_the_mirror() {
+        // behind the player and to their right (+y when facing east)
+        let mut with_npc = mirrored(&vec![NPC::new(250.0, 300.0, 0.0)]);
+        let mut without = mirrored(&Vec::new());
+        let rect = RearMirror::rect(200, 150);
+        let mut columns = Vec::new();
+        for y in rect.y..rect.bottom() {
+            for x in rect.x..rect.right() {
+                if with_npc.get_pixel(x as u32, y as u32) != without.get_pixel(x as u32, y as u32) {
+                    columns.push(x);
+                }
+            }
+        }
+        assert!(!columns.is_empty(), "the NPC isn't in the mirror");
+        assert!(columns.iter().all(|&x| x > rect.x + rect.w / 2), "{:?}", columns);
+        // the frame around the strip
+        assert_eq!(with_npc.get_pixel(rect.x as u32 - 1, rect.y as u32), FRAME_COLOR);
+    }
+}
@@ -0,0 +1,276 @@
+// particles.rs - short-lived particles for dust, sparks and confetti
+//
+// A Particles pool holds at most `capacity` particles; spawning into a full pool recycles the
+// oldest one, so a burst never grows it. World-space particles live on the maze plane (pos in
+// world units, `z` height above the floor, 35 = eye level) and are drawn as depth-tested
+// billboards by renderer::render_particles. Screen-space ones are in framebuffer pixels and
+// drawn straight onto the framebuffer by draw_screen. Colors run from `start` to `end` over the
+// lifetime while the alpha fades out.
+
+use std::collections::VecDeque;
+use raylib::prelude::*;
+use crate::clip::Rect;
+use crate::framebuffer::Framebuffer;
+use crate::rng::Rng;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Space {
+    World,
+    Screen,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Particle {
+    pub space: Space,
+    pub pos: Vector2,
+    pub vel: Vector2,
+    // height above the floor and its speed (world space only)
+    pub z: f32,
+    pub vz: f32,
+    // pulls vz down (world) or vel.y down the screen (screen), per second
+    pub gravity: f32,
+    pub age: f32,
+    pub life: f32,
+    pub start: Color,
+    pub end: Color,
+    // world units (world space) or pixels (screen space)
+    pub size: f32,
+}
+
+impl Particle {
+    pub fn progress(&self) -> f32 {
+        (self.age / self.life.max(1e-4)).clamp(0.0, 1.0)
+    }
+
+    // Color along the ramp, fading out with age.
+    pub fn color(&self) -> Color {
+        let t = self.progress();
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        let (s, e) = (self.start, self.end);
+        Color::new(mix(s.r, e.r), mix(s.g, e.g), mix(s.b, e.b), (mix(s.a, e.a) as f32 * (1.0 - t)).round() as u8)
+    }
+}
+
+pub struct Particles {
+    live: VecDeque<Particle>,
+    capacity: usize,
+    rng: Rng,
+}
+
+impl Particles {
+    pub fn new(capacity: usize, rng: Rng) -> Self {
+        Self { live: VecDeque::with_capacity(capacity), capacity: capacity.max(1), rng }
+    }
+
+    pub fn len(&self) -> usize {
+        self.live.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.live.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.live.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Particle> {
+        self.live.iter()
+    }
+
+    pub fn spawn(&mut self, particle: Particle) {
+        if self.live.len() == self.capacity {
+            self.live.pop_front();
+        }
+        self.live.push_back(particle);
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for p in &mut self.live {
+            p.age += dt;
+            p.pos += p.vel * dt;
+            match p.space {
+                Space::World => {
+                    p.vz -= p.gravity * dt;
+                    p.z += p.vz * dt;
+                    // settle on the floor
+                    if p.z < 0.0 {
+                        (p.z, p.vz) = (0.0, 0.0);
+                        p.vel *= 0.5;
+                    }
+                }
+                Space::Screen => p.vel.y += p.gravity * dt,
+            }
+        }
+        self.live.retain(|p| p.age < p.life);
+    }
+
+    // A few grey puffs kicked up around `pos`, drifting up and out.
+    pub fn dust(&mut self, pos: Vector2) {
+        for _ in 0..4 {
+            let angle = self.rng.range_f32(0.0, std::f32::consts::TAU);
+            let speed = self.rng.range_f32(4.0, 12.0);
+            let life = self.rng.range_f32(0.35, 0.6);
+            let offset = Vector2::new(angle.cos(), angle.sin()) * self.rng.range_f32(0.0, 6.0);
+            let particle = Particle {
+                space: Space::World,
+                pos: pos + offset,
+                vel: Vector2::new(angle.cos(), angle.sin()) * speed,
+                z: 1.0,
+                vz: self.rng.range_f32(6.0, 14.0),
+                gravity: 10.0,
+                age: 0.0,
+                life,
+                start: Color::new(150, 140, 120, 150),
+                end: Color::new(90, 85, 80, 0),
+                size: self.rng.range_f32(3.0, 5.0),
+            };
+            self.spawn(particle);
+        }
+    }
+
+    // Gold sparks bursting out of a picked-up coin at `pos`, falling back to the floor.
+    pub fn sparks(&mut self, pos: Vector2) {
+        for _ in 0..14 {
+            let angle = self.rng.range_f32(0.0, std::f32::consts::TAU);
+            let speed = self.rng.range_f32(30.0, 80.0);
+            let particle = Particle {
+                space: Space::World,
+                pos,
+                vel: Vector2::new(angle.cos(), angle.sin()) * speed,
+                z: 35.0,
+                vz: self.rng.range_f32(20.0, 70.0),
+                gravity: 160.0,
+                age: 0.0,
+                life: self.rng.range_f32(0.3, 0.6),
+                start: Color::new(255, 240, 140, 255),
+                end: Color::new(230, 120, 20, 255),
+                size: self.rng.range_f32(1.2, 2.2),
+            };
+            self.spawn(particle);
+        }
+    }
+
+    // `count` confetti pieces starting just above the top of a `width` pixel wide screen.
+    pub fn confetti(&mut self, width: u32, count: usize) {
+        const COLORS: [Color; 5] = [Color::GOLD, Color::RED, Color::SKYBLUE, Color::LIME, Color::PINK];
+        for _ in 0..count {
+            let color = COLORS[self.rng.below(COLORS.len())];
+            let particle = Particle {
+                space: Space::Screen,
+                pos: Vector2::new(self.rng.range_f32(0.0, width as f32), self.rng.range_f32(-20.0, 0.0)),
+                vel: Vector2::new(self.rng.range_f32(-25.0, 25.0), self.rng.range_f32(20.0, 60.0)),
+                z: 0.0,
+                vz: 0.0,
+                gravity: 40.0,
+                age: 0.0,
+                life: self.rng.range_f32(2.5, 4.0),
+                start: color,
+                end: color,
+                size: self.rng.range_f32(2.0, 4.0),
+            };
+            self.spawn(particle);
+        }
+    }
+
+    // Screen-space particles as small squares blended over the framebuffer.
+    pub fn draw_screen(&self, fb: &mut Framebuffer) {
+        let bounds = fb.bounds();
+        for p in self.live.iter().filter(|p| p.space == Space::Screen) {
+            let size = p.size.max(1.0) as i32;
+            let square = Rect::new(p.pos.x as i32 - size / 2, p.pos.y as i32 - size / 2, size, size);
+            let Some(area) = square.intersect(&bounds) else { continue };
+            let color = p.color();
+            for y in area.y..area.bottom() {
+                for x in area.x..area.right() {
+                    fb.blend_pixel(x, y, color, 1.0);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen_particle(x: f32, life: f32) -> Particle {
+        Particle {
+            space: Space::Screen,
+            pos: Vector2::new(x, 5.0),
+            vel: Vector2::new(0.0, 10.0),
+            z: 0.0,
+            vz: 0.0,
+            gravity: 0.0,
+            age: 0.0,
+            life,
+            start: Color::new(255, 0, 0, 255),
+            end: Color::new(0, 0, 255, 255),
+            size: 2.0,
+        }
+    }
+
+    #[test]
+    fn full_pool_recycles_the_oldest_particle() {
+        let mut pool = Particles::new(3, Rng::new(1));
+        for x in 0..5 {
+            pool.spawn(screen_particle(x as f32, 1.0));
+        }
+        assert_eq!(pool.len(), 3);
+        let xs: Vec<f32> = pool.iter().map(|p| p.pos.x).collect();
+        assert_eq!(xs, [2.0, 3.0, 4.0]);
+        // bursts bigger than the pool keep it at capacity
+        pool.sparks(Vector2::new(50.0, 50.0));
+        assert_eq!(pool.len(), 3);
+    }
+
+    #[test]
+    fn particles_move_ramp_their_color_and_expire() {
+        let mut pool = Particles::new(8, Rng::new(1));
+        pool.spawn(screen_particle(0.0, 1.0));
+        pool.spawn(screen_particle(1.0, 0.25));
+        pool.update(0.5);
+        assert_eq!(pool.len(), 1);
+        let p = pool.iter().next().unwrap();
+        assert_eq!(p.pos, Vector2::new(0.0, 10.0));
+        assert_eq!(p.color(), Color::new(128, 0, 128, 128));
+        pool.update(0.5);
+        assert!(pool.is_empty());
+        // world particles fall back to the floor and stay there
+        pool.sparks(Vector2::new(50.0, 50.0));
+        pool.update(0.25);
+        pool.update(0.25);
+        assert!(pool.iter().all(|p| p.z >= 0.0 && p.space == Space::World));
+    }
+
+    #[test]
+    fn screen_particles_are_drawn_and_clipped() {
+        let mut fb = Framebuffer::new(8, 8);
+        let mut pool = Particles::new(4, Rng::new(1));
+        pool.spawn(Particle { pos: Vector2::new(3.0, 3.0), ..screen_particle(0.0, 1.0) });
+        pool.spawn(Particle { pos: Vector2::new(-20.0, 3.0), ..screen_particle(0.0, 1.0) });
+        pool.draw_screen(&mut fb);
+        assert_eq!(fb.get_pixel(2, 2).r, 255);
+        assert_eq!(fb.get_pixel(5, 5), Color::BLACK);
+        // confetti lands inside the screen width
+        pool.confetti(8, 4);
+        assert!(pool.iter().all(|p| (0.0..8.0).contains(&p.pos.x)));
+    }
+
+    // Cost of a full pool: cargo test --release -- --ignored bench
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_500_particles() {
+        let mut fb = Framebuffer::new(320, 240);
+        let mut pool = Particles::new(500, Rng::new(3));
+        pool.confetti(320, 500);
+        let start = std::time::Instant::now();
+        for _ in 0..100 {
+            pool.update(1.0 / 60.0);
+            pool.draw_screen(&mut fb);
+        }
+        // a full pool has to fit well inside a 60 FPS frame
+        let per_frame = start.elapsed() / 100;
+        assert!(per_frame < std::time::Duration::from_millis(4), "500 screen particles: {:?} per frame", per_frame);
+    }
+}
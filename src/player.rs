@@ -2,12 +2,50 @@
 
 use raylib::prelude::*;
 use std::f32::consts::PI;
-use crate::maze::Maze;
+use crate::maze::{CellKind, Maze};
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Player {
     pub pos: Vector2,
     pub a: f32,
     pub fov: f32, // field of view
+    pub vel: Vector2, // world units per tick (per frame with the coupled loop)
+}
+
+impl Player {
+    // Angle of `target` relative to the view direction (-PI..PI, positive = to the right).
+    pub fn relative_angle(&self, target: Vector2) -> f32 {
+        let to_target = (target.y - self.pos.y).atan2(target.x - self.pos.x);
+        (to_target - self.a + PI).rem_euclid(2.0 * PI) - PI
+    }
+
+    pub fn distance_to(&self, target: Vector2) -> f32 {
+        (target.x - self.pos.x).hypot(target.y - self.pos.y)
+    }
+}
+
+// Movement tunables (per frame, like MOVE_SPEED).
+// Top walking speed
+pub const MOVE_SPEED: f32 = 7.0;
+// fraction of the gap to the target velocity closed each frame while a key is held
+pub const ACCELERATION: f32 = 0.35;
+// velocity kept each frame after the keys are released (lower = stops sooner)
+pub const FRICTION: f32 = 0.55;
+// below this speed the player stops dead, so releasing a key never leaves a slow drift
+pub const STOP_SPEED: f32 = 0.4;
+
+// One frame of momentum: accelerate toward `wish` (desired velocity) or coast to a stop
+// with friction when there is no input.
+pub fn step_velocity(vel: Vector2, wish: Vector2) -> Vector2 {
+    if wish.x != 0.0 || wish.y != 0.0 {
+        return Vector2::new(vel.x + (wish.x - vel.x) * ACCELERATION, vel.y + (wish.y - vel.y) * ACCELERATION);
+    }
+    let coast = Vector2::new(vel.x * FRICTION, vel.y * FRICTION);
+    if (coast.x * coast.x + coast.y * coast.y).sqrt() < STOP_SPEED {
+        Vector2::zero()
+    } else {
+        coast
+    }
 }
 
 // Check whether a point (x,y) in world coordinates is inside a free cell of the maze
@@ -27,17 +65,13 @@ pub fn can_move_to(maze: &Maze, x: f32, y: f32, block_size: usize, doors_open: b
         return false;
     }
     let cell = maze[j][i];
-    // treat 'R' (sprite NPC) and 'C' (coins) as non-blocking so player can walk around/over them
-    // treat 'G' (door) as non-blocking only if doors are open
-    cell == ' ' || cell == 'R' || cell == 'C' || (cell == 'G' && doors_open)
+    // spawn cells (NPC, coins, player) are floor; doors only once they are open
+    CellKind::of(cell).is_walkable(doors_open)
 }
 
-// Process input and perform movement with simple collision against maze walls.
-// Uses axis-aligned sliding: if full move collides, tries X-only and Y-only moves.
-pub fn process_events(player: &mut Player, rl: &mut RaylibHandle, maze: &Maze, block_size: usize, capture_mouse: bool, doors_open: bool) {
-    // Movement: WASD -> forward/back + strafing. Mouse -> camera yaw.
-    // Slightly increased movement speed so player can better evade NPCs
-    const MOVE_SPEED: f32 = 7.0;
+// Mouse -> camera yaw. Once per rendered frame (the mouse delta is per frame), even when
+// the fixed timestep runs zero or several movement ticks in it.
+pub fn mouse_look(player: &mut Player, rl: &mut RaylibHandle, capture_mouse: bool) {
     const MOUSE_SENSITIVITY: f32 = 0.0035;
 
     // Mouse look: apply relative mouse delta when mouse is captured (ESC key toggles this)
@@ -50,39 +84,106 @@ pub fn process_events(player: &mut Player, rl: &mut RaylibHandle, maze: &Maze, b
         let screen_height = rl.get_screen_height();
         rl.set_mouse_position(Vector2::new((screen_width / 2) as f32, (screen_height / 2) as f32));
     }
+}
 
+// Movement keys held during a tick: forward/back and right/left strafe, each -1, 0 or 1.
+// Kept apart from the keyboard so ticks can be replayed from recorded input.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MoveInput {
+    pub forward: f32,
+    pub strafe: f32,
+}
+
+impl MoveInput {
     // WASD: W forward, S backward, A left strafe, D right strafe
-    let mut forward: f32 = 0.0;
-    let mut strafe: f32 = 0.0;
-    if rl.is_key_down(KeyboardKey::KEY_W) { forward += 1.0; }
-    if rl.is_key_down(KeyboardKey::KEY_S) { forward -= 1.0; }
-    if rl.is_key_down(KeyboardKey::KEY_D) { strafe += 1.0; }
-    if rl.is_key_down(KeyboardKey::KEY_A) { strafe -= 1.0; }
+    pub fn read(rl: &RaylibHandle) -> Self {
+        let mut input = Self::default();
+        if rl.is_key_down(KeyboardKey::KEY_W) { input.forward += 1.0; }
+        if rl.is_key_down(KeyboardKey::KEY_S) { input.forward -= 1.0; }
+        if rl.is_key_down(KeyboardKey::KEY_D) { input.strafe += 1.0; }
+        if rl.is_key_down(KeyboardKey::KEY_A) { input.strafe -= 1.0; }
+        input
+    }
+}
+
+// Desired velocity for `input` facing the player's view angle.
+fn wish_velocity(player: &Player, input: MoveInput) -> Vector2 {
+    let MoveInput { forward, strafe } = input;
 
+    // desired velocity from input (diagonals normalized so they aren't faster)
+    let mut wish = Vector2::zero();
     if forward != 0.0 || strafe != 0.0 {
         // movement vector in world coordinates
         let fx = player.a.cos();
         let fy = player.a.sin();
         let sx = (player.a + PI / 2.0).cos();
         let sy = (player.a + PI / 2.0).sin();
+        let len = (forward * forward + strafe * strafe).sqrt();
+        wish.x = (forward * fx + strafe * sx) / len * MOVE_SPEED;
+        wish.y = (forward * fy + strafe * sy) / len * MOVE_SPEED;
+    }
+    wish
+}
 
-        let dx = (forward * fx + strafe * sx) * MOVE_SPEED;
-        let dy = (forward * fy + strafe * sy) * MOVE_SPEED;
+// One tick of movement through walls (the console's noclip).
+pub fn step_noclip(player: &mut Player, input: MoveInput) {
+    player.vel = step_velocity(player.vel, wish_velocity(player, input));
+    player.pos += player.vel;
+}
+
+// Perform one tick of movement from `input` with simple collision against maze walls.
+// Uses axis-aligned sliding: if full move collides, tries X-only and Y-only moves.
+pub fn step_player(player: &mut Player, input: MoveInput, maze: &Maze, block_size: usize, doors_open: bool) {
+    // Movement with momentum, see step_velocity.
+    player.vel = step_velocity(player.vel, wish_velocity(player, input));
 
-        let new_x = player.pos.x + dx;
-        let new_y = player.pos.y + dy;
+    if player.vel.x != 0.0 || player.vel.y != 0.0 {
+        let new_x = player.pos.x + player.vel.x;
+        let new_y = player.pos.y + player.vel.y;
 
-        // collision with sliding: try full move, then X-only and Y-only
+        // collision with sliding: try full move, then X-only and Y-only;
+        // a blocked axis loses its velocity so the player doesn't keep pushing into the wall
         if can_move_to(maze, new_x, new_y, block_size, doors_open) {
             player.pos.x = new_x;
             player.pos.y = new_y;
         } else {
             if can_move_to(maze, new_x, player.pos.y, block_size, doors_open) {
                 player.pos.x = new_x;
+            } else {
+                player.vel.x = 0.0;
             }
             if can_move_to(maze, player.pos.x, new_y, block_size, doors_open) {
                 player.pos.y = new_y;
+            } else {
+                player.vel.y = 0.0;
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn velocity_ramps_up_to_move_speed() {
+        let wish = Vector2::new(MOVE_SPEED, 0.0);
+        let mut vel = step_velocity(Vector2::zero(), wish);
+        assert!(vel.x > 0.0 && vel.x < MOVE_SPEED);
+        for _ in 0..30 {
+            vel = step_velocity(vel, wish);
+        }
+        assert!((vel.x - MOVE_SPEED).abs() < 0.01);
+    }
+
+    #[test]
+    fn releasing_keys_stops_within_a_few_frames() {
+        let mut vel = Vector2::new(MOVE_SPEED, 0.0);
+        let mut frames = 0;
+        while vel.x != 0.0 {
+            vel = step_velocity(vel, Vector2::zero());
+            frames += 1;
+            assert!(frames < 10, "still drifting after {} frames", frames);
+        }
+    }
+}
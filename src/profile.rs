@@ -0,0 +1,175 @@
+// profile.rs - per-frame scoped profiler with named zones
+//
+// `let _zone = profile::zone("walls");` times the rest of the scope into the current frame's
+// table; take_frame() hands the table over (F3 overlay, --bench) and starts the next one.
+// Zones nest: a zone's self time is its total minus the zones opened inside it, so the self
+// times of a frame add up to the time spent in its outermost zones. The profiler is off until
+// set_enabled(true), and while off zone() returns an inert guard without reading the clock.
+// Zones are per thread; the sprite worker threads don't record any.
+
+use std::cell::{Cell, RefCell};
+use std::time::Instant;
+
+// One named zone's time over a frame; entered more than once, the times add up.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ZoneTime {
+    pub name: &'static str,
+    // zones open around this one when it was first entered
+    pub depth: usize,
+    pub calls: u32,
+    pub total_ms: f64,
+    // total minus the time in nested zones
+    pub self_ms: f64,
+}
+
+struct OpenZone {
+    // index into Recorder::zones
+    slot: usize,
+    start_ms: f64,
+    children_ms: f64,
+}
+
+// The accounting behind zone(), driven with explicit timestamps so it can be tested with
+// synthetic timings.
+#[derive(Default)]
+pub struct Recorder {
+    open: Vec<OpenZone>,
+    zones: Vec<ZoneTime>,
+}
+
+impl Recorder {
+    pub fn enter(&mut self, name: &'static str, now_ms: f64) {
+        let depth = self.open.len();
+        // zones are listed in the order they were first entered, so parents come before children
+        let slot = match self.zones.iter().position(|z| z.name == name && z.depth == depth) {
+            Some(slot) => slot,
+            None => {
+                self.zones.push(ZoneTime { name, depth, ..Default::default() });
+                self.zones.len() - 1
+            }
+        };
+        self.open.push(OpenZone { slot, start_ms: now_ms, children_ms: 0.0 });
+    }
+
+    pub fn exit(&mut self, now_ms: f64) {
+        let Some(zone) = self.open.pop() else { return };
+        let elapsed = (now_ms - zone.start_ms).max(0.0);
+        if let Some(parent) = self.open.last_mut() {
+            parent.children_ms += elapsed;
+        }
+        let time = &mut self.zones[zone.slot];
+        time.calls += 1;
+        time.total_ms += elapsed;
+        time.self_ms += (elapsed - zone.children_ms).max(0.0);
+    }
+
+    // The finished zones so far, leaving the recorder empty. Zones still open are dropped.
+    pub fn take(&mut self) -> Vec<ZoneTime> {
+        self.open.clear();
+        std::mem::take(&mut self.zones).into_iter().filter(|z| z.calls > 0).collect()
+    }
+}
+
+thread_local! {
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+    static RECORDER: RefCell<Recorder> = RefCell::new(Recorder::default());
+    static EPOCH: Instant = Instant::now();
+}
+
+fn now_ms() -> f64 {
+    EPOCH.with(|epoch| epoch.elapsed().as_secs_f64() * 1000.0)
+}
+
+pub fn set_enabled(on: bool) {
+    ENABLED.with(|e| e.set(on));
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.with(Cell::get)
+}
+
+// Guard returned by zone(); the zone ends when it is dropped.
+#[must_use = "the zone ends when the guard is dropped"]
+pub struct Zone {
+    active: bool,
+}
+
+pub fn zone(name: &'static str) -> Zone {
+    let active = is_enabled();
+    if active {
+        let now = now_ms();
+        RECORDER.with(|r| r.borrow_mut().enter(name, now));
+    }
+    Zone { active }
+}
+
+impl Drop for Zone {
+    fn drop(&mut self) {
+        if self.active {
+            let now = now_ms();
+            RECORDER.with(|r| r.borrow_mut().exit(now));
+        }
+    }
+}
+
+// This thread's zones since the last call (empty while disabled).
+pub fn take_frame() -> Vec<ZoneTime> {
+    RECORDER.with(|r| r.borrow_mut().take())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_zones_split_self_and_total_time() {
+        let mut rec = Recorder::default();
+        rec.enter("render", 0.0);
+        rec.enter("walls", 1.0);
+        rec.exit(5.0);
+        rec.enter("sprites", 5.0);
+        rec.exit(7.0);
+        rec.exit(8.0);
+        rec.enter("present", 8.0);
+        rec.exit(10.0);
+        let zones = rec.take();
+        let names: Vec<_> = zones.iter().map(|z| (z.name, z.depth)).collect();
+        assert_eq!(names, [("render", 0), ("walls", 1), ("sprites", 1), ("present", 0)]);
+        assert_eq!((zones[0].total_ms, zones[0].self_ms), (8.0, 2.0));
+        assert_eq!((zones[1].total_ms, zones[1].self_ms), (4.0, 4.0));
+        // self times add up to the outermost zones' totals
+        let self_sum: f64 = zones.iter().map(|z| z.self_ms).sum();
+        let top_sum: f64 = zones.iter().filter(|z| z.depth == 0).map(|z| z.total_ms).sum();
+        assert_eq!(self_sum, top_sum);
+        assert!(rec.take().is_empty());
+    }
+
+    #[test]
+    fn repeated_zones_accumulate_and_unfinished_ones_are_dropped() {
+        let mut rec = Recorder::default();
+        for start in [0.0, 10.0, 20.0] {
+            rec.enter("audio", start);
+            rec.exit(start + 1.5);
+        }
+        rec.enter("minimap", 30.0);
+        rec.exit(40.0);
+        rec.exit(50.0); // unbalanced exit is ignored
+        rec.enter("open", 60.0);
+        let zones = rec.take();
+        assert_eq!(zones.len(), 2);
+        assert_eq!((zones[0].calls, zones[0].total_ms), (3, 4.5));
+    }
+
+    #[test]
+    fn disabled_profiler_records_nothing() {
+        set_enabled(false);
+        drop(zone("walls"));
+        assert!(take_frame().is_empty());
+        set_enabled(true);
+        drop(zone("walls"));
+        set_enabled(false);
+        let zones = take_frame();
+        assert_eq!(zones.len(), 1);
+        assert_eq!((zones[0].name, zones[0].calls), ("walls", 1));
+    }
+}
@@ -0,0 +1,133 @@
+// progress.rs - campaign progress saved between sessions (saves/progress.toml)
+//
+// Level 1 is always playable; finishing level N unlocks level N + 1. The file records the
+// highest unlocked level and which levels have been completed at least once:
+//
+//     unlocked = 2
+//     [level1]
+//     completed = true
+
+use crate::settings::parse_toml;
+
+pub const LEVEL_COUNT: i32 = 3;
+
+const PROGRESS_PATH: &str = "saves/progress.toml";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Progress {
+    // highest level that can be picked in the level select (1..=LEVEL_COUNT)
+    pub unlocked: i32,
+    // completed[i] = level i + 1 was finished at least once
+    pub completed: [bool; LEVEL_COUNT as usize],
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Self { unlocked: 1, completed: [false; LEVEL_COUNT as usize] }
+    }
+}
+
+impl Progress {
+    // Saved progress, or a fresh campaign when there is no file.
+    pub fn load() -> Self {
+        match std::fs::read_to_string(PROGRESS_PATH) {
+            Ok(text) => Self::from_toml(&text),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) {
+        let result = std::fs::create_dir_all("saves").and_then(|_| std::fs::write(PROGRESS_PATH, self.to_toml()));
+        if let Err(e) = result {
+            eprintln!("[warn] couldn't write {}: {}", PROGRESS_PATH, e);
+        }
+    }
+
+    pub fn from_toml(text: &str) -> Self {
+        let values = parse_toml(text);
+        let mut p = Self::default();
+        if let Some(v) = values.get("unlocked") {
+            match v.parse::<i32>() {
+                Ok(n) => p.unlocked = n.clamp(1, LEVEL_COUNT),
+                Err(_) => eprintln!("[warn] {}: invalid unlocked '{}'", PROGRESS_PATH, v),
+            }
+        }
+        for level in 1..=LEVEL_COUNT {
+            if let Some(v) = values.get(&format!("level{}.completed", level)) {
+                match v.parse::<bool>() {
+                    Ok(done) => p.completed[level as usize - 1] = done,
+                    Err(_) => eprintln!("[warn] {}: invalid completed '{}' for level {}", PROGRESS_PATH, v, level),
+                }
+            }
+        }
+        // a completed level always unlocks the next one, even if the file says otherwise
+        for level in 1..=LEVEL_COUNT {
+            if p.completed[level as usize - 1] {
+                p.unlocked = p.unlocked.max((level + 1).min(LEVEL_COUNT));
+            }
+        }
+        p
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut out = format!("unlocked = {}\n", self.unlocked);
+        for level in 1..=LEVEL_COUNT {
+            out.push_str(&format!("\n[level{}]\ncompleted = {}\n", level, self.completed[level as usize - 1]));
+        }
+        out
+    }
+
+    pub fn is_unlocked(&self, level: i32) -> bool {
+        (1..=self.unlocked).contains(&level)
+    }
+
+    pub fn is_completed(&self, level: i32) -> bool {
+        level >= 1 && self.completed.get(level as usize - 1).copied().unwrap_or(false)
+    }
+
+    // Record that `level` was finished, unlocking the next one. True only the first time (the
+    // caller saves then), false for replays and out-of-range levels.
+    pub fn complete(&mut self, level: i32) -> bool {
+        if !(1..=LEVEL_COUNT).contains(&level) || self.is_completed(level) {
+            return false;
+        }
+        self.completed[level as usize - 1] = true;
+        self.unlocked = self.unlocked.max((level + 1).min(LEVEL_COUNT));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml_round_trip() {
+        let p = Progress { unlocked: 3, completed: [true, true, false] };
+        assert_eq!(Progress::from_toml(&p.to_toml()), p);
+        assert_eq!(Progress::from_toml(""), Progress::default());
+    }
+
+    #[test]
+    fn completing_a_level_unlocks_the_next_once() {
+        let mut p = Progress::default();
+        assert!(p.is_unlocked(1) && !p.is_unlocked(2));
+        assert!(p.complete(1));
+        assert!(p.is_unlocked(2) && !p.is_unlocked(3));
+        // replaying a finished level changes nothing, so nothing is written again
+        assert!(!p.complete(1));
+        assert!(p.complete(3));
+        assert_eq!(p.unlocked, 3);
+        assert!(!p.complete(4) && !p.complete(0));
+    }
+
+    #[test]
+    fn saved_values_are_clamped_and_kept_consistent() {
+        assert_eq!(Progress::from_toml("unlocked = 9\n").unlocked, LEVEL_COUNT);
+        assert_eq!(Progress::from_toml("unlocked = 0\n").unlocked, 1);
+        // a completion recorded without the matching unlock still opens the next level
+        let p = Progress::from_toml("unlocked = 1\n[level2]\ncompleted = true\n");
+        assert_eq!(p.unlocked, 3);
+        assert!(p.is_completed(2) && !p.is_completed(1));
+    }
+}
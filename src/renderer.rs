@@ -3,13 +3,19 @@
 
 use raylib::prelude::*;
 use crate::framebuffer::Framebuffer;
-use crate::maze::Maze;
+use crate::maze::{CellKind, Lighting, Maze};
 use crate::player::Player;
 use crate::caster::cast_ray;
-use crate::textures::{TextureAtlas, TextureKind};
-use crate::sprite::{NPC, Coin};
-use crate::anim::CoinAnimation;
+use crate::clip::Rect;
+use crate::line::line;
+use crate::shapes::fill_circle;
+use crate::textures::{TextureAtlas, TextureId, TextureKind};
+use crate::sprite::{NPC, Coin, CoinPop};
+use crate::particles::{Particles, Space};
+use crate::anim::{self, Animation};
+use crate::profile;
 use std::f32::consts::PI;
+use std::time::Instant;
 
 fn cell_to_color(cell: char) -> Color {
     match cell {
@@ -21,44 +27,477 @@ fn cell_to_color(cell: char) -> Color {
     }
 }
 
-fn draw_cell(
-    framebuffer: &mut Framebuffer,
-    xo: usize,
-    yo: usize,
-    block_size: usize,
-    cell: char,
-) {
-    if cell == ' ' || cell == 'C' || cell == 'G' { return; } // 'C' should be empty space for coins, 'G' for doors (handled in 3D)
-    let color = cell_to_color(cell);
-    framebuffer.set_current_color(color);
-    for x in xo..xo + block_size {
-        for y in yo..yo + block_size {
-            framebuffer.set_pixel(x as u32, y as u32);
-        }
+// Filled square of `size` framebuffer pixels at (x, y), clipped to the framebuffer.
+fn fill_square(framebuffer: &mut Framebuffer, x: f32, y: f32, size: f32, color: Color) {
+    let (x0, y0) = (x.floor() as i32, y.floor() as i32);
+    let (x1, y1) = ((x + size).ceil() as i32, (y + size).ceil() as i32);
+    framebuffer.fill_rect(Rect::new(x0, y0, x1 - x0, y1 - y0), color);
+}
+
+fn draw_cell(framebuffer: &mut Framebuffer, view: &TopDownView, col: usize, row: usize, cell: char) {
+    let kind = CellKind::of(cell);
+    if !kind.is_wall() && !kind.is_door() { return; } // floor and spawns are left empty
+    let corner = view.to_screen(Vector2::new(col as f32, row as f32) * view.block_size as f32);
+    fill_square(framebuffer, corner.x, corner.y, view.cell_px(), cell_to_color(cell));
+}
+
+// World to framebuffer mapping for the top-down debug view: the whole maze, as large as fits,
+// centered.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TopDownView {
+    // framebuffer pixels per world unit
+    pub scale: f32,
+    pub offset: Vector2,
+    pub block_size: usize,
+}
+
+impl TopDownView {
+    pub fn fit(width: u32, height: u32, maze: &Maze, block_size: usize) -> Self {
+        let cols = maze.iter().map(|r| r.len()).max().unwrap_or(0).max(1) as f32;
+        let rows = maze.len().max(1) as f32;
+        let bs = block_size.max(1) as f32;
+        let scale = (width as f32 / (cols * bs)).min(height as f32 / (rows * bs));
+        let offset = Vector2::new((width as f32 - cols * bs * scale) / 2.0, (height as f32 - rows * bs * scale) / 2.0);
+        Self { scale, offset, block_size }
+    }
+
+    pub fn to_screen(self, pos: Vector2) -> Vector2 {
+        self.offset + pos * self.scale
+    }
+
+    pub fn cell_px(self) -> f32 {
+        self.block_size as f32 * self.scale
     }
 }
 
+// Top-down debug view (F10): the maze cells scaled to fit the framebuffer, `rays` rays across
+// the player's FOV cast exactly like render_world casts them, and the player with a heading line.
 pub fn render_maze(
     framebuffer: &mut Framebuffer,
     maze: &Maze,
     block_size: usize,
     player: &Player,
     doors_open: bool,
-) {
+    rays: usize,
+) -> TopDownView {
+    let view = TopDownView::fit(framebuffer.width, framebuffer.height, maze, block_size);
     for (row_index, row) in maze.iter().enumerate() {
         for (col_index, &cell) in row.iter().enumerate() {
-            let xo = col_index * block_size;
-            let yo = row_index * block_size;
-            draw_cell(framebuffer, xo, yo, block_size, cell);
+            draw_cell(framebuffer, &view, col_index, row_index, cell);
         }
     }
-    framebuffer.set_current_color(Color::WHITESMOKE);
-    // debug: draw a few rays to visualize
-    for i in 0..5 {
-        let t = i as f32 / 5.0;
+    let eye = view.to_screen(player.pos);
+    for i in 0..rays {
+        let t = i as f32 / rays as f32;
         let a = player.a - (player.fov / 2.0) + (player.fov * t);
-        cast_ray(framebuffer, &maze, &player, a, block_size, true, doors_open);
+        let hit = cast_ray(framebuffer, maze, player, a, block_size, false, doors_open);
+        let end = player.pos + Vector2::new(a.cos(), a.sin()) * hit.distance;
+        framebuffer.set_current_color(Color::new(245, 245, 245, 255));
+        line(framebuffer, eye, view.to_screen(end));
+    }
+    let heading = Vector2::new(player.a.cos(), player.a.sin()) * (view.cell_px() * 0.8).max(4.0);
+    framebuffer.set_current_color(Color::RED);
+    line(framebuffer, eye, eye + heading);
+    let size = (view.cell_px() * 0.3).max(3.0);
+    fill_square(framebuffer, eye.x - size / 2.0, eye.y - size / 2.0, size, Color::SKYBLUE);
+    view
+}
+
+// NPCs (red squares) and uncollected coins (gold discs) on the top-down view.
+pub fn render_top_down_sprites(framebuffer: &mut Framebuffer, view: &TopDownView, npcs: &[NPC], coins: &[Coin]) {
+    let size = (view.cell_px() * 0.3).max(3.0);
+    for npc in npcs {
+        let p = view.to_screen(npc.pos);
+        fill_square(framebuffer, p.x - size / 2.0, p.y - size / 2.0, size, Color::RED);
+    }
+    for coin in coins.iter().filter(|c| !c.collected) {
+        let p = view.to_screen(coin.pos);
+        fill_circle(framebuffer, p.x.floor() as i32, p.y.floor() as i32, (size / 2.0) as i32, Color::GOLD);
+    }
+}
+
+// Distance fog, in cells from the player: none before FOG_START_CELLS, fully fogged at FOG_END_CELLS.
+pub const FOG_START_CELLS: f32 = 4.0;
+pub const FOG_END_CELLS: f32 = 14.0;
+pub const FOG_COLOR: Color = Color { r: 18, g: 18, b: 30, a: 255 };
+
+// 0.0 = clear, 1.0 = fully fogged, for a world-space distance
+pub fn fog_factor(dist: f32, block_size: usize) -> f32 {
+    let cells = dist / block_size as f32;
+    ((cells - FOG_START_CELLS) / (FOG_END_CELLS - FOG_START_CELLS)).clamp(0.0, 1.0)
+}
+
+// A level's lighting mode (parsed with the maze metadata) as brightness.
+impl Lighting {
+    // Brightness multiplier for a surface `dist` world units from the player, with the
+    // ambient light scaled by `daylight` (see day_night; the torch itself is unaffected).
+    pub fn light_at(self, dist: f32, block_size: usize, flicker: f32, daylight: f32) -> f32 {
+        match self {
+            Lighting::Fullbright => daylight,
+            Lighting::Torch => torch_light(dist, block_size, flicker, daylight),
+        }
+    }
+}
+
+// brightness far from the torch, and the distance (cells) at which the torch adds half its light
+const TORCH_AMBIENT: f32 = 0.12;
+const TORCH_RADIUS_CELLS: f32 = 2.5;
+
+// Torch brightness at `dist`: inverse-square-ish falloff from `flicker` at the player down to
+// TORCH_AMBIENT (times `daylight`) far away.
+pub fn torch_light(dist: f32, block_size: usize, flicker: f32, daylight: f32) -> f32 {
+    let ambient = TORCH_AMBIENT * daylight;
+    let d = dist / (TORCH_RADIUS_CELLS * block_size as f32);
+    ambient + (flicker - ambient).max(0.0) / (1.0 + d * d)
+}
+
+// Day/night keyframes at time_of_day 0.0 (noon), 0.25 (dusk), 0.5 (midnight) and 0.75
+// (dawn): the palette the sky is pulled toward, how far, and the ambient light.
+const DAY_KEYS: [(Color, f32, f32); 4] = [
+    (Color { r: 255, g: 255, b: 255, a: 255 }, 0.0, 1.0),
+    (Color { r: 250, g: 120, b: 70, a: 255 }, 0.45, 0.75),
+    (Color { r: 10, g: 15, b: 45, a: 255 }, 0.8, 0.4),
+    (Color { r: 255, g: 170, b: 140, a: 255 }, 0.35, 0.8),
+];
+
+// Sky palette, palette weight and ambient light at `time_of_day` (0..1, wrapping), blended
+// linearly between the DAY_KEYS on either side.
+pub fn day_night(time_of_day: f32) -> (Color, f32, f32) {
+    let t = time_of_day.rem_euclid(1.0) * DAY_KEYS.len() as f32;
+    let i = (t.floor() as usize).min(DAY_KEYS.len() - 1);
+    let f = t - i as f32;
+    let (c0, w0, l0) = DAY_KEYS[i];
+    let (c1, w1, l1) = DAY_KEYS[(i + 1) % DAY_KEYS.len()];
+    (mix(c0, c1, f), w0 + (w1 - w0) * f, l0 + (l1 - l0) * f)
+}
+
+// Torch strength around 0.85 (0.7..=1.0) wavering over `time` seconds: a few slow sines of
+// unrelated frequencies, so the pattern doesn't visibly repeat.
+pub fn torch_flicker(time: f32) -> f32 {
+    let noise = 0.5 * (time * 7.3).sin() + 0.3 * (time * 13.1 + 1.7).sin() + 0.2 * (time * 2.9 + 0.4).sin();
+    0.85 + 0.15 * noise
+}
+
+// `col` with its RGB scaled by `light` (clamped to 0..=1)
+pub fn shade(col: Color, light: f32) -> Color {
+    let l = light.clamp(0.0, 1.0);
+    let s = |c: u8| (c as f32 * l).round() as u8;
+    Color::new(s(col.r), s(col.g), s(col.b), col.a)
+}
+
+// NPC tint while it winds up an attack
+const WINDUP_TINT: Color = Color::new(255, 40, 40, 255);
+
+fn mix(a: Color, b: Color, t: f32) -> Color {
+    let l = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * t).round() as u8;
+    Color::new(l(a.r, b.r), l(a.g, b.g), l(a.b, b.b), 255)
+}
+
+pub fn apply_fog(col: Color, dist: f32, block_size: usize) -> Color {
+    mix(col, FOG_COLOR, fog_factor(dist, block_size))
+}
+
+// Blend a sprite texel over what is already in the framebuffer (walls are fogged by then).
+// The texel is fogged itself and also fades out with the fog, so a sprite at the fog limit
+// disappears into the hazy wall behind it instead of staying crisp.
+pub fn blend_sprite_pixel(dst: Color, src: Color, dist: f32, block_size: usize) -> Color {
+    let fog = fog_factor(dist, block_size);
+    let fogged = mix(src, FOG_COLOR, fog);
+    let alpha = (src.a as f32 / 255.0) * (1.0 - fog);
+    mix(dst, fogged, alpha)
+}
+
+#[derive(Clone, Copy)]
+enum SpriteKind {
+    // flash: red attack-windup tint, 0..1; alpha: spawn/defeat fade, 0..1
+    Npc { flash: f32, alpha: f32 },
+    Coin { spin: Animation },
+    // a collected coin growing by `scale` and fading to `alpha`
+    CoinPop { spin: Animation, scale: f32, alpha: f32 },
+    // a world-space particle: a flat square `size` units wide, `z` above the floor
+    Particle { color: Color, z: f32, size: f32 },
+}
+
+// A sprite projected to the screen: pixel columns sx-half..=sx+half, rows top..=bottom.
+pub struct SpriteDraw {
+    kind: SpriteKind,
+    sx: isize,
+    top: isize,
+    bottom: isize,
+    half: isize,
+    w: isize,
+    dist: f32,
+}
+
+// Project a sprite at `pos`, None when it is outside the field of view.
+// Sprites are centered on the horizon row `hh` and sized like walls (`vscale`, see PROJECTION_SCALE).
+fn project_sprite(fb_width: u32, hh: f32, vscale: f32, player: &Player, pos: Vector2, kind: SpriteKind) -> Option<SpriteDraw> {
+    let dx = pos.x - player.pos.x;
+    let dy = pos.y - player.pos.y;
+    let dist = (dx*dx + dy*dy).sqrt().max(0.001);
+    let ang = dy.atan2(dx);
+    let rel = (ang - player.a + PI).rem_euclid(2.0*PI) - PI;
+    if rel.abs() > player.fov / 2.0 { return None }
+    // screen_x in pixels (full framebuffer width), later mapped to a depth buffer column
+    let screen_x = ((rel + player.fov/2.0) / player.fov) * fb_width as f32;
+    let (sprite_h, float_offset, w) = match kind {
+        SpriteKind::Npc { .. } => {
+            let h = (vscale / dist) * 70.0;
+            (h, 0.0, (h * 0.5).max(3.0))
+        }
+        SpriteKind::Coin { spin } => {
+            // slightly smaller than NPCs, wider, and floating (anim module)
+            let h = (vscale / dist) * 60.0;
+            (h, anim::coin_float_offset(&spin), (h * 0.8).max(4.0))
+        }
+        SpriteKind::CoinPop { spin, scale, .. } => {
+            let h = (vscale / dist) * 60.0 * scale;
+            (h, anim::coin_float_offset(&spin), (h * 0.8).max(4.0))
+        }
+        SpriteKind::Particle { z, size, .. } => {
+            // walls span 70 units centered on the horizon, so the floor is 35 below it
+            let h = (vscale / dist * size).max(1.0);
+            (h, (35.0 - z) * vscale / dist, h)
+        }
+    };
+    let w = w as isize;
+    Some(SpriteDraw {
+        kind,
+        sx: screen_x as isize,
+        top: (hh - sprite_h/2.0 + float_offset) as isize,
+        bottom: (hh + sprite_h/2.0 + float_offset) as isize,
+        half: (w / 2).max(1),
+        w,
+        dist,
+    })
+}
+
+// Project every visible NPC and uncollected coin, sorted far to near so nearer sprites
+// are blended over farther ones.
+fn project_sprites(fb_width: u32, hh: f32, vscale: f32, player: &Player, npcs: &[NPC], coins: &[Coin]) -> Vec<SpriteDraw> {
+    let npcs = npcs.iter().map(|npc| (npc.pos, SpriteKind::Npc { flash: npc.windup_flash(), alpha: npc.alpha() }));
+    let coins = coins.iter().filter(|c| !c.collected).map(|coin| (coin.pos, SpriteKind::Coin { spin: coin.spin }));
+    let mut draws: Vec<SpriteDraw> = npcs.chain(coins).filter_map(|(pos, kind)| project_sprite(fb_width, hh, vscale, player, pos, kind)).collect();
+    draws.sort_by(|a, b| b.dist.total_cmp(&a.dist));
+    draws
+}
+
+// Coin pickup bursts, drawn after render_world with no depth test so a wall edge never
+// swallows one.
+pub fn render_coin_pops(framebuffer: &mut Framebuffer, player: &Player, textures: &TextureAtlas, pops: &[CoinPop], block_size: usize) {
+    let hh = framebuffer.height as f32 / 2.0;
+    let vscale = focal_length(framebuffer.width, player.fov) * PROJECTION_SCALE;
+    let mut draws: Vec<SpriteDraw> = pops
+        .iter()
+        .filter_map(|pop| project_sprite(framebuffer.width, hh, vscale, player, pop.pos, SpriteKind::CoinPop { spin: pop.spin, scale: pop.scale(), alpha: pop.alpha() }))
+        .collect();
+    draws.sort_by(|a, b| b.dist.total_cmp(&a.dist));
+    let no_depth = vec![f32::INFINITY; framebuffer.width as usize];
+    rasterize_sprites(&mut framebuffer.color_buffer, &draws, textures, &no_depth, 1, block_size, 1);
+}
+
+// World-space particles as billboards, hidden behind the walls of the last render_world.
+pub fn render_particles(framebuffer: &mut Framebuffer, player: &Player, textures: &TextureAtlas, particles: &Particles, block_size: usize) {
+    if framebuffer.wall_depth().0.is_empty() {
+        return;
+    }
+    let hh = framebuffer.height as f32 / 2.0;
+    let vscale = focal_length(framebuffer.width, player.fov) * PROJECTION_SCALE;
+    let mut draws: Vec<SpriteDraw> = particles
+        .iter()
+        .filter(|p| p.space == Space::World)
+        .filter_map(|p| project_sprite(framebuffer.width, hh, vscale, player, p.pos, SpriteKind::Particle { color: p.color(), z: p.z, size: p.size }))
+        .collect();
+    draws.sort_by(|a, b| b.dist.total_cmp(&a.dist));
+    let (pixels, depth, column_step) = framebuffer.pixels_and_wall_depth();
+    rasterize_sprites(pixels, &draws, textures, depth, column_step, block_size, 1);
+}
+
+// distance to the projection plane in pixels for a `fov` wide view
+fn focal_length(fb_width: u32, fov: f32) -> f32 {
+    (fb_width as f32 / 2.0) / (fov / 2.0).tan()
+}
+
+// A copy of a rectangle of the framebuffer that sprites are rasterized into.
+// Tiles are disjoint horizontal bands, so each one can be drawn on its own thread.
+struct Tile {
+    x0: u32,
+    y0: u32,
+    w: u32,
+    h: u32,
+    pixels: Vec<Color>,
+    dirty: Vec<bool>,
+}
+
+fn rasterize_tile(tile: &mut Tile, draws: &[SpriteDraw], textures: &TextureAtlas, depth_buffer: &[f32], column_step: usize, block_size: usize) {
+    let (x0, y0) = (tile.x0 as isize, tile.y0 as isize);
+    let (x1, y1) = (x0 + tile.w as isize, y0 + tile.h as isize);
+    for d in draws {
+        if d.bottom < y0 || d.top >= y1 { continue }
+        for xoff in -d.half..=d.half {
+            let px = d.sx + xoff;
+            if px < x0 || px >= x1 { continue }
+            // map pixel x to depth_buffer column index (integer division by COLUMN_STEP)
+            let col_idx = (px as usize) / column_step;
+            if col_idx >= depth_buffer.len() { continue }
+            if d.dist > depth_buffer[col_idx] - 1.0 { continue } // occlusion check
+
+            let u = (xoff + d.half) as f32 / (d.w as f32);
+            for y in d.top.max(y0)..=d.bottom.min(y1 - 1) {
+                let v = (y as f32 - d.top as f32) / (d.bottom as f32 - d.top as f32 + 1.0);
+                let texel = match d.kind {
+                    SpriteKind::Npc { flash, alpha } => textures.sample_npc(u, v).filter(|c| c.a > 16).map(|c| {
+                        let tinted = mix(c, WINDUP_TINT, flash);
+                        Color::new(tinted.r, tinted.g, tinted.b, (c.a as f32 * alpha).round() as u8)
+                    }),
+                    // higher alpha threshold for better visibility
+                    SpriteKind::Coin { spin } => textures.sample_coin(u, v, spin.t).filter(|c| c.a > 64),
+                    SpriteKind::CoinPop { spin, alpha, .. } => textures
+                        .sample_coin(u, v, spin.t)
+                        .filter(|c| c.a > 64)
+                        .map(|c| Color::new(c.r, c.g, c.b, (c.a as f32 * alpha) as u8)),
+                    SpriteKind::Particle { color, .. } => Some(color),
+                };
+                if let Some(col) = texel {
+                    let idx = ((y - y0) as u32 * tile.w + (px - x0) as u32) as usize;
+                    tile.pixels[idx] = blend_sprite_pixel(tile.pixels[idx], col, d.dist, block_size);
+                    tile.dirty[idx] = true;
+                }
+            }
+        }
+    }
+}
+
+// Rasterize projected sprites. The screen area the sprites cover is copied out of the
+// framebuffer and split into `threads` horizontal tiles drawn in parallel (0 = one per CPU),
+// then the touched pixels are written back. threads == 1 is the single-threaded fallback.
+pub fn rasterize_sprites(
+    pixels: &mut Image,
+    draws: &[SpriteDraw],
+    textures: &TextureAtlas,
+    depth_buffer: &[f32],
+    column_step: usize,
+    block_size: usize,
+    threads: usize,
+) {
+    if draws.is_empty() || pixels.width() <= 0 || pixels.height() <= 0 {
+        return;
+    }
+    let max_x = pixels.width() as isize - 1;
+    let max_y = pixels.height() as isize - 1;
+    let x0 = draws.iter().map(|d| d.sx - d.half).min().unwrap_or(0).clamp(0, max_x);
+    let x1 = draws.iter().map(|d| d.sx + d.half).max().unwrap_or(0).clamp(0, max_x);
+    let y0 = draws.iter().map(|d| d.top).min().unwrap_or(0).clamp(0, max_y);
+    let y1 = draws.iter().map(|d| d.bottom).max().unwrap_or(0).clamp(0, max_y);
+    let (w, h) = ((x1 - x0 + 1) as u32, (y1 - y0 + 1) as u32);
+
+    let threads = if threads == 0 {
+        std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+    } else {
+        threads
+    };
+    let band = h.div_ceil(threads.clamp(1, h as usize) as u32);
+    let mut tiles = Vec::new();
+    let mut ty = y0 as u32;
+    while ty <= y1 as u32 {
+        let th = band.min(y1 as u32 + 1 - ty);
+        let mut tile_pixels = Vec::with_capacity((w * th) as usize);
+        for y in ty..ty + th {
+            for x in x0 as u32..x0 as u32 + w {
+                tile_pixels.push(pixels.get_color(x as i32, y as i32));
+            }
+        }
+        tiles.push(Tile { x0: x0 as u32, y0: ty, w, h: th, dirty: vec![false; tile_pixels.len()], pixels: tile_pixels });
+        ty += th;
+    }
+
+    if tiles.len() == 1 {
+        rasterize_tile(&mut tiles[0], draws, textures, depth_buffer, column_step, block_size);
+    } else {
+        std::thread::scope(|scope| {
+            for tile in tiles.iter_mut() {
+                scope.spawn(move || rasterize_tile(tile, draws, textures, depth_buffer, column_step, block_size));
+            }
+        });
+    }
+
+    for tile in &tiles {
+        for (i, (&col, &dirty)) in tile.pixels.iter().zip(tile.dirty.iter()).enumerate() {
+            if dirty {
+                pixels.draw_pixel((tile.x0 + i as u32 % tile.w) as i32, (tile.y0 + i as u32 / tile.w) as i32, col);
+            }
+        }
+    }
+}
+
+// Mip level for drawing `texels` texels over `screen_px` screen pixels: log2 of how many
+// texels land on each pixel, 0 when the texture is magnified.
+pub fn mip_lod(texels: f32, screen_px: f32) -> f32 {
+    (texels / screen_px.max(1.0)).log2().max(0.0)
+}
+
+// Horizontal framebuffer pixels that share one ray so that about `ray_columns` rays cover
+// `fb_width`. `ray_columns == 0` means auto and uses `auto_step` instead. This is independent of
+// the framebuffer resolution: a full-res framebuffer can still cast few rays (blockier walls,
+// faster) or a downscaled one can cast a ray per pixel.
+pub fn column_step_for(fb_width: u32, ray_columns: u32, auto_step: usize) -> usize {
+    if ray_columns == 0 {
+        return auto_step.max(1);
+    }
+    (fb_width as usize).div_ceil(ray_columns as usize).max(1)
+}
+
+// Rendering options for one frame, from the settings and the clock.
+pub struct RenderSettings {
+    // horizontal pixels sharing one ray (see column_step_for)
+    pub column_step: usize,
+    // threads used to rasterize sprites; 0 = one per CPU, 1 = single-threaded
+    pub sprite_threads: usize,
+    // seconds since start, for the torch flicker and the sky drift
+    pub time: f32,
+    // clouds: the sky panorama slowly pans sideways
+    pub sky_drift: bool,
+    pub lighting: Lighting,
+    // seconds per day/night cycle, None = always noon (level metadata `day_cycle`)
+    pub day_cycle: Option<f32>,
+}
+
+// fraction of the sky panorama drifting past per second (one full turn in 5 minutes)
+const SKY_DRIFT: f32 = 1.0 / 300.0;
+
+// Wall and sprite heights scale with the focal length (which follows the framebuffer width),
+// so the horizontal FOV stays fixed when the window is resized and a taller window sees more
+// above and below. 0.4 keeps the look of the default 13:9 window (focal 563, half height 225).
+const PROJECTION_SCALE: f32 = 0.4;
+
+// Row (0 = top, 1 = horizon) of a sky panorama `aspect` (height / width) tall seen at screen
+// row `y`. The image is wrapped around a cylinder whose circumference is its width, so rows
+// rise linearly with the tangent of the elevation and the horizon row sits on the horizon
+// line, where the tops of far walls converge, at any window size.
+pub fn sky_v(y: f32, hh: f32, focal: f32, aspect: f32) -> f32 {
+    let above = (hh - y) / focal / (2.0 * PI);
+    (1.0 - above / aspect.max(0.0001)).clamp(0.0, 1.0)
+}
+
+// Time spent in each part of render_world (for --bench) and ray counts (for the F3 overlay).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RenderStats {
+    // sky, floor and walls (the raycasting pass)
+    pub walls_ms: f32,
+    pub sprites_ms: f32,
+    pub rays: u32,
+    // DDA steps summed over all rays
+    pub dda_steps: u64,
+}
+
+// While a door opens, the closed door slides up by `progress` of its height and the open door
+// art shows through the gap below it. Which texture and v to sample there, None for other faces.
+fn door_slide(kind: TextureKind, v: f32, progress: f32) -> Option<(TextureKind, f32)> {
+    if !matches!(kind, TextureKind::DoorClosed) || progress <= 0.0 {
+        return None;
     }
+    let slid = v + progress;
+    Some(if slid < 1.0 { (TextureKind::DoorClosed, slid) } else { (TextureKind::DoorOpen, v) })
 }
 
 pub fn render_world(
@@ -69,17 +508,42 @@ pub fn render_world(
     textures: &TextureAtlas,
     npcs: &Vec<NPC>,
     coins: &Vec<Coin>,
-    column_step: usize,
-    doors_open: bool,
-) {
+    door_progress: f32,
+    opts: &RenderSettings,
+) -> RenderStats {
+    // nothing loaded (empty maze file): leave the cleared framebuffer, main shows a message
+    if maze.iter().all(|row| row.is_empty()) {
+        return RenderStats::default();
+    }
+    let started = Instant::now();
+    let walls_zone = profile::zone("walls");
+
     // Render using coarse columns to reduce the number of rays (improves FPS).
     // column_step controls how many horizontal pixels share the same ray.
-    let column_step = column_step.max(1);
+    let column_step = opts.column_step.max(1);
     let num_rays = ((framebuffer.width as usize) + column_step - 1) / column_step;
     let hh = framebuffer.height as f32 / 2.0;
+    // distance to the projection plane in pixels, and the sky's height / width (None: gradient)
+    let focal = focal_length(framebuffer.width, player.fov);
+    let vscale = focal * PROJECTION_SCALE;
+    let sky_aspect = textures.get(&TextureId::Sky).map(|img| img.h as f32 / img.w as f32);
+    let sky_time = if opts.sky_drift { opts.time } else { 0.0 };
+    // lighting: one torch strength per frame, one light value per floor row (the floor at
+    // row y lies vscale * 35 / (y - hh) units ahead, the inverse of the wall height below)
+    let flicker = torch_flicker(opts.time);
+    let (sky_palette, sky_tint, daylight) = day_night(opts.day_cycle.map_or(0.0, |period| opts.time / period));
+    // torch levels keep the sky as dark as their ambient light; the day cycle only tints it
+    let sky_light = opts.lighting.light_at(f32::INFINITY, block_size, flicker, 1.0);
+    let floor_light: Vec<f32> = (0..framebuffer.height)
+        .map(|y| {
+            let below = (y as f32 - hh).max(0.5);
+            opts.lighting.light_at(vscale * 35.0 / below, block_size, flicker, daylight)
+        })
+        .collect();
 
     // depth buffer per column for sprite occlusion
     let mut depth_buffer = vec![f32::INFINITY; num_rays];
+    let mut dda_steps = 0u64;
 
     // render walls and fill depth buffer (one ray per COLUMN_STEP pixels)
     for i in 0..num_rays {
@@ -87,9 +551,10 @@ pub fn render_world(
         let ix = screen_x as u32;
         let t = i as f32 / num_rays as f32;
         let a = player.a - (player.fov / 2.0) + (player.fov * t);
-        // sky: sample based on ray angle (u)
-        let sky_u = (a / (2.0 * PI)).rem_euclid(1.0);
-        let intersect = cast_ray(framebuffer, &maze, &player, a, block_size, false, doors_open);
+        // sky: u from the absolute ray angle (the image spans 360°), drifting with time
+        let sky_u = (a / (2.0 * PI) + sky_time * SKY_DRIFT).rem_euclid(1.0);
+        let intersect = cast_ray(framebuffer, &maze, &player, a, block_size, false, door_progress >= 1.0);
+        dda_steps += intersect.steps as u64;
 
         // Correct fish-eye: compute angular difference and use cos to get perpendicular distance
         let distance = intersect.distance.max(0.0001);
@@ -97,34 +562,29 @@ pub fn render_world(
         if angle_diff > PI { angle_diff -= 2.0 * PI; }
         let perp_dist = (distance * angle_diff.cos()).abs().max(0.0001);
         depth_buffer[i] = perp_dist;
-        let stake_h = (hh / perp_dist) * 70.0;
+        let stake_h = (vscale / perp_dist) * 70.0;
 
         let mut top = (hh - stake_h / 2.0) as isize;
         let mut bottom = (hh + stake_h / 2.0) as isize;
         if top < 0 { top = 0 }
         if bottom as u32 >= framebuffer.height { bottom = framebuffer.height as isize - 1 }
 
-        // compute texture coordinate u using hit position
-            // compute texture coordinate u using hit position and the side the ray hit
-            // side == 0 means an x-side (vertical wall), so u should be hit_y fraction
-            // side == 1 means a y-side (horizontal wall), so u should be hit_x fraction
-            let u = {
-                let bx = block_size as f32;
-                let frac_x = (intersect.hit_x / bx).fract();
-                let frac_y = (intersect.hit_y / bx).fract();
-                if intersect.side == 0 { frac_y } else { frac_x }
-            };
+        // texture coordinate u along the wall face (mirrored per face, see caster::face_u)
+        let u = intersect.tex_u;
 
-        let kind = match intersect.impact { 
-            '+' => TextureKind::Pillar, 
-            'G' => if doors_open { TextureKind::DoorOpen } else { TextureKind::DoorClosed },
-            _ => TextureKind::Wall 
+        let kind = match intersect.impact {
+            '+' => TextureKind::Pillar,
+            c if CellKind::of(c).is_door() => if door_progress >= 1.0 { TextureKind::DoorOpen } else { TextureKind::DoorClosed },
+            _ => TextureKind::Wall
         };
 
         // draw sky above the top of the wall column (same color across the COLUMN_STEP width)
         for y in 0..top.max(0) as isize {
-            let v = (y as f32) / (hh); // top..hh maps to 0..1
-            let col = textures.sample_sky(sky_u, v);
+            let v = match sky_aspect {
+                Some(aspect) => sky_v(y as f32, hh, focal, aspect),
+                None => (y as f32) / (hh), // gradient: top..hh maps to 0..1
+            };
+            let col = shade(mix(textures.sample_sky(sky_u, v), sky_palette, sky_tint), sky_light);
             framebuffer.set_current_color(col);
             for xoff in 0..column_step {
                 let px = ix + xoff as u32;
@@ -133,33 +593,29 @@ pub fn render_world(
             }
         }
 
+        // get the texture pixel height for this kind, default to 32 if missing
+        let tex_h_pixels: u32 = textures.get(&TextureId::from(kind)).map(|i| i.h).unwrap_or(32);
+        // Tile the texture according to world-space wall height (block_size) so the
+        // texture repeats per block remain constant regardless of camera distance.
+        // Exception: doors should be displayed as single textures without tiling
+        let repeats = match kind {
+            TextureKind::DoorClosed | TextureKind::DoorOpen => 1.0,
+            _ => ((block_size as f32) / (tex_h_pixels as f32)).clamp(0.25, 4.0),
+        };
+        // far columns squeeze many texels into each pixel: sample a smaller mip level there
+        let lod = mip_lod(tex_h_pixels as f32 * repeats, stake_h);
+        let wall_light = opts.lighting.light_at(distance, block_size, flicker, daylight);
+
         // draw wall column across COLUMN_STEP width
         for y in top..=bottom {
             // screen-space fraction along the wall column
             let v_frac = (y as f32 - top as f32) / (bottom as f32 - top as f32 + 1.0);
-            // get the texture pixel height for this kind, default to 32 if missing
-            let tex_h_pixels: u32 = match kind {
-                TextureKind::Wall => textures.wall.as_ref().map(|i| i.h).unwrap_or(32),
-                TextureKind::Pillar => textures.pillar.as_ref().map(|i| i.h).unwrap_or(32),
-                TextureKind::DoorClosed => textures.door_closed.as_ref().map(|i| i.h).unwrap_or(32),
-                TextureKind::DoorOpen => textures.door_open.as_ref().map(|i| i.h).unwrap_or(32),
+            let v_param = v_frac * repeats;
+            let texel = match door_slide(kind, v_param, door_progress) {
+                Some((slid_kind, v)) => textures.sample_wall(slid_kind, u, v, lod),
+                None => textures.sample_wall(kind, u, v_param, lod),
             };
-            // Tile the texture according to world-space wall height (block_size) so the
-            // texture repeats per block remain constant regardless of camera distance.
-            // Exception: doors should be displayed as single textures without tiling
-            let v_param = match kind {
-                TextureKind::DoorClosed | TextureKind::DoorOpen => {
-                    // For doors, use the screen fraction directly without tiling
-                    v_frac
-                },
-                _ => {
-                    // For walls and pillars, use the tiling logic
-                    let repeats_world = (block_size as f32) / (tex_h_pixels as f32);
-                    let repeats = repeats_world.clamp(0.25, 4.0);
-                    v_frac * repeats
-                }
-            };
-            let col = textures.sample(kind, u, v_param);
+            let col = apply_fog(shade(texel, wall_light), perp_dist, block_size);
             framebuffer.set_current_color(col);
             for xoff in 0..column_step {
                 let px = ix + xoff as u32;
@@ -171,7 +627,7 @@ pub fn render_world(
         // draw floor below the wall column - fill COLUMN_STEP width
         let floor_base = Color::new(90, 30, 30, 255);
         for y in (bottom+1)..=(framebuffer.height as isize - 1) {
-            framebuffer.set_current_color(floor_base);
+            framebuffer.set_current_color(shade(floor_base, floor_light[y as usize]));
             for xoff in 0..column_step {
                 let px = ix + xoff as u32;
                 if px >= framebuffer.width { break }
@@ -180,86 +636,199 @@ pub fn render_world(
         }
     }
 
-    // render sprites with occlusion using column depth buffer
-    for npc in npcs.iter() {
-        let dx = npc.pos.x - player.pos.x;
-        let dy = npc.pos.y - player.pos.y;
-        let dist = (dx*dx + dy*dy).sqrt().max(0.001);
-        let ang = dy.atan2(dx);
-        let rel = (ang - player.a + std::f32::consts::PI).rem_euclid(2.0*std::f32::consts::PI) - std::f32::consts::PI;
-        if rel.abs() > player.fov / 2.0 { continue }
-
-    // screen_x in pixels (full framebuffer width), then we will map pixel -> column index
-    let screen_x = ((rel + player.fov/2.0) / player.fov) * framebuffer.width as f32;
-        let sprite_h = (hh / dist) * 70.0;
-        let top = (hh - sprite_h/2.0) as isize;
-        let bottom = (hh + sprite_h/2.0) as isize;
-        let sx = screen_x as isize;
-        let w = ((sprite_h * 0.5).max(3.0)) as isize;
-        let half = (w / 2).max(1);
-
-        for xoff in -half..=half {
-            let px = sx + xoff;
-            if px < 0 { continue }
-            // map pixel x to depth_buffer column index (integer division by COLUMN_STEP)
-            let col_idx = (px as usize) / column_step;
-            if col_idx >= num_rays { continue }
-            if dist > depth_buffer[col_idx] - 1.0 { continue }
-
-            for y in top.max(0)..=bottom.min(framebuffer.height as isize - 1) {
-                let v = (y as f32 - top as f32) / (bottom as f32 - top as f32 + 1.0);
-                let u = (xoff + half) as f32 / (w as f32);
-                if let Some(col) = textures.sample_npc(u, v) {
-                    if col.a > 16 {
-                        framebuffer.set_current_color(col);
-                        framebuffer.set_pixel(px as u32, y as u32);
-                    }
-                }
+    // sprites (NPCs, coins), occluded by the column depth buffer
+    let walls_done = Instant::now();
+    drop(walls_zone);
+    let _sprites_zone = profile::zone("sprites");
+    let draws = project_sprites(framebuffer.width, hh, vscale, player, npcs, coins);
+    rasterize_sprites(&mut framebuffer.color_buffer, &draws, textures, &depth_buffer, column_step, block_size, opts.sprite_threads);
+    framebuffer.set_wall_depth(depth_buffer, column_step);
+    RenderStats {
+        walls_ms: (walls_done - started).as_secs_f32() * 1000.0,
+        sprites_ms: walls_done.elapsed().as_secs_f32() * 1000.0,
+        rays: num_rays as u32,
+        dda_steps,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_step_follows_ray_columns_or_auto() {
+        assert_eq!(column_step_for(640, 0, 2), 2);
+        assert_eq!(column_step_for(640, 640, 2), 1);
+        assert_eq!(column_step_for(640, 160, 1), 4);
+        assert_eq!(column_step_for(640, 100, 1), 7);
+        assert_eq!(column_step_for(640, 10_000, 3), 1);
+    }
+
+    #[test]
+    fn opening_doors_slide_up_over_the_open_art() {
+        assert!(door_slide(TextureKind::DoorClosed, 0.3, 0.0).is_none());
+        assert!(door_slide(TextureKind::Wall, 0.3, 0.5).is_none());
+        // halfway: the top half shows the closed door's lower half, the bottom the open door
+        assert!(matches!(door_slide(TextureKind::DoorClosed, 0.2, 0.5), Some((TextureKind::DoorClosed, v)) if (v - 0.7).abs() < 1e-6));
+        assert!(matches!(door_slide(TextureKind::DoorClosed, 0.6, 0.5), Some((TextureKind::DoorOpen, v)) if v == 0.6));
+    }
+
+    #[test]
+    fn top_down_view_fits_the_maze_and_draws_the_rays() {
+        let maze: Maze = ["+---+", "|   |", "+---+"].iter().map(|r| r.chars().collect()).collect();
+        // 5x3 cells of 100 into 100x100: width-limited, 20 px per cell, centered vertically
+        let view = TopDownView::fit(100, 100, &maze, 100);
+        assert_eq!((view.scale, view.cell_px()), (0.2, 20.0));
+        assert_eq!(view.to_screen(Vector2::new(0.0, 0.0)), Vector2::new(0.0, 20.0));
+        assert_eq!(view.to_screen(Vector2::new(500.0, 300.0)), Vector2::new(100.0, 80.0));
+
+        let mut fb = Framebuffer::new(100, 100);
+        let player = Player { pos: Vector2::new(150.0, 150.0), a: 0.0, fov: 0.01, vel: Vector2::zero() };
+        render_maze(&mut fb, &maze, 100, &player, false, 3);
+        // walls fill their cells; the ray runs east from the player to the wall at x = 400
+        assert_eq!(fb.get_pixel(5, 25), cell_to_color('+'));
+        assert_eq!(fb.get_pixel(70, 50), Color::new(245, 245, 245, 255));
+        assert_eq!(fb.get_pixel(70, 45), Color::BLACK);
+        render_top_down_sprites(&mut fb, &view, &[NPC::new(250.0, 150.0, 1.0)], &[]);
+        assert_eq!(fb.get_pixel(50, 50), Color::RED);
+    }
+
+    #[test]
+    fn mip_lod_grows_with_texel_density() {
+        assert_eq!(mip_lod(64.0, 128.0), 0.0);
+        assert_eq!(mip_lod(64.0, 64.0), 0.0);
+        assert_eq!(mip_lod(64.0, 16.0), 2.0);
+        // walls thinner than a pixel don't blow up
+        assert_eq!(mip_lod(64.0, 0.0), 6.0);
+    }
+
+    #[test]
+    fn torch_light_falls_off_with_distance() {
+        let near = torch_light(0.0, 100, 1.0, 1.0);
+        let radius = torch_light(250.0, 100, 1.0, 1.0);
+        let far = torch_light(5000.0, 100, 1.0, 1.0);
+        assert_eq!(near, 1.0);
+        // half the torch's own light at its radius
+        assert!((radius - (TORCH_AMBIENT + (1.0 - TORCH_AMBIENT) / 2.0)).abs() < 1e-6);
+        assert!(far < TORCH_AMBIENT + 0.01);
+        assert_eq!(Lighting::Fullbright.light_at(5000.0, 100, 0.7, 1.0), 1.0);
+        // night dims the ambient light but not the torch
+        assert_eq!(torch_light(0.0, 100, 1.0, 0.4), 1.0);
+        assert!((torch_light(5000.0, 100, 1.0, 0.4) - far * 0.4).abs() < 0.01);
+        for i in 0..1000 {
+            let f = torch_flicker(i as f32 * 0.037);
+            assert!((0.7..=1.0).contains(&f), "flicker {} out of range", f);
+        }
+        assert_eq!(shade(Color::new(200, 100, 0, 90), 0.5), Color::new(100, 50, 0, 90));
+        assert_eq!(Lighting::from_code("Torch"), Some(Lighting::Torch));
+    }
+
+    #[test]
+    fn day_night_cycle_hits_its_keyframes() {
+        let white = Color::new(255, 255, 255, 255);
+        assert_eq!(day_night(0.0), (white, 0.0, 1.0));
+        assert_eq!(day_night(1.0), day_night(0.0));
+        assert_eq!(day_night(0.25), (Color::new(250, 120, 70, 255), 0.45, 0.75));
+        assert_eq!(day_night(0.5), (Color::new(10, 15, 45, 255), 0.8, 0.4));
+        assert_eq!(day_night(0.75), (Color::new(255, 170, 140, 255), 0.35, 0.8));
+        // halfway from midnight to dawn
+        let (col, weight, light) = day_night(0.625);
+        assert_eq!(col, Color::new(133, 93, 93, 255));
+        assert!((weight - 0.575).abs() < 1e-5 && (light - 0.6).abs() < 1e-5);
+        assert_eq!(Lighting::Fullbright.light_at(100.0, 100, 1.0, light), light);
+    }
+
+    #[test]
+    fn sky_horizon_row_sits_on_the_horizon() {
+        assert_eq!(sky_v(200.0, 200.0, 500.0, 0.5), 1.0);
+        // rows rise linearly: 2π·500·0.5 px above the horizon reaches the top row
+        let top = 200.0 - 2.0 * PI * 500.0 * 0.5;
+        assert!(sky_v(top, 200.0, 500.0, 0.5).abs() < 1e-4);
+        assert!((sky_v((200.0 + top) / 2.0, 200.0, 500.0, 0.5) - 0.5).abs() < 1e-4);
+        // beyond the image's top row it stays clamped
+        assert_eq!(sky_v(top - 100.0, 200.0, 500.0, 0.5), 0.0);
+    }
+
+    #[test]
+    fn sprite_at_fog_limit_is_nearly_invisible() {
+        let wall = apply_fog(Color::new(200, 180, 160, 255), 1400.0, 100);
+        let coin = Color::new(255, 215, 0, 255);
+        let far = blend_sprite_pixel(wall, coin, FOG_END_CELLS * 100.0, 100);
+        assert!((far.r as i32 - wall.r as i32).abs() <= 2 && (far.g as i32 - wall.g as i32).abs() <= 2);
+        // up close the sprite replaces the background
+        let near = blend_sprite_pixel(wall, coin, 100.0, 100);
+        assert_eq!((near.r, near.g, near.b), (255, 215, 0));
+    }
+
+    // atlas with a 16x16 half-transparent NPC sprite (opaque left half, translucent right half)
+    fn sprite_atlas() -> TextureAtlas {
+        let mut data = Vec::new();
+        for _y in 0..16 {
+            for x in 0..16u8 {
+                data.extend_from_slice(&[200, x * 10, 40, if x < 8 { 255 } else { 120 }]);
             }
         }
+        let mut atlas = TextureAtlas::default();
+        atlas.insert(TextureId::Npc, crate::textures::ImageBuf { w: 16, h: 16, data });
+        atlas
     }
 
-    // render coins with occlusion using column depth buffer
-    for coin in coins.iter() {
-        if coin.collected { continue; }
-        
-        let dx = coin.pos.x - player.pos.x;
-        let dy = coin.pos.y - player.pos.y;
-        let dist = (dx*dx + dy*dy).sqrt().max(0.001);
-        let ang = dy.atan2(dx);
-        let rel = (ang - player.a + std::f32::consts::PI).rem_euclid(2.0*std::f32::consts::PI) - std::f32::consts::PI;
-        if rel.abs() > player.fov / 2.0 { continue }
-
-        // screen_x in pixels (full framebuffer width), then we will map pixel -> column index
-        let screen_x = ((rel + player.fov/2.0) / player.fov) * framebuffer.width as f32;
-        
-        // Add floating motion using anim module
-        let float_offset = CoinAnimation::get_float_offset(coin.animation_time);
-        let sprite_h = (hh / dist) * 60.0; // slightly smaller than NPCs
-        let top = (hh - sprite_h/2.0 + float_offset) as isize;
-        let bottom = (hh + sprite_h/2.0 + float_offset) as isize;
-        let sx = screen_x as isize;
-        let w = ((sprite_h * 0.8).max(4.0)) as isize; // slightly wider
-        let half = (w / 2).max(1);
-
-        for xoff in -half..=half {
-            let px = sx + xoff;
-            if px < 0 { continue }
-            // map pixel x to depth_buffer column index (integer division by COLUMN_STEP)
-            let col_idx = (px as usize) / column_step;
-            if col_idx >= num_rays { continue }
-            if dist > depth_buffer[col_idx] - 1.0 { continue } // occlusion check
-
-            for y in top.max(0)..=bottom.min(framebuffer.height as isize - 1) {
-                let v = (y as f32 - top as f32) / (bottom as f32 - top as f32 + 1.0);
-                let u = (xoff + half) as f32 / (w as f32);
-                if let Some(col) = textures.sample_coin(u, v, coin.animation_time) {
-                    if col.a > 64 { // higher alpha threshold for better visibility
-                        framebuffer.set_current_color(col);
-                        framebuffer.set_pixel(px as u32, y as u32);
-                    }
-                }
+    fn sprite_scene(count: usize) -> (Player, Vec<NPC>) {
+        let player = Player { pos: Vector2::new(0.0, 0.0), a: 0.0, fov: PI / 3.0, vel: Vector2::zero() };
+        let npcs = (0..count)
+            .map(|i| NPC::new(120.0 + (i % 7) as f32 * 35.0, -60.0 + (i % 5) as f32 * 30.0, 1.0))
+            .collect();
+        (player, npcs)
+    }
+
+    fn draw_sprites(threads: usize, w: u32, h: u32, count: usize) -> Framebuffer {
+        let textures = sprite_atlas();
+        let (player, npcs) = sprite_scene(count);
+        let mut fb = Framebuffer::new(w, h);
+        fb.set_background_color(Color::new(50, 50, 100, 255));
+        fb.clear();
+        let draws = project_sprites(w, h as f32 / 2.0, h as f32 / 2.0, &player, &npcs, &[]);
+        let depth = vec![f32::INFINITY; w as usize];
+        rasterize_sprites(&mut fb.color_buffer, &draws, &textures, &depth, 1, 100, threads);
+        fb
+    }
+
+    #[test]
+    fn tiled_sprite_rasterization_matches_single_thread() {
+        let mut serial = draw_sprites(1, 64, 48, 12);
+        let mut tiled = draw_sprites(4, 64, 48, 12);
+        let mut drawn = 0;
+        for y in 0..48 {
+            for x in 0..64 {
+                let (a, b) = (serial.get_pixel(x, y), tiled.get_pixel(x, y));
+                assert_eq!((a.r, a.g, a.b), (b.r, b.g, b.b), "pixel {},{}", x, y);
+                if a.r != 50 { drawn += 1; }
             }
         }
+        assert!(drawn > 0, "no sprite pixels were drawn");
+    }
+
+    // Timing comparison of the two paths: cargo test --release -- --ignored --nocapture bench
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_sprite_rasterization_serial_vs_tiled() {
+        for threads in [1, 0] {
+            let start = std::time::Instant::now();
+            for _ in 0..20 {
+                draw_sprites(threads, 320, 240, 200);
+            }
+            eprintln!("sprite threads={} (0 = auto): {:?} per frame", threads, start.elapsed() / 20);
+        }
+    }
+
+    #[test]
+    fn empty_and_single_cell_mazes_render_without_panicking() {
+        let mut fb = Framebuffer::new(32, 24);
+        let textures = TextureAtlas::default();
+        let player = Player { pos: Vector2::new(50.0, 50.0), a: 0.0, fov: PI / 3.0, vel: Vector2::zero() };
+        let opts = |column_step, sprite_threads| RenderSettings { column_step, sprite_threads, time: 0.0, sky_drift: false, lighting: Lighting::Torch, day_cycle: None };
+        render_world(&mut fb, &Vec::new(), 100, &player, &textures, &Vec::new(), &Vec::new(), 0.0, &opts(1, 1));
+        render_world(&mut fb, &vec![vec![' ']], 100, &player, &textures, &Vec::new(), &Vec::new(), 0.0, &opts(1, 1));
+        render_world(&mut fb, &vec![vec!['#']], 100, &player, &textures, &Vec::new(), &Vec::new(), 0.0, &opts(2, 0));
     }
 }
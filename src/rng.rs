@@ -0,0 +1,98 @@
+// rng.rs - tiny deterministic random number generator (xorshift64*)
+//
+// Every randomized system draws from the run seed (set once at startup from `--seed <n>` or
+// the clock), so passing the same seed reproduces an identical run. Systems take their own
+// generator from `Rng::stream(id)` so adding randomness to one system never shifts another.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static RUN_SEED: AtomicU64 = AtomicU64::new(0x9E37_79B9_7F4A_7C15);
+
+pub fn set_seed(seed: u64) {
+    RUN_SEED.store(seed, Ordering::Relaxed);
+}
+
+pub fn seed() -> u64 {
+    RUN_SEED.load(Ordering::Relaxed)
+}
+
+// Seed derived from the system clock, for runs without `--seed`.
+pub fn clock_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(1)
+}
+
+// Stream ids for Rng::stream, one per randomized system.
+pub const STREAM_AMBIENT: u64 = 1;
+pub const STREAM_SFX_PITCH: u64 = 2;
+pub const STREAM_PARTICLES: u64 = 3;
+
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // splitmix64 the seed so nearby seeds give unrelated sequences (and 0 is valid)
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        Self { state: if z == 0 { 1 } else { z } }
+    }
+
+    // Generator for one system (e.g. spawns, flicker), derived from the run seed.
+    pub fn stream(id: u64) -> Self {
+        Self::new(seed() ^ id.wrapping_mul(0xD1B5_4A32_D192_ED03))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    // uniform in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    // uniform in [lo, hi)
+    pub fn range_f32(&mut self, lo: f32, hi: f32) -> f32 {
+        lo + (hi - lo) * self.next_f32()
+    }
+
+    // uniform integer in [0, n); 0 when n == 0
+    pub fn below(&mut self, n: usize) -> usize {
+        if n == 0 {
+            return 0;
+        }
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn chance(&mut self, p: f32) -> bool {
+        self.next_f32() < p
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        let mut c = Rng::new(43);
+        assert_ne!(Rng::new(42).next_u64(), c.next_u64());
+    }
+}
@@ -0,0 +1,291 @@
+// savegame.rs - a run in progress saved with "guardar y salir" (saves/slot1.toml)
+//
+// The save key while paused writes the level, the player, which coins are collected, every
+// NPC, the level time and the minimap's discovered grid. "CONTINUAR" in the main menu loads
+// it once and deletes it. Coins and NPCs are identified by their index in the level's load
+// order (sprite::load_coins_from_maze / load_npcs_from_maze scan the grid row by row), so a
+// save only fits the level file it was made with; anything that doesn't fit is rejected and
+// the game starts fresh instead.
+//
+//     version = 1
+//     level = 2
+//     elapsed = 41.5
+//     [player]
+//     x = 150
+//     ...
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use raylib::prelude::*;
+use crate::maze::Maze;
+use crate::player::{can_move_to, Player};
+use crate::progress::LEVEL_COUNT;
+use crate::settings::parse_toml;
+use crate::sprite::{Coin, NpcState, NPC};
+
+pub const SAVE_PATH: &str = "saves/slot1.toml";
+// bump when the layout changes; other versions are rejected rather than misread
+const SAVE_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SavedNpc {
+    pub pos: Vector2,
+    pub state: NpcState,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SaveGame {
+    pub level: i32,
+    pub player_pos: Vector2,
+    pub player_angle: f32,
+    // load-order indices of the collected coins
+    pub collected: Vec<usize>,
+    // every NPC, in load order
+    pub npcs: Vec<SavedNpc>,
+    // level time (hud::RunTimer) in seconds
+    pub elapsed: f64,
+    pub discovered: Vec<Vec<bool>>,
+}
+
+fn state_code(state: NpcState) -> &'static str {
+    match state {
+        NpcState::Hunt => "hunt",
+        NpcState::Chase => "chase",
+        NpcState::Windup => "windup",
+    }
+}
+
+fn state_from_code(code: &str) -> Option<NpcState> {
+    match code {
+        "hunt" => Some(NpcState::Hunt),
+        "chase" => Some(NpcState::Chase),
+        "windup" => Some(NpcState::Windup),
+        _ => None,
+    }
+}
+
+// Parse a required value, naming the key when it is missing or malformed.
+fn field<T: FromStr>(values: &HashMap<String, String>, key: &str) -> Result<T, String> {
+    let v = values.get(key).ok_or(format!("missing {}", key))?;
+    v.trim().parse::<T>().map_err(|_| format!("invalid {} '{}'", key, v))
+}
+
+impl SaveGame {
+    pub fn capture(level: i32, player: &Player, npcs: &[NPC], coins: &[Coin], elapsed: f64, discovered: &[Vec<bool>]) -> Self {
+        Self {
+            level,
+            player_pos: player.pos,
+            player_angle: player.a,
+            collected: coins.iter().enumerate().filter(|(_, c)| c.collected).map(|(i, _)| i).collect(),
+            npcs: npcs.iter().map(|n| SavedNpc { pos: n.pos, state: n.state }).collect(),
+            elapsed,
+            discovered: discovered.to_vec(),
+        }
+    }
+
+    // Put the saved state into a freshly started level (see main::start_level). Everything is
+    // checked before anything changes; on success returns how many coins were collected. A saved
+    // position outside the maze or inside a wall (stale or edited file) leaves the player at the spawn.
+    pub fn restore(&self, maze: &Maze, block_size: usize, player: &mut Player, npcs: &mut [NPC], coins: &mut [Coin], discovered: &mut Vec<Vec<bool>>) -> Result<usize, String> {
+        if self.npcs.len() != npcs.len() {
+            return Err(format!("{} NPC(s) saved but the level has {}", self.npcs.len(), npcs.len()));
+        }
+        if let Some(&id) = self.collected.iter().find(|&&id| id >= coins.len()) {
+            return Err(format!("coin {} saved but the level has {} coin(s)", id, coins.len()));
+        }
+        let same_shape = self.discovered.len() == discovered.len() && self.discovered.iter().zip(discovered.iter()).all(|(a, b)| a.len() == b.len());
+        if !same_shape {
+            return Err("the discovered grid doesn't match the maze".to_string());
+        }
+        let pos = self.player_pos;
+        if pos.x.is_finite() && pos.y.is_finite() && can_move_to(maze, pos.x, pos.y, block_size, true) {
+            player.pos = pos;
+            player.a = self.player_angle;
+        } else {
+            eprintln!("[warn] {}: saved position ({:.0}, {:.0}) isn't on a walkable cell, starting at the spawn", SAVE_PATH, pos.x, pos.y);
+        }
+        player.vel = Vector2::zero();
+        for (npc, saved) in npcs.iter_mut().zip(&self.npcs) {
+            npc.pos = saved.pos;
+            npc.state = saved.state;
+        }
+        for (i, coin) in coins.iter_mut().enumerate() {
+            coin.collected = self.collected.contains(&i);
+        }
+        discovered.clone_from(&self.discovered);
+        Ok(coins.iter().filter(|c| c.collected).count())
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut out = format!("version = {}\nlevel = {}\nelapsed = {}\n", SAVE_VERSION, self.level, self.elapsed);
+        out.push_str(&format!("\n[player]\nx = {}\ny = {}\nangle = {}\n", self.player_pos.x, self.player_pos.y, self.player_angle));
+        let ids: Vec<String> = self.collected.iter().map(|i| i.to_string()).collect();
+        out.push_str(&format!("\n[coins]\ncollected = \"{}\"\n", ids.join(",")));
+        out.push_str(&format!("\n[npcs]\ncount = {}\n", self.npcs.len()));
+        for (i, n) in self.npcs.iter().enumerate() {
+            out.push_str(&format!("npc{} = \"{},{},{}\"\n", i, n.pos.x, n.pos.y, state_code(n.state)));
+        }
+        out.push_str(&format!("\n[discovered]\nrows = {}\n", self.discovered.len()));
+        for (j, row) in self.discovered.iter().enumerate() {
+            let cells: String = row.iter().map(|&d| if d { '#' } else { '.' }).collect();
+            out.push_str(&format!("row{} = \"{}\"\n", j, cells));
+        }
+        out
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        let values = parse_toml(text);
+        let version: u32 = field(&values, "version")?;
+        if version != SAVE_VERSION {
+            return Err(format!("unsupported save version {} (expected {})", version, SAVE_VERSION));
+        }
+        let level: i32 = field(&values, "level")?;
+        if !(1..=LEVEL_COUNT).contains(&level) {
+            return Err(format!("invalid level {}", level));
+        }
+        let elapsed: f64 = field(&values, "elapsed")?;
+        let player_pos = Vector2::new(field(&values, "player.x")?, field(&values, "player.y")?);
+        let player_angle: f32 = field(&values, "player.angle")?;
+        if !(elapsed.is_finite() && player_pos.x.is_finite() && player_pos.y.is_finite() && player_angle.is_finite()) {
+            return Err("non-finite number".to_string());
+        }
+        let ids = values.get("coins.collected").ok_or("missing coins.collected")?;
+        let collected = ids
+            .split(',')
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| s.trim().parse::<usize>().map_err(|_| format!("invalid coin id '{}'", s)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let npc_count: usize = field(&values, "npcs.count")?;
+        let mut npcs = Vec::with_capacity(npc_count.min(1024));
+        for i in 0..npc_count {
+            let key = format!("npcs.npc{}", i);
+            let text = values.get(&key).ok_or(format!("missing {}", key))?;
+            let parts: Vec<&str> = text.split(',').map(str::trim).collect();
+            let npc = match parts[..] {
+                [x, y, state] => match (x.parse::<f32>(), y.parse::<f32>(), state_from_code(state)) {
+                    (Ok(x), Ok(y), Some(state)) if x.is_finite() && y.is_finite() => Some(SavedNpc { pos: Vector2::new(x, y), state }),
+                    _ => None,
+                },
+                _ => None,
+            };
+            npcs.push(npc.ok_or(format!("invalid {} '{}'", key, text))?);
+        }
+        let rows: usize = field(&values, "discovered.rows")?;
+        let mut discovered = Vec::with_capacity(rows.min(1024));
+        for j in 0..rows {
+            let key = format!("discovered.row{}", j);
+            let row = values.get(&key).ok_or(format!("missing {}", key))?;
+            discovered.push(row.chars().map(|c| c == '#').collect());
+        }
+        Ok(Self { level, player_pos, player_angle, collected, npcs, elapsed, discovered })
+    }
+
+    pub fn exists() -> bool {
+        std::path::Path::new(SAVE_PATH).is_file()
+    }
+
+    // The saved run: None when there is none, Err when the file can't be used.
+    pub fn load() -> Option<Result<Self, String>> {
+        let text = std::fs::read_to_string(SAVE_PATH).ok()?;
+        Some(Self::from_toml(&text))
+    }
+
+    pub fn write(&self) {
+        let result = std::fs::create_dir_all("saves").and_then(|_| std::fs::write(SAVE_PATH, self.to_toml()));
+        match result {
+            Ok(()) => eprintln!("[info] run saved to {}", SAVE_PATH),
+            Err(e) => eprintln!("[warn] couldn't write {}: {}", SAVE_PATH, e),
+        }
+    }
+
+    pub fn delete() {
+        if let Err(e) = std::fs::remove_file(SAVE_PATH)
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            eprintln!("[warn] couldn't delete {}: {}", SAVE_PATH, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    // a small level's worth of state: 2 NPCs, 3 coins, a 3x4 discovered grid
+    fn world() -> (Player, Vec<NPC>, Vec<Coin>, Vec<Vec<bool>>) {
+        let player = Player { pos: Vector2::new(150.0, 150.0), a: PI / 3.0, fov: PI / 3.0, vel: Vector2::zero() };
+        let npcs = vec![NPC::new(250.0, 150.0, 1.0), NPC::new(350.0, 250.0, 1.0)];
+        let coins = vec![Coin::new(150.0, 250.0), Coin::new(250.0, 250.0), Coin::new(350.0, 150.0)];
+        (player, npcs, coins, vec![vec![false; 4]; 3])
+    }
+
+    // open 3x2 interior (x 100..400, y 100..300) at block size 100
+    fn maze() -> Maze {
+        ["+---+", "|   |", "|   |", "+---+"].iter().map(|r| r.chars().collect()).collect()
+    }
+
+    #[test]
+    fn save_round_trips_through_toml_and_restores_the_world() {
+        let (mut player, mut npcs, mut coins, mut discovered) = world();
+        player.pos = Vector2::new(212.5, 187.25);
+        player.a = 2.3;
+        npcs[1].pos = Vector2::new(301.0, 222.0);
+        npcs[1].state = NpcState::Chase;
+        coins[0].collected = true;
+        coins[2].collected = true;
+        discovered[1][2] = true;
+        let save = SaveGame::capture(2, &player, &npcs, &coins, 41.5, &discovered);
+        assert_eq!(save.collected, vec![0, 2]);
+        let loaded = SaveGame::from_toml(&save.to_toml()).unwrap();
+        assert_eq!(loaded, save);
+
+        let (mut p, mut n, mut c, mut d) = world();
+        assert_eq!(loaded.restore(&maze(), 100, &mut p, &mut n, &mut c, &mut d), Ok(2));
+        assert_eq!((p.pos, p.a), (player.pos, player.a));
+        assert_eq!((n[1].pos, n[1].state), (npcs[1].pos, NpcState::Chase));
+        assert_eq!(c.iter().map(|c| c.collected).collect::<Vec<_>>(), vec![true, false, true]);
+        assert_eq!(d, discovered);
+    }
+
+    #[test]
+    fn a_save_for_another_layout_changes_nothing() {
+        let (player, npcs, coins, discovered) = world();
+        let save = SaveGame::capture(1, &player, &npcs, &coins, 0.0, &discovered);
+        let (mut p, mut n, mut c, mut d) = world();
+        n.pop();
+        assert!(save.restore(&maze(), 100, &mut p, &mut n, &mut c, &mut d).is_err());
+        let (mut p, mut n, mut c, mut d) = world();
+        d.pop();
+        c[0].collected = true;
+        assert!(save.restore(&maze(), 100, &mut p, &mut n, &mut c, &mut d).is_err());
+        assert!(c[0].collected, "rejected restore must not touch the world");
+    }
+
+    #[test]
+    fn a_position_in_a_wall_or_off_the_maze_keeps_the_spawn() {
+        let (player, npcs, coins, discovered) = world();
+        for bad in [Vector2::new(50.0, 150.0), Vector2::new(-20.0, 150.0), Vector2::new(150.0, 5000.0), Vector2::new(f32::NAN, 150.0)] {
+            let mut save = SaveGame::capture(1, &player, &npcs, &coins, 0.0, &discovered);
+            save.player_pos = bad;
+            save.player_angle = 1.0;
+            let (mut p, mut n, mut c, mut d) = world();
+            assert_eq!(save.restore(&maze(), 100, &mut p, &mut n, &mut c, &mut d), Ok(0));
+            assert_eq!((p.pos, p.a), (player.pos, player.a), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn damaged_or_foreign_files_are_errors() {
+        let (player, npcs, coins, discovered) = world();
+        let good = SaveGame::capture(1, &player, &npcs, &coins, 3.0, &discovered).to_toml();
+        assert!(SaveGame::from_toml(&good).is_ok());
+        assert!(SaveGame::from_toml("").is_err());
+        assert!(SaveGame::from_toml(&good.replace("version = 1", "version = 99")).unwrap_err().contains("version"));
+        assert!(SaveGame::from_toml(&good.replace("level = 1", "level = 7")).is_err());
+        assert!(SaveGame::from_toml(&good.replace(",hunt", ",dancing")).is_err());
+        assert!(SaveGame::from_toml(&good.replace("count = 2", "count = 3")).is_err());
+        // a file cut off halfway
+        assert!(SaveGame::from_toml(&good[..good.len() / 2]).is_err());
+    }
+}
@@ -0,0 +1,446 @@
+// settings.rs - user preferences persisted to settings.toml
+//
+// Only a tiny TOML subset is supported (enough for flat settings): `[section]` headers,
+// `key = value` pairs with quoted strings, numbers or booleans, and `#` comments.
+// Unknown keys are ignored and missing keys keep their defaults.
+
+use std::collections::HashMap;
+use crate::audio::PauseMode;
+use crate::hud::ObjectiveArrow;
+use crate::ui::PaletteMode;
+use crate::strings::Lang;
+use crate::timestep::FPS_LIMITS;
+
+pub const SETTINGS_PATH: &str = "settings.toml";
+
+// allowed range for `gamma`
+pub const GAMMA_MIN: f32 = 0.5;
+pub const GAMMA_MAX: f32 = 2.0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub lang: Lang,
+    // keep the minimap's explored cells between sessions (saved per level in saves/)
+    pub remember_exploration: bool,
+    // number of rays cast per frame across the screen; 0 = auto (one ray per `render_scale`
+    // framebuffer pixels). Fewer rays is faster but walls look blockier (wider columns).
+    pub ray_columns: u32,
+    // brightness as a gamma curve applied to the 3D view (0.5..=2.0, 1.0 = unchanged, higher = brighter)
+    pub gamma: f32,
+    // sample every texture with nearest-neighbor filtering, overriding the manifest's per-texture filter
+    pub pixel_perfect: bool,
+    // clouds: the sky panorama slowly pans sideways
+    pub sky_drift: bool,
+    // start in fullscreen (toggled with F11, which saves the choice here)
+    pub fullscreen: bool,
+    // wall, door and floor textures larger than this (either side, in pixels) are downscaled on load
+    pub max_texture_size: u32,
+    // memory (MB) for decoded full-screen images (menu, game over, victory); the least recently shown are dropped first
+    pub image_cache_mb: u32,
+    // run the game logic at a fixed 60 Hz independent of the frame rate (false = one update per frame)
+    pub fixed_timestep: bool,
+    // frames per second cap, one of timestep::FPS_LIMITS (0 = unlimited)
+    pub fps_limit: u32,
+    // wait for the monitor's refresh (set when the window opens)
+    pub vsync: bool,
+    // threads used to rasterize sprites; 0 = one per CPU, 1 = single-threaded
+    pub sprite_threads: usize,
+    pub audio: AudioSettings,
+    // HUD text size multiplier on top of the automatic scaling by window height (0.5..=3.0)
+    pub hud_scale: f32,
+    // seconds wandering with the doors open before the exit hint arrow appears; 0 = only with the hint key
+    pub exit_hint_delay: f32,
+    // N/E/S/W bearing strip at the top of the screen (toggled in game with the compass key)
+    pub compass: bool,
+    // rear-view mirror strip near the top of the screen (toggled in game with the mirror key)
+    pub rear_mirror: bool,
+    // arrow toward the nearest coin (then the exit); auto = every level but DIFÍCIL
+    pub objective_arrow: ObjectiveArrow,
+    // colors of the HUD and minimap markers (ui::Palette); colorblind = blue/orange based
+    pub palette: PaletteMode,
+    // show the alert sting, heartbeat and door unlock sounds on screen too (cues.rs)
+    pub visual_cues: bool,
+    // gamepad vibration on damage, coin pickups and the heartbeat
+    pub rumble: bool,
+    // key names for in-game hotkeys (see input::key_from_name)
+    pub key_mute: String,
+    pub key_pause: String,
+    pub key_hint: String,
+    pub key_compass: String,
+    pub key_mirror: String,
+    pub key_restart: String,
+    pub key_save: String,
+}
+
+// The [audio] section, handed to AudioManager::apply_settings as a whole.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioSettings {
+    // volumes in 0.0..=1.0
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    // all audio silenced with the mute hotkey (volumes above are kept for unmuting)
+    pub muted: bool,
+    // music while paused: hard pause or duck to 30%
+    pub pause_mode: PauseMode,
+    // how strongly music ducks under alerts and the heartbeat (0.0 = never, 1.0 = full)
+    pub duck_amount: f32,
+    // random ambience one-shots from sounds/ambient/
+    pub ambient: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self { master_volume: 1.0, music_volume: 1.0, sfx_volume: 1.0, muted: false, pause_mode: PauseMode::Pause, duck_amount: 1.0, ambient: true }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self { lang: Lang::Es, remember_exploration: false, ray_columns: 0, gamma: 1.0, pixel_perfect: false, sky_drift: false, fullscreen: false, max_texture_size: 1024, image_cache_mb: 16, fixed_timestep: true, fps_limit: 60, vsync: false, sprite_threads: 0, audio: AudioSettings::default(), hud_scale: 1.0, exit_hint_delay: 45.0, compass: true, rear_mirror: false, objective_arrow: ObjectiveArrow::Auto, palette: PaletteMode::Default, visual_cues: false, rumble: true, key_mute: "N".to_string(), key_pause: "P".to_string(), key_hint: "H".to_string(), key_compass: "C".to_string(), key_mirror: "V".to_string(), key_restart: "R".to_string(), key_save: "G".to_string() }
+    }
+}
+
+impl Settings {
+    // Load from settings.toml, falling back to defaults when the file is missing or unreadable.
+    pub fn load() -> Self {
+        Self::load_from(SETTINGS_PATH)
+    }
+
+    pub fn load_from(path: &str) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::from_toml(&text),
+            Err(_) => {
+                eprintln!("[info] no {} found, using default settings", path);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        self.save_to(SETTINGS_PATH);
+    }
+
+    pub fn save_to(&self, path: &str) {
+        if let Err(e) = std::fs::write(path, self.to_toml()) {
+            eprintln!("[warn] couldn't write {}: {}", path, e);
+        }
+    }
+
+    pub fn from_toml(text: &str) -> Self {
+        let values = parse_toml(text);
+        let mut s = Self::default();
+        if let Some(v) = values.get("general.lang") {
+            match Lang::from_code(v) {
+                Some(lang) => s.lang = lang,
+                None => eprintln!("[warn] settings: unknown language '{}', using {}", v, s.lang.code()),
+            }
+        }
+        if let Some(v) = values.get("general.remember_exploration") {
+            match v.parse::<bool>() {
+                Ok(b) => s.remember_exploration = b,
+                Err(_) => eprintln!("[warn] settings: invalid remember_exploration '{}', expected true/false", v),
+            }
+        }
+        if let Some(v) = values.get("render.ray_columns") {
+            match v.parse::<u32>() {
+                Ok(n) => s.ray_columns = n,
+                Err(_) => eprintln!("[warn] settings: invalid ray_columns '{}', using auto", v),
+            }
+        }
+        if let Some(v) = values.get("render.gamma") {
+            match v.parse::<f32>() {
+                Ok(x) if (GAMMA_MIN..=GAMMA_MAX).contains(&x) => s.gamma = x,
+                Ok(x) => {
+                    eprintln!("[warn] settings: gamma = {} out of range, clamping to {}-{}", v, GAMMA_MIN, GAMMA_MAX);
+                    s.gamma = x.clamp(GAMMA_MIN, GAMMA_MAX);
+                }
+                Err(_) => eprintln!("[warn] settings: invalid gamma '{}', using {}", v, s.gamma),
+            }
+        }
+        if let Some(v) = values.get("render.pixel_perfect") {
+            match v.parse::<bool>() {
+                Ok(b) => s.pixel_perfect = b,
+                Err(_) => eprintln!("[warn] settings: invalid pixel_perfect '{}', expected true/false", v),
+            }
+        }
+        if let Some(v) = values.get("render.sky_drift") {
+            match v.parse::<bool>() {
+                Ok(b) => s.sky_drift = b,
+                Err(_) => eprintln!("[warn] settings: invalid sky_drift '{}', expected true/false", v),
+            }
+        }
+        if let Some(v) = values.get("render.fullscreen") {
+            match v.parse::<bool>() {
+                Ok(b) => s.fullscreen = b,
+                Err(_) => eprintln!("[warn] settings: invalid fullscreen '{}', expected true/false", v),
+            }
+        }
+        if let Some(v) = values.get("render.max_texture_size") {
+            match v.parse::<u32>() {
+                Ok(n) if (16..=8192).contains(&n) => s.max_texture_size = n,
+                _ => eprintln!("[warn] settings: invalid max_texture_size '{}', expected 16-8192", v),
+            }
+        }
+        if let Some(v) = values.get("render.image_cache_mb") {
+            match v.parse::<u32>() {
+                Ok(n) if (1..=1024).contains(&n) => s.image_cache_mb = n,
+                _ => eprintln!("[warn] settings: invalid image_cache_mb '{}', expected 1-1024", v),
+            }
+        }
+        if let Some(v) = values.get("render.fixed_timestep") {
+            match v.parse::<bool>() {
+                Ok(b) => s.fixed_timestep = b,
+                Err(_) => eprintln!("[warn] settings: invalid fixed_timestep '{}', expected true/false", v),
+            }
+        }
+        if let Some(v) = values.get("render.fps_limit") {
+            match v.parse::<u32>() {
+                Ok(n) if FPS_LIMITS.contains(&n) => s.fps_limit = n,
+                _ => eprintln!("[warn] settings: invalid fps_limit '{}', expected one of {:?} (0 = unlimited)", v, FPS_LIMITS),
+            }
+        }
+        if let Some(v) = values.get("render.vsync") {
+            match v.parse::<bool>() {
+                Ok(b) => s.vsync = b,
+                Err(_) => eprintln!("[warn] settings: invalid vsync '{}', expected true/false", v),
+            }
+        }
+        if let Some(v) = values.get("render.sprite_threads") {
+            match v.parse::<usize>() {
+                Ok(n) => s.sprite_threads = n,
+                Err(_) => eprintln!("[warn] settings: invalid sprite_threads '{}', using auto", v),
+            }
+        }
+        s.audio.master_volume = read_volume(&values, "audio.master_volume", s.audio.master_volume);
+        s.audio.music_volume = read_volume(&values, "audio.music_volume", s.audio.music_volume);
+        s.audio.sfx_volume = read_volume(&values, "audio.sfx_volume", s.audio.sfx_volume);
+        if let Some(v) = values.get("audio.muted") {
+            match v.parse::<bool>() {
+                Ok(b) => s.audio.muted = b,
+                Err(_) => eprintln!("[warn] settings: invalid muted '{}', expected true/false", v),
+            }
+        }
+        if let Some(v) = values.get("audio.pause_mode") {
+            match PauseMode::from_code(v) {
+                Some(mode) => s.audio.pause_mode = mode,
+                None => eprintln!("[warn] settings: unknown pause_mode '{}', expected pause/duck", v),
+            }
+        }
+        s.audio.duck_amount = read_volume(&values, "audio.duck_amount", s.audio.duck_amount);
+        if let Some(v) = values.get("audio.ambient") {
+            match v.parse::<bool>() {
+                Ok(b) => s.audio.ambient = b,
+                Err(_) => eprintln!("[warn] settings: invalid ambient '{}', expected true/false", v),
+            }
+        }
+        if let Some(v) = values.get("hud.hud_scale") {
+            match v.parse::<f32>() {
+                Ok(x) if (0.5..=3.0).contains(&x) => s.hud_scale = x,
+                Ok(x) => {
+                    eprintln!("[warn] settings: hud_scale = {} out of range, clamping to 0.5-3.0", v);
+                    s.hud_scale = x.clamp(0.5, 3.0);
+                }
+                Err(_) => eprintln!("[warn] settings: invalid hud_scale '{}', using {}", v, s.hud_scale),
+            }
+        }
+        if let Some(v) = values.get("hud.exit_hint_delay") {
+            match v.parse::<f32>() {
+                Ok(x) if x >= 0.0 => s.exit_hint_delay = x,
+                _ => eprintln!("[warn] settings: invalid exit_hint_delay '{}', using {}", v, s.exit_hint_delay),
+            }
+        }
+        if let Some(v) = values.get("hud.compass") {
+            match v.parse::<bool>() {
+                Ok(b) => s.compass = b,
+                Err(_) => eprintln!("[warn] settings: invalid compass '{}', expected true/false", v),
+            }
+        }
+        if let Some(v) = values.get("hud.rear_mirror") {
+            match v.parse::<bool>() {
+                Ok(b) => s.rear_mirror = b,
+                Err(_) => eprintln!("[warn] settings: invalid rear_mirror '{}', expected true/false", v),
+            }
+        }
+        if let Some(v) = values.get("hud.objective_arrow") {
+            match ObjectiveArrow::from_code(v) {
+                Some(mode) => s.objective_arrow = mode,
+                None => eprintln!("[warn] settings: unknown objective_arrow '{}', expected auto/on/off", v),
+            }
+        }
+        if let Some(v) = values.get("hud.palette") {
+            match PaletteMode::from_code(v) {
+                Some(mode) => s.palette = mode,
+                None => eprintln!("[warn] settings: unknown palette '{}', expected default/colorblind", v),
+            }
+        }
+        if let Some(v) = values.get("hud.visual_cues") {
+            match v.parse::<bool>() {
+                Ok(b) => s.visual_cues = b,
+                Err(_) => eprintln!("[warn] settings: invalid visual_cues '{}', expected true/false", v),
+            }
+        }
+        if let Some(v) = values.get("general.rumble") {
+            match v.parse::<bool>() {
+                Ok(b) => s.rumble = b,
+                Err(_) => eprintln!("[warn] settings: invalid rumble '{}', expected true/false", v),
+            }
+        }
+        if let Some(v) = values.get("keys.mute") {
+            s.key_mute = v.clone();
+        }
+        if let Some(v) = values.get("keys.pause") {
+            s.key_pause = v.clone();
+        }
+        if let Some(v) = values.get("keys.hint") {
+            s.key_hint = v.clone();
+        }
+        if let Some(v) = values.get("keys.compass") {
+            s.key_compass = v.clone();
+        }
+        if let Some(v) = values.get("keys.mirror") {
+            s.key_mirror = v.clone();
+        }
+        if let Some(v) = values.get("keys.restart") {
+            s.key_restart = v.clone();
+        }
+        if let Some(v) = values.get("keys.save") {
+            s.key_save = v.clone();
+        }
+        s
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut out = String::new();
+        out.push_str("[general]\n");
+        out.push_str(&format!("lang = \"{}\"\n", self.lang.code()));
+        out.push_str(&format!("remember_exploration = {}\n", self.remember_exploration));
+        out.push_str(&format!("rumble = {} # gamepad vibration\n", self.rumble));
+        out.push_str("\n[render]\n");
+        out.push_str(&format!("ray_columns = {} # 0 = auto\n", self.ray_columns));
+        out.push_str(&format!("gamma = {:.2} # brightness, 1.0 = unchanged\n", self.gamma));
+        out.push_str(&format!("pixel_perfect = {} # true = nearest filtering for every texture\n", self.pixel_perfect));
+        out.push_str(&format!("sky_drift = {} # slowly pan the sky\n", self.sky_drift));
+        out.push_str(&format!("fullscreen = {} # F11 toggles\n", self.fullscreen));
+        out.push_str(&format!("max_texture_size = {} # larger wall/floor textures are downscaled\n", self.max_texture_size));
+        out.push_str(&format!("image_cache_mb = {} # memory for menu/game over/victory images\n", self.image_cache_mb));
+        out.push_str(&format!("fixed_timestep = {} # false = update once per frame\n", self.fixed_timestep));
+        out.push_str(&format!("fps_limit = {} # 30/40/60/120/144, 0 = unlimited\n", self.fps_limit));
+        out.push_str(&format!("vsync = {} # applied at startup\n", self.vsync));
+        out.push_str(&format!("sprite_threads = {} # 0 = auto, 1 = single-threaded\n", self.sprite_threads));
+        out.push_str("\n[audio]\n");
+        out.push_str(&format!("master_volume = {:.2}\n", self.audio.master_volume));
+        out.push_str(&format!("music_volume = {:.2}\n", self.audio.music_volume));
+        out.push_str(&format!("sfx_volume = {:.2}\n", self.audio.sfx_volume));
+        out.push_str(&format!("muted = {}\n", self.audio.muted));
+        out.push_str(&format!("pause_mode = \"{}\" # pause | duck\n", self.audio.pause_mode.code()));
+        out.push_str(&format!("duck_amount = {:.2} # music ducking under alerts, 0 = off\n", self.audio.duck_amount));
+        out.push_str(&format!("ambient = {}\n", self.audio.ambient));
+        out.push_str("\n[hud]\n");
+        out.push_str(&format!("hud_scale = {:.2}\n", self.hud_scale));
+        out.push_str(&format!("exit_hint_delay = {:.1} # seconds, 0 = key only\n", self.exit_hint_delay));
+        out.push_str(&format!("compass = {}\n", self.compass));
+        out.push_str(&format!("rear_mirror = {} # skipped while frames run slow\n", self.rear_mirror));
+        out.push_str(&format!("objective_arrow = \"{}\" # auto (off on DIFICIL) | on | off\n", self.objective_arrow.code()));
+        out.push_str(&format!("palette = \"{}\" # default | colorblind\n", self.palette.code()));
+        out.push_str(&format!("visual_cues = {} # show alert, heartbeat and door sounds on screen\n", self.visual_cues));
+        out.push_str("\n[keys]\n");
+        out.push_str(&format!("mute = \"{}\"\n", self.key_mute));
+        out.push_str(&format!("pause = \"{}\"\n", self.key_pause));
+        out.push_str(&format!("hint = \"{}\"\n", self.key_hint));
+        out.push_str(&format!("compass = \"{}\"\n", self.key_compass));
+        out.push_str(&format!("mirror = \"{}\"\n", self.key_mirror));
+        out.push_str(&format!("restart = \"{}\" # hold to restart the level\n", self.key_restart));
+        out.push_str(&format!("save = \"{}\" # while paused: save the run and quit\n", self.key_save));
+        out
+    }
+}
+
+// Read a 0.0..=1.0 volume, clamping out-of-range values and keeping `default` when invalid.
+fn read_volume(values: &HashMap<String, String>, key: &str, default: f32) -> f32 {
+    let Some(v) = values.get(key) else { return default };
+    match v.parse::<f32>() {
+        Ok(x) if (0.0..=1.0).contains(&x) => x,
+        Ok(x) => {
+            eprintln!("[warn] settings: {} = {} out of range, clamping to 0.0-1.0", key, v);
+            x.clamp(0.0, 1.0)
+        }
+        Err(_) => {
+            eprintln!("[warn] settings: invalid {} '{}', using {}", key, v, default);
+            default
+        }
+    }
+}
+
+// Parse the supported TOML subset into a flat map keyed by "section.key".
+// String values are returned without their quotes.
+pub fn parse_toml(text: &str) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    let mut section = String::new();
+    for raw in text.lines() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            section = line[1..line.len() - 1].trim().to_string();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            eprintln!("[warn] settings: ignoring malformed line '{}'", line);
+            continue;
+        };
+        let mut value = value.trim();
+        if let Some(unquoted) = value.strip_prefix('"').and_then(|v| v.split_once('"')) {
+            value = unquoted.0;
+        } else if let Some((before_comment, _)) = value.split_once('#') {
+            value = before_comment.trim();
+        }
+        let full_key = if section.is_empty() { key.trim().to_string() } else { format!("{}.{}", section, key.trim()) };
+        values.insert(full_key, value.to_string());
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_keeps_all_fields() {
+        let s = Settings { lang: Lang::En, remember_exploration: true, ray_columns: 160, gamma: 1.3, pixel_perfect: true, sky_drift: true, fullscreen: true, max_texture_size: 512, image_cache_mb: 8, fixed_timestep: false, fps_limit: 144, vsync: true, sprite_threads: 2, audio: AudioSettings { master_volume: 0.5, music_volume: 0.25, sfx_volume: 0.75, muted: true, pause_mode: PauseMode::Duck, duck_amount: 0.4, ambient: false }, hud_scale: 1.5, exit_hint_delay: 20.0, compass: false, rear_mirror: true, objective_arrow: ObjectiveArrow::On, palette: PaletteMode::Colorblind, visual_cues: true, rumble: false, key_mute: "M".to_string(), key_pause: "F1".to_string(), key_hint: "F2".to_string(), key_compass: "F3".to_string(), key_mirror: "F6".to_string(), key_restart: "F4".to_string(), key_save: "F5".to_string() };
+        assert_eq!(Settings::from_toml(&s.to_toml()), s);
+    }
+
+    #[test]
+    fn parse_handles_sections_comments_and_quotes() {
+        let v = parse_toml("# comment\ntop = 1\n[general]\nlang = \"en\" # trailing\nflag = true # note\n");
+        assert_eq!(v.get("top").map(String::as_str), Some("1"));
+        assert_eq!(v.get("general.lang").map(String::as_str), Some("en"));
+        assert_eq!(v.get("general.flag").map(String::as_str), Some("true"));
+    }
+
+    #[test]
+    fn unknown_language_falls_back_to_default() {
+        assert_eq!(Settings::from_toml("[general]\nlang = \"xx\"\n").lang, Lang::Es);
+    }
+
+    #[test]
+    fn volumes_are_clamped() {
+        let s = Settings::from_toml("[audio]\nmaster_volume = 1.5\nmusic_volume = -2\nsfx_volume = loud\nduck_amount = 3\n");
+        assert_eq!(s.audio.master_volume, 1.0);
+        assert_eq!(s.audio.music_volume, 0.0);
+        assert_eq!(s.audio.sfx_volume, 1.0);
+        assert_eq!(s.audio.duck_amount, 1.0);
+    }
+
+    #[test]
+    fn audio_section_round_trips_on_its_own() {
+        let audio = AudioSettings { duck_amount: 0.25, ambient: false, ..AudioSettings::default() };
+        let s = Settings { audio: audio.clone(), ..Settings::default() };
+        assert_eq!(Settings::from_toml(&s.to_toml()).audio, audio);
+        // a file without the new keys keeps their defaults
+        let old = Settings::from_toml("[audio]\nmaster_volume = 0.5\n").audio;
+        assert_eq!((old.duck_amount, old.ambient), (1.0, true));
+    }
+}
@@ -0,0 +1,245 @@
+// shapes.rs - circles, arcs and filled polygons drawn into the framebuffer
+//
+// Midpoint circle algorithm: one octant is stepped in integers and mirrored into the other
+// seven, so every circle is exactly symmetric about its center. Outlines plot single pixels,
+// fills draw one horizontal span per row. Everything is clipped (to the framebuffer, or a
+// viewport inside it with the *_in variants), so centers may lie off-screen.
+//
+// Triangles and convex polygons take f32 points (rotated shapes need no pre-rounding) and
+// fill the pixels whose centers fall inside, tested against every edge function. Either
+// winding works; collinear points have no area and draw nothing. Translucent colors are
+// blended over what is already there.
+
+use std::f32::consts::TAU;
+use raylib::prelude::Color;
+use crate::clip::Rect;
+use crate::framebuffer::Framebuffer;
+
+// Offsets (x, y) of one octant of a radius `r` circle, from (r, 0) to the diagonal.
+fn octant(r: i32) -> Vec<(i32, i32)> {
+    let (mut x, mut y, mut err) = (r, 0, 1 - r);
+    let mut points = Vec::new();
+    while x >= y {
+        points.push((x, y));
+        y += 1;
+        if err < 0 {
+            err += 2 * y + 1;
+        } else {
+            x -= 1;
+            err += 2 * (y - x) + 1;
+        }
+    }
+    points
+}
+
+// Every outline pixel of a radius `r` circle as an offset from its center, without repeats.
+fn outline(r: i32) -> Vec<(i32, i32)> {
+    if r <= 0 {
+        return vec![(0, 0)];
+    }
+    let mut points: Vec<(i32, i32)> = octant(r)
+        .into_iter()
+        .flat_map(|(x, y)| [(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)])
+        .collect();
+    points.sort_unstable();
+    points.dedup();
+    points
+}
+
+fn plot(fb: &mut Framebuffer, clip: &Rect, x: i32, y: i32) {
+    if clip.contains(x, y) {
+        fb.set_pixel(x as u32, y as u32);
+    }
+}
+
+// Circle outline of radius `r` around (cx, cy); radius 0 is a single pixel.
+pub fn draw_circle(fb: &mut Framebuffer, cx: i32, cy: i32, r: i32, color: Color) {
+    let bounds = fb.bounds();
+    fb.set_current_color(color);
+    for (dx, dy) in outline(r) {
+        plot(fb, &bounds, cx + dx, cy + dy);
+    }
+}
+
+// The part of the outline from angle `start` to `end` (radians, 0 = +x, increasing clockwise
+// on screen like the view angle). A sweep of a full turn or more draws the whole circle.
+pub fn draw_arc(fb: &mut Framebuffer, cx: i32, cy: i32, r: i32, start: f32, end: f32, color: Color) {
+    let sweep = end - start;
+    if sweep <= 0.0 {
+        return;
+    }
+    let bounds = fb.bounds();
+    fb.set_current_color(color);
+    for (dx, dy) in outline(r) {
+        let angle = (dy as f32).atan2(dx as f32);
+        if sweep >= TAU || (angle - start).rem_euclid(TAU) <= sweep {
+            plot(fb, &bounds, cx + dx, cy + dy);
+        }
+    }
+}
+
+// Filled disc of radius `r` around (cx, cy), clipped to `clip` (and the framebuffer).
+pub fn fill_circle_in(fb: &mut Framebuffer, clip: &Rect, cx: i32, cy: i32, r: i32, color: Color) {
+    let Some(clip) = clip.intersect(&fb.bounds()) else { return };
+    if r <= 0 {
+        fb.set_current_color(color);
+        plot(fb, &clip, cx, cy);
+        return;
+    }
+    // widest span for each row offset, from the octant and its mirror
+    let mut half_width = vec![0; r as usize + 1];
+    for (x, y) in octant(r) {
+        half_width[y as usize] = half_width[y as usize].max(x);
+        half_width[x as usize] = half_width[x as usize].max(y);
+    }
+    for (dy, &w) in half_width.iter().enumerate() {
+        for row in [cy - dy as i32, cy + dy as i32] {
+            if let Some(span) = Rect::new(cx - w, row, 2 * w + 1, 1).intersect(&clip) {
+                fb.fill_rect(span, color);
+            }
+            if dy == 0 {
+                break;
+            }
+        }
+    }
+}
+
+pub fn fill_circle(fb: &mut Framebuffer, cx: i32, cy: i32, r: i32, color: Color) {
+    let bounds = fb.bounds();
+    fill_circle_in(fb, &bounds, cx, cy, r, color);
+}
+
+pub fn fill_triangle(fb: &mut Framebuffer, p0: (f32, f32), p1: (f32, f32), p2: (f32, f32), color: Color) {
+    fill_convex_polygon(fb, &[p0, p1, p2], color);
+}
+
+pub fn fill_convex_polygon(fb: &mut Framebuffer, points: &[(f32, f32)], color: Color) {
+    let bounds = fb.bounds();
+    fill_convex_polygon_in(fb, &bounds, points, color);
+}
+
+// Filled convex polygon clipped to `clip` (and the framebuffer).
+pub fn fill_convex_polygon_in(fb: &mut Framebuffer, clip: &Rect, points: &[(f32, f32)], color: Color) {
+    if points.len() < 3 || points.iter().any(|p| !p.0.is_finite() || !p.1.is_finite()) {
+        return;
+    }
+    let edges: Vec<_> = points.iter().zip(points.iter().cycle().skip(1)).map(|(&a, &b)| (a, b)).collect();
+    // twice the signed area; its sign is the winding, which the edge tests are flipped by
+    let signed_area: f32 = edges.iter().map(|(a, b)| a.0 * b.1 - b.0 * a.1).sum();
+    if signed_area.abs() < 1e-3 {
+        return;
+    }
+    let winding = signed_area.signum();
+    let (min_x, max_x) = points.iter().fold((f32::MAX, f32::MIN), |(lo, hi), p| (lo.min(p.0), hi.max(p.0)));
+    let (min_y, max_y) = points.iter().fold((f32::MAX, f32::MIN), |(lo, hi), p| (lo.min(p.1), hi.max(p.1)));
+    let (x0, y0) = (min_x.floor() as i32, min_y.floor() as i32);
+    let bbox = Rect::new(x0, y0, max_x.floor() as i32 - x0 + 1, max_y.floor() as i32 - y0 + 1);
+    let Some(region) = bbox.intersect(clip).and_then(|r| r.intersect(&fb.bounds())) else { return };
+    fb.set_current_color(color);
+    for y in region.y..region.bottom() {
+        for x in region.x..region.right() {
+            let (fx, fy) = (x as f32 + 0.5, y as f32 + 0.5);
+            let inside = edges.iter().all(|(a, b)| winding * ((b.0 - a.0) * (fy - a.1) - (b.1 - a.1) * (fx - a.0)) >= 0.0);
+            if !inside {
+                continue;
+            }
+            if color.a == 255 {
+                fb.set_pixel(x as u32, y as u32);
+            } else {
+                fb.blend_pixel(x, y, color, 1.0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // drawn pixels as offsets from the center of a 21x21 framebuffer
+    fn drawn(draw: impl FnOnce(&mut Framebuffer)) -> Vec<(i32, i32)> {
+        let mut fb = Framebuffer::new(21, 21);
+        draw(&mut fb);
+        let mut out = Vec::new();
+        for y in 0..21 {
+            for x in 0..21 {
+                if fb.get_pixel(x, y) != Color::BLACK {
+                    out.push((x as i32 - 10, y as i32 - 10));
+                }
+            }
+        }
+        out
+    }
+
+    fn mirrored(points: &[(i32, i32)]) -> bool {
+        points.iter().all(|&(x, y)| [(-x, y), (x, -y), (-x, -y), (y, x)].iter().all(|p| points.contains(p)))
+    }
+
+    #[test]
+    fn circles_are_symmetric_in_all_quadrants() {
+        for r in 0..=9 {
+            let ring = drawn(|fb| draw_circle(fb, 10, 10, r, Color::WHITE));
+            assert!(mirrored(&ring), "outline r = {}", r);
+            assert!(ring.iter().all(|&(x, y)| ((x * x + y * y) as f32).sqrt().round() as i32 == r), "r = {}", r);
+            let disc = drawn(|fb| fill_circle(fb, 10, 10, r, Color::WHITE));
+            assert!(mirrored(&disc), "fill r = {}", r);
+            // the fill covers the outline
+            assert!(ring.iter().all(|p| disc.contains(p)));
+        }
+    }
+
+    #[test]
+    fn tiny_radii() {
+        assert_eq!(drawn(|fb| draw_circle(fb, 10, 10, 0, Color::WHITE)), [(0, 0)]);
+        assert_eq!(drawn(|fb| fill_circle(fb, 10, 10, 0, Color::WHITE)), [(0, 0)]);
+        assert_eq!(drawn(|fb| draw_circle(fb, 10, 10, 1, Color::WHITE)), [(0, -1), (-1, 0), (1, 0), (0, 1)]);
+        assert_eq!(drawn(|fb| fill_circle(fb, 10, 10, 1, Color::WHITE)).len(), 5);
+        assert!(drawn(|fb| draw_circle(fb, 10, 10, -3, Color::WHITE)).len() == 1);
+    }
+
+    #[test]
+    fn arcs_cover_their_sweep_and_circles_clip() {
+        use std::f32::consts::PI;
+        // right half (from straight up, clockwise, to straight down)
+        let half = drawn(|fb| draw_arc(fb, 10, 10, 6, -PI / 2.0, PI / 2.0, Color::WHITE));
+        assert!(half.iter().all(|&(x, _)| x >= 0) && half.contains(&(6, 0)) && half.contains(&(0, -6)));
+        let full = drawn(|fb| draw_arc(fb, 10, 10, 6, 0.0, TAU, Color::WHITE));
+        assert_eq!(full, drawn(|fb| draw_circle(fb, 10, 10, 6, Color::WHITE)));
+        assert!(drawn(|fb| draw_arc(fb, 10, 10, 6, 1.0, 1.0, Color::WHITE)).is_empty());
+        // mostly off-screen, and clipped to a viewport
+        let corner = drawn(|fb| fill_circle(fb, -1, -1, 3, Color::WHITE));
+        assert_eq!(corner, [(-10, -10), (-9, -10), (-8, -10), (-10, -9), (-9, -9), (-10, -8)]);
+        assert!(drawn(|fb| fill_circle(fb, -8, -8, 4, Color::WHITE)).is_empty());
+        let inside = drawn(|fb| fill_circle_in(fb, &Rect::new(10, 10, 21, 21), 10, 10, 3, Color::WHITE));
+        assert!(inside.iter().all(|&(x, y)| x >= 0 && y >= 0) && inside.contains(&(3, 0)));
+    }
+
+    #[test]
+    fn triangles_fill_their_area_in_either_winding() {
+        // collinear and repeated points have no area
+        assert!(drawn(|fb| fill_triangle(fb, (1.0, 1.0), (10.0, 10.0), (19.0, 19.0), Color::WHITE)).is_empty());
+        assert!(drawn(|fb| fill_triangle(fb, (5.0, 5.0), (5.0, 5.0), (12.0, 3.0), Color::WHITE)).is_empty());
+        let cw = drawn(|fb| fill_triangle(fb, (2.0, 2.0), (18.0, 4.0), (6.0, 17.5), Color::WHITE));
+        let ccw = drawn(|fb| fill_triangle(fb, (2.0, 2.0), (6.0, 17.5), (18.0, 4.0), Color::WHITE));
+        assert_eq!(cw, ccw);
+        // area 0.5 * |16 * 15.5 - 2 * 4| = 120
+        assert!((cw.len() as i32 - 120).abs() <= 8, "{} pixels", cw.len());
+        // right triangle on pixel corners: exactly the pixel centers below the diagonal
+        let half = drawn(|fb| fill_triangle(fb, (0.0, 0.0), (10.0, 0.0), (0.0, 10.0), Color::WHITE));
+        assert_eq!(half.len(), 55);
+    }
+
+    #[test]
+    fn convex_polygons_clip_and_match_their_rectangles() {
+        let square = [(3.0, 3.0), (9.0, 3.0), (9.0, 9.0), (3.0, 9.0)];
+        let filled = drawn(|fb| fill_convex_polygon(fb, &square, Color::WHITE));
+        let rect = drawn(|fb| fb.fill_rect(Rect::new(3, 3, 6, 6), Color::WHITE));
+        assert_eq!(filled, rect);
+        // off the top-left corner: only the on-screen part, nothing past the clip rect
+        let off = [(-30.0, -30.0), (4.0, -30.0), (4.0, 4.0), (-30.0, 4.0)];
+        assert_eq!(drawn(|fb| fill_convex_polygon(fb, &off, Color::WHITE)).len(), 16);
+        let clipped = drawn(|fb| fill_convex_polygon_in(fb, &Rect::new(0, 0, 2, 21), &off, Color::WHITE));
+        assert_eq!(clipped.len(), 8);
+        assert!(drawn(|fb| fill_convex_polygon(fb, &[(0.0, 0.0), (f32::NAN, 3.0), (5.0, 5.0)], Color::WHITE)).is_empty());
+    }
+}
@@ -2,11 +2,13 @@
 
 use raylib::prelude::*;
 use crate::framebuffer::Framebuffer;
-use crate::maze::Maze;
+use crate::maze::{CellKind, Maze};
 use crate::player::Player;
 use crate::textures::TextureAtlas;
 use crate::player::can_move_to;
-use crate::anim::CoinAnimation;
+use crate::rng::Rng;
+use crate::anim::{self, ease, AnimMode, Animation, Tween};
+use crate::timestep::TICK_SECS;
 use std::collections::VecDeque;
 
 // Helpers: grid-based Bresenham line check for line-of-sight and a BFS to get the next
@@ -29,8 +31,8 @@ fn in_bounds(maze: &Maze, i: isize, j: isize) -> bool {
 
 fn is_walkable_cell(maze: &Maze, i: isize, j: isize) -> bool {
     if !in_bounds(maze, i, j) { return false; }
-    let c = maze[j as usize][i as usize];
-    c == ' ' || c == 'R' || c == 'C'
+    // NPCs never path through doors, open or not
+    CellKind::of(maze[j as usize][i as usize]).is_walkable(false)
 }
 
 // Bresenham integer line between grid cells to test LOS (returns true when no wall cell encountered)
@@ -116,42 +118,196 @@ fn next_step_bfs(maze: &Maze, from_x: f32, from_y: f32, to_x: f32, to_y: f32, bl
     Some((center_x, center_y))
 }
 
+// Hunt: no line of sight, following the BFS path. Chase: sees the player, moving straight at them.
+// Windup: close enough to attack; flashes and growls, and only hurts once the windup completes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NpcState {
+    Hunt,
+    Chase,
+    Windup,
+}
+
+// frames after an alert during which the same NPC can't alert again (~5 s at 60 FPS)
+const ALERT_COOLDOWN_FRAMES: u32 = 300;
+// an NPC within this many cells starts winding up its attack
+const WINDUP_RANGE_CELLS: f32 = 0.6;
+// frames the windup lasts before contact becomes deadly (~0.5 s at 60 FPS)
+const WINDUP_FRAMES: u32 = 30;
+// NPC footsteps: one every this many cells walked, heard within NPC_STEP_RANGE_CELLS of the
+// player, and at most MAX_NPC_STEPS per update (the nearest ones) so a crowd can't flood the
+// sound pool
+const NPC_STRIDE_CELLS: f32 = 1.0;
+const NPC_STEP_RANGE_CELLS: f32 = 4.0;
+const MAX_NPC_STEPS: usize = 3;
+// speed of the NPCs a level starts with, in world units per tick
+pub const NPC_SPEED: f32 = 6.0;
+// seconds a new NPC takes to fade in (harmless meanwhile) and a defeated one to fade out
+pub const NPC_FADE_SECS: f32 = 0.5;
+
+#[derive(Clone)]
 pub struct NPC {
     pub pos: Vector2,
     pub speed: f32,
     pub phase: f32, // animation phase for bob/pulse
+    pub state: NpcState,
+    alert_cooldown: u32,
+    // frames spent in Windup so far
+    windup: u32,
+    // distance walked since the last footstep
+    stride: f32,
+    // alpha tween while fading in after a spawn or out after defeat; None when fully there
+    fade: Option<Tween>,
+    defeated: bool,
 }
 
 impl NPC {
     pub fn new(x: f32, y: f32, speed: f32) -> Self {
-        NPC { pos: Vector2::new(x, y), speed, phase: (x + y) * 0.01 }
+        NPC { pos: Vector2::new(x, y), speed, phase: (x + y) * 0.01, state: NpcState::Hunt, alert_cooldown: 0, windup: 0, stride: 0.0, fade: None, defeated: false }
     }
+
+    // An NPC appearing mid-level: fades in over NPC_FADE_SECS and can't hurt the player until it's done.
+    pub fn spawning(x: f32, y: f32, speed: f32) -> Self {
+        NPC { fade: Some(Tween::new(0.0, 1.0, NPC_FADE_SECS, ease::linear)), ..NPC::new(x, y, speed) }
+    }
+
+    // Start fading out; update_npcs removes the NPC once it's gone. Defeating twice is a no-op.
+    pub fn defeat(&mut self) {
+        if self.defeated {
+            return;
+        }
+        self.defeated = true;
+        self.fade = Some(Tween::new(self.alpha(), 0.0, NPC_FADE_SECS, ease::linear));
+    }
+
+    pub fn is_defeated(&self) -> bool {
+        self.defeated
+    }
+
+    // Sprite opacity, 0..1.
+    pub fn alpha(&self) -> f32 {
+        self.fade.as_ref().map_or(1.0, Tween::value)
+    }
+
+    // Fading in or out: contact does no damage.
+    pub fn is_harmless(&self) -> bool {
+        self.defeated || self.fade.is_some()
+    }
+
+    // Red tint strength (0..1) for the renderer: pulses while winding up, stronger near the end.
+    pub fn windup_flash(&self) -> f32 {
+        if self.state != NpcState::Windup {
+            return 0.0;
+        }
+        let progress = (self.windup as f32 / WINDUP_FRAMES as f32).min(1.0);
+        (0.5 + 0.5 * (self.windup as f32 * 0.6).sin()) * (0.4 + 0.6 * progress)
+    }
+}
+
+// Result of one update_npcs call.
+pub struct NpcUpdate {
+    // an NPC touched the player
+    pub touched: bool,
+    // indices of NPCs that just spotted the player (left Hunt, off cooldown)
+    pub alerted: Vec<usize>,
+    // indices of NPCs that just started winding up an attack
+    pub winding_up: Vec<usize>,
+    // indices of NPCs within earshot that took a step, nearest first (at most MAX_NPC_STEPS)
+    pub steps: Vec<usize>,
+    // defeated NPCs that finished fading out and were removed before this update; the indices
+    // above refer to the list after removal
+    pub removed: usize,
 }
 
 pub struct Coin {
     pub pos: Vector2,
-    pub animation_time: f32, // for animation frames
+    pub spin: Animation,
     pub collected: bool,
 }
 
 impl Coin {
     pub fn new(x: f32, y: f32) -> Self {
+        // start each coin at its own point of the spin (hashed from its position), so
+        // neighboring coins don't turn in lockstep
+        let mut spin = anim::coin_spin();
+        spin.advance(Rng::new(((x.to_bits() as u64) << 32) | y.to_bits() as u64).next_f32() * spin.duration());
         Coin {
             pos: Vector2::new(x, y),
-            animation_time: 0.0,
+            spin,
             collected: false,
         }
     }
 }
 
+// The burst left where a coin was picked up: the coin keeps spinning while it grows to
+// COIN_POP_SCALE and fades out over COIN_POP_SECS.
+pub const COIN_POP_SECS: f32 = 0.3;
+pub const COIN_POP_SCALE: f32 = 1.4;
+
+#[derive(Clone, Copy, Debug)]
+pub struct CoinPop {
+    pub pos: Vector2,
+    pub spin: Animation,
+    pub fade: Animation,
+}
+
+impl CoinPop {
+    pub fn new(coin: &Coin) -> Self {
+        Self { pos: coin.pos, spin: coin.spin, fade: Animation::new(1, 1.0 / COIN_POP_SECS, AnimMode::Once) }
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        self.spin.advance(dt);
+        self.fade.advance(dt);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.fade.is_finished()
+    }
+
+    // size relative to the coin sprite, and opacity
+    pub fn scale(&self) -> f32 {
+        1.0 + (COIN_POP_SCALE - 1.0) * self.fade.progress()
+    }
+
+    pub fn alpha(&self) -> f32 {
+        1.0 - self.fade.progress()
+    }
+}
+
+// Quick succession of coin pickups: each pickup within STREAK_WINDOW seconds of the previous
+// one extends the streak (used to raise the pickup sound's pitch).
+pub struct CoinStreak {
+    count: u32,
+    timer: f32,
+}
+
+impl CoinStreak {
+    const STREAK_WINDOW: f32 = 2.0;
+
+    pub fn new() -> Self {
+        Self { count: 0, timer: 0.0 }
+    }
+
+    pub fn tick(&mut self, dt: f32) {
+        self.timer = (self.timer - dt).max(0.0);
+    }
+
+    // Register a pickup; returns how many pickups came right before it (0 = streak start).
+    pub fn collect(&mut self) -> u32 {
+        self.count = if self.timer > 0.0 { self.count + 1 } else { 0 };
+        self.timer = Self::STREAK_WINDOW;
+        self.count
+    }
+}
+
 pub fn load_npcs_from_maze(maze: &Maze, block_size: usize) -> Vec<NPC> {
     let mut out = Vec::new();
     for (ry, row) in maze.iter().enumerate() {
         for (rx, &cell) in row.iter().enumerate() {
-            if cell == 'R' {
+            if CellKind::of(cell) == CellKind::NpcSpawn {
                 let cx = (rx as f32 + 0.5) * block_size as f32;
                 let cy = (ry as f32 + 0.5) * block_size as f32;
-                out.push(NPC::new(cx, cy, 6.0));
+                out.push(NPC::new(cx, cy, NPC_SPEED));
             }
         }
     }
@@ -162,7 +318,7 @@ pub fn load_coins_from_maze(maze: &Maze, block_size: usize) -> Vec<Coin> {
     let mut out = Vec::new();
     for (ry, row) in maze.iter().enumerate() {
         for (rx, &cell) in row.iter().enumerate() {
-            if cell == 'C' {
+            if CellKind::of(cell) == CellKind::CoinSpawn {
                 let cx = (rx as f32 + 0.5) * block_size as f32;
                 let cy = (ry as f32 + 0.5) * block_size as f32;
                 out.push(Coin::new(cx, cy));
@@ -172,26 +328,69 @@ pub fn load_coins_from_maze(maze: &Maze, block_size: usize) -> Vec<Coin> {
     out
 }
 
-pub fn update_npcs(npcs: &mut Vec<NPC>, player: &Player, maze: &Maze, block_size: usize, doors_open: bool) -> bool {
-    // return true when any NPC touches the player
-    let mut touched = false;
+pub fn update_npcs(npcs: &mut Vec<NPC>, player: &Player, maze: &Maze, block_size: usize, doors_open: bool, dt: f32) -> NpcUpdate {
+    // fades run first, so an NPC that finishes fading out is removed in the same update
     for npc in npcs.iter_mut() {
+        if let Some(fade) = &mut npc.fade {
+            fade.advance(dt);
+            if fade.is_done() && !npc.defeated {
+                npc.fade = None;
+            }
+        }
+    }
+    let before = npcs.len();
+    npcs.retain(|npc| !(npc.defeated && npc.alpha() <= 0.0));
+    let removed = before - npcs.len();
+
+    let mut touched = false;
+    let mut alerted = Vec::new();
+    let mut winding_up = Vec::new();
+    let start: Vec<Vector2> = npcs.iter().map(|n| n.pos).collect();
+    for (idx, npc) in npcs.iter_mut().enumerate() {
+        // a defeated NPC just fades where it stands
+        if npc.defeated {
+            continue;
+        }
+        npc.alert_cooldown = npc.alert_cooldown.saturating_sub(1);
     // advance animation phase
     npc.phase += 0.12;
     if npc.phase > std::f32::consts::TAU { npc.phase = npc.phase % std::f32::consts::TAU; }
         let dir_x = player.pos.x - npc.pos.x;
         let dir_y = player.pos.y - npc.pos.y;
         let len = (dir_x*dir_x + dir_y*dir_y).sqrt();
-        // collision threshold (world pixels). If npc gets very close, consider player dead.
+        let sees_player = line_of_sight(maze, npc.pos.x, npc.pos.y, player.pos.x, player.pos.y, block_size);
+        let in_windup_range = len <= block_size as f32 * WINDUP_RANGE_CELLS;
+        let new_state = if in_windup_range {
+            NpcState::Windup
+        } else if sees_player {
+            NpcState::Chase
+        } else {
+            NpcState::Hunt
+        };
+        if new_state != NpcState::Hunt && npc.state == NpcState::Hunt && npc.alert_cooldown == 0 {
+            alerted.push(idx);
+            npc.alert_cooldown = ALERT_COOLDOWN_FRAMES;
+        }
+        if new_state == NpcState::Windup {
+            if npc.state != NpcState::Windup {
+                npc.windup = 0;
+                winding_up.push(idx);
+            } else {
+                npc.windup += 1;
+            }
+        }
+        npc.state = new_state;
+
+        // collision threshold (world pixels). If npc gets very close after winding up, consider player dead.
         let collision_dist = (block_size as f32) * 0.25; // quarter of cell
-        if len <= collision_dist {
+        if len <= collision_dist && npc.state == NpcState::Windup && npc.windup >= WINDUP_FRAMES && !npc.is_harmless() {
             touched = true;
             // continue updating others but mark touched
         }
 
         if len > 1.0 {
             // If direct LOS to player exists, try moving straight (with sliding)
-            if line_of_sight(maze, npc.pos.x, npc.pos.y, player.pos.x, player.pos.y, block_size) {
+            if sees_player {
                 let vx = dir_x / len * npc.speed;
                 let vy = dir_y / len * npc.speed;
                 let nx = npc.pos.x + vx;
@@ -235,21 +434,44 @@ pub fn update_npcs(npcs: &mut Vec<NPC>, player: &Player, maze: &Maze, block_size
             }
         }
     }
-    touched
+    // footsteps from the distance each NPC actually moved (idle or blocked NPCs are silent)
+    let stride = NPC_STRIDE_CELLS * block_size as f32;
+    let range = NPC_STEP_RANGE_CELLS * block_size as f32;
+    let mut steps: Vec<(usize, f32)> = Vec::new();
+    for (idx, (npc, from)) in npcs.iter_mut().zip(start).enumerate() {
+        let moved = (npc.pos.x - from.x).hypot(npc.pos.y - from.y);
+        if moved < 1e-3 {
+            npc.stride = 0.0;
+            continue;
+        }
+        npc.stride += moved;
+        if npc.stride >= stride {
+            npc.stride -= stride;
+            let dist = (npc.pos.x - player.pos.x).hypot(npc.pos.y - player.pos.y);
+            if dist <= range {
+                steps.push((idx, dist));
+            }
+        }
+    }
+    steps.sort_by(|a, b| a.1.total_cmp(&b.1));
+    steps.truncate(MAX_NPC_STEPS);
+    let steps = steps.into_iter().map(|(idx, _)| idx).collect();
+    NpcUpdate { touched, alerted, winding_up, steps, removed }
 }
 
-pub fn update_coins(coins: &mut Vec<Coin>, player: &Player, block_size: usize) -> (usize, bool) {
-    let mut collected_count = 0;
-    let mut any_collected = false;
+// Advance coin animations by `dt` seconds and collect every coin within pickup range of the player.
+// Pure game-state update (no audio): returns the indices of the coins collected this call
+// so the caller decides what feedback to play.
+pub fn update_coins(coins: &mut [Coin], player: &Player, block_size: usize, dt: f32) -> Vec<usize> {
+    let mut collected = Vec::new();
     let collection_distance = (block_size as f32) * 0.4; // slightly larger collection radius
     
-    for coin in coins.iter_mut() {
+    for (idx, coin) in coins.iter_mut().enumerate() {
         if coin.collected {
             continue;
         }
         
-        // Update animation using anim module
-        coin.animation_time = CoinAnimation::update_time(coin.animation_time, 0.15);
+        coin.spin.advance(dt);
         
         // Check if player is close enough to collect
         let dx = player.pos.x - coin.pos.x;
@@ -258,12 +480,22 @@ pub fn update_coins(coins: &mut Vec<Coin>, player: &Player, block_size: usize) -
         
         if distance <= collection_distance {
             coin.collected = true;
-            collected_count += 1;
-            any_collected = true;
+            collected.push(idx);
         }
     }
     
-    (collected_count, any_collected)
+    collected
+}
+
+// Distance in world units from the player to the closest NPC, or None when there are no NPCs.
+pub fn nearest_npc_distance(npcs: &[NPC], player: &Player) -> Option<f32> {
+    npcs.iter()
+        .map(|npc| {
+            let dx = npc.pos.x - player.pos.x;
+            let dy = npc.pos.y - player.pos.y;
+            (dx * dx + dy * dy).sqrt()
+        })
+        .min_by(|a, b| a.total_cmp(b))
 }
 
 pub fn render_npcs(framebuffer: &mut Framebuffer, textures: &TextureAtlas, player: &Player, npcs: &Vec<NPC>) {
@@ -343,7 +575,7 @@ pub fn render_coins(framebuffer: &mut Framebuffer, textures: &TextureAtlas, play
         let screen_x = ((rel_ang + half_fov) / player.fov) * num_rays;
         
         // Add floating motion
-        let float_offset = 8.0 * (coin.animation_time * 0.8).sin();
+        let float_offset = anim::coin_float_offset(&coin.spin);
         let sprite_height = (hh / dist) * 60.0; // slightly smaller than NPCs
         let top = (hh - (sprite_height/2.0) + float_offset) as isize;
         let bottom = (hh + (sprite_height/2.0) + float_offset) as isize;
@@ -358,7 +590,7 @@ pub fn render_coins(framebuffer: &mut Framebuffer, textures: &TextureAtlas, play
                 let v = (y as f32 - top as f32) / (bottom as f32 - top as f32 + 1.0);
                 let px = sx + xoff;
                 if px >= 0 && px < num_rays as isize {
-                    if let Some(col) = textures.sample_coin(u, v, coin.animation_time) {
+                    if let Some(col) = textures.sample_coin(u, v, coin.spin.t) {
                         if col.a > 64 { // higher alpha threshold for better visibility
                             framebuffer.set_current_color(col);
                             framebuffer.set_pixel(px as u32, y as u32);
@@ -369,3 +601,197 @@ pub fn render_coins(framebuffer: &mut Framebuffer, textures: &TextureAtlas, play
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player_at(x: f32, y: f32) -> Player {
+        Player { pos: Vector2::new(x, y), a: 0.0, fov: std::f32::consts::PI / 3.0, vel: Vector2::zero() }
+    }
+
+    #[test]
+    fn coin_collected_exactly_at_pickup_radius() {
+        let block_size = 100;
+        let mut coins = vec![Coin::new(150.0, 150.0)];
+        // pickup radius is 0.4 * block_size = 40 world units
+        let player = player_at(190.0, 150.0);
+        assert_eq!(update_coins(&mut coins, &player, block_size, TICK_SECS), vec![0]);
+        assert!(coins[0].collected);
+    }
+
+    #[test]
+    fn coin_just_outside_radius_is_not_collected() {
+        let block_size = 100;
+        let mut coins = vec![Coin::new(150.0, 150.0)];
+        let player = player_at(190.5, 150.0);
+        assert!(update_coins(&mut coins, &player, block_size, TICK_SECS).is_empty());
+        assert!(!coins[0].collected);
+    }
+
+    #[test]
+    fn coin_spin_follows_wall_clock_time_at_any_fps() {
+        let far = player_at(5000.0, 5000.0);
+        let frame_after_a_second = |fps: usize| {
+            let mut coins = vec![Coin::new(150.0, 150.0), Coin::new(250.0, 150.0)];
+            for _ in 0..fps {
+                update_coins(&mut coins, &far, 100, 1.0 / fps as f32);
+            }
+            coins.iter().map(|c| (c.spin.frame(), anim::coin_float_offset(&c.spin))).collect::<Vec<_>>()
+        };
+        let (slow, fast) = (frame_after_a_second(30), frame_after_a_second(144));
+        for (a, b) in slow.iter().zip(&fast) {
+            assert_eq!(a.0, b.0);
+            assert!((a.1 - b.1).abs() < 0.05);
+        }
+        // neighbors start at different points of the spin, the same coin always at the same one
+        assert_ne!(Coin::new(150.0, 150.0).spin.t, Coin::new(250.0, 150.0).spin.t);
+        assert_eq!(Coin::new(150.0, 150.0).spin.t, Coin::new(150.0, 150.0).spin.t);
+    }
+
+    #[test]
+    fn coin_pop_grows_and_fades_out() {
+        let mut pop = CoinPop::new(&Coin::new(150.0, 150.0));
+        assert_eq!((pop.scale(), pop.alpha()), (1.0, 1.0));
+        pop.advance(COIN_POP_SECS / 2.0);
+        assert!((pop.scale() - 1.2).abs() < 1e-4 && (pop.alpha() - 0.5).abs() < 1e-4 && !pop.is_finished());
+        pop.advance(COIN_POP_SECS);
+        assert!(pop.is_finished() && (pop.scale() - COIN_POP_SCALE).abs() < 1e-6 && pop.alpha() == 0.0);
+    }
+
+    #[test]
+    fn collected_coin_is_not_collected_again() {
+        let block_size = 100;
+        let mut coins = vec![Coin::new(150.0, 150.0), Coin::new(450.0, 150.0)];
+        let player = player_at(150.0, 150.0);
+        assert_eq!(update_coins(&mut coins, &player, block_size, TICK_SECS), vec![0]);
+        // standing on the same spot next frame must not report it again
+        assert!(update_coins(&mut coins, &player, block_size, TICK_SECS).is_empty());
+        assert!(coins[0].collected);
+        assert!(!coins[1].collected);
+    }
+
+    #[test]
+    fn npc_alerts_once_when_it_first_spots_the_player() {
+        let maze: Maze = vec![
+            "+-----+".chars().collect(),
+            "|     |".chars().collect(),
+            "|  |  |".chars().collect(),
+            "+-----+".chars().collect(),
+        ];
+        // NPC below-left of the pillar, player below-right: no line of sight at first
+        let mut npcs = vec![NPC::new(150.0, 250.0, 0.0)];
+        let hidden = player_at(450.0, 250.0);
+        assert!(update_npcs(&mut npcs, &hidden, &maze, 100, false, TICK_SECS).alerted.is_empty());
+        assert_eq!(npcs[0].state, NpcState::Hunt);
+
+        let visible = player_at(150.0, 150.0);
+        assert_eq!(update_npcs(&mut npcs, &visible, &maze, 100, false, TICK_SECS).alerted, vec![0]);
+        assert_eq!(npcs[0].state, NpcState::Chase);
+        // still chasing: no new sting
+        assert!(update_npcs(&mut npcs, &visible, &maze, 100, false, TICK_SECS).alerted.is_empty());
+        // lose and regain sight within the cooldown: no new sting either
+        update_npcs(&mut npcs, &hidden, &maze, 100, false, TICK_SECS);
+        assert!(update_npcs(&mut npcs, &visible, &maze, 100, false, TICK_SECS).alerted.is_empty());
+    }
+
+    #[test]
+    fn contact_only_hurts_after_windup() {
+        let maze: Maze = vec![
+            "+---+".chars().collect(),
+            "|   |".chars().collect(),
+            "+---+".chars().collect(),
+        ];
+        let player = player_at(150.0, 150.0);
+        let mut npcs = vec![NPC::new(160.0, 150.0, 0.0)];
+        let first = update_npcs(&mut npcs, &player, &maze, 100, false, TICK_SECS);
+        assert!(!first.touched);
+        assert_eq!(first.winding_up, vec![0]);
+        assert!(npcs[0].windup_flash() > 0.0);
+        // harmless for the rest of the windup, deadly on the frame it completes
+        for _ in 1..WINDUP_FRAMES {
+            assert!(!update_npcs(&mut npcs, &player, &maze, 100, false, TICK_SECS).touched);
+        }
+        assert!(update_npcs(&mut npcs, &player, &maze, 100, false, TICK_SECS).touched);
+    }
+
+    #[test]
+    fn fading_in_npc_deals_no_damage() {
+        let maze: Maze = vec![
+            "+---+".chars().collect(),
+            "|   |".chars().collect(),
+            "+---+".chars().collect(),
+        ];
+        let player = player_at(150.0, 150.0);
+        // a slow fade-in, so the windup completes long before it does
+        let mut npcs = vec![NPC { fade: Some(Tween::new(0.0, 1.0, 1.0, ease::linear)), ..NPC::spawning(160.0, 150.0, 0.0) }];
+        let mut ticks = 0;
+        let touched = loop {
+            let touched = update_npcs(&mut npcs, &player, &maze, 100, false, TICK_SECS).touched;
+            ticks += 1;
+            if !npcs[0].is_harmless() {
+                break touched;
+            }
+            assert!(!touched && ticks <= 61);
+        };
+        // deadly on the tick the fade-in completes
+        assert!(ticks as u32 > WINDUP_FRAMES && npcs[0].alpha() == 1.0 && touched);
+        // the regular spawn fades in over NPC_FADE_SECS
+        let mut npc = vec![NPC::spawning(350.0, 150.0, 0.0)];
+        update_npcs(&mut npc, &player, &maze, 100, false, TICK_SECS);
+        assert!((npc[0].alpha() - TICK_SECS / NPC_FADE_SECS).abs() < 1e-4);
+    }
+
+    #[test]
+    fn faded_out_npc_is_removed_exactly_once() {
+        let maze: Maze = vec![
+            "+------+".chars().collect(),
+            "|      |".chars().collect(),
+            "+------+".chars().collect(),
+        ];
+        let player = player_at(550.0, 150.0);
+        let mut npcs = vec![NPC::new(150.0, 150.0, 0.0), NPC::new(250.0, 150.0, 0.0)];
+        npcs[0].defeat();
+        npcs[0].defeat();
+        let mut removed = 0;
+        for _ in 0..(2.0 * NPC_FADE_SECS / TICK_SECS) as usize {
+            let update = update_npcs(&mut npcs, &player, &maze, 100, false, TICK_SECS);
+            assert!(!update.touched);
+            removed += update.removed;
+        }
+        assert_eq!(removed, 1);
+        assert_eq!(npcs.len(), 1);
+        assert_eq!(npcs[0].pos, Vector2::new(250.0, 150.0));
+    }
+
+    #[test]
+    fn only_the_nearest_walking_npcs_are_heard() {
+        let maze: Maze = vec![
+            "+----------+".chars().collect(),
+            "|          |".chars().collect(),
+            "+----------+".chars().collect(),
+        ];
+        let player = player_at(150.0, 150.0);
+        // four walkers in earshot, one far away, one standing still next to the player
+        let mut npcs: Vec<NPC> = [250.0, 300.0, 350.0, 400.0, 1050.0].iter().map(|&x| NPC::new(x, 150.0, 10.0)).collect();
+        npcs.push(NPC::new(200.0, 150.0, 0.0));
+        let mut heard = Vec::new();
+        for _ in 0..10 {
+            let update = update_npcs(&mut npcs, &player, &maze, 100, false, TICK_SECS);
+            assert!(update.steps.len() <= MAX_NPC_STEPS);
+            heard.extend(update.steps);
+        }
+        heard.sort();
+        heard.dedup();
+        // the walkers take their first step after one cell; the farthest walker in range
+        // loses out to the three nearer ones, the far NPC is out of range and the idle one silent
+        assert_eq!(heard, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn nearest_npc_distance_picks_closest() {
+        let npcs = vec![NPC::new(500.0, 150.0, 1.0), NPC::new(150.0, 250.0, 1.0)];
+        assert_eq!(nearest_npc_distance(&npcs, &player_at(150.0, 150.0)), Some(100.0));
+        assert_eq!(nearest_npc_distance(&[], &player_at(150.0, 150.0)), None);
+    }
+}
@@ -0,0 +1,261 @@
+// strings.rs - user-facing UI text in every supported language
+//
+// Spanish is the reference language: every key must have a Spanish entry, and any key
+// missing from another language falls back to Spanish instead of panicking.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Lang {
+    Es,
+    En,
+}
+
+impl Lang {
+    pub const ALL: [Lang; 2] = [Lang::Es, Lang::En];
+
+    // code used in settings.toml
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::Es => "es",
+            Lang::En => "en",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Lang> {
+        match code.trim().to_lowercase().as_str() {
+            "es" => Some(Lang::Es),
+            "en" => Some(Lang::En),
+            _ => None,
+        }
+    }
+
+    // name shown in the options menu (always in its own language)
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Lang::Es => "ESPAÑOL",
+            Lang::En => "ENGLISH",
+        }
+    }
+
+    pub fn next(self) -> Lang {
+        let idx = Lang::ALL.iter().position(|l| *l == self).unwrap_or(0);
+        Lang::ALL[(idx + 1) % Lang::ALL.len()]
+    }
+}
+
+// Keys for every string drawn on screen.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Str {
+    MenuContinue,
+    MenuPlay,
+    MenuOptions,
+    MenuQuit,
+    MenuSelectLevel,
+    MenuLevel1,
+    MenuLevel2,
+    MenuLevel3,
+    MenuLevelHint,
+    MenuOptionsTitle,
+    MenuLanguage,
+    MenuMasterVolume,
+    MenuMusicVolume,
+    MenuSfxVolume,
+    MenuBrightness,
+    MenuPixelPerfect,
+    MenuPalette,
+    MenuPaletteDefault,
+    MenuPaletteColorblind,
+    MenuFpsLimit,
+    MenuUnlimited,
+    MenuFpsCoupled,
+    MenuOn,
+    MenuOff,
+    MenuResetExploration,
+    MenuDone,
+    MenuBack,
+    MenuOptionsHint,
+    HudCoins,
+    HudLevel,
+    DoorsOpen,
+    TexturesReloaded,
+    LoadingTextures,
+    NoLevelLoaded,
+    Paused,
+    PausedSaveHint,
+    SaveDamaged,
+    StartupFailed,
+    MissingLevelFile,
+    MissingWallTextures,
+    LookedIn,
+    QuitKeyHint,
+    LevelCompleted,
+    AdvancingToLevel,
+    ContinueHint,
+    AllLevelsCompleted,
+    VictoryStats,
+    RestartOrQuit,
+    GameOver,
+}
+
+static CURRENT_LANG: AtomicU8 = AtomicU8::new(0);
+
+// Set the language used by `tr`/`trf` (from settings at startup and from the options menu).
+pub fn set_lang(lang: Lang) {
+    let idx = Lang::ALL.iter().position(|l| *l == lang).unwrap_or(0);
+    CURRENT_LANG.store(idx as u8, Ordering::Relaxed);
+}
+
+pub fn current_lang() -> Lang {
+    Lang::ALL.get(CURRENT_LANG.load(Ordering::Relaxed) as usize).copied().unwrap_or(Lang::Es)
+}
+
+fn es(key: Str) -> &'static str {
+    match key {
+        Str::MenuContinue => "CONTINUAR",
+        Str::MenuPlay => "JUGAR",
+        Str::MenuOptions => "OPCIONES",
+        Str::MenuQuit => "SALIR",
+        Str::MenuSelectLevel => "SELECCIONAR NIVEL",
+        Str::MenuLevel1 => "NIVEL 1 - FACIL (2 fichas)",
+        Str::MenuLevel2 => "NIVEL 2 - MEDIO (4 fichas)",
+        Str::MenuLevel3 => "NIVEL 3 - DIFICIL (6 fichas)",
+        Str::MenuLevelHint => "ESC = Volver | ENTER = Jugar",
+        Str::MenuOptionsTitle => "OPCIONES",
+        Str::MenuLanguage => "IDIOMA",
+        Str::MenuMasterVolume => "VOLUMEN GENERAL",
+        Str::MenuMusicVolume => "MUSICA",
+        Str::MenuSfxVolume => "EFECTOS",
+        Str::MenuBrightness => "BRILLO",
+        Str::MenuPixelPerfect => "PIXELES NITIDOS",
+        Str::MenuPalette => "COLORES",
+        Str::MenuPaletteDefault => "NORMALES",
+        Str::MenuPaletteColorblind => "DALTONISMO",
+        Str::MenuFpsLimit => "LIMITE DE FPS",
+        Str::MenuUnlimited => "SIN LIMITE",
+        Str::MenuFpsCoupled => "{0} (SIN FIXED_TIMESTEP: {1})",
+        Str::MenuOn => "SI",
+        Str::MenuOff => "NO",
+        Str::MenuResetExploration => "BORRAR EXPLORACIÓN",
+        Str::MenuDone => "HECHO",
+        Str::MenuBack => "VOLVER",
+        Str::MenuOptionsHint => "IZQ/DER = Cambiar | ESC = Volver",
+        Str::HudCoins => "Monedas: {0}/{1}",
+        Str::HudLevel => "Nivel: {0}",
+        Str::DoorsOpen => "¡Puertas abiertas!",
+        Str::TexturesReloaded => "Texturas recargadas ({0} cambiadas)",
+        Str::LoadingTextures => "Cargando texturas {0}/{1}",
+        Str::NoLevelLoaded => "Sin nivel cargado",
+        Str::Paused => "PAUSA",
+        Str::PausedSaveHint => "PAUSA  ({0} = GUARDAR Y SALIR)",
+        Str::SaveDamaged => "Partida guardada dañada, empezando de nuevo",
+        Str::StartupFailed => "NO SE PUEDE INICIAR: FALTAN ARCHIVOS",
+        Str::MissingLevelFile => "Nivel {0}",
+        Str::MissingWallTextures => "Textura de pared o de piso",
+        Str::LookedIn => "buscado en:",
+        Str::QuitKeyHint => "Q = SALIR",
+        Str::LevelCompleted => "NIVEL {0} - COMPLETADO!",
+        Str::AdvancingToLevel => "AVANZANDO AL NIVEL {0}",
+        Str::ContinueHint => "ENTER = CONTINUAR",
+        Str::AllLevelsCompleted => "¡TODOS LOS NIVELES COMPLETADOS!",
+        Str::VictoryStats => "TIEMPO {0}   FICHAS {1}",
+        Str::RestartOrQuit => "ENTER = REINICIAR  Q = SALIR",
+        Str::GameOver => "GAME OVER",
+    }
+}
+
+fn en(key: Str) -> Option<&'static str> {
+    Some(match key {
+        Str::MenuContinue => "CONTINUE",
+        Str::MenuPlay => "PLAY",
+        Str::MenuOptions => "OPTIONS",
+        Str::MenuQuit => "QUIT",
+        Str::MenuSelectLevel => "SELECT LEVEL",
+        Str::MenuLevel1 => "LEVEL 1 - EASY (2 chips)",
+        Str::MenuLevel2 => "LEVEL 2 - MEDIUM (4 chips)",
+        Str::MenuLevel3 => "LEVEL 3 - HARD (6 chips)",
+        Str::MenuLevelHint => "ESC = Back | ENTER = Play",
+        Str::MenuOptionsTitle => "OPTIONS",
+        Str::MenuLanguage => "LANGUAGE",
+        Str::MenuMasterVolume => "MASTER VOLUME",
+        Str::MenuMusicVolume => "MUSIC",
+        Str::MenuSfxVolume => "EFFECTS",
+        Str::MenuBrightness => "BRIGHTNESS",
+        Str::MenuPixelPerfect => "PIXEL PERFECT",
+        Str::MenuPalette => "COLORS",
+        Str::MenuPaletteDefault => "DEFAULT",
+        Str::MenuPaletteColorblind => "COLORBLIND",
+        Str::MenuFpsLimit => "FPS LIMIT",
+        Str::MenuUnlimited => "UNLIMITED",
+        Str::MenuFpsCoupled => "{0} (FIXED_TIMESTEP OFF: {1})",
+        Str::MenuOn => "ON",
+        Str::MenuOff => "OFF",
+        Str::MenuResetExploration => "RESET EXPLORATION",
+        Str::MenuDone => "DONE",
+        Str::MenuBack => "BACK",
+        Str::MenuOptionsHint => "LEFT/RIGHT = Change | ESC = Back",
+        Str::HudCoins => "Coins: {0}/{1}",
+        Str::HudLevel => "Level: {0}",
+        Str::DoorsOpen => "Doors open!",
+        Str::TexturesReloaded => "Textures reloaded ({0} changed)",
+        Str::LoadingTextures => "Loading textures {0}/{1}",
+        Str::NoLevelLoaded => "No level loaded",
+        Str::Paused => "PAUSED",
+        Str::PausedSaveHint => "PAUSED  ({0} = SAVE AND QUIT)",
+        Str::SaveDamaged => "Saved game is damaged, starting a new game",
+        Str::StartupFailed => "CAN'T START: FILES ARE MISSING",
+        Str::MissingLevelFile => "Level file {0}",
+        Str::MissingWallTextures => "Wall or floor texture",
+        Str::LookedIn => "looked in:",
+        Str::QuitKeyHint => "Q = QUIT",
+        Str::LevelCompleted => "LEVEL {0} - COMPLETE!",
+        Str::AdvancingToLevel => "ADVANCING TO LEVEL {0}",
+        Str::ContinueHint => "ENTER = CONTINUE",
+        Str::AllLevelsCompleted => "ALL LEVELS COMPLETE!",
+        Str::VictoryStats => "TIME {0}   COINS {1}",
+        Str::RestartOrQuit => "ENTER = RESTART  Q = QUIT",
+        Str::GameOver => "GAME OVER",
+    })
+}
+
+// Look up `key` in `lang`, falling back to Spanish when the translation is missing.
+pub fn tr_in(lang: Lang, key: Str) -> &'static str {
+    let translated = match lang {
+        Lang::Es => None,
+        Lang::En => en(key),
+    };
+    translated.unwrap_or_else(|| es(key))
+}
+
+// Look up `key` in the current language.
+pub fn tr(key: Str) -> &'static str {
+    tr_in(current_lang(), key)
+}
+
+// Like `tr`, replacing `{0}`, `{1}`, ... placeholders with `args`.
+pub fn trf(key: Str, args: &[&dyn std::fmt::Display]) -> String {
+    let mut out = tr(key).to_string();
+    for (i, arg) in args.iter().enumerate() {
+        out = out.replace(&format!("{{{}}}", i), &arg.to_string());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn english_overrides_and_spanish_is_reference() {
+        assert_eq!(tr_in(Lang::Es, Str::MenuPlay), "JUGAR");
+        assert_eq!(tr_in(Lang::En, Str::MenuPlay), "PLAY");
+    }
+
+    #[test]
+    fn lang_codes_round_trip() {
+        for lang in Lang::ALL {
+            assert_eq!(Lang::from_code(lang.code()), Some(lang));
+        }
+        assert_eq!(Lang::from_code("xx"), None);
+    }
+}
@@ -1,10 +1,15 @@
 // textures.rs
 
 use raylib::prelude::*;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Mutex;
 use image::GenericImageView;
-use crate::anim::CoinAnimation;
+use crate::anim;
 
+// Textures drawn on wall faces; the discriminant indexes TextureAtlas::warned.
 #[derive(Copy, Clone, Debug)]
 pub enum TextureKind {
     Wall,
@@ -13,393 +18,1256 @@ pub enum TextureKind {
     DoorOpen,
 }
 
+// Every texture the atlas can hold. Manifest names map to the built-in ids; any other name
+// becomes an Extra, loaded and kept for features that look it up by name.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TextureId {
+    Wall,
+    Pillar,
+    Npc,
+    Sky,
+    Floor,
+    Menu,
+    GameOver,
+    Victoria,
+    Coin,
+    DoorClosed,
+    DoorOpen,
+    Extra(String),
+}
+
+impl TextureId {
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "wall" => TextureId::Wall,
+            "pillar" => TextureId::Pillar,
+            "npc" => TextureId::Npc,
+            "sky" => TextureId::Sky,
+            "floor" => TextureId::Floor,
+            "menu" => TextureId::Menu,
+            "game_over" => TextureId::GameOver,
+            "victoria" => TextureId::Victoria,
+            "coin" => TextureId::Coin,
+            "door_closed" => TextureId::DoorClosed,
+            "door_open" => TextureId::DoorOpen,
+            other => TextureId::Extra(other.to_string()),
+        }
+    }
+
+    // the manifest name, inverse of from_name
+    pub fn name(&self) -> &str {
+        match self {
+            TextureId::Wall => "wall",
+            TextureId::Pillar => "pillar",
+            TextureId::Npc => "npc",
+            TextureId::Sky => "sky",
+            TextureId::Floor => "floor",
+            TextureId::Menu => "menu",
+            TextureId::GameOver => "game_over",
+            TextureId::Victoria => "victoria",
+            TextureId::Coin => "coin",
+            TextureId::DoorClosed => "door_closed",
+            TextureId::DoorOpen => "door_open",
+            TextureId::Extra(name) => name,
+        }
+    }
+}
+
+impl From<TextureKind> for TextureId {
+    fn from(kind: TextureKind) -> Self {
+        match kind {
+            TextureKind::Wall => TextureId::Wall,
+            TextureKind::Pillar => TextureId::Pillar,
+            TextureKind::DoorClosed => TextureId::DoorClosed,
+            TextureKind::DoorOpen => TextureId::DoorOpen,
+        }
+    }
+}
+
+// Per-texture loading options from the manifest.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct TextureOptions {
+    frames: usize,
+    square_frames: bool,
+    fps: Option<f32>,
+    filter: FilterMode,
+    adjust: Adjustment,
+}
+
+impl From<&TextureEntry> for TextureOptions {
+    fn from(e: &TextureEntry) -> Self {
+        Self { frames: e.frames, square_frames: e.square_frames, fps: e.fps, filter: e.filter, adjust: e.adjust }
+    }
+}
+
+impl TextureOptions {
+    // for textures set up without a manifest entry (tests, TextureAtlas::insert)
+    fn default_for(id: &TextureId) -> Self {
+        let frames = if *id == TextureId::Coin { COIN_FRAMES } else { 1 };
+        let filter = match id {
+            TextureId::Wall | TextureId::Pillar | TextureId::DoorClosed | TextureId::DoorOpen | TextureId::Sky => FilterMode::Bilinear,
+            _ => FilterMode::Nearest,
+        };
+        Self { frames, square_frames: false, fps: None, filter, adjust: Adjustment::default() }
+    }
+
+    // Textures with more than one frame are kept as SpriteSheets.
+    fn animated(&self) -> bool {
+        self.frames > 1 || self.square_frames
+    }
+}
+
+#[derive(PartialEq)]
 pub struct ImageBuf {
     pub w: u32,
     pub h: u32,
     pub data: Vec<u8>, // RGBA8
 }
 
-pub struct TextureAtlas {
-    pub wall: Option<ImageBuf>,
-    pub pillar: Option<ImageBuf>,
-    pub npc: Option<ImageBuf>,
-    pub sky: Option<ImageBuf>,
-    pub floor: Option<ImageBuf>,
-    pub menu: Option<ImageBuf>,
-    pub game_over: Option<ImageBuf>,
-    pub victoria: Option<ImageBuf>,
-    pub coin: Option<ImageBuf>,
-    pub door_closed: Option<ImageBuf>,
-    pub door_open: Option<ImageBuf>,
-}
-
-impl TextureAtlas {
-    pub fn new() -> Self {
-        // Try a few candidate relative paths because the working directory may vary.
-        let wall_candidates = [
-            "./textures/Textura1_PARED.png",
-            "textures/Textura1_PARED.png",
-            "../textures/Textura1_PARED.png",
-        ];
-        let pillar_candidates = [
-            "./textures/Textura2_Pilar.png",
-            "textures/Textura2_Pilar.png",
-            "../textures/Textura2_Pilar.png",
-        ];
-
-        let mut wall: Option<ImageBuf> = None;
-        for p in wall_candidates.iter() {
-            let path = Path::new(p);
-            if path.exists() {
-                eprintln!("[textures] found wall image at {}", path.display());
-                match image::open(path) {
-                    Ok(img) => {
-                        let img = img.to_rgba8();
-                        let (w, h) = img.dimensions();
-                        wall = Some(ImageBuf { w, h, data: img.into_raw() });
-                        break;
+impl ImageBuf {
+    // A copy scaled down (keeping the aspect ratio) so neither side exceeds `max`, each pixel
+    // the average of the source pixels it covers.
+    pub fn downscaled(&self, max: u32) -> ImageBuf {
+        let scale = max as f32 / self.w.max(self.h) as f32;
+        if scale >= 1.0 {
+            return ImageBuf { w: self.w, h: self.h, data: self.data.clone() };
+        }
+        let w = ((self.w as f32 * scale).round() as u32).clamp(1, max);
+        let h = ((self.h as f32 * scale).round() as u32).clamp(1, max);
+        let mut data = Vec::with_capacity((w * h * 4) as usize);
+        for y in 0..h {
+            let (y0, y1) = (y * self.h / h, ((y + 1) * self.h / h).max(y * self.h / h + 1));
+            for x in 0..w {
+                let (x0, x1) = (x * self.w / w, ((x + 1) * self.w / w).max(x * self.w / w + 1));
+                let mut sum = [0u32; 4];
+                for sy in y0..y1 {
+                    for sx in x0..x1 {
+                        let i = ((sy * self.w + sx) * 4) as usize;
+                        for (c, total) in sum.iter_mut().enumerate() {
+                            *total += self.data[i + c] as u32;
+                        }
                     }
-                    Err(e) => eprintln!("[textures] failed to load {}: {:?}", path.display(), e),
                 }
+                let n = (x1 - x0) * (y1 - y0);
+                data.extend(sum.iter().map(|s| ((s + n / 2) / n) as u8));
             }
         }
+        ImageBuf { w, h, data }
+    }
+}
 
-        let mut pillar: Option<ImageBuf> = None;
-        for p in pillar_candidates.iter() {
-            let path = Path::new(p);
-            if path.exists() {
-                eprintln!("[textures] found pillar image at {}", path.display());
-                match image::open(path) {
-                    Ok(img) => {
-                        let img = img.to_rgba8();
-                        let (w, h) = img.dimensions();
-                        let raw = img.into_raw();
-                        // debug: print first pixel if available
-                        if raw.len() >= 4 {
-                            eprintln!(
-                                "[textures] pillar dims={}x{} first_rgba={},{},{},{}",
-                                w,
-                                h,
-                                raw[0],
-                                raw[1],
-                                raw[2],
-                                raw[3]
-                            );
-                        }
-                        pillar = Some(ImageBuf { w, h, data: raw });
-                        break;
-                    }
-                    Err(e) => eprintln!("[textures] failed to load {}: {:?}", path.display(), e),
-                }
+// An animation stored as equally wide frames side by side. When the width isn't a multiple of
+// the frame count the leftover columns on the right are never shown.
+pub struct SpriteSheet {
+    pub image: ImageBuf,
+    pub frames: u32,
+    pub fps: f32,
+}
+
+impl SpriteSheet {
+    pub fn new(image: ImageBuf, frames: u32, fps: f32) -> Self {
+        let frames = frames.clamp(1, image.w.max(1));
+        Self { image, frames, fps }
+    }
+
+    // as many square (height x height) frames as fit in the width
+    pub fn with_square_frames(image: ImageBuf, fps: f32) -> Self {
+        let frames = image.w / image.h.max(1);
+        Self::new(image, frames, fps)
+    }
+
+    pub fn frame_width(&self) -> u32 {
+        self.image.w / self.frames
+    }
+
+    pub fn frame_at(&self, time: f32) -> u32 {
+        anim::frame_at(time, self.fps, self.frames as usize) as u32
+    }
+
+    // Color at (u, v) within the frame shown at `time` (nearest filtering).
+    pub fn sample(&self, u: f32, v: f32, time: f32) -> Color {
+        self.sample_frame(self.frame_at(time), u, v, FilterMode::Nearest)
+    }
+
+    // Color at (u, v) within frame `frame` (wrapping).
+    pub fn sample_frame(&self, frame: u32, u: f32, v: f32, filter: FilterMode) -> Color {
+        let x0 = (frame % self.frames) * self.frame_width();
+        sample_region(&self.image, x0, self.frame_width(), u, v, filter)
+    }
+}
+
+// All sprite images packed into a single GPU texture, with the source rectangle of each sprite.
+// Lets the overlay pass draw crisp billboards with `draw_texture_pro` instead of per-pixel sampling.
+pub struct GpuSpriteAtlas {
+    pub texture: Texture2D,
+    pub regions: HashMap<&'static str, Rectangle>,
+}
+
+impl GpuSpriteAtlas {
+    pub fn region(&self, name: &str) -> Option<Rectangle> {
+        self.regions.get(name).copied()
+    }
+
+    // source rect of one frame of a horizontal spritesheet region (e.g. the coin)
+    pub fn frame_region(&self, name: &str, frame: usize, num_frames: usize) -> Option<Rectangle> {
+        let r = self.region(name)?;
+        let fw = r.width / num_frames.max(1) as f32;
+        Some(Rectangle::new(r.x + fw * (frame % num_frames.max(1)) as f32, r.y, fw, r.height))
+    }
+}
+
+// Shelf-pack images of the given sizes left to right in a single row with `padding` pixels
+// between them (avoids bleeding when filtering). Returns (atlas_w, atlas_h, top-left of each).
+pub fn pack_row(sizes: &[(u32, u32)], padding: u32) -> (u32, u32, Vec<(u32, u32)>) {
+    pack_shelves(sizes, u32::MAX, padding)
+}
+
+// Like pack_row, but starting a new row (shelf) below the tallest image of the current one
+// whenever the next image would end past `max_width`.
+pub fn pack_shelves(sizes: &[(u32, u32)], max_width: u32, padding: u32) -> (u32, u32, Vec<(u32, u32)>) {
+    let (mut x, mut y, mut shelf_h) = (0u32, 0u32, 0u32);
+    let mut width = 0;
+    let mut positions = Vec::with_capacity(sizes.len());
+    for &(w, h) in sizes {
+        if x > 0 && x.saturating_add(w) > max_width {
+            x = 0;
+            y += shelf_h + padding;
+            shelf_h = 0;
+        }
+        positions.push((x, y));
+        width = width.max(x + w);
+        x += w + padding;
+        shelf_h = shelf_h.max(h);
+    }
+    (width, y + shelf_h, positions)
+}
+
+// Where the texture manifest is looked for; entry paths are relative to its directory.
+const MANIFEST_CANDIDATES: [&str; 2] = ["textures/manifest.toml", "../textures/manifest.toml"];
+// Optional packed atlas (written by --pack-textures): one image holding many textures plus a
+// descriptor with each one's rectangle. Textures it contains skip their individual files.
+const ATLAS_CANDIDATES: [&str; 2] = ["textures/atlas.toml", "../textures/atlas.toml"];
+const ATLAS_IMAGE: &str = "atlas.png";
+// widest packed atlas image; textures continue on a new shelf past it
+const ATLAS_MAX_WIDTH: u32 = 4096;
+// Directories the built-in default file names are looked for in (the working directory may vary).
+const TEXTURE_DIRS: [&str; 2] = ["textures", "../textures"];
+// frames in the coin spritesheet when neither the manifest nor the defaults say otherwise
+const COIN_FRAMES: usize = 12;
+// halved copies generated per wall texture, sampled for far surfaces to avoid shimmer
+const MIP_LEVELS: usize = 3;
+// Full-screen art shown for a few seconds at a time: decoded on first use (TextureAtlas::prepare)
+// and kept in a small LRU instead of for the whole session. Everything else is pinned.
+const FULLSCREEN: [TextureId; 3] = [TextureId::Menu, TextureId::GameOver, TextureId::Victoria];
+// textures that get mip levels (spritesheets are left alone: halving would blur their frames)
+const MIPMAPPED: [TextureId; 4] = [TextureId::Wall, TextureId::Pillar, TextureId::DoorClosed, TextureId::DoorOpen];
+
+// How a texture is sampled: bilinear smooths walls seen up close, nearest keeps pixel art crisp.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FilterMode {
+    Nearest,
+    Bilinear,
+}
+
+impl FilterMode {
+    fn from_code(code: &str) -> Option<Self> {
+        match code.to_ascii_lowercase().as_str() {
+            "nearest" => Some(FilterMode::Nearest),
+            "bilinear" => Some(FilterMode::Bilinear),
+            _ => None,
+        }
+    }
+}
+
+// Color change baked into a texture's pixels when it's loaded (level theming without new art):
+// each channel is multiplied by the tint and the brightness; alpha is kept.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Adjustment {
+    pub tint: [u8; 3],
+    pub brightness: f32,
+}
+
+impl Default for Adjustment {
+    fn default() -> Self {
+        Self { tint: [255, 255, 255], brightness: 1.0 }
+    }
+}
+
+impl Adjustment {
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn apply(&self, img: &ImageBuf) -> ImageBuf {
+        let scale = self.tint.map(|t| t as f32 / 255.0 * self.brightness);
+        let mut data = img.data.clone();
+        for px in data.chunks_exact_mut(4) {
+            for c in 0..3 {
+                px[c] = (px[c] as f32 * scale[c]).round().min(255.0) as u8;
             }
         }
+        ImageBuf { w: img.w, h: img.h, data }
+    }
+}
 
-        if wall.is_none() {
-            eprintln!("[textures] wall image not found in candidates");
-        }
-        if pillar.is_none() {
-            eprintln!("[textures] pillar image not found in candidates");
-        }
-
-        // try NPC sprite
-        let npc_candidates = [
-            "./textures/Letra _R_ Amenazante en Pixel Art.png",
-            "textures/Letra _R_ Amenazante en Pixel Art.png",
-            "../textures/Letra _R_ Amenazante en Pixel Art.png",
-        ];
-        let mut npc: Option<ImageBuf> = None;
-        for p in npc_candidates.iter() {
-            let path = Path::new(p);
-            if path.exists() {
-                eprintln!("[textures] found npc sprite at {}", path.display());
-                match image::open(path) {
-                    Ok(img) => {
-                        let img = img.to_rgba8();
-                        let (w, h) = img.dimensions();
-                        npc = Some(ImageBuf { w, h, data: img.into_raw() });
-                        break;
-                    }
-                    Err(e) => eprintln!("[textures] failed to load {}: {:?}", path.display(), e),
-                }
+// "#rrggbb" (the '#' is optional) as [r, g, b]
+pub fn parse_tint(text: &str) -> Option<[u8; 3]> {
+    let hex = text.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+fn parse_brightness(text: &str) -> Option<f32> {
+    text.trim().parse::<f32>().ok().filter(|b| (0.0..=4.0).contains(b))
+}
+
+// One texture to load: its logical name, candidate files (first existing one wins), the
+// number of horizontal frames for spritesheets (or square frames counted from the image
+// size), their playback rate, the sampling filter and the tint/brightness adjustment.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextureEntry {
+    pub name: String,
+    pub paths: Vec<String>,
+    pub frames: usize,
+    pub square_frames: bool,
+    // frames per second; None = one cycle per TAU of animation time (the coin's clock)
+    pub fps: Option<f32>,
+    pub filter: FilterMode,
+    pub adjust: Adjustment,
+}
+
+impl TextureEntry {
+    fn new(name: &str, files: &[&str], frames: usize, filter: FilterMode) -> Self {
+        let paths = files.iter().flat_map(|f| TEXTURE_DIRS.iter().map(move |d| format!("{}/{}", d, f))).collect();
+        Self { name: name.to_string(), paths, frames, square_frames: false, fps: None, filter, adjust: Adjustment::default() }
+    }
+}
+
+// The textures the game shipped with, used when there is no manifest (or it leaves a name out).
+fn default_entries() -> Vec<TextureEntry> {
+    use FilterMode::*;
+    vec![
+        TextureEntry::new("wall", &["Textura1_PARED.png"], 1, Bilinear),
+        TextureEntry::new("pillar", &["Textura2_Pilar.png"], 1, Bilinear),
+        TextureEntry::new("npc", &["Letra _R_ Amenazante en Pixel Art.png"], 1, Nearest),
+        TextureEntry::new("sky", &["Textura_Cielo.png"], 1, Bilinear),
+        TextureEntry::new("floor", &["Textura_Piso.png", "floor.jpg", "floor.png"], 1, Nearest),
+        TextureEntry::new("menu", &["menu.png", "menu_background.png"], 1, Nearest),
+        TextureEntry::new("game_over", &["game_over.png", "gameover.png"], 1, Nearest),
+        TextureEntry::new("victoria", &["victoria.png", "victory.png"], 1, Nearest),
+        TextureEntry::new("coin", &["coin_spin_64x64_12f.png"], COIN_FRAMES, Nearest),
+        TextureEntry::new("door_closed", &["puertacerrada.png"], 1, Bilinear),
+        TextureEntry::new("door_open", &["Puertaabierta.png"], 1, Bilinear),
+    ]
+}
+
+// Parse a texture manifest: one `[name]` section per texture with `path = "file.png"`
+// (relative to `base_dir`) and optional `frames = N` (or `square_frames = true`), `fps = F`,
+// `filter = "nearest" | "bilinear"`, `tint = "#rrggbb"` and `brightness = B` (0..=4).
+// Returns the entries plus a message for every invalid value; built-in names the manifest
+// doesn't mention keep their defaults.
+pub fn parse_manifest(text: &str, base_dir: &str) -> (Vec<TextureEntry>, Vec<String>) {
+    let values = crate::settings::parse_toml(text);
+    let mut names: Vec<&str> = values.keys().filter_map(|k| k.split_once('.').map(|(section, _)| section)).collect();
+    names.sort();
+    names.dedup();
+    let mut errors = Vec::new();
+    let mut entries = default_entries();
+    for name in names {
+        let default = entries.iter().find(|e| e.name == name).cloned();
+        let get = |key: &str| values.get(&format!("{}.{}", name, key));
+        let paths = match (get("path"), &default) {
+            (Some(path), _) => vec![format!("{}/{}", base_dir, path)],
+            (None, Some(d)) => d.paths.clone(),
+            (None, None) => {
+                errors.push(format!("{}: no path", name));
+                continue;
+            }
+        };
+        let mut frames = default.as_ref().map(|d| d.frames).unwrap_or(1);
+        if let Some(v) = get("frames") {
+            match v.parse::<usize>() {
+                Ok(n) if n >= 1 => frames = n,
+                _ => errors.push(format!("{}: invalid frames '{}'", name, v)),
+            }
+        }
+        let mut square_frames = default.as_ref().is_some_and(|d| d.square_frames);
+        if let Some(v) = get("square_frames") {
+            match v.parse::<bool>() {
+                Ok(b) => square_frames = b,
+                Err(_) => errors.push(format!("{}: invalid square_frames '{}', expected true/false", name, v)),
+            }
+        }
+        let mut fps = default.as_ref().and_then(|d| d.fps);
+        if let Some(v) = get("fps") {
+            match v.parse::<f32>() {
+                Ok(f) if f > 0.0 => fps = Some(f),
+                _ => errors.push(format!("{}: invalid fps '{}'", name, v)),
+            }
+        }
+        let mut filter = default.as_ref().map(|d| d.filter).unwrap_or(FilterMode::Nearest);
+        if let Some(v) = get("filter") {
+            match FilterMode::from_code(v) {
+                Some(f) => filter = f,
+                None => errors.push(format!("{}: unknown filter '{}', expected nearest/bilinear", name, v)),
+            }
+        }
+        let mut adjust = default.as_ref().map(|d| d.adjust).unwrap_or_default();
+        if let Some(v) = get("tint") {
+            match parse_tint(v) {
+                Some(t) => adjust.tint = t,
+                None => errors.push(format!("{}: invalid tint '{}', expected \"#rrggbb\"", name, v)),
+            }
+        }
+        if let Some(v) = get("brightness") {
+            match parse_brightness(v) {
+                Some(b) => adjust.brightness = b,
+                None => errors.push(format!("{}: invalid brightness '{}', expected 0-4", name, v)),
             }
         }
+        let entry = TextureEntry { name: name.to_string(), paths, frames, square_frames, fps, filter, adjust };
+        match entries.iter_mut().find(|e| e.name == name) {
+            Some(slot) => *slot = entry,
+            None => entries.push(entry),
+        }
+    }
+    (entries, errors)
+}
 
-        // try sky texture
-        let sky_candidates = [
-            "./textures/Textura_Cielo.png",
-            "textures/Textura_Cielo.png",
-            "../textures/Textura_Cielo.png",
-        ];
-        let mut sky: Option<ImageBuf> = None;
-        for p in sky_candidates.iter() {
-            let path = Path::new(p);
-            if path.exists() {
-                eprintln!("[textures] found sky image at {}", path.display());
-                match image::open(path) {
-                    Ok(img) => {
-                        let img = img.to_rgba8();
-                        let (w, h) = img.dimensions();
-                        sky = Some(ImageBuf { w, h, data: img.into_raw() });
-                        break;
-                    }
-                    Err(e) => eprintln!("[textures] failed to load {}: {:?}", path.display(), e),
+// The first manifest found: its path and text.
+fn read_manifest() -> Option<(&'static str, String)> {
+    MANIFEST_CANDIDATES.iter().find_map(|&path| std::fs::read_to_string(path).ok().map(|text| (path, text)))
+}
+
+fn entries_from(manifest: Option<&(&str, String)>) -> (Vec<TextureEntry>, Vec<String>) {
+    match manifest {
+        Some((path, text)) => {
+            let base_dir = Path::new(path).parent().and_then(|d| d.to_str()).unwrap_or(".");
+            parse_manifest(text, base_dir)
+        }
+        None => (default_entries(), Vec::new()),
+    }
+}
+
+// Entries from the first manifest found, or the built-in defaults when there is none.
+fn manifest_entries() -> (Vec<TextureEntry>, Vec<String>) {
+    let manifest = read_manifest();
+    match &manifest {
+        Some((path, _)) => eprintln!("[textures] using manifest {}", path),
+        None => eprintln!("[textures] no manifest found, using built-in texture list"),
+    }
+    entries_from(manifest.as_ref())
+}
+
+// Files the wall and floor textures would load from (for the startup check in diagnostics.rs),
+// from the manifest or the built-in list. A packed atlas provides them too, see packed_atlas_found.
+pub fn wall_and_floor_candidates() -> Vec<String> {
+    let (entries, _) = entries_from(read_manifest().as_ref());
+    entries.into_iter().filter(|e| e.name == "wall" || e.name == "floor").flat_map(|e| e.paths).collect()
+}
+
+pub fn packed_atlas_found() -> bool {
+    ATLAS_CANDIDATES.iter().any(|p| Path::new(p).is_file())
+}
+
+// Small copies of the default textures compiled into the binary (textures/embedded/), used
+// for any of these that can't be loaded from disk, e.g. when the binary runs outside the repo.
+const EMBEDDED: [(&str, &[u8]); 6] = [
+    ("wall", include_bytes!("../textures/embedded/wall.png")),
+    ("floor", include_bytes!("../textures/embedded/floor.png")),
+    ("sky", include_bytes!("../textures/embedded/sky.png")),
+    ("coin", include_bytes!("../textures/embedded/coin.png")),
+    ("door_closed", include_bytes!("../textures/embedded/door_closed.png")),
+    ("door_open", include_bytes!("../textures/embedded/door_open.png")),
+];
+
+// What a decoded texture must satisfy, checked by `validate` on every image from disk (separate
+// files or the packed atlas).
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Rule {
+    // at least one pixel; empty images are dropped
+    NonEmpty,
+    // neither side above the max texture size; bigger ones are downscaled (the wall sampler
+    // runs per pixel, so a huge wall texture costs every frame)
+    MaxSize,
+    // animated strips: the width is a whole number of frames
+    WholeFrames,
+}
+
+// Texture name ("*" = every texture) and the rule it's checked against.
+const RULES: [(&str, Rule); 7] = [
+    ("*", Rule::NonEmpty),
+    ("*", Rule::WholeFrames),
+    ("wall", Rule::MaxSize),
+    ("pillar", Rule::MaxSize),
+    ("door_closed", Rule::MaxSize),
+    ("door_open", Rule::MaxSize),
+    ("floor", Rule::MaxSize),
+];
+
+// Check `img` (texture `name`, laid out as `frames` frames) against RULES, fixing what can be
+// fixed; every finding is appended to `problems`. None if the image is unusable. `max_size`
+// 0 means no limit.
+fn validate(name: &str, img: ImageBuf, frames: usize, max_size: u32, problems: &mut Vec<String>) -> Option<ImageBuf> {
+    let mut img = img;
+    for (_, rule) in RULES.iter().filter(|(n, _)| *n == "*" || *n == name) {
+        match rule {
+            Rule::NonEmpty => {
+                if img.w == 0 || img.h == 0 {
+                    problems.push(format!("{}: image is empty ({}x{})", name, img.w, img.h));
+                    return None;
+                }
+            }
+            Rule::MaxSize => {
+                if max_size > 0 && img.w.max(img.h) > max_size {
+                    let small = img.downscaled(max_size);
+                    problems.push(format!("{}: {}x{} is larger than {}, downscaled to {}x{}", name, img.w, img.h, max_size, small.w, small.h));
+                    img = small;
+                }
+            }
+            Rule::WholeFrames => {
+                let frames = frames as u32;
+                if frames > 1 && !img.w.is_multiple_of(frames) {
+                    problems.push(format!("{}: width {} is not a multiple of {} frames, the last {} columns are never shown", name, img.w, frames, img.w % frames));
                 }
             }
         }
+    }
+    Some(img)
+}
 
-        // try floor texture
-        let floor_candidates = [
-            "./textures/Textura_Piso.png",
-            "textures/Textura_Piso.png",
-            "./textures/floor.jpg",
-            "textures/floor.jpg",
-            "./textures/floor.png",
-            "textures/floor.png",
-            "../textures/floor.jpg",
-        ];
-        let mut floor: Option<ImageBuf> = None;
-        for p in floor_candidates.iter() {
-            let path = Path::new(p);
-            if path.exists() {
-                eprintln!("[textures] found floor image at {}", path.display());
-                match image::open(path) {
-                    Ok(img) => {
-                        let img = img.to_rgba8();
-                        let (w, h) = img.dimensions();
-                        floor = Some(ImageBuf { w, h, data: img.into_raw() });
-                        break;
-                    }
-                    Err(e) => eprintln!("[textures] failed to load {}: {:?}", path.display(), e),
-                }
+fn to_image_buf(img: image::DynamicImage) -> ImageBuf {
+    let img = img.to_rgba8();
+    let (w, h) = img.dimensions();
+    ImageBuf { w, h, data: img.into_raw() }
+}
+
+fn load_image(path: &Path) -> Result<ImageBuf, String> {
+    image::open(path).map(to_image_buf).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// Decode the embedded copy of every texture not in `loaded`, with its name.
+fn embedded_missing(loaded: &HashSet<TextureId>) -> Vec<(&'static str, ImageBuf)> {
+    let mut images = Vec::new();
+    for (name, bytes) in EMBEDDED {
+        if loaded.contains(&TextureId::from_name(name)) {
+            continue;
+        }
+        match image::load_from_memory(bytes) {
+            Ok(img) => images.push((name, to_image_buf(img))),
+            Err(e) => eprintln!("[textures] embedded {} is corrupt: {}", name, e),
+        }
+    }
+    images
+}
+
+// Contents of a packed atlas descriptor: the image path and each texture's [x, y, w, h].
+#[derive(Debug, PartialEq)]
+pub struct AtlasDesc {
+    pub image: Option<String>,
+    pub regions: Vec<(String, [u32; 4])>,
+}
+
+// Parse an atlas descriptor: `image = "file.png"` (relative to `base_dir`) and one `[name]`
+// section per texture with its rectangle as `x`, `y`, `w`, `h` pixels. Returns the descriptor
+// plus a message for every section that isn't a valid rectangle.
+pub fn parse_atlas_desc(text: &str, base_dir: &str) -> (AtlasDesc, Vec<String>) {
+    let values = crate::settings::parse_toml(text);
+    let image = values.get("image").map(|file| format!("{}/{}", base_dir, file));
+    let mut names: Vec<&str> = values.keys().filter_map(|k| k.split_once('.').map(|(section, _)| section)).collect();
+    names.sort();
+    names.dedup();
+    let mut regions = Vec::new();
+    let mut errors = Vec::new();
+    for name in names {
+        let field = |key: &str| values.get(&format!("{}.{}", name, key)).and_then(|v| v.parse::<u32>().ok());
+        match (field("x"), field("y"), field("w"), field("h")) {
+            (Some(x), Some(y), Some(w), Some(h)) if w > 0 && h > 0 => regions.push((name.to_string(), [x, y, w, h])),
+            _ => errors.push(format!("atlas: {} needs x, y and positive w, h", name)),
+        }
+    }
+    (AtlasDesc { image, regions }, errors)
+}
+
+// Copy of the `w` x `h` rectangle of `img` at (x, y); None when it doesn't fit inside.
+fn slice(img: &ImageBuf, [x, y, w, h]: [u32; 4]) -> Option<ImageBuf> {
+    if x.checked_add(w)? > img.w || y.checked_add(h)? > img.h {
+        return None;
+    }
+    let mut data = Vec::with_capacity((w * h * 4) as usize);
+    for row in y..y + h {
+        let start = ((row * img.w + x) * 4) as usize;
+        data.extend_from_slice(&img.data[start..start + (w * 4) as usize]);
+    }
+    Some(ImageBuf { w, h, data })
+}
+
+// Textures sliced out of the packed atlas described by `desc_path`; problems with the
+// descriptor, the image or a rectangle are appended to `problems`.
+fn load_packed_from(desc_path: &Path, problems: &mut Vec<String>) -> HashMap<TextureId, ImageBuf> {
+    let mut images = HashMap::new();
+    let Ok(text) = std::fs::read_to_string(desc_path) else {
+        return images;
+    };
+    let base_dir = desc_path.parent().and_then(|d| d.to_str()).unwrap_or(".");
+    let (AtlasDesc { image, regions }, errors) = parse_atlas_desc(&text, base_dir);
+    problems.extend(errors);
+    let Some(image) = image else {
+        problems.push(format!("atlas: {} has no image", desc_path.display()));
+        return images;
+    };
+    let atlas = match load_image(Path::new(&image)) {
+        Ok(img) => img,
+        Err(e) => {
+            problems.push(format!("atlas: {}", e));
+            return images;
+        }
+    };
+    for (name, rect) in regions {
+        match slice(&atlas, rect) {
+            Some(img) => {
+                images.insert(TextureId::from_name(&name), img);
             }
+            None => problems.push(format!("atlas: {} rectangle {:?} is outside the {}x{} image", name, rect, atlas.w, atlas.h)),
         }
+    }
+    eprintln!("[textures] using packed atlas {} ({} textures)", desc_path.display(), images.len());
+    images
+}
 
-        // try menu background texture (user-provided)
-        let menu_candidates = [
-            "./textures/menu.png",
-            "textures/menu.png",
-            "./textures/menu_background.png",
-            "textures/menu_background.png",
-            "../textures/menu.png",
-        ];
-        let mut menu: Option<ImageBuf> = None;
-        for p in menu_candidates.iter() {
-            let path = Path::new(p);
-            if path.exists() {
-                eprintln!("[textures] found menu image at {}", path.display());
-                match image::open(path) {
-                    Ok(img) => {
-                        let img = img.to_rgba8();
-                        let (w, h) = img.dimensions();
-                        menu = Some(ImageBuf { w, h, data: img.into_raw() });
-                        break;
-                    }
-                    Err(e) => eprintln!("[textures] failed to load {}: {:?}", path.display(), e),
-                }
+// A decoded texture on its way from the loader, with where it came from (for the texture viewer).
+type Loaded = (TextureId, ImageBuf, String);
+
+// The packed atlas if there is one, then the individual files of every entry it lacks, each
+// image validated and handed to `sink` as soon as it's decoded. Returns the ids loaded.
+fn load_all(entries: &[TextureEntry], max_size: u32, problems: &mut Vec<String>, failures: &mut Vec<(String, String)>, sink: &mut dyn FnMut(Loaded)) -> HashSet<TextureId> {
+    let desc = ATLAS_CANDIDATES.iter().map(Path::new).find(|p| p.exists());
+    let packed = desc.map(|d| load_packed_from(d, problems)).unwrap_or_default();
+    let frames_of = |id: &TextureId| entries.iter().find(|e| e.name == id.name()).filter(|e| !e.square_frames).map_or(1, |e| e.frames);
+    let mut loaded = HashSet::new();
+    for (id, img) in packed {
+        if let Some(img) = validate(id.name(), img, frames_of(&id), max_size, problems) {
+            loaded.insert(id.clone());
+            let source = format!("{} (packed)", desc.map(|d| d.display().to_string()).unwrap_or_default());
+            sink((id, img, source));
+        }
+    }
+    for entry in entries {
+        if loaded.contains(&TextureId::from_name(&entry.name)) {
+            continue;
+        }
+        let source = entry.paths.iter().find(|p| Path::new(p).exists()).cloned().unwrap_or_default();
+        for (id, img) in load_entries(std::slice::from_ref(entry), failures) {
+            if let Some(img) = validate(id.name(), img, frames_of(&id), max_size, problems) {
+                loaded.insert(id.clone());
+                sink((id, img, source.clone()));
             }
         }
+    }
+    loaded
+}
 
-        // try game over texture
-        let game_candidates = [
-            "./textures/game_over.png",
-            "textures/game_over.png",
-            "./textures/gameover.png",
-            "textures/gameover.png",
-            "../textures/game_over.png",
-        ];
-        let mut game_over: Option<ImageBuf> = None;
-        for p in game_candidates.iter() {
-            let path = Path::new(p);
-            if path.exists() {
-                eprintln!("[textures] found game_over image at {}", path.display());
-                match image::open(path) {
-                    Ok(img) => {
-                        let img = img.to_rgba8();
-                        let (w, h) = img.dimensions();
-                        game_over = Some(ImageBuf { w, h, data: img.into_raw() });
-                        break;
-                    }
-                    Err(e) => eprintln!("[textures] failed to load {}: {:?}", path.display(), e),
-                }
+// Decode everything on a worker thread (image decoding needs no raylib), sending each image
+// through the returned channel as it completes; the channel closes once loading is over.
+fn spawn_loader(entries: Vec<TextureEntry>, mut problems: Vec<String>, use_embedded: bool, max_size: u32) -> Receiver<Loaded> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut failures = Vec::new();
+        let mut loaded = load_all(&entries, max_size, &mut problems, &mut failures, &mut |item| {
+            let _ = tx.send(item);
+        });
+        let from_disk = loaded.len();
+        if use_embedded {
+            let embedded = embedded_missing(&loaded);
+            let used: Vec<&str> = embedded.iter().map(|(name, _)| *name).collect();
+            failures.retain(|(name, _)| !used.contains(&name.as_str()));
+            if !used.is_empty() {
+                eprintln!("[textures] using built-in copies of: {}", used.join(", "));
+            }
+            for (name, img) in embedded {
+                loaded.insert(TextureId::from_name(name));
+                let _ = tx.send((TextureId::from_name(name), img, "built-in".to_string()));
             }
         }
+        problems.extend(failures.into_iter().map(|(name, reason)| format!("{}: {}", name, reason)));
+        if !problems.is_empty() {
+            eprintln!("[textures] {} problem(s), using fallback patterns where needed:\n  {}", problems.len(), problems.join("\n  "));
+        }
+        eprintln!("[textures] loaded {} of {} textures from disk", from_disk, entries.len());
+    });
+    rx
+}
 
-        // try victoria texture
-        let victoria_candidates = [
-            "./textures/victoria.png",
-            "textures/victoria.png",
-            "../textures/victoria.png",
-        ];
-        let mut victoria: Option<ImageBuf> = None;
-        for p in victoria_candidates.iter() {
-            let path = Path::new(p);
-            if path.exists() {
-                eprintln!("[textures] found victoria image at {}", path.display());
-                match image::open(path) {
-                    Ok(img) => {
-                        let img = img.to_rgba8();
-                        let (w, h) = img.dimensions();
-                        victoria = Some(ImageBuf { w, h, data: img.into_raw() });
-                        break;
-                    }
-                    Err(e) => eprintln!("[textures] failed to load {}: {:?}", path.display(), e),
-                }
+// Pack the images `inputs` (name, file) into one atlas image `out_png` and write its
+// descriptor to `out_desc` (the image path in it is relative to the descriptor). Returns
+// the names that couldn't be loaded and were left out.
+pub fn pack_atlas(inputs: &[(String, String)], out_png: &Path, out_desc: &Path) -> Result<Vec<String>, String> {
+    let mut skipped = Vec::new();
+    let mut images = Vec::new();
+    for (name, file) in inputs {
+        match load_image(Path::new(file)) {
+            Ok(img) => images.push((name, img)),
+            Err(e) => {
+                eprintln!("[textures] pack: skipping {}: {}", name, e);
+                skipped.push(name.clone());
             }
         }
+    }
+    if images.is_empty() {
+        return Err("nothing to pack".to_string());
+    }
+    let sizes: Vec<(u32, u32)> = images.iter().map(|(_, i)| (i.w, i.h)).collect();
+    let (w, h, positions) = pack_shelves(&sizes, ATLAS_MAX_WIDTH, 1);
+    let mut packed = image::RgbaImage::new(w, h);
+    let image_file = out_png.file_name().and_then(|f| f.to_str()).unwrap_or(ATLAS_IMAGE);
+    let mut desc = format!("# Packed texture atlas written by --pack-textures; delete it to load the individual files.\nimage = \"{}\"\n", image_file);
+    for ((name, img), &(x, y)) in images.iter().zip(positions.iter()) {
+        let part = image::RgbaImage::from_raw(img.w, img.h, img.data.clone()).ok_or("bad image buffer")?;
+        image::imageops::replace(&mut packed, &part, x as i64, y as i64);
+        desc.push_str(&format!("\n[{}]\nx = {}\ny = {}\nw = {}\nh = {}\n", name, x, y, img.w, img.h));
+    }
+    packed.save(out_png).map_err(|e| format!("{}: {}", out_png.display(), e))?;
+    std::fs::write(out_desc, desc).map_err(|e| format!("{}: {}", out_desc.display(), e))?;
+    Ok(skipped)
+}
 
-        // try coin spritesheet
-        let coin_candidates = [
-            "./textures/coin_spin_64x64_12f.png",
-            "textures/coin_spin_64x64_12f.png",
-            "../textures/coin_spin_64x64_12f.png",
-        ];
-        let mut coin: Option<ImageBuf> = None;
-        for p in coin_candidates.iter() {
-            let path = Path::new(p);
-            if path.exists() {
-                eprintln!("[textures] found coin spritesheet at {}", path.display());
-                match image::open(path) {
-                    Ok(img) => {
-                        let img = img.to_rgba8();
-                        let (w, h) = img.dimensions();
-                        coin = Some(ImageBuf { w, h, data: img.into_raw() });
-                        break;
-                    }
-                    Err(e) => eprintln!("[textures] failed to load {}: {:?}", path.display(), e),
-                }
+// --pack-textures: pack the first existing file of every manifest entry into atlas.png and
+// atlas.toml next to the manifest (or in textures/).
+pub fn pack_textures() -> Result<String, String> {
+    let (entries, _) = manifest_entries();
+    let inputs: Vec<(String, String)> = entries
+        .iter()
+        .filter_map(|e| e.paths.iter().find(|p| Path::new(p).exists()).map(|p| (e.name.clone(), p.clone())))
+        .collect();
+    let dir = TEXTURE_DIRS.iter().find(|d| Path::new(d).is_dir()).ok_or("no textures/ directory")?;
+    let out_desc = Path::new(dir).join("atlas.toml");
+    // a previous atlas would otherwise shadow the files being packed
+    let _ = std::fs::remove_file(&out_desc);
+    let skipped = pack_atlas(&inputs, &Path::new(dir).join(ATLAS_IMAGE), &out_desc)?;
+    Ok(format!("packed {} textures into {}", inputs.len() - skipped.len(), out_desc.display()))
+}
+
+// Split `entries` into the ones decoded up front and the FULLSCREEN ones, which only get their
+// file resolved (first existing candidate); those with no file are reported in `failures`.
+fn split_lazy(entries: &[TextureEntry], failures: &mut Vec<(String, String)>) -> (Vec<TextureEntry>, HashMap<TextureId, PathBuf>) {
+    let mut eager = Vec::new();
+    let mut lazy = HashMap::new();
+    for entry in entries {
+        let id = TextureId::from_name(&entry.name);
+        if !FULLSCREEN.contains(&id) {
+            eager.push(entry.clone());
+            continue;
+        }
+        match entry.paths.iter().map(PathBuf::from).find(|p| p.exists()) {
+            Some(path) => {
+                lazy.insert(id, path);
             }
+            None => failures.push((entry.name.clone(), format!("not found ({})", entry.paths.join(", ")))),
         }
+    }
+    (eager, lazy)
+}
 
-        // Load door textures
-        let door_closed_candidates = [
-            "./textures/puertacerrada.png",
-            "textures/puertacerrada.png",
-            "../textures/puertacerrada.png",
-        ];
-        let mut door_closed: Option<ImageBuf> = None;
-        for p in door_closed_candidates.iter() {
-            let path = Path::new(p);
-            if path.exists() {
-                eprintln!("[textures] found door closed texture at {}", path.display());
-                match image::open(path) {
-                    Ok(img) => {
-                        let img = img.to_rgba8();
-                        let (w, h) = img.dimensions();
-                        door_closed = Some(ImageBuf { w, h, data: img.into_raw() });
-                        break;
-                    }
-                    Err(e) => eprintln!("[textures] failed to load {}: {:?}", path.display(), e),
-                }
+// Decoded images kept while they fit in `budget` bytes, most recently used last; inserting
+// evicts from the front, though the newest image always stays even if it alone is over.
+#[derive(Default)]
+struct LruImages {
+    budget: usize,
+    images: Vec<(TextureId, ImageBuf)>,
+}
+
+impl LruImages {
+    fn new(budget: usize) -> Self {
+        Self { budget, images: Vec::new() }
+    }
+
+    fn get(&self, id: &TextureId) -> Option<&ImageBuf> {
+        self.images.iter().find(|(i, _)| i == id).map(|(_, img)| img)
+    }
+
+    // Mark `id` as just used; false if it isn't cached.
+    fn touch(&mut self, id: &TextureId) -> bool {
+        let Some(at) = self.images.iter().position(|(i, _)| i == id) else {
+            return false;
+        };
+        let item = self.images.remove(at);
+        self.images.push(item);
+        true
+    }
+
+    // Add (or replace) `id` as the most recent image; returns the ids evicted to fit.
+    fn insert(&mut self, id: TextureId, img: ImageBuf) -> Vec<TextureId> {
+        self.images.retain(|(i, _)| *i != id);
+        self.images.push((id, img));
+        let mut evicted = Vec::new();
+        while self.images.len() > 1 && self.bytes() > self.budget {
+            evicted.push(self.images.remove(0).0);
+        }
+        evicted
+    }
+
+    fn bytes(&self) -> usize {
+        self.images.iter().map(|(_, img)| img.data.len()).sum()
+    }
+
+    fn clear(&mut self) {
+        self.images.clear();
+    }
+}
+
+// Load every entry; failures (no candidate file exists, or it can't be decoded) are
+// appended to `failures` as (name, reason) instead of being logged one by one.
+fn load_entries(entries: &[TextureEntry], failures: &mut Vec<(String, String)>) -> HashMap<TextureId, ImageBuf> {
+    let mut images = HashMap::new();
+    for entry in entries {
+        let Some(path) = entry.paths.iter().map(Path::new).find(|p| p.exists()) else {
+            failures.push((entry.name.clone(), format!("not found ({})", entry.paths.join(", "))));
+            continue;
+        };
+        match load_image(path) {
+            Ok(img) => {
+                images.insert(TextureId::from_name(&entry.name), img);
             }
+            Err(e) => failures.push((entry.name.clone(), e)),
         }
+    }
+    images
+}
 
-        let door_open_candidates = [
-            "./textures/Puertaabierta.png",
-            "textures/Puertaabierta.png", 
-            "../textures/Puertaabierta.png",
-        ];
-        let mut door_open: Option<ImageBuf> = None;
-        for p in door_open_candidates.iter() {
-            let path = Path::new(p);
-            if path.exists() {
-                eprintln!("[textures] found door open texture at {}", path.display());
-                match image::open(path) {
-                    Ok(img) => {
-                        let img = img.to_rgba8();
-                        let (w, h) = img.dimensions();
-                        door_open = Some(ImageBuf { w, h, data: img.into_raw() });
-                        break;
-                    }
-                    Err(e) => eprintln!("[textures] failed to load {}: {:?}", path.display(), e),
+// One texture as listed by the texture viewer.
+pub struct TextureInfo {
+    pub id: TextureId,
+    pub source: String,
+    pub w: u32,
+    pub h: u32,
+    // bytes held for it: the image, its mips and its unadjusted copy
+    pub bytes: usize,
+    // frames of a sprite sheet, 1 for a still, 0 if not decoded yet (lazy full-screen art)
+    pub frames: u32,
+}
+
+#[derive(Default)]
+pub struct TextureAtlas {
+    textures: HashMap<TextureId, ImageBuf>,
+    // animated textures (frames > 1 or square frames), kept apart from the still ones
+    sheets: HashMap<TextureId, SpriteSheet>,
+    // frames and filter per texture; ids without an entry use TextureOptions::default_for
+    options: HashMap<TextureId, TextureOptions>,
+    // "pixel perfect" option: sample every texture with FilterMode::Nearest
+    pixel_perfect: bool,
+    // halved levels 1..=MIP_LEVELS of the MIPMAPPED textures (level 0 is the texture itself)
+    mips: HashMap<TextureId, Vec<ImageBuf>>,
+    // unadjusted pixels of the textures currently drawn tinted, the adjustment each one has,
+    // and the current level's overrides of the manifest adjustments
+    originals: HashMap<TextureId, ImageBuf>,
+    adjusted: HashMap<TextureId, Adjustment>,
+    level_adjust: HashMap<TextureId, Adjustment>,
+    // images still being decoded by the loader thread (None once it's done), how many have
+    // arrived and how many the manifest lists
+    loader: Option<Mutex<Receiver<Loaded>>>,
+    loaded: usize,
+    total: usize,
+    // Rule::MaxSize limit for loading and reloading (0 = none)
+    max_size: u32,
+    // files of the FULLSCREEN textures not decoded yet, and the ones decoded so far
+    lazy: HashMap<TextureId, PathBuf>,
+    fullscreen: LruImages,
+    // file (or "built-in") each texture was loaded from
+    sources: HashMap<TextureId, String>,
+    // wall kinds already reported missing by sample_wall (warned once, not per pixel), and the
+    // number of such warnings printed
+    warned: [AtomicBool; 4],
+    missing_warnings: AtomicUsize,
+}
+
+impl TextureAtlas {
+    // Start loading the textures listed in textures/manifest.toml (or the built-in list) on a
+    // background thread and return right away; the procedural fallbacks are drawn until
+    // poll_loaded swaps each image in. Every texture that couldn't be loaded is reported in a
+    // single message. Missing ones that have an embedded copy use it unless `use_embedded` is
+    // off (--no-embedded, to see the procedural fallbacks). Wall-like textures bigger than
+    // `max_size` are downscaled. The FULLSCREEN ones are decoded when first shown and share
+    // `cache_mb` megabytes (a packed atlas holds them anyway, so there they load with the rest).
+    pub fn load_in_background(use_embedded: bool, max_size: u32, cache_mb: u32) -> Self {
+        let (entries, mut problems) = manifest_entries();
+        let mut failures = Vec::new();
+        let (eager, lazy) = split_lazy(&entries, &mut failures);
+        problems.extend(failures.into_iter().map(|(name, reason)| format!("{}: {}", name, reason)));
+        let mut atlas = Self::from_images(&entries, HashMap::new());
+        atlas.total = eager.len();
+        atlas.max_size = max_size;
+        atlas.lazy = lazy;
+        atlas.fullscreen = LruImages::new(cache_mb as usize * 1024 * 1024);
+        atlas.loader = Some(Mutex::new(spawn_loader(eager, problems, use_embedded, max_size)));
+        atlas
+    }
+
+    // Take the images the loader thread has finished since the last call (once per frame).
+    // Returns how many arrived.
+    pub fn poll_loaded(&mut self) -> usize {
+        let Some(loader) = self.loader.as_mut() else {
+            return 0;
+        };
+        let rx = loader.get_mut().unwrap_or_else(|e| e.into_inner());
+        let mut arrived = Vec::new();
+        let finished = loop {
+            match rx.try_recv() {
+                Ok(item) => arrived.push(item),
+                Err(TryRecvError::Empty) => break false,
+                Err(TryRecvError::Disconnected) => break true,
+            }
+        };
+        let count = arrived.len();
+        for (id, img, source) in arrived {
+            self.sources.insert(id.clone(), source);
+            self.insert(id.clone(), img);
+            self.readjust(&id);
+        }
+        self.loaded += count;
+        if finished {
+            self.loader = None;
+            self.report_missing();
+        }
+        count
+    }
+
+    // textures received so far / listed in the manifest, for the loading progress
+    pub fn loaded_count(&self) -> usize {
+        self.loaded
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loader.is_some()
+    }
+
+    fn from_images(entries: &[TextureEntry], images: HashMap<TextureId, ImageBuf>) -> Self {
+        let options = entries.iter().map(|e| (TextureId::from_name(&e.name), TextureOptions::from(e))).collect();
+        let mut atlas = Self { options, ..Default::default() };
+        for (id, img) in images {
+            atlas.insert(id.clone(), img);
+            atlas.readjust(&id);
+        }
+        atlas
+    }
+
+    // Re-run the loading (manifest or default file names) in place, e.g. while editing wall
+    // art. Textures that fail to load keep their previous image. Returns how many changed.
+    pub fn reload(&mut self) -> usize {
+        let (entries, mut problems) = manifest_entries();
+        let mut failures = Vec::new();
+        let mut images = HashMap::new();
+        // full-screen art is decoded again the next time it's shown
+        let (eager, lazy) = split_lazy(&entries, &mut failures);
+        self.lazy = lazy;
+        self.fullscreen.clear();
+        let mut sources = HashMap::new();
+        load_all(&eager, self.max_size, &mut problems, &mut failures, &mut |(id, img, source)| {
+            sources.insert(id.clone(), source);
+            images.insert(id, img);
+        });
+        self.sources.extend(sources);
+        problems.extend(failures.into_iter().map(|(name, reason)| format!("{}: {}", name, reason)));
+        if !problems.is_empty() {
+            eprintln!("[textures] reload: {} problem(s), keeping the previous images:\n  {}", problems.len(), problems.join("\n  "));
+        }
+        let changed = self.merge(&entries, images);
+        eprintln!("[textures] reloaded, {} changed", changed);
+        changed
+    }
+
+    // Take the options of `entries` and every image in `images` that differs from the current
+    // one (regenerating its mips); returns the number of images replaced.
+    fn merge(&mut self, entries: &[TextureEntry], images: HashMap<TextureId, ImageBuf>) -> usize {
+        let mut changed = std::collections::HashSet::new();
+        for e in entries {
+            let id = TextureId::from_name(&e.name);
+            let opts = TextureOptions::from(e);
+            if self.options.insert(id.clone(), opts) != Some(opts) && !images.contains_key(&id) {
+                // same image with a new frame layout: store it again under the new options
+                if let Some(img) = self.take(&id) {
+                    self.insert(id.clone(), img);
+                    changed.insert(id);
                 }
             }
         }
+        for (id, img) in images {
+            if self.originals.get(&id).or(self.get(&id)) != Some(&img) {
+                self.insert(id.clone(), img);
+                changed.insert(id);
+            }
+        }
+        let ids: Vec<TextureId> = self.options.keys().cloned().collect();
+        for id in ids {
+            if self.readjust(&id) {
+                changed.insert(id);
+            }
+        }
+        changed.len()
+    }
 
-    TextureAtlas { wall, pillar, npc, sky, floor, menu, game_over, victoria, coin, door_closed, door_open }
+    // Apply the texture overrides of a level's metadata (`tint.<texture> = #rrggbb`,
+    // `brightness.<texture> = B`) on top of the manifest's adjustments, and drop the previous
+    // level's. Returns how many textures were re-tinted.
+    pub fn apply_level(&mut self, meta: &HashMap<String, String>) -> usize {
+        self.level_adjust.clear();
+        for (key, value) in meta {
+            let (field, name) = match key.split_once('.') {
+                Some((field @ ("tint" | "brightness"), name)) => (field, name),
+                _ => continue,
+            };
+            let id = TextureId::from_name(name);
+            let base = self.options(&id).adjust;
+            let adjust = self.level_adjust.entry(id).or_insert(base);
+            match (field, parse_tint(value), parse_brightness(value)) {
+                ("tint", Some(t), _) => adjust.tint = t,
+                ("brightness", _, Some(b)) => adjust.brightness = b,
+                _ => eprintln!("[warn] maze metadata: invalid {} '{}'", key, value),
+            }
+        }
+        let ids: std::collections::HashSet<TextureId> = self.level_adjust.keys().chain(self.adjusted.keys()).cloned().collect();
+        ids.iter().filter(|id| self.readjust(id)).count()
     }
 
-    // Sample color from the chosen texture image by normalized u,v in [0,1]
-    // If the image isn't loaded, return a procedural fallback color pattern.
-    pub fn sample(&self, kind: TextureKind, u: f32, v: f32) -> Color {
-        // keep fractional repeat behavior, but sample with bilinear filtering
-        let u = u.fract().abs();
-        let v = v.fract().abs();
+    // Re-tint `id` from its unadjusted pixels when the adjustment it should have (the level's,
+    // else the manifest's) isn't the one applied; true if the image changed.
+    fn readjust(&mut self, id: &TextureId) -> bool {
+        let want = self.level_adjust.get(id).copied().unwrap_or(self.options(id).adjust);
+        if self.adjusted.get(id).copied().unwrap_or_default() == want {
+            return false;
+        }
+        let Some(raw) = self.take(id) else {
+            return false;
+        };
+        if want.is_identity() {
+            self.insert(id.clone(), raw);
+        } else {
+            self.insert(id.clone(), want.apply(&raw));
+            self.originals.insert(id.clone(), raw);
+            self.adjusted.insert(id.clone(), want);
+        }
+        true
+    }
+
+    fn rebuild_mips(&mut self, id: &TextureId) {
+        if !MIPMAPPED.contains(id) {
+            return;
+        }
+        match self.get(id) {
+            Some(img) => {
+                let mips = build_mips(img, MIP_LEVELS);
+                self.mips.insert(id.clone(), mips);
+            }
+            None => {
+                self.mips.remove(id);
+            }
+        }
+    }
 
-        let img_opt = match kind {
-            TextureKind::Wall => &self.wall,
-            TextureKind::Pillar => &self.pillar,
-            TextureKind::DoorClosed => &self.door_closed,
-            TextureKind::DoorOpen => &self.door_open,
+    // Make sure the FULLSCREEN texture `id` is decoded (call before drawing it each frame; it
+    // also marks it as recently used). Other textures are always ready once loaded. False if
+    // there is no image to show.
+    pub fn prepare(&mut self, id: &TextureId) -> bool {
+        if self.fullscreen.touch(id) || self.get(id).is_some() {
+            return true;
+        }
+        let Some(path) = self.lazy.get(id) else {
+            return false;
         };
+        let mut problems = Vec::new();
+        let img = load_image(path).map_err(|e| problems.push(e)).ok().and_then(|img| validate(id.name(), img, 1, self.max_size, &mut problems));
+        if !problems.is_empty() {
+            eprintln!("[textures] {}", problems.join("; "));
+        }
+        let Some(img) = img else {
+            // don't retry every frame
+            self.lazy.remove(id);
+            return false;
+        };
+        let adjust = self.options(id).adjust;
+        let img = if adjust.is_identity() { img } else { adjust.apply(&img) };
+        // evicted images keep their file in `lazy` and are decoded again when next shown
+        self.fullscreen.insert(id.clone(), img);
+        true
+    }
 
-        if img_opt.is_none() {
-            eprintln!("[textures::sample] warning: requested texture {:?} not loaded", kind);
-        }
-
-        if let Some(img) = img_opt {
-            if img.data.len() >= 4 {
-                // bilinear filtering: compute floating sample coordinates in [0, w-1], [0, h-1]
-                let fw = (img.w - 1) as f32;
-                let fh = (img.h - 1) as f32;
-                let xf = (u * fw).clamp(0.0, fw);
-                let yf = (v * fh).clamp(0.0, fh);
-                let x0 = xf.floor() as u32;
-                let y0 = yf.floor() as u32;
-                let x1 = (x0 + 1).min(img.w - 1);
-                let y1 = (y0 + 1).min(img.h - 1);
-                let sx = xf - x0 as f32;
-                let sy = yf - y0 as f32;
-
-                let sample_pixel = |xx: u32, yy: u32| -> (f32,f32,f32,f32) {
-                    let idx = ((yy * img.w + xx) * 4) as usize;
-                    if idx + 3 < img.data.len() {
-                        let r = img.data[idx] as f32 / 255.0;
-                        let g = img.data[idx + 1] as f32 / 255.0;
-                        let b = img.data[idx + 2] as f32 / 255.0;
-                        let a = img.data[idx + 3] as f32 / 255.0;
-                        let a = if a == 0.0 { 1.0 } else { a };
-                        return (r, g, b, a);
-                    }
-                    (0.0, 0.0, 0.0, 1.0)
+    pub fn get(&self, id: &TextureId) -> Option<&ImageBuf> {
+        self.textures
+            .get(id)
+            .or_else(|| self.sheets.get(id).map(|s| &s.image))
+            .or_else(|| self.fullscreen.get(id))
+            .filter(|img| img.w > 0 && img.h > 0 && img.data.len() >= (img.w * img.h * 4) as usize)
+    }
+
+    // Every texture the atlas knows about, decoded or not, sorted by name.
+    pub fn inventory(&self) -> Vec<TextureInfo> {
+        let ids: HashSet<&TextureId> = self.textures.keys().chain(self.sheets.keys()).chain(self.lazy.keys()).chain(self.fullscreen.images.iter().map(|(id, _)| id)).collect();
+        let mut list: Vec<TextureInfo> = ids
+            .into_iter()
+            .map(|id| {
+                let img = self.get(id);
+                let size = |img: &ImageBuf| img.data.len();
+                let mips: usize = self.mips.get(id).map_or(0, |m| m.iter().map(size).sum());
+                let bytes = img.map_or(0, size) + mips + self.originals.get(id).map_or(0, size);
+                let frames = match (self.sheet(id), img) {
+                    (Some(sheet), _) => sheet.frames,
+                    (None, Some(_)) => 1,
+                    (None, None) => 0,
                 };
+                let source = self.sources.get(id).cloned().or_else(|| self.lazy.get(id).map(|p| p.display().to_string())).unwrap_or_default();
+                TextureInfo { id: id.clone(), source, w: img.map_or(0, |i| i.w), h: img.map_or(0, |i| i.h), bytes, frames }
+            })
+            .collect();
+        list.sort_by(|a, b| a.id.name().cmp(b.id.name()));
+        list
+    }
 
-                let (r00,g00,b00,a00) = sample_pixel(x0,y0);
-                let (r10,g10,b10,a10) = sample_pixel(x1,y0);
-                let (r01,g01,b01,a01) = sample_pixel(x0,y1);
-                let (r11,g11,b11,a11) = sample_pixel(x1,y1);
-
-                // lerp horizontally then vertically
-                let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
-
-                let r0 = lerp(r00, r10, sx);
-                let g0 = lerp(g00, g10, sx);
-                let b0 = lerp(b00, b10, sx);
-                let a0 = lerp(a00, a10, sx);
-
-                let r1 = lerp(r01, r11, sx);
-                let g1 = lerp(g01, g11, sx);
-                let b1 = lerp(b01, b11, sx);
-                let a1 = lerp(a01, a11, sx);
-
-                let r = lerp(r0, r1, sy);
-                let g = lerp(g0, g1, sy);
-                let b = lerp(b0, b1, sy);
-                let a = lerp(a0, a1, sy);
-
-                let out_r = (r*255.0) as u8;
-                let out_g = (g*255.0) as u8;
-                let out_b = (b*255.0) as u8;
-                let out_a = (a*255.0) as u8;
-                // If the sampled color is pure black, treat it as missing and fall back
-                if out_r == 0 && out_g == 0 && out_b == 0 {
-                    // fall through to procedural fallback below
-                } else {
-                    return Color::new(out_r, out_g, out_b, out_a);
+    // The animation stored for `id`, if it has more than one frame.
+    pub fn sheet(&self, id: &TextureId) -> Option<&SpriteSheet> {
+        self.sheets.get(id).filter(|_| self.get(id).is_some())
+    }
+
+    // Store `img` for `id` as is: as a SpriteSheet when its options declare frames, else as a still.
+    pub fn insert(&mut self, id: TextureId, img: ImageBuf) {
+        self.take(&id);
+        let opts = self.options(&id);
+        if opts.animated() {
+            let fps = opts.fps.unwrap_or(anim::default_fps(opts.frames));
+            let sheet = if opts.square_frames { SpriteSheet::with_square_frames(img, fps) } else { SpriteSheet::new(img, opts.frames as u32, fps) };
+            self.sheets.insert(id.clone(), sheet);
+        } else {
+            self.textures.insert(id.clone(), img);
+        }
+        self.rebuild_mips(&id);
+    }
+
+    // Remove the image of `id`, returning its unadjusted pixels.
+    fn take(&mut self, id: &TextureId) -> Option<ImageBuf> {
+        let still = self.textures.remove(id);
+        let sheet = self.sheets.remove(id).map(|s| s.image);
+        self.adjusted.remove(id);
+        self.originals.remove(id).or(still).or(sheet)
+    }
+
+    fn options(&self, id: &TextureId) -> TextureOptions {
+        let mut opts = self.options.get(id).copied().unwrap_or_else(|| TextureOptions::default_for(id));
+        if self.pixel_perfect {
+            opts.filter = FilterMode::Nearest;
+        }
+        opts
+    }
+
+    // One line listing the built-in textures that aren't loaded (and so use a fallback);
+    // printed at level start instead of warning from the samplers.
+    pub fn report_missing(&self) {
+        if self.is_loading() {
+            return;
+        }
+        let names = ["wall", "pillar", "door_closed", "door_open", "npc", "sky", "floor", "coin"];
+        let missing: Vec<&str> = names.into_iter().filter(|n| self.get(&TextureId::from_name(n)).is_none()).collect();
+        if !missing.is_empty() {
+            eprintln!("[textures] not loaded, using fallbacks: {}", missing.join(", "));
+        }
+    }
+
+    pub fn set_pixel_perfect(&mut self, on: bool) {
+        self.pixel_perfect = on;
+    }
+
+    // Upload every loaded sprite image (npc, coin) into one raylib texture, for a GPU sprite
+    // pass; the game itself still rasterizes sprites on the CPU and doesn't call it.
+    // Returns None when there are no sprites loaded or the upload fails.
+    pub fn upload_to_gpu(&self, window: &mut RaylibHandle, thread: &RaylibThread) -> Option<GpuSpriteAtlas> {
+        let sprites: Vec<(&'static str, &ImageBuf)> = [("npc", TextureId::Npc), ("coin", TextureId::Coin)]
+            .into_iter()
+            .filter_map(|(name, id)| self.get(&id).map(|i| (name, i)))
+            .collect();
+        if sprites.is_empty() {
+            return None;
+        }
+
+        let sizes: Vec<(u32, u32)> = sprites.iter().map(|(_, i)| (i.w, i.h)).collect();
+        let (atlas_w, atlas_h, positions) = pack_row(&sizes, 1);
+        let mut image = Image::gen_image_color(atlas_w as i32, atlas_h as i32, Color::BLANK);
+        let mut regions = HashMap::new();
+        for ((name, img), (ox, oy)) in sprites.iter().zip(positions.iter()) {
+            for y in 0..img.h {
+                for x in 0..img.w {
+                    let idx = ((y * img.w + x) * 4) as usize;
+                    let d = &img.data[idx..idx + 4];
+                    image.draw_pixel((ox + x) as i32, (oy + y) as i32, Color::new(d[0], d[1], d[2], d[3]));
+                }
+            }
+            regions.insert(*name, Rectangle::new(*ox as f32, *oy as f32, img.w as f32, img.h as f32));
+        }
+
+        match window.load_texture_from_image(thread, &image) {
+            Ok(texture) => {
+                eprintln!("[textures] uploaded sprite atlas {}x{} ({} sprites)", atlas_w, atlas_h, regions.len());
+                Some(GpuSpriteAtlas { texture, regions })
+            }
+            Err(e) => {
+                eprintln!("[textures] failed to upload sprite atlas: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Color of texture `id` at normalized (u, v) (repeating outside 0..1), with the texture's
+    // filter; None when it isn't loaded. Spritesheets are sampled across all their frames.
+    pub fn sample(&self, id: &TextureId, u: f32, v: f32) -> Option<Color> {
+        let img = self.get(id)?;
+        Some(sample_rgba(img, u, v, self.options(id).filter))
+    }
+
+    // Like sample, from the mip level closest to `lod` (0 = full size, each level halves it;
+    // clamped to the levels available).
+    pub fn sample_lod(&self, id: &TextureId, u: f32, v: f32, lod: f32) -> Option<Color> {
+        let level = lod.max(0.0).round() as usize;
+        match self.mips.get(id).and_then(|mips| mips.get(level.min(mips.len()).checked_sub(1)?)) {
+            Some(mip) => Some(sample_rgba(mip, u, v, self.options(id).filter)),
+            None => self.sample(id, u, v),
+        }
+    }
+
+    // Like sample, but within frame `frame` (wrapping) of a spritesheet; stills have one frame.
+    pub fn sample_frame(&self, id: &TextureId, u: f32, v: f32, frame: usize) -> Option<Color> {
+        let filter = self.options(id).filter;
+        match self.sheet(id) {
+            Some(sheet) => Some(sheet.sample_frame(frame as u32, u, v, filter)),
+            None => self.sample(id, u, v),
+        }
+    }
+
+    // Like sample_frame, with the frame shown at `time` by the sheet's fps (NPC walk cycles,
+    // animated walls, ...).
+    pub fn sample_anim(&self, id: &TextureId, u: f32, v: f32, time: f32) -> Option<Color> {
+        let frame = self.sheet(id).map_or(0, |s| s.frame_at(time));
+        self.sample_frame(id, u, v, frame as usize)
+    }
+
+    // Wall, pillar and door faces: a procedural checkerboard stands in for a missing texture,
+    // and transparency is ignored (black texels are drawn as black). `lod` picks the mip level.
+    pub fn sample_wall(&self, kind: TextureKind, u: f32, v: f32, lod: f32) -> Color {
+        let id = TextureId::from(kind);
+        match self.sample_lod(&id, u, v, lod) {
+            Some(c) => return Color::new(c.r, c.g, c.b, 255),
+            None => {
+                if !self.is_loading() && !self.warned[kind as usize].swap(true, Ordering::Relaxed) {
+                    self.missing_warnings.fetch_add(1, Ordering::Relaxed);
+                    eprintln!("[textures::sample] warning: requested texture {:?} not loaded", kind);
                 }
             }
         }
 
         // Procedural fallback: a simple checkerboard
+        let (u, v) = (u.fract().abs(), v.fract().abs());
         let checks = 8.0;
         let uu = (u * checks) as i32;
         let vv = (v * checks) as i32;
@@ -411,193 +1279,665 @@ impl TextureAtlas {
     }
 
     pub fn sample_npc(&self, u: f32, v: f32) -> Option<Color> {
-        let u = u.fract().abs();
-        let v = v.fract().abs();
-        if let Some(img) = &self.npc {
-            if img.data.len() >= 4 {
-                let x = ((u * img.w as f32).clamp(0.0, (img.w - 1) as f32)) as u32;
-                let y = ((v * img.h as f32).clamp(0.0, (img.h - 1) as f32)) as u32;
-                let idx = ((y * img.w + x) * 4) as usize;
-                if idx + 3 < img.data.len() {
-                    let r = img.data[idx];
-                    let g = img.data[idx + 1];
-                    let b = img.data[idx + 2];
-                    let a = img.data[idx + 3];
-                    return Some(Color::new(r as u8, g as u8, b as u8, a as u8));
-                }
-            }
-        }
-        None
+        self.sample(&TextureId::Npc, u, v)
     }
 
-    // Sample the sky texture by normalized u (horiz) and v (vert). If missing, return a gradient.
+    // Sample the sky as a 360° panorama: u wraps around the full image width (blending the last
+    // and first columns at the seam), v runs from the top row (0) down to the horizon row (1),
+    // clamped. If missing, return a gradient.
     pub fn sample_sky(&self, u: f32, v: f32) -> Color {
-        let u = u.fract().abs();
-        let v = v.fract().abs();
-        if let Some(img) = &self.sky {
-            if img.data.len() >= 4 {
-                let x = ((u * img.w as f32).clamp(0.0, (img.w - 1) as f32)) as u32;
-                let y = ((v * img.h as f32).clamp(0.0, (img.h - 1) as f32)) as u32;
-                let idx = ((y * img.w + x) * 4) as usize;
-                if idx + 3 < img.data.len() {
-                    let r = img.data[idx];
-                    let g = img.data[idx + 1];
-                    let b = img.data[idx + 2];
-                    let a = img.data[idx + 3];
-                    return Color::new(r as u8, g as u8, b as u8, a as u8);
-                }
-            }
+        let v = v.clamp(0.0, 0.9999);
+        match self.get(&TextureId::Sky) {
+            Some(img) => sample_panorama(img, u, v, self.options(&TextureId::Sky).filter),
+            None => vertical_gradient(Color::new(80, 160, 240, 255), Color::new(160, 200, 240, 255), v),
         }
-        // fallback: vertical gradient sky
-        let top = Color::new(80, 160, 240, 255);
-        let bottom = Color::new(160, 200, 240, 255);
-        let mix = v;
-        let r = (top.r as f32 * (1.0 - mix) + bottom.r as f32 * mix) as u8;
-        let g = (top.g as f32 * (1.0 - mix) + bottom.g as f32 * mix) as u8;
-        let b = (top.b as f32 * (1.0 - mix) + bottom.b as f32 * mix) as u8;
-        Color::new(r, g, b, 255)
-    }
-
-    pub fn sample_floor(&self, u: f32, v: f32) -> Color {
-        let u = u.fract().abs();
-        let v = v.fract().abs();
-        if let Some(img) = &self.floor {
-            if img.data.len() >= 4 {
-                let x = ((u * img.w as f32).clamp(0.0, (img.w - 1) as f32)) as u32;
-                let y = ((v * img.h as f32).clamp(0.0, (img.h - 1) as f32)) as u32;
-                let idx = ((y * img.w + x) * 4) as usize;
-                if idx + 3 < img.data.len() {
-                    let r = img.data[idx];
-                    let g = img.data[idx + 1];
-                    let b = img.data[idx + 2];
-                    let a = img.data[idx + 3];
-                    return Color::new(r as u8, g as u8, b as u8, a as u8);
-                }
-            }
+    }
+
+    // Sample a full-screen image (menu, game over, victory) stretched so (0,0)-(1,1) covers the
+    // screen; each has its own gradient while the image is missing.
+    pub fn sample_fullscreen(&self, id: &TextureId, u: f32, v: f32) -> Color {
+        self.sample(id, u, v).unwrap_or_else(|| fullscreen_fallback(id, u, v))
+    }
+
+    // Sample the coin spritesheet `time` seconds into the coin's spin
+    // (the coin's frame count comes from the manifest; 12 frames of 64x64 by default)
+    pub fn sample_coin(&self, u: f32, v: f32, time: f32) -> Option<Color> {
+        self.sample_anim(&TextureId::Coin, u, v, time)
+    }
+}
+
+// Up to `levels` successively halved copies of `img` (box filter over 2x2 texels), stopping
+// once both sides are down to one pixel.
+fn build_mips(img: &ImageBuf, levels: usize) -> Vec<ImageBuf> {
+    let mut mips: Vec<ImageBuf> = Vec::with_capacity(levels);
+    for _ in 0..levels {
+        let src = mips.last().unwrap_or(img);
+        if src.w == 1 && src.h == 1 {
+            break;
         }
-        // fallback tiled checker
-        let checks = 6.0;
-        let uu = (u * checks) as i32;
-        let vv = (v * checks) as i32;
-        if (uu + vv) % 2 == 0 {
-            Color::new(120, 90, 60, 255)
-        } else {
-            Color::new(100, 80, 60, 255)
-        }
-    }
-
-    // Sample the menu background texture if available, else return a dark gradient
-    pub fn sample_menu(&self, u: f32, v: f32) -> Color {
-        let u = u.fract().abs();
-        let v = v.fract().abs();
-        if let Some(img) = &self.menu {
-            if img.data.len() >= 4 {
-                let x = ((u * img.w as f32).clamp(0.0, (img.w - 1) as f32)) as u32;
-                let y = ((v * img.h as f32).clamp(0.0, (img.h - 1) as f32)) as u32;
-                let idx = ((y * img.w + x) * 4) as usize;
-                if idx + 3 < img.data.len() {
-                    let r = img.data[idx];
-                    let g = img.data[idx + 1];
-                    let b = img.data[idx + 2];
-                    let a = img.data[idx + 3];
-                    return Color::new(r as u8, g as u8, b as u8, a as u8);
-                }
+        mips.push(halve(src));
+    }
+    mips
+}
+
+fn halve(src: &ImageBuf) -> ImageBuf {
+    let (w, h) = ((src.w / 2).max(1), (src.h / 2).max(1));
+    let mut data = Vec::with_capacity((w * h * 4) as usize);
+    for y in 0..h {
+        for x in 0..w {
+            let (x0, y0) = (x * 2, y * 2);
+            let (x1, y1) = ((x0 + 1).min(src.w - 1), (y0 + 1).min(src.h - 1));
+            for c in 0..4 {
+                let at = |px: u32, py: u32| src.data[((py * src.w + px) * 4 + c) as usize] as u32;
+                data.push(((at(x0, y0) + at(x1, y0) + at(x0, y1) + at(x1, y1)) / 4) as u8);
             }
         }
-        // fallback: dark vignette-like gradient
-        let top = Color::new(30, 20, 10, 255);
-        let bottom = Color::new(10, 10, 10, 255);
-        let mix = v;
-        let r = (top.r as f32 * (1.0 - mix) + bottom.r as f32 * mix) as u8;
-        let g = (top.g as f32 * (1.0 - mix) + bottom.g as f32 * mix) as u8;
-        let b = (top.b as f32 * (1.0 - mix) + bottom.b as f32 * mix) as u8;
-        Color::new(r, g, b, 255)
-    }
-
-    pub fn sample_gameover(&self, u: f32, v: f32) -> Color {
-        let u = u.fract().abs();
-        let v = v.fract().abs();
-        if let Some(img) = &self.game_over {
-            if img.data.len() >= 4 {
-                let x = ((u * img.w as f32).clamp(0.0, (img.w - 1) as f32)) as u32;
-                let y = ((v * img.h as f32).clamp(0.0, (img.h - 1) as f32)) as u32;
-                let idx = ((y * img.w + x) * 4) as usize;
-                if idx + 3 < img.data.len() {
-                    let r = img.data[idx];
-                    let g = img.data[idx + 1];
-                    let b = img.data[idx + 2];
-                    let a = img.data[idx + 3];
-                    return Color::new(r as u8, g as u8, b as u8, a as u8);
-                }
-            }
+    }
+    ImageBuf { w, h, data }
+}
+
+// Color of `img` at normalized (u, v), repeating outside 0..1.
+pub fn sample_rgba(img: &ImageBuf, u: f32, v: f32, filter: FilterMode) -> Color {
+    match filter {
+        FilterMode::Nearest => sample_nearest(img, u, v),
+        FilterMode::Bilinear => sample_bilinear(img, u, v),
+    }
+}
+
+// The texel covering (u, v): u in [0, 1] spans the w texels left to right (u = 1.0 is the last
+// one, not the first of the next repeat). Transparent for an empty or truncated image.
+pub fn sample_nearest(img: &ImageBuf, u: f32, v: f32) -> Color {
+    nearest_in(img, 0, img.w, u, v)
+}
+
+// Bilinear blend of the 4 texels around (u, v), with u = 0 / 1 on the centers of the first /
+// last column (so the edges never blend with the opposite side). Transparent for an empty image.
+pub fn sample_bilinear(img: &ImageBuf, u: f32, v: f32) -> Color {
+    bilinear_in(img, 0, img.w, u, v)
+}
+
+// Like sample_rgba, but horizontally the image is a closed loop: bilinear filtering blends the
+// last column into the first instead of clamping at the edge.
+fn sample_panorama(img: &ImageBuf, u: f32, v: f32, filter: FilterMode) -> Color {
+    if filter == FilterMode::Nearest {
+        return sample_rgba(img, u, v, filter);
+    }
+    // texel x is centered at u = (x + 0.5) / w
+    let xf = u.rem_euclid(1.0) * img.w as f32 - 0.5;
+    let t = xf - xf.floor();
+    let x0 = (xf.floor() as i64).rem_euclid(img.w as i64) as u32;
+    let x1 = (x0 + 1) % img.w;
+    // each column filtered vertically on its own
+    let a = sample_region(img, x0, 1, 0.0, v, filter);
+    let b = sample_region(img, x1, 1, 0.0, v, filter);
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    Color::new(lerp(a.r, b.r), lerp(a.g, b.g), lerp(a.b, b.b), lerp(a.a, b.a))
+}
+
+// Sample the `w` pixel wide column range of `img` starting at `x0`, at normalized (u, v).
+fn sample_region(img: &ImageBuf, x0: u32, w: u32, u: f32, v: f32, filter: FilterMode) -> Color {
+    match filter {
+        FilterMode::Nearest => nearest_in(img, x0, w, u, v),
+        FilterMode::Bilinear => bilinear_in(img, x0, w, u, v),
+    }
+}
+
+// Texture coordinate folded into 0..=1 for repeating: whole numbers other than 0 land on 1.0
+// (the far edge) instead of wrapping back to 0, negative coordinates repeat like positive ones.
+fn wrap_unit(t: f32) -> f32 {
+    let r = t.rem_euclid(1.0);
+    if r == 0.0 && t > 0.0 { 1.0 } else { r }
+}
+
+// Whether the `w` columns from `x0` hold pixels to sample.
+fn region_ok(img: &ImageBuf, x0: u32, w: u32) -> bool {
+    w > 0 && img.h > 0 && x0 + w <= img.w && img.data.len() >= (img.w * img.h * 4) as usize
+}
+
+fn texel(img: &ImageBuf, x: u32, y: u32) -> Color {
+    let idx = ((y * img.w + x) * 4) as usize;
+    let d = &img.data[idx..idx + 4];
+    Color::new(d[0], d[1], d[2], d[3])
+}
+
+fn nearest_in(img: &ImageBuf, x0: u32, w: u32, u: f32, v: f32) -> Color {
+    if !region_ok(img, x0, w) {
+        return Color::BLANK;
+    }
+    let x = ((wrap_unit(u) * w as f32) as u32).min(w - 1);
+    let y = ((wrap_unit(v) * img.h as f32) as u32).min(img.h - 1);
+    texel(img, x0 + x, y)
+}
+
+fn bilinear_in(img: &ImageBuf, x0: u32, w: u32, u: f32, v: f32) -> Color {
+    if !region_ok(img, x0, w) {
+        return Color::BLANK;
+    }
+    // floating sample coordinates in [0, w-1], [0, h-1], lerp horizontally then vertically
+    let fw = (w - 1) as f32;
+    let fh = (img.h - 1) as f32;
+    let xf = wrap_unit(u) * fw;
+    let yf = wrap_unit(v) * fh;
+    let (xa, ya) = (xf.floor() as u32, yf.floor() as u32);
+    let (xb, yb) = ((xa + 1).min(w - 1), (ya + 1).min(img.h - 1));
+    let (sx, sy) = (xf - xa as f32, yf - ya as f32);
+    let lerp = |a: u8, b: u8, t: f32| a as f32 + (b as f32 - a as f32) * t;
+    let mix = |a: Color, b: Color, t: f32| (lerp(a.r, b.r, t), lerp(a.g, b.g, t), lerp(a.b, b.b, t), lerp(a.a, b.a, t));
+    let top = mix(texel(img, x0 + xa, ya), texel(img, x0 + xb, ya), sx);
+    let bottom = mix(texel(img, x0 + xa, yb), texel(img, x0 + xb, yb), sx);
+    let ch = |a: f32, b: f32| (a + (b - a) * sy) as u8;
+    Color::new(ch(top.0, bottom.0), ch(top.1, bottom.1), ch(top.2, bottom.2), ch(top.3, bottom.3))
+}
+
+// Stand-in for a missing full-screen image: dark brown for the menu, a red vignette for game
+// over, and gold rays over deep green for victory.
+fn fullscreen_fallback(id: &TextureId, u: f32, v: f32) -> Color {
+    match id {
+        TextureId::GameOver => vertical_gradient(Color::new(80, 10, 10, 255), Color::new(20, 10, 10, 255), v),
+        TextureId::Victoria => {
+            let base = vertical_gradient(Color::new(190, 150, 40, 255), Color::new(10, 60, 30, 255), v);
+            // soft rays fanning out from the top centre
+            let angle = (u - 0.5).atan2(v.max(0.0) + 0.05);
+            let ray = (angle * 12.0).cos().max(0.0).powi(4) * 60.0 * (1.0 - v.clamp(0.0, 1.0));
+            let add = |c: u8| (c as f32 + ray).min(255.0) as u8;
+            Color::new(add(base.r), add(base.g), add(base.b), 255)
         }
-        // fallback: red vignette
-        let top = Color::new(80, 10, 10, 255);
-        let bottom = Color::new(20, 10, 10, 255);
-        let mix = v;
-        let r = (top.r as f32 * (1.0 - mix) + bottom.r as f32 * mix) as u8;
-        let g = (top.g as f32 * (1.0 - mix) + bottom.g as f32 * mix) as u8;
-        let b = (top.b as f32 * (1.0 - mix) + bottom.b as f32 * mix) as u8;
-        Color::new(r, g, b, 255)
-    }
-
-    pub fn sample_victoria(&self, u: f32, v: f32) -> Color {
-        let u = u.fract().abs();
-        let v = v.fract().abs();
-        if let Some(img) = &self.victoria {
-            if img.data.len() >= 4 {
-                let x = ((u * img.w as f32).clamp(0.0, (img.w - 1) as f32)) as u32;
-                let y = ((v * img.h as f32).clamp(0.0, (img.h - 1) as f32)) as u32;
-                let idx = ((y * img.w + x) * 4) as usize;
-                if idx + 3 < img.data.len() {
-                    let r = img.data[idx];
-                    let g = img.data[idx + 1];
-                    let b = img.data[idx + 2];
-                    let a = img.data[idx + 3];
-                    return Color::new(r as u8, g as u8, b as u8, a as u8);
-                }
-            }
+        _ => vertical_gradient(Color::new(30, 20, 10, 255), Color::new(10, 10, 10, 255), v),
+    }
+}
+
+// `top` at v = 0 blending to `bottom` at v = 1
+fn vertical_gradient(top: Color, bottom: Color, v: f32) -> Color {
+    let mix = v.fract().abs();
+    let r = (top.r as f32 * (1.0 - mix) + bottom.r as f32 * mix) as u8;
+    let g = (top.g as f32 * (1.0 - mix) + bottom.g as f32 * mix) as u8;
+    let b = (top.b as f32 * (1.0 - mix) + bottom.b as f32 * mix) as u8;
+    Color::new(r, g, b, 255)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_row_places_sprites_side_by_side_with_padding() {
+        let (w, h, pos) = pack_row(&[(64, 64), (768, 64), (10, 80)], 1);
+        assert_eq!(pos, vec![(0, 0), (65, 0), (834, 0)]);
+        assert_eq!((w, h), (844, 80));
+    }
+
+    #[test]
+    fn pack_shelves_wraps_at_max_width() {
+        let (w, h, pos) = pack_shelves(&[(40, 10), (40, 20), (40, 5), (100, 8)], 100, 1);
+        assert_eq!(pos, vec![(0, 0), (41, 0), (0, 21), (0, 27)]);
+        assert_eq!((w, h), (100, 35));
+    }
+
+    #[test]
+    fn packed_atlas_round_trips_through_the_descriptor() {
+        let dir = std::env::temp_dir().join(format!("atlas_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let quad_path = dir.join("quad.png");
+        let strip_path = dir.join("strip.png");
+        image::RgbaImage::from_raw(2, 2, quad().data).unwrap().save(&quad_path).unwrap();
+        image::RgbaImage::from_raw(4, 1, strip().data).unwrap().save(&strip_path).unwrap();
+        let path = |p: &Path| p.to_str().unwrap().to_string();
+        let inputs = vec![("wall".to_string(), path(&quad_path)), ("torch".to_string(), path(&strip_path)), ("ghost".to_string(), path(&dir.join("none.png")))];
+        let desc = dir.join("atlas.toml");
+        assert_eq!(pack_atlas(&inputs, &dir.join("atlas.png"), &desc), Ok(vec!["ghost".to_string()]));
+
+        let mut problems = Vec::new();
+        let images = load_packed_from(&desc, &mut problems);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert!(images[&TextureId::Wall] == quad());
+        assert!(images[&TextureId::Extra("torch".to_string())] == strip());
+    }
+
+    #[test]
+    fn atlas_descriptor_rejects_bad_rectangles() {
+        let (AtlasDesc { image, regions }, errors) = parse_atlas_desc("image = \"a.png\"\n[wall]\nx = 0\ny = 0\nw = 4\nh = 2\n[sky]\nx = 1\nw = 0\n", "tex");
+        assert_eq!(image.as_deref(), Some("tex/a.png"));
+        assert_eq!(regions, vec![("wall".to_string(), [0, 0, 4, 2])]);
+        assert_eq!(errors.len(), 1);
+        let img = ImageBuf { w: 4, h: 2, data: vec![0; 32] };
+        assert!(slice(&img, [0, 0, 4, 2]).is_some());
+        assert!(slice(&img, [1, 0, 4, 2]).is_none());
+    }
+
+    #[test]
+    fn adjustment_scales_color_and_keeps_alpha() {
+        let img = ImageBuf { w: 2, h: 1, data: vec![200, 100, 50, 128, 255, 255, 255, 0] };
+        let blue = Adjustment { tint: [128, 128, 255], brightness: 0.8 };
+        // 200 * 128/255 * 0.8 = 80.3, 255 * 0.8 = 204; alpha untouched
+        assert_eq!(blue.apply(&img).data, vec![80, 40, 40, 128, 102, 102, 204, 0]);
+        // brightening saturates instead of wrapping
+        assert_eq!(Adjustment { brightness: 2.0, ..Default::default() }.apply(&img).data, vec![255, 200, 100, 128, 255, 255, 255, 0]);
+        assert_eq!(parse_tint("#3050ff"), Some([0x30, 0x50, 0xff]));
+        assert_eq!(parse_tint("3050FF"), Some([0x30, 0x50, 0xff]));
+        assert_eq!(parse_tint("#3050f"), None);
+        assert_eq!(parse_tint("#zz50ff"), None);
+    }
+
+    #[test]
+    fn level_overrides_retint_from_the_original_pixels() {
+        let (entries, errors) = parse_manifest("[wall]\ntint = \"#ff0000\"\n[sky]\nbrightness = 9\n", ".");
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        let grey = || ImageBuf { w: 1, h: 1, data: vec![100, 100, 100, 255] };
+        let mut atlas = TextureAtlas::from_images(&entries, HashMap::from([(TextureId::Wall, grey()), (TextureId::Sky, grey())]));
+        let wall = |a: &TextureAtlas| a.get(&TextureId::Wall).unwrap().data.clone();
+        assert_eq!(wall(&atlas), vec![100, 0, 0, 255]);
+        // the level keeps the manifest tint and halves the brightness; repeated loads don't compound
+        let level = HashMap::from([("brightness.wall".to_string(), "0.5".to_string())]);
+        assert_eq!(atlas.apply_level(&level), 1);
+        assert_eq!(atlas.apply_level(&level), 0);
+        assert_eq!(wall(&atlas), vec![50, 0, 0, 255]);
+        // a level without overrides goes back to the manifest's look
+        assert_eq!(atlas.apply_level(&HashMap::new()), 1);
+        assert_eq!(wall(&atlas), vec![100, 0, 0, 255]);
+        // reloading the same file isn't a change
+        assert_eq!(atlas.merge(&entries, HashMap::from([(TextureId::Wall, grey())])), 0);
+    }
+
+    #[test]
+    fn manifest_overrides_defaults_and_adds_extras() {
+        let text = "[coin]\npath = \"coins.png\"\nframes = 8\n[wall]\nfilter = \"nearest\"\n[torch]\npath = \"torch.png\"\nframes = 4\n";
+        let (entries, errors) = parse_manifest(text, "tex");
+        assert!(errors.is_empty(), "{:?}", errors);
+        let find = |name: &str| entries.iter().find(|e| e.name == name).unwrap();
+        assert_eq!(find("coin").paths, vec!["tex/coins.png".to_string()]);
+        assert_eq!(find("coin").frames, 8);
+        // only the filter changed: the default file candidates stay
+        assert_eq!(find("wall").filter, FilterMode::Nearest);
+        assert_eq!(find("wall").paths, TextureEntry::new("wall", &["Textura1_PARED.png"], 1, FilterMode::Bilinear).paths);
+        assert_eq!((find("torch").paths[0].as_str(), find("torch").frames), ("tex/torch.png", 4));
+        // names the manifest doesn't mention keep their defaults
+        assert_eq!(find("sky").paths, TextureEntry::new("sky", &["Textura_Cielo.png"], 1, FilterMode::Bilinear).paths);
+    }
+
+    #[test]
+    fn invalid_manifest_values_are_collected() {
+        let (entries, errors) = parse_manifest("[coin]\nframes = 0\nfilter = \"fuzzy\"\n[ghost]\nframes = 2\n", ".");
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert_eq!(entries.iter().find(|e| e.name == "coin").unwrap().frames, COIN_FRAMES);
+        assert!(entries.iter().all(|e| e.name != "ghost"));
+    }
+
+    #[test]
+    fn missing_files_fall_back_to_empty_slots() {
+        let entries = vec![TextureEntry { name: "wall".to_string(), paths: vec!["no/such/file.png".to_string()], frames: 1, square_frames: false, fps: None, filter: FilterMode::Nearest, adjust: Adjustment::default() }];
+        let mut failures = Vec::new();
+        let images = load_entries(&entries, &mut failures);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, "wall");
+        let atlas = TextureAtlas::from_images(&entries, images);
+        assert!(atlas.get(&TextureId::Wall).is_none());
+        // unloaded textures sample the procedural checkerboard instead of panicking
+        assert_eq!(atlas.sample(&TextureId::Wall, 0.01, 0.01), None);
+        assert_eq!(atlas.sample_wall(TextureKind::Wall, 0.01, 0.01, 0.0), Color::new(200, 180, 160, 255));
+    }
+
+    #[test]
+    fn embedded_copies_fill_only_missing_textures() {
+        let embedded = embedded_missing(&HashSet::from([TextureId::Wall]));
+        let used: Vec<&str> = embedded.iter().map(|(name, _)| *name).collect();
+        assert_eq!(used, vec!["floor", "sky", "coin", "door_closed", "door_open"]);
+        // the embedded ones decode like any other image
+        let coin = &embedded[2].1;
+        assert_eq!(coin.data.len(), (coin.w * coin.h * 4) as usize);
+        assert_eq!(coin.w % COIN_FRAMES as u32, 0);
+    }
+
+    #[test]
+    fn empty_images_are_rejected() {
+        let mut problems = Vec::new();
+        let empty = ImageBuf { w: 0, h: 16, data: Vec::new() };
+        assert!(validate("wall", empty, 1, 1024, &mut problems).is_none());
+        assert_eq!(problems, vec!["wall: image is empty (0x16)".to_string()]);
+    }
+
+    #[test]
+    fn oversized_wall_textures_are_downscaled_with_a_box_filter() {
+        let mut problems = Vec::new();
+        // 4x2: left half black, right half white
+        let px = |v: u8| [v, v, v, 255];
+        let data = [px(0), px(0), px(255), px(255), px(0), px(0), px(255), px(255)].concat();
+        let big = ImageBuf { w: 4, h: 2, data };
+        let img = validate("wall", big, 1, 2, &mut problems).unwrap();
+        assert_eq!((img.w, img.h), (2, 1));
+        assert_eq!(img.data, [px(0), px(255)].concat());
+        assert_eq!(problems.len(), 1);
+        // the limit only applies to the textures the table lists
+        let menu = ImageBuf { w: 4, h: 2, data: vec![0; 32] };
+        let img = validate("menu", menu, 1, 2, &mut problems).unwrap();
+        assert_eq!((img.w, img.h), (4, 2));
+        assert_eq!(problems.len(), 1);
+        // averaging rounds, and odd sizes still cover every source pixel
+        let odd = ImageBuf { w: 3, h: 1, data: [px(0), px(255), px(255)].concat() };
+        let small = odd.downscaled(1);
+        assert_eq!((small.w, small.h), (1, 1));
+        assert_eq!(small.data[0], 170);
+    }
+
+    #[test]
+    fn strips_that_are_not_whole_frames_are_reported() {
+        let mut problems = Vec::new();
+        let coin = ImageBuf { w: 13, h: 1, data: vec![255; 13 * 4] };
+        let img = validate("coin", coin, 12, 1024, &mut problems).unwrap();
+        assert_eq!(img.w, 13);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("not a multiple of 12"), "{}", problems[0]);
+        let ok = ImageBuf { w: 24, h: 1, data: vec![255; 24 * 4] };
+        validate("coin", ok, 12, 1024, &mut problems).unwrap();
+        assert_eq!(problems.len(), 1);
+    }
+
+    fn blank(w: u32, h: u32) -> ImageBuf {
+        ImageBuf { w, h, data: vec![0; (w * h * 4) as usize] }
+    }
+
+    #[test]
+    fn lru_evicts_the_least_recently_used_image() {
+        // room for two 4x4 images
+        let mut lru = LruImages::new(2 * 64);
+        assert!(lru.insert(TextureId::Menu, blank(4, 4)).is_empty());
+        assert!(lru.insert(TextureId::GameOver, blank(4, 4)).is_empty());
+        assert!(lru.touch(&TextureId::Menu));
+        assert_eq!(lru.insert(TextureId::Victoria, blank(4, 4)), vec![TextureId::GameOver]);
+        assert!(lru.get(&TextureId::Menu).is_some() && lru.get(&TextureId::GameOver).is_none());
+        assert!(!lru.touch(&TextureId::GameOver));
+        // one image over the whole budget pushes everything else out but stays itself
+        assert_eq!(lru.insert(TextureId::GameOver, blank(8, 8)), vec![TextureId::Menu, TextureId::Victoria]);
+        assert_eq!(lru.get(&TextureId::GameOver).map(|img| img.w), Some(8));
+    }
+
+    #[test]
+    fn fullscreen_textures_decode_on_first_use_and_walls_stay_pinned() {
+        let dir = std::env::temp_dir().join(format!("lazy_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let menu_path = dir.join("menu.png");
+        let over_path = dir.join("over.png");
+        image::RgbaImage::from_raw(2, 2, quad().data).unwrap().save(&menu_path).unwrap();
+        image::RgbaImage::from_raw(4, 1, strip().data).unwrap().save(&over_path).unwrap();
+        let mut atlas = TextureAtlas { fullscreen: LruImages::new(16), ..Default::default() };
+        atlas.insert(TextureId::Wall, quad());
+        atlas.lazy.insert(TextureId::Menu, menu_path);
+        atlas.lazy.insert(TextureId::GameOver, over_path);
+        atlas.lazy.insert(TextureId::Victoria, dir.join("none.png"));
+
+        assert!(atlas.get(&TextureId::Menu).is_none());
+        assert!(atlas.prepare(&TextureId::Menu));
+        assert!(atlas.get(&TextureId::Menu) == Some(&quad()));
+        // the budget holds one of them: showing game over drops the menu, never the wall
+        assert!(atlas.prepare(&TextureId::GameOver));
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(atlas.get(&TextureId::Menu).is_none());
+        assert!(atlas.get(&TextureId::GameOver) == Some(&strip()));
+        assert!(atlas.get(&TextureId::Wall) == Some(&quad()));
+        // a file that can't be decoded falls back to the gradient and isn't retried
+        assert!(!atlas.prepare(&TextureId::Victoria));
+        assert!(!atlas.lazy.contains_key(&TextureId::Victoria));
+        assert!(atlas.prepare(&TextureId::Wall));
+    }
+
+    #[test]
+    fn inventory_lists_every_texture_with_its_size_and_source() {
+        let mut atlas = TextureAtlas::default();
+        atlas.insert(TextureId::Wall, quad());
+        atlas.insert(TextureId::Coin, ImageBuf { w: 24, h: 2, data: vec![0; 24 * 2 * 4] });
+        atlas.sources.insert(TextureId::Wall, "textures/wall.png".to_string());
+        atlas.lazy.insert(TextureId::Menu, PathBuf::from("textures/menu.png"));
+        let list = atlas.inventory();
+        let names: Vec<&str> = list.iter().map(|t| t.id.name()).collect();
+        assert_eq!(names, vec!["coin", "menu", "wall"]);
+        assert_eq!((list[0].w, list[0].frames), (24, COIN_FRAMES as u32));
+        assert_eq!((list[1].frames, list[1].bytes, list[1].source.as_str()), (0, 0, "textures/menu.png"));
+        // the wall's 2x2 image plus its 1x1 mip
+        assert_eq!((list[2].bytes, list[2].source.as_str()), (16 + 4, "textures/wall.png"));
+    }
+
+    // 2x2 image: red, green / blue, white
+    fn quad() -> ImageBuf {
+        let data = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 255]].concat();
+        ImageBuf { w: 2, h: 2, data }
+    }
+
+    #[test]
+    fn nearest_filter_picks_the_covering_texel() {
+        let img = quad();
+        assert_eq!(sample_rgba(&img, 0.25, 0.25, FilterMode::Nearest), Color::new(255, 0, 0, 255));
+        assert_eq!(sample_rgba(&img, 0.75, 0.25, FilterMode::Nearest), Color::new(0, 255, 0, 255));
+        assert_eq!(sample_rgba(&img, 0.25, 0.75, FilterMode::Nearest), Color::new(0, 0, 255, 255));
+        assert_eq!(sample_rgba(&img, 0.99, 0.99, FilterMode::Nearest), Color::new(255, 255, 255, 255));
+    }
+
+    #[test]
+    fn bilinear_filter_blends_neighbouring_texels() {
+        let img = quad();
+        // corners hit the texels exactly
+        assert_eq!(sample_rgba(&img, 0.0, 0.0, FilterMode::Bilinear), Color::new(255, 0, 0, 255));
+        assert_eq!(sample_rgba(&img, 0.999, 0.999, FilterMode::Bilinear), Color::new(254, 254, 254, 255));
+        // halfway along the top row, and the even mix of all four in the middle
+        assert_eq!(sample_rgba(&img, 0.5, 0.0, FilterMode::Bilinear), Color::new(127, 127, 0, 255));
+        assert_eq!(sample_rgba(&img, 0.5, 0.5, FilterMode::Bilinear), Color::new(127, 127, 127, 255));
+    }
+
+    #[test]
+    fn mips_halve_with_a_box_filter() {
+        // 4x4 black/white checker averages to flat grey
+        let data: Vec<u8> = (0..16).flat_map(|i| if (i % 4 + i / 4) % 2 == 0 { [0, 0, 0, 255] } else { [255, 255, 255, 255] }).collect();
+        let mips = build_mips(&ImageBuf { w: 4, h: 4, data }, MIP_LEVELS);
+        // 2x2, 1x1, then stops
+        assert_eq!(mips.iter().map(|m| (m.w, m.h)).collect::<Vec<_>>(), vec![(2, 2), (1, 1)]);
+        assert!(mips[0].data.chunks(4).all(|p| p == [127, 127, 127, 255]));
+        // odd and thin sizes clamp at the edge instead of reading out of bounds
+        let strip = ImageBuf { w: 3, h: 1, data: [[10, 0, 0, 255], [30, 0, 0, 255], [90, 0, 0, 0]].concat() };
+        let mip = &build_mips(&strip, 1)[0];
+        assert_eq!((mip.w, mip.h), (1, 1));
+        assert_eq!(mip.data, vec![20, 0, 0, 255]);
+    }
+
+    #[test]
+    fn sample_lod_picks_the_closest_level() {
+        let mut atlas = TextureAtlas::default();
+        let data: Vec<u8> = (0..16).flat_map(|i| if (i % 4 + i / 4) % 2 == 0 { [0, 0, 0, 255] } else { [255, 255, 255, 255] }).collect();
+        atlas.set_pixel_perfect(true);
+        atlas.insert(TextureId::Wall, ImageBuf { w: 4, h: 4, data });
+        assert_eq!(atlas.sample_lod(&TextureId::Wall, 0.1, 0.1, 0.0), Some(Color::new(0, 0, 0, 255)));
+        assert_eq!(atlas.sample_lod(&TextureId::Wall, 0.1, 0.1, 0.4), Some(Color::new(0, 0, 0, 255)));
+        assert_eq!(atlas.sample_lod(&TextureId::Wall, 0.1, 0.1, 1.0), Some(Color::new(127, 127, 127, 255)));
+        // past the last level stays on the smallest one
+        assert_eq!(atlas.sample_lod(&TextureId::Wall, 0.1, 0.1, 9.0), Some(Color::new(127, 127, 127, 255)));
+        // textures without mips ignore the hint
+        atlas.insert(TextureId::Npc, ImageBuf { w: 1, h: 1, data: vec![1, 2, 3, 4] });
+        assert_eq!(atlas.sample_lod(&TextureId::Npc, 0.5, 0.5, 2.0), Some(Color::new(1, 2, 3, 4)));
+    }
+
+    #[test]
+    fn reload_replaces_changed_images_and_keeps_missing_ones() {
+        let mut atlas = TextureAtlas::default();
+        let px = |r: u8| ImageBuf { w: 2, h: 2, data: [r, 0, 0, 255].repeat(4) };
+        atlas.insert(TextureId::Wall, px(10));
+        atlas.insert(TextureId::Sky, px(20));
+        let entries = vec![TextureEntry::new("wall", &["w.png"], 1, FilterMode::Nearest)];
+        // sky failed to load this time: it keeps the old image
+        let changed = atlas.merge(&entries, HashMap::from([(TextureId::Wall, px(99))]));
+        assert_eq!(changed, 1);
+        assert_eq!(atlas.sample(&TextureId::Sky, 0.5, 0.5), Some(Color::new(20, 0, 0, 255)));
+        // the mips follow the new image
+        assert_eq!(atlas.sample_lod(&TextureId::Wall, 0.5, 0.5, 1.0), Some(Color::new(99, 0, 0, 255)));
+        assert_eq!(atlas.merge(&entries, HashMap::from([(TextureId::Wall, px(99))])), 0);
+    }
+
+    #[test]
+    fn background_loading_swaps_images_in_as_they_arrive() {
+        let (tx, rx) = mpsc::channel();
+        let mut atlas = TextureAtlas { total: 2, loader: Some(Mutex::new(rx)), ..Default::default() };
+        assert!(atlas.is_loading());
+        // fallbacks until the image arrives, without warning about it
+        assert_eq!(atlas.sample_wall(TextureKind::Wall, 0.01, 0.01, 0.0), Color::new(200, 180, 160, 255));
+        assert_eq!(atlas.missing_warnings.load(Ordering::Relaxed), 0);
+        tx.send((TextureId::Wall, quad(), "wall.png".to_string())).unwrap();
+        assert_eq!(atlas.poll_loaded(), 1);
+        assert_eq!(atlas.sample_wall(TextureKind::Wall, 0.0, 0.0, 0.0), Color::new(255, 0, 0, 255));
+        assert_eq!((atlas.loaded_count(), atlas.total()), (1, 2));
+        assert_eq!(atlas.poll_loaded(), 0);
+        // the loader finishing closes the channel
+        drop(tx);
+        atlas.poll_loaded();
+        assert!(!atlas.is_loading());
+    }
+
+    #[test]
+    fn missing_wall_texture_is_warned_once() {
+        let atlas = TextureAtlas::default();
+        for i in 0..1000 {
+            atlas.sample_wall(TextureKind::Pillar, i as f32 / 1000.0, 0.5, 0.0);
         }
-        // fallback: green vignette for victory
-        let top = Color::new(10, 80, 10, 255);
-        let bottom = Color::new(10, 40, 10, 255);
-        let mix = v;
-        let r = (top.r as f32 * (1.0 - mix) + bottom.r as f32 * mix) as u8;
-        let g = (top.g as f32 * (1.0 - mix) + bottom.g as f32 * mix) as u8;
-        let b = (top.b as f32 * (1.0 - mix) + bottom.b as f32 * mix) as u8;
-        Color::new(r, g, b, 255)
-    }
-
-    // Sample coin spritesheet with animation using anim module
-    // The spritesheet has 12 frames arranged horizontally (64x64 each)
-    pub fn sample_coin(&self, u: f32, v: f32, animation_time: f32) -> Option<Color> {
-        let u = u.fract().abs();
-        let v = v.fract().abs();
-        
-        if let Some(img) = &self.coin {
-            if img.data.len() >= 4 {
-                // Get frame info from animation module
-                let num_frames = 12;
-                let frame_width = img.w / num_frames as u32;
-                let frame_height = img.h;
-                
-                // Get the x offset for the current frame using anim module
-                let frame_x_offset = CoinAnimation::get_frame_offset(animation_time, frame_width);
-                
-                // Sample within the current frame
-                let x = ((u * frame_width as f32).clamp(0.0, (frame_width - 1) as f32)) as u32 + frame_x_offset;
-                let y = ((v * frame_height as f32).clamp(0.0, (frame_height - 1) as f32)) as u32;
-                
-                let idx = ((y * img.w + x) * 4) as usize;
-                if idx + 3 < img.data.len() {
-                    let r = img.data[idx];
-                    let g = img.data[idx + 1];
-                    let b = img.data[idx + 2];
-                    let a = img.data[idx + 3];
-                    return Some(Color::new(r as u8, g as u8, b as u8, a as u8));
-                }
-            }
+        assert_eq!(atlas.missing_warnings.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn sky_wraps_across_the_seam() {
+        let mut atlas = TextureAtlas::default();
+        atlas.insert(TextureId::Sky, ImageBuf { w: 2, h: 1, data: [[200, 0, 0, 255], [0, 0, 100, 255]].concat() });
+        // texel centers sit at u = 0.25 and 0.75
+        assert_eq!(atlas.sample_sky(0.25, 0.5), Color::new(200, 0, 0, 255));
+        assert_eq!(atlas.sample_sky(0.75, 0.5), Color::new(0, 0, 100, 255));
+        // u = 0 (and 1) is halfway between the last column and the first
+        assert_eq!(atlas.sample_sky(0.0, 0.5), Color::new(100, 0, 50, 255));
+        assert_eq!(atlas.sample_sky(1.0, 0.5), atlas.sample_sky(0.0, 0.5));
+        assert_eq!(atlas.sample_sky(-0.25, 0.5), atlas.sample_sky(0.75, 0.5));
+    }
+
+    #[test]
+    fn black_wall_texels_are_not_treated_as_missing() {
+        let mut atlas = TextureAtlas::default();
+        let data = [[0, 0, 0, 255], [200, 10, 10, 255]].concat();
+        atlas.insert(TextureId::DoorClosed, ImageBuf { w: 2, h: 1, data });
+        assert_eq!(atlas.sample_wall(TextureKind::DoorClosed, 0.0, 0.5, 0.0), Color::new(0, 0, 0, 255));
+        // still opaque even where the image is transparent black
+        atlas.insert(TextureId::Wall, ImageBuf { w: 1, h: 1, data: vec![0, 0, 0, 0] });
+        assert_eq!(atlas.sample_wall(TextureKind::Wall, 0.5, 0.5, 0.0), Color::new(0, 0, 0, 255));
+    }
+
+    #[test]
+    fn samplers_handle_edges_and_degenerate_images() {
+        let img = quad();
+        let (red, green, blue, white) = (Color::new(255, 0, 0, 255), Color::new(0, 255, 0, 255), Color::new(0, 0, 255, 255), Color::new(255, 255, 255, 255));
+        // corners, including u/v exactly 1.0 (the far edge, not the next repeat)
+        for (u, v, want) in [(0.0, 0.0, red), (1.0, 0.0, green), (0.0, 1.0, blue), (1.0, 1.0, white)] {
+            assert_eq!(sample_nearest(&img, u, v), want, "nearest at ({}, {})", u, v);
+            assert_eq!(sample_bilinear(&img, u, v), want, "bilinear at ({}, {})", u, v);
         }
-        None
+        // repeats: 1.25 and -0.75 land where 0.25 does
+        assert_eq!(sample_nearest(&img, 1.25, 0.25), red);
+        assert_eq!(sample_nearest(&img, -0.25, 0.25), green);
+        assert_eq!(sample_nearest(&img, 2.0, 0.25), green);
+        // a 1x1 image is that color everywhere
+        let dot = ImageBuf { w: 1, h: 1, data: vec![9, 8, 7, 6] };
+        for (u, v) in [(0.0, 0.0), (0.5, 0.99), (1.0, 1.0), (-3.2, 7.5)] {
+            assert_eq!(sample_nearest(&dot, u, v), Color::new(9, 8, 7, 6));
+            assert_eq!(sample_bilinear(&dot, u, v), Color::new(9, 8, 7, 6));
+        }
+        // empty or truncated data doesn't panic
+        for bad in [ImageBuf { w: 0, h: 0, data: vec![] }, ImageBuf { w: 2, h: 2, data: vec![1; 8] }] {
+            assert_eq!(sample_nearest(&bad, 0.5, 0.5), Color::BLANK);
+            assert_eq!(sample_bilinear(&bad, 0.5, 0.5), Color::BLANK);
+        }
+    }
+
+    #[test]
+    fn pixel_perfect_forces_nearest_everywhere() {
+        let mut atlas = TextureAtlas::default();
+        atlas.insert(TextureId::Wall, quad());
+        assert_eq!(atlas.sample(&TextureId::Wall, 0.5, 0.0), Some(Color::new(127, 127, 0, 255)));
+        atlas.set_pixel_perfect(true);
+        assert_eq!(atlas.sample(&TextureId::Wall, 0.5, 0.0), Some(Color::new(0, 255, 0, 255)));
+    }
+
+    // 4x1 image whose pixel x has red = 10 * (x + 1)
+    fn strip() -> ImageBuf {
+        let data = (0..4u8).flat_map(|x| [10 * (x + 1), 0, 0, 255]).collect();
+        ImageBuf { w: 4, h: 1, data }
+    }
+
+    #[test]
+    fn sprite_sheet_frames_change_exactly_at_boundaries() {
+        // 4 frames at 4 fps: one frame per quarter second, looping every second
+        let sheet = SpriteSheet::new(strip(), 4, 4.0);
+        let frames: Vec<u32> = [0.0, 0.2499, 0.25, 0.5, 0.9999, 1.0, 1.25, -0.5].iter().map(|&t| sheet.frame_at(t)).collect();
+        assert_eq!(frames, vec![0, 0, 1, 2, 3, 0, 1, 0]);
+        assert_eq!(sheet.sample(0.5, 0.5, 0.75).r, 40);
+        // the default rate keeps the sheet in step with the coin's spin
+        let coin = SpriteSheet::new(ImageBuf { w: 12, h: 1, data: vec![0; 48] }, 12, anim::default_fps(anim::COIN_FRAMES));
+        let mut spin = anim::coin_spin();
+        spin.advance(anim::COIN_SPIN_SECS * 0.5 + 0.01);
+        assert_eq!(coin.frame_at(spin.t) as usize, spin.frame());
+    }
+
+    #[test]
+    fn sprite_sheet_ignores_leftover_columns() {
+        // 5 px wide, 2 frames: frames are 2 px, column 4 is never sampled
+        let data = (0..5u8).flat_map(|x| [10 * (x + 1), 0, 0, 255]).collect();
+        let sheet = SpriteSheet::new(ImageBuf { w: 5, h: 1, data }, 2, 1.0);
+        assert_eq!(sheet.frame_width(), 2);
+        assert_eq!(sheet.sample_frame(1, 0.99, 0.5, FilterMode::Nearest).r, 40);
+        // filtering stays within the frame instead of blending toward column 4 (r = 50)
+        assert!(sheet.sample_frame(1, 0.99, 0.5, FilterMode::Bilinear).r <= 40);
+        // square frames: a 5x2 strip holds two 2x2 frames; more frames than columns clamp
+        assert_eq!(SpriteSheet::with_square_frames(ImageBuf { w: 5, h: 2, data: vec![0; 40] }, 1.0).frames, 2);
+        assert_eq!(SpriteSheet::new(strip(), 9, 1.0).frames, 4);
+    }
+
+    #[test]
+    fn manifest_declares_animation_rate_and_square_frames() {
+        let (entries, errors) = parse_manifest("[torch]
+path = \"torch.png\"
+square_frames = true
+fps = 8
+[coin]
+fps = -1
+", ".");
+        assert_eq!(errors, vec!["coin: invalid fps '-1'".to_string()]);
+        let torch = entries.iter().find(|e| e.name == "torch").unwrap();
+        assert_eq!((torch.square_frames, torch.fps), (true, Some(8.0)));
+        let mut atlas = TextureAtlas::from_images(&entries, HashMap::from([(TextureId::Extra("torch".to_string()), ImageBuf { w: 6, h: 2, data: vec![0; 48] })]));
+        let torch = TextureId::Extra("torch".to_string());
+        assert_eq!(atlas.sheet(&torch).map(|s| (s.frames, s.fps)), Some((3, 8.0)));
+        // stills aren't sheets
+        atlas.insert(TextureId::Sky, strip());
+        assert!(atlas.sheet(&TextureId::Sky).is_none());
+    }
+
+    #[test]
+    fn sample_and_sample_frame_address_any_id() {
+        let mut atlas = TextureAtlas::default();
+        let torch = TextureId::Extra("torch".to_string());
+        atlas.options.insert(torch.clone(), TextureOptions { frames: 2, square_frames: false, fps: Some(1.0), filter: FilterMode::Nearest, adjust: Adjustment::default() });
+        atlas.insert(torch.clone(), strip());
+        assert_eq!(atlas.sample(&torch, 0.8, 0.5).map(|c| c.r), Some(40));
+        // a 2-frame sheet: u spans just the chosen frame, frame numbers wrap
+        assert_eq!(atlas.sample_frame(&torch, 0.0, 0.5, 1).map(|c| c.r), Some(30));
+        assert_eq!(atlas.sample_frame(&torch, 0.9, 0.5, 2).map(|c| c.r), Some(20));
+        assert_eq!(atlas.sample_anim(&torch, 0.0, 0.5, 1.5).map(|c| c.r), Some(30));
+        assert_eq!(atlas.sample(&TextureId::Sky, 0.5, 0.5), None);
+        assert_eq!(TextureId::from_name("door_open"), TextureId::DoorOpen);
+        assert_eq!(TextureId::from_name("torch"), torch);
+    }
+
+    #[test]
+    fn fullscreen_images_fall_back_to_their_own_gradient() {
+        let mut atlas = TextureAtlas::default();
+        let victory = atlas.sample_fullscreen(&TextureId::Victoria, 0.5, 0.1);
+        let game_over = atlas.sample_fullscreen(&TextureId::GameOver, 0.5, 0.1);
+        assert_ne!(victory, game_over);
+        // warm at the top, darker towards the bottom
+        assert!(victory.r > victory.b);
+        let bottom = atlas.sample_fullscreen(&TextureId::Victoria, 0.5, 0.95);
+        assert!(bottom.r < victory.r);
+        // a decoded image replaces the gradient
+        atlas.insert(TextureId::Victoria, ImageBuf { w: 1, h: 1, data: vec![1, 2, 3, 255] });
+        assert_eq!(atlas.sample_fullscreen(&TextureId::Victoria, 0.5, 0.1), Color::new(1, 2, 3, 255));
     }
 }
@@ -0,0 +1,280 @@
+// timestep.rs - fixed-rate simulation ticks decoupled from the render frame rate
+//
+// Each frame adds its duration to an accumulator and the game logic runs once per whole
+// TICK_SECS in it (0, 1 or several times per frame). What's left over is the fraction of a
+// tick the renderer is ahead of the simulation, used to interpolate positions.
+//
+// FramePacer caps the render rate (the `fps_limit` setting) by waiting at the end of each frame.
+// SlowFrameGuard switches optional work (the rear mirror) off while frames miss that target.
+
+use std::time::{Duration, Instant};
+
+pub const TICK_HZ: f32 = 60.0;
+pub const TICK_SECS: f32 = 1.0 / TICK_HZ;
+// after a long stall (window drag, breakpoint) don't try to catch up more than this
+const MAX_TICKS_PER_FRAME: u32 = 5;
+
+pub struct FixedTimestep {
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    pub fn new() -> Self {
+        Self { accumulator: 0.0 }
+    }
+
+    // Add a frame of `dt` seconds; returns how many simulation ticks to run now.
+    pub fn advance(&mut self, dt: f32) -> u32 {
+        self.accumulator += dt.max(0.0);
+        let ticks = (self.accumulator / TICK_SECS) as u32;
+        self.accumulator -= ticks as f32 * TICK_SECS;
+        if ticks > MAX_TICKS_PER_FRAME {
+            self.accumulator = 0.0;
+            return MAX_TICKS_PER_FRAME;
+        }
+        ticks
+    }
+
+    // How far (0..1) the render time is past the last tick, for interpolating positions.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / TICK_SECS).clamp(0.0, 1.0)
+    }
+}
+
+// Frame limits offered in the options menu, in order; 0 = unlimited.
+pub const FPS_LIMITS: [u32; 6] = [30, 40, 60, 120, 144, 0];
+// "unlimited" still yields the CPU when a frame is trivial: never faster than this
+const UNCAPPED_MAX_FPS: u32 = 1000;
+// OS sleeps overshoot, so the end of a wait is spun; never for longer than this, nor more than
+// a quarter of the frame budget (otherwise uncapped frames would never sleep at all)
+const MAX_SPIN: Duration = Duration::from_micros(1500);
+
+// The limit `step` places after `current` in FPS_LIMITS (wrapping); unknown values start over.
+pub fn next_fps_limit(current: u32, step: i32) -> u32 {
+    let n = FPS_LIMITS.len() as i32;
+    let idx = FPS_LIMITS.iter().position(|&l| l == current).map_or(0, |i| (i as i32 + step).rem_euclid(n));
+    FPS_LIMITS[idx as usize]
+}
+
+// Cap to show next to the FPS counter: with vsync the monitor's refresh rate also limits it.
+pub fn effective_cap(limit: u32, vsync_hz: Option<u32>) -> Option<u32> {
+    match (limit, vsync_hz) {
+        (0, hz) => hz,
+        (l, Some(hz)) => Some(l.min(hz)),
+        (l, None) => Some(l),
+    }
+}
+
+// The limit the pacer actually uses. Without fixed_timestep the game updates once per frame, so
+// its speed follows the frame rate: that loop always runs at TICK_HZ whatever `fps_limit` says.
+pub fn pacing_limit(fps_limit: u32, fixed_timestep: bool) -> u32 {
+    if fixed_timestep { fps_limit } else { TICK_HZ as u32 }
+}
+
+fn frame_budget(limit: u32) -> Duration {
+    let fps = if limit == 0 { UNCAPPED_MAX_FPS } else { limit };
+    Duration::from_secs_f64(1.0 / fps as f64)
+}
+
+// How much of the `remaining` wait in a frame of `budget` to sleep; the rest is spun.
+fn sleep_part(remaining: Duration, budget: Duration) -> Duration {
+    remaining.saturating_sub(MAX_SPIN.min(budget / 4))
+}
+
+// When the frame after one that ended at `prev` should end. Deadlines advance by whole budgets
+// so the average rate is exact; after falling more than a frame behind (a stall, a slow frame)
+// the schedule restarts from `now` instead of rushing several frames to catch up.
+fn next_deadline(prev: Option<Instant>, now: Instant, budget: Duration) -> Instant {
+    match prev {
+        Some(p) if p + budget + budget > now => p + budget,
+        _ => now,
+    }
+}
+
+pub struct FramePacer {
+    limit: u32,
+    deadline: Option<Instant>,
+}
+
+impl FramePacer {
+    pub fn new(limit: u32) -> Self {
+        Self { limit, deadline: None }
+    }
+
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    // A new limit starts a fresh schedule, so the first frame at the new rate neither waits
+    // for the old deadline nor rushes; frame times (and movement) change in one step.
+    pub fn set_limit(&mut self, limit: u32) {
+        if limit != self.limit {
+            self.limit = limit;
+            self.deadline = None;
+        }
+    }
+
+    // Seconds per frame the game is aiming for: the limit, lowered by vsync to the refresh rate.
+    // Uncapped without vsync there is no target, so a simulation tick is the yardstick.
+    pub fn target_secs(&self, vsync_hz: Option<u32>) -> f32 {
+        effective_cap(self.limit, vsync_hz).map_or(TICK_SECS, |fps| 1.0 / fps.max(1) as f32)
+    }
+
+    // Wait out the rest of this frame's slot; call once per frame after presenting it.
+    pub fn wait(&mut self) {
+        let now = Instant::now();
+        let budget = frame_budget(self.limit);
+        let target = next_deadline(self.deadline, now, budget);
+        self.deadline = Some(target);
+        if target <= now {
+            return;
+        }
+        let sleep = sleep_part(target - now, budget);
+        if !sleep.is_zero() {
+            std::thread::sleep(sleep);
+        }
+        while Instant::now() < target {
+            std::hint::spin_loop();
+        }
+    }
+}
+
+// a frame is slow when it takes longer than this many times the budget; this many slow frames
+// in a row switch the work off, which then waits RECOVER_FRAMES on-time frames (doubled for
+// each earlier drop, up to 16x) before coming back
+const SLOW_FACTOR: f32 = 1.25;
+const SLOW_FRAMES: u32 = 10;
+const RECOVER_FRAMES: u32 = 120;
+
+// Hysteresis for work that can be skipped when frames run late: one spike doesn't turn it off,
+// and a machine that keeps struggling waits longer each time before trying again.
+pub struct SlowFrameGuard {
+    slow_frames: u32,
+    fast_frames: u32,
+    drops: u32,
+    skipping: bool,
+}
+
+impl SlowFrameGuard {
+    pub fn new() -> Self {
+        Self { slow_frames: 0, fast_frames: 0, drops: 0, skipping: false }
+    }
+
+    // Feed the last frame's duration and the target (FramePacer::target_secs); false while
+    // the work should be skipped.
+    pub fn allow(&mut self, frame_secs: f32, budget_secs: f32) -> bool {
+        if frame_secs > budget_secs * SLOW_FACTOR {
+            self.slow_frames += 1;
+            self.fast_frames = 0;
+        } else {
+            self.fast_frames += 1;
+            self.slow_frames = 0;
+        }
+        if !self.skipping && self.slow_frames >= SLOW_FRAMES {
+            self.skipping = true;
+        } else if self.skipping && self.fast_frames >= RECOVER_FRAMES << self.drops {
+            self.skipping = false;
+            self.drops = (self.drops + 1).min(4);
+        }
+        !self.skipping
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fps_limits_cycle_and_vsync_lowers_the_cap() {
+        assert_eq!(next_fps_limit(60, 1), 120);
+        assert_eq!(next_fps_limit(30, -1), 0);
+        assert_eq!(next_fps_limit(0, 1), 30);
+        assert_eq!(next_fps_limit(75, 1), 30);
+        assert_eq!(effective_cap(144, Some(60)), Some(60));
+        assert_eq!(effective_cap(40, Some(60)), Some(40));
+        assert_eq!(effective_cap(0, Some(60)), Some(60));
+        assert_eq!(effective_cap(0, None), None);
+        assert_eq!(pacing_limit(144, true), 144);
+        assert_eq!(pacing_limit(144, false), 60);
+        assert_eq!(pacing_limit(0, false), 60);
+    }
+
+    #[test]
+    fn pacer_keeps_its_schedule_and_restarts_after_a_stall() {
+        let budget = frame_budget(60);
+        let t0 = Instant::now();
+        let first = next_deadline(None, t0, budget);
+        assert_eq!(first, t0);
+        // a fast frame waits for the next slot
+        assert_eq!(next_deadline(Some(first), t0 + budget / 2, budget), t0 + budget);
+        // slightly late: stay on the grid so the average rate holds
+        assert_eq!(next_deadline(Some(first), t0 + budget + budget / 2, budget), t0 + budget);
+        // far behind: start over from now instead of bursting
+        let late = t0 + budget * 5;
+        assert_eq!(next_deadline(Some(first), late, budget), late);
+    }
+
+    #[test]
+    fn trivial_frames_sleep_even_when_uncapped() {
+        // unlimited: a frame that took no time sleeps most of its 1 ms slot instead of spinning it
+        let budget = frame_budget(0);
+        assert!(sleep_part(budget, budget) >= budget * 3 / 4);
+        // capped: only the last MAX_SPIN is spun
+        let budget = frame_budget(60);
+        assert_eq!(sleep_part(budget, budget), budget - MAX_SPIN);
+        // nothing left to wait: no sleep
+        assert!(sleep_part(Duration::from_micros(100), budget).is_zero());
+    }
+
+    #[test]
+    fn slow_frames_switch_off_until_frames_recover() {
+        let mut guard = SlowFrameGuard::new();
+        let budget = 1.0 / 60.0;
+        // a single spike doesn't drop it
+        assert!(guard.allow(0.1, budget));
+        assert!(guard.allow(budget, budget));
+        for _ in 0..SLOW_FRAMES - 1 {
+            assert!(guard.allow(0.05, budget));
+        }
+        assert!(!guard.allow(0.05, budget));
+        for _ in 0..RECOVER_FRAMES - 1 {
+            assert!(!guard.allow(budget, budget));
+        }
+        assert!(guard.allow(budget, budget));
+        // dropped again: twice the wait
+        for _ in 0..SLOW_FRAMES {
+            guard.allow(0.05, budget);
+        }
+        for _ in 0..RECOVER_FRAMES * 2 - 1 {
+            assert!(!guard.allow(budget, budget));
+        }
+        assert!(guard.allow(budget, budget));
+    }
+
+    #[test]
+    fn the_budget_follows_what_the_pacer_aims_for() {
+        assert_eq!(FramePacer::new(120).target_secs(None), 1.0 / 120.0);
+        assert_eq!(FramePacer::new(120).target_secs(Some(60)), 1.0 / 60.0);
+        assert_eq!(FramePacer::new(0).target_secs(Some(144)), 1.0 / 144.0);
+        assert_eq!(FramePacer::new(0).target_secs(None), TICK_SECS);
+    }
+
+    #[test]
+    fn ticks_follow_elapsed_time_not_frames() {
+        let mut step = FixedTimestep::new();
+        // 144 Hz rendering: one second still gives ~60 ticks
+        let ticks: u32 = (0..144).map(|_| step.advance(1.0 / 144.0)).sum();
+        assert!((59..=60).contains(&ticks));
+        // 30 Hz rendering: two ticks per frame
+        let mut step = FixedTimestep::new();
+        assert_eq!(step.advance(1.0 / 30.0 + 1e-4), 2);
+        assert!(step.alpha() < 0.1);
+    }
+
+    #[test]
+    fn long_stall_is_capped() {
+        let mut step = FixedTimestep::new();
+        assert_eq!(step.advance(3.0), MAX_TICKS_PER_FRAME);
+        assert_eq!(step.alpha(), 0.0);
+    }
+}
@@ -0,0 +1,135 @@
+// ui.rs - the colors the HUD and the minimap give meaning to
+//
+// Markers and HUD text pick their color by role from a Palette instead of naming a Color, so
+// the whole interface can switch to a colorblind-safe set (settings `hud.palette`, options
+// menu). The colorblind palette is built on blue, orange and yellow (Okabe-Ito), which stay
+// apart for red-green color blindness; the minimap also gives each marker its own shape, so
+// color is never the only cue.
+
+use raylib::prelude::Color;
+use crate::strings::Str;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Palette {
+    // NPCs and their spawn cells, the muted-audio cross
+    pub danger: Color,
+    // doors (the way out)
+    pub objective: Color,
+    // coins and the coin counter
+    pub pickup: Color,
+    // the player's arrow and view cone
+    pub player: Color,
+    // informational HUD text (level indicator)
+    pub neutral: Color,
+}
+
+impl Palette {
+    pub const DEFAULT: Palette = Palette {
+        danger: Color::RED,
+        objective: Color::new(80, 160, 80, 255),
+        pickup: Color::GOLD,
+        player: Color::SKYBLUE,
+        neutral: Color::CYAN,
+    };
+
+    pub const COLORBLIND: Palette = Palette {
+        danger: Color::new(213, 94, 0, 255),
+        objective: Color::new(0, 114, 178, 255),
+        pickup: Color::new(240, 228, 66, 255),
+        player: Color::new(86, 180, 233, 255),
+        neutral: Color::new(235, 235, 235, 255),
+    };
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::DEFAULT
+    }
+}
+
+// Which palette the interface uses (settings `hud.palette`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaletteMode {
+    Default,
+    Colorblind,
+}
+
+impl PaletteMode {
+    pub fn code(self) -> &'static str {
+        match self {
+            PaletteMode::Default => "default",
+            PaletteMode::Colorblind => "colorblind",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<PaletteMode> {
+        match code.trim().to_lowercase().as_str() {
+            "default" => Some(PaletteMode::Default),
+            "colorblind" => Some(PaletteMode::Colorblind),
+            _ => None,
+        }
+    }
+
+    pub fn next(self) -> PaletteMode {
+        match self {
+            PaletteMode::Default => PaletteMode::Colorblind,
+            PaletteMode::Colorblind => PaletteMode::Default,
+        }
+    }
+
+    // Name shown in the options menu.
+    pub fn label(self) -> Str {
+        match self {
+            PaletteMode::Default => Str::MenuPaletteDefault,
+            PaletteMode::Colorblind => Str::MenuPaletteColorblind,
+        }
+    }
+
+    pub fn palette(self) -> Palette {
+        match self {
+            PaletteMode::Default => Palette::DEFAULT,
+            PaletteMode::Colorblind => Palette::COLORBLIND,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // How a color looks with deuteranopia (Machado et al. 2009, full severity, applied to sRGB).
+    fn deuteranopia(c: Color) -> [f32; 3] {
+        const M: [[f32; 3]; 3] = [[0.367_322, 0.860_646, -0.227_968], [0.280_085, 0.672_501, 0.047_413], [-0.011_820, 0.042_940, 0.968_881]];
+        let rgb = [c.r as f32, c.g as f32, c.b as f32];
+        M.map(|row| (row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2]).clamp(0.0, 255.0))
+    }
+
+    fn seen_apart(a: Color, b: Color) -> f32 {
+        let (a, b) = (deuteranopia(a), deuteranopia(b));
+        ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn colorblind_roles_stay_apart_with_deuteranopia() {
+        let p = Palette::COLORBLIND;
+        let roles = [p.danger, p.objective, p.pickup, p.player];
+        for (i, &a) in roles.iter().enumerate() {
+            for &b in &roles[i + 1..] {
+                assert!(seen_apart(a, b) > 100.0, "{:?} vs {:?}: {}", a, b, seen_apart(a, b));
+            }
+        }
+        // what the default palette loses: red NPCs next to green doors
+        assert!(seen_apart(Palette::DEFAULT.danger, Palette::DEFAULT.objective) < 100.0);
+    }
+
+    #[test]
+    fn palette_modes_round_trip_and_cycle() {
+        for mode in [PaletteMode::Default, PaletteMode::Colorblind] {
+            assert_eq!(PaletteMode::from_code(mode.code()), Some(mode));
+            assert_eq!(mode.next().next(), mode);
+        }
+        assert_eq!(PaletteMode::from_code(" Colorblind "), Some(PaletteMode::Colorblind));
+        assert_eq!(PaletteMode::from_code("sepia"), None);
+        assert_eq!(PaletteMode::Default.palette(), Palette::default());
+    }
+}
@@ -0,0 +1,109 @@
+// victory.rs - the "all levels complete" screen
+//
+// VictoryScreen holds what animates on it: confetti raining over the stretched victory art,
+// the run's stats sliding up from the bottom (out-back, so they overshoot and settle) and the
+// prompt pulsing. The game loop advances it with the frame's dt and draws it every frame.
+
+use raylib::prelude::*;
+use crate::anim::{ease, MenuAnimation, Tween};
+use crate::framebuffer::Framebuffer;
+use crate::particles::Particles;
+use crate::rng::{Rng, STREAM_PARTICLES};
+use crate::strings::{tr, Str};
+use crate::textures::{TextureAtlas, TextureId};
+
+// confetti pieces falling at once, and how many start falling per second
+const CONFETTI_CAPACITY: usize = 500;
+const CONFETTI_PER_SEC: f32 = 180.0;
+// seconds the stats take to slide into place
+const STATS_SLIDE_SECS: f32 = 0.9;
+
+pub struct VictoryScreen {
+    stats: String,
+    confetti: Particles,
+    // confetti owed to the next frame (fractions of a piece)
+    confetti_due: f32,
+    // 1 = below the bottom of the screen, 0 = in place
+    stats_slide: Tween,
+    prompt: MenuAnimation,
+}
+
+impl VictoryScreen {
+    pub fn new(stats: String) -> Self {
+        Self {
+            stats,
+            confetti: Particles::new(CONFETTI_CAPACITY, Rng::stream(STREAM_PARTICLES)),
+            confetti_due: 0.0,
+            stats_slide: Tween::new(1.0, 0.0, STATS_SLIDE_SECS, ease::out_back),
+            prompt: MenuAnimation::new(),
+        }
+    }
+
+    // Advance everything by `dt` seconds on a `width` pixel wide framebuffer.
+    pub fn update(&mut self, dt: f32, width: u32) {
+        self.confetti_due += CONFETTI_PER_SEC * dt;
+        let count = self.confetti_due as usize;
+        self.confetti_due -= count as f32;
+        self.confetti.confetti(width, count);
+        self.confetti.update(dt);
+        self.stats_slide.advance(dt);
+        self.prompt.update(dt);
+    }
+
+    // Pixels the stats line sits below its place when it rests at `rest_y` on a `screen_h` tall screen.
+    pub fn stats_offset(&self, rest_y: i32, screen_h: i32) -> i32 {
+        (self.stats_slide.value() * (screen_h - rest_y) as f32).round() as i32
+    }
+
+    // The victory art stretched over the framebuffer with the confetti on top.
+    pub fn draw_background(&self, fb: &mut Framebuffer, textures: &mut TextureAtlas) {
+        textures.prepare(&TextureId::Victoria);
+        fb.fill_fullscreen(textures, &TextureId::Victoria);
+        self.confetti.draw_screen(fb);
+    }
+
+    // Title, sliding stats and the pulsing prompt, in screen pixels.
+    pub fn draw_text(&self, d: &mut RaylibDrawHandle, screen_w: i32, screen_h: i32) {
+        let centered = |d: &mut RaylibDrawHandle, text: &str, y: i32, size: i32, color: Color| {
+            let w = d.measure_text(text, size);
+            d.draw_text(text, screen_w / 2 - w / 2, y, size, color);
+        };
+        centered(d, tr(Str::AllLevelsCompleted), screen_h / 2 - 100, 40, Color::YELLOW);
+        let stats_y = screen_h / 2 - 25;
+        centered(d, &self.stats, stats_y + self.stats_offset(stats_y, screen_h), 24, Color::RAYWHITE);
+        let size = (20.0 * self.prompt.scale()).round() as i32;
+        centered(d, tr(Str::RestartOrQuit), screen_h / 2 + 50, size, Color::WHITE.fade(0.55 + 0.45 * self.prompt.pulse()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_slide_up_overshoot_and_settle() {
+        let mut screen = VictoryScreen::new(String::new());
+        assert_eq!(screen.stats_offset(200, 600), 400);
+        let mut overshot = false;
+        for _ in 0..60 {
+            screen.update(1.0 / 60.0, 320);
+            overshot |= screen.stats_offset(200, 600) < 0;
+        }
+        assert!(overshot);
+        assert_eq!(screen.stats_offset(200, 600), 0);
+    }
+
+    #[test]
+    fn confetti_falls_at_a_steady_rate_whatever_the_frame_rate() {
+        let mut fast = VictoryScreen::new(String::new());
+        let mut slow = VictoryScreen::new(String::new());
+        for _ in 0..30 {
+            fast.update(1.0 / 120.0, 320);
+        }
+        for _ in 0..15 {
+            slow.update(1.0 / 60.0, 320);
+        }
+        assert_eq!(fast.confetti.len(), 45);
+        assert_eq!(slow.confetti.len(), 45);
+    }
+}
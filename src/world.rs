@@ -0,0 +1,301 @@
+// world.rs - the simulated part of a level, advanced in fixed ticks
+//
+// Everything the game logic changes from tick to tick lives in World, and World::tick reads no
+// clock, window or audio: given the same maze and the same MoveInput per tick it produces the
+// same states whatever the render rate. What happened during a tick comes back as GameEvents
+// (sounds, banners) and a TickResult (level over), handled by the game loop.
+
+use raylib::prelude::*;
+use crate::doors::Doors;
+use crate::events::{EventQueue, GameEvent, RisingEdge};
+use crate::maze::{self, Maze};
+use crate::player::{can_move_to, step_noclip, step_player, MoveInput, Player};
+use crate::sprite::{self, Coin, CoinPop, NPC};
+use crate::particles::Particles;
+use crate::rng::{Rng, STREAM_PARTICLES};
+use crate::timestep::TICK_SECS;
+
+// particles alive at once in a level (oldest recycled first)
+const PARTICLE_CAPACITY: usize = 512;
+
+pub struct World {
+    pub player: Player,
+    pub npcs: Vec<NPC>,
+    pub coins: Vec<Coin>,
+    pub total_coins_collected: usize,
+    // pickup bursts still playing where coins were collected
+    pub coin_pops: Vec<CoinPop>,
+    // footstep dust and pickup sparks
+    pub particles: Particles,
+    // when the doors open and when the player has walked out through one
+    pub doors: Doors,
+    // doors unlock once per level, on the tick the last required coin is collected
+    pub door_unlock: RisingEdge,
+    // walk through walls (developer console)
+    pub noclip: bool,
+    // distance walked since the last footstep sound
+    stride_distance: f32,
+}
+
+// How a tick ended the level, if it did.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TickResult {
+    // the exit the player walked out through
+    pub escaped: Option<(usize, usize)>,
+    pub died: bool,
+}
+
+impl TickResult {
+    pub fn level_over(&self) -> bool {
+        self.escaped.is_some() || self.died
+    }
+}
+
+impl World {
+    // Player at `spawn`, NPCs and coins from the maze. `coin_goal` is LevelMeta::required_coins.
+    pub fn new(maze: &Maze, block_size: usize, spawn: Player, coin_goal: Option<usize>) -> Self {
+        let coins = sprite::load_coins_from_maze(maze, block_size);
+        let doors = Doors::new(maze, coin_goal, coins.len(), spawn.pos, block_size);
+        Self {
+            player: spawn,
+            npcs: sprite::load_npcs_from_maze(maze, block_size),
+            door_unlock: RisingEdge::new(doors.is_open(0)),
+            doors,
+            coins,
+            total_coins_collected: 0,
+            coin_pops: Vec::new(),
+            particles: Particles::new(PARTICLE_CAPACITY, Rng::stream(STREAM_PARTICLES)),
+            noclip: false,
+            stride_distance: 0.0,
+        }
+    }
+
+    // unlocked: enough coins collected (the doors may still be sliding open)
+    pub fn doors_open(&self) -> bool {
+        self.doors.is_open(self.total_coins_collected)
+    }
+
+    // open far enough to walk through
+    pub fn doors_passable(&self) -> bool {
+        self.doors.is_passable()
+    }
+
+    // Move the player to `pos` if they could stand there (anywhere with noclip).
+    pub fn teleport(&mut self, maze: &Maze, block_size: usize, pos: Vector2) -> bool {
+        if !self.noclip && !can_move_to(maze, pos.x, pos.y, block_size, self.doors_passable()) {
+            return false;
+        }
+        self.player.pos = pos;
+        self.player.vel = Vector2::zero();
+        true
+    }
+
+    // Collect up to `n` coins as if the player had picked them up; returns how many.
+    pub fn give_coins(&mut self, n: usize, events: &mut EventQueue) -> usize {
+        let mut given = 0;
+        for (idx, coin) in self.coins.iter_mut().enumerate().filter(|(_, c)| !c.collected).take(n) {
+            coin.collected = true;
+            events.push(GameEvent::CoinCollected(idx));
+            given += 1;
+        }
+        self.total_coins_collected += given;
+        given
+    }
+
+    // Spawn a fading-in NPC one cell in front of the player; returns where, or None if that's a wall.
+    pub fn spawn_npc(&mut self, maze: &Maze, block_size: usize) -> Option<Vector2> {
+        let pos = self.player.pos + Vector2::new(self.player.a.cos(), self.player.a.sin()) * block_size as f32;
+        if !can_move_to(maze, pos.x, pos.y, block_size, self.doors_passable()) {
+            return None;
+        }
+        self.npcs.push(NPC::spawning(pos.x, pos.y, sprite::NPC_SPEED));
+        Some(pos)
+    }
+
+    // Defeat every NPC still standing; they fade out and are removed by the next ticks.
+    pub fn defeat_npcs(&mut self) -> usize {
+        let standing: Vec<&mut NPC> = self.npcs.iter_mut().filter(|n| !n.is_defeated()).collect();
+        let count = standing.len();
+        for npc in standing {
+            npc.defeat();
+        }
+        count
+    }
+
+    pub fn set_npc_speed(&mut self, speed: f32) {
+        for npc in &mut self.npcs {
+            npc.speed = speed;
+        }
+    }
+
+    // One simulation tick of `dt` seconds (TICK_SECS, or the frame time with the coupled loop):
+    // the player moves on `input`, then NPCs and coins update.
+    pub fn tick(&mut self, maze: &Maze, block_size: usize, input: MoveInput, dt: f32, events: &mut EventQueue) -> TickResult {
+        let doors_passable = self.doors_passable();
+        if self.noclip {
+            step_noclip(&mut self.player, input);
+        } else {
+            step_player(&mut self.player, input, maze, block_size, doors_passable);
+        }
+        let player = self.player;
+        // one footstep every 0.8 cells walked
+        self.stride_distance += player.vel.x.hypot(player.vel.y);
+        let footstep = self.stride_distance >= 0.8 * block_size as f32;
+        if footstep || player.vel == Vector2::zero() {
+            self.stride_distance = 0.0;
+        }
+
+        // check if player has escaped: walked into an exit ('G') from outside while the doors are open
+        let escaped = self.doors.update(maze, player.pos, block_size, doors_passable);
+
+        // update NPCs and check for collision (player death)
+        let npc_update = sprite::update_npcs(&mut self.npcs, &player, maze, block_size, doors_passable, dt);
+
+        // update coins and check for collection
+        let collected_coins = sprite::update_coins(&mut self.coins, &player, block_size, dt);
+        self.total_coins_collected += collected_coins.len();
+
+        for pop in &mut self.coin_pops {
+            pop.advance(dt);
+        }
+        self.coin_pops.retain(|p| !p.is_finished());
+        self.particles.update(dt);
+        for idx in collected_coins {
+            self.coin_pops.push(CoinPop::new(&self.coins[idx]));
+            self.particles.sparks(self.coins[idx].pos);
+            events.push(GameEvent::CoinCollected(idx));
+        }
+        // the door sound plays as the opening slide starts
+        self.doors.animate(self.doors_open(), dt);
+        if self.door_unlock.update(self.doors_open()) {
+            // heard from the nearest exit (or centered when the level has none)
+            let cell_center = |(col, row): (usize, usize)| Vector2::new((col as f32 + 0.5) * block_size as f32, (row as f32 + 0.5) * block_size as f32);
+            let pos = maze::exits(maze)
+                .into_iter()
+                .map(cell_center)
+                .min_by(|a, b| {
+                    let dist = |p: &Vector2| (p.x - player.pos.x).hypot(p.y - player.pos.y);
+                    dist(a).total_cmp(&dist(b))
+                })
+                .unwrap_or(player.pos);
+            events.push(GameEvent::DoorOpened { pos });
+        }
+        if footstep {
+            // just ahead of the player, where the floor is in view
+            let ahead = Vector2::new(player.a.cos(), player.a.sin()) * (0.5 * block_size as f32);
+            self.particles.dust(player.pos + ahead);
+            events.push(GameEvent::Footstep);
+        }
+        if let Some(exit) = escaped {
+            events.push(GameEvent::PlayerEscaped(exit));
+        }
+        for idx in npc_update.alerted {
+            events.push(GameEvent::NpcAlerted { pos: self.npcs[idx].pos });
+        }
+        for idx in npc_update.winding_up {
+            events.push(GameEvent::NpcWindup { pos: self.npcs[idx].pos });
+        }
+        for idx in npc_update.steps {
+            events.push(GameEvent::NpcStep { pos: self.npcs[idx].pos });
+        }
+        if npc_update.touched {
+            events.push(GameEvent::PlayerDied);
+        }
+        TickResult { escaped, died: npc_update.touched }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timestep::FixedTimestep;
+
+    const LEVEL: [&str; 7] = [
+        "+-------+",
+        "|  C    |",
+        "| +-+ C |",
+        "|   |   |",
+        "| C   N |",
+        "|      G|",
+        "+-------+",
+    ];
+
+    // scripted input: walk forward, strafe, stop, walk back, by tick index
+    fn input_at(tick: usize) -> MoveInput {
+        match tick % 90 {
+            0..=29 => MoveInput { forward: 1.0, strafe: 0.0 },
+            30..=49 => MoveInput { forward: 0.0, strafe: 1.0 },
+            50..=59 => MoveInput::default(),
+            _ => MoveInput { forward: -1.0, strafe: -1.0 },
+        }
+    }
+
+    // Render frames of `frame_secs` until `ticks` ticks ran; the state after every tick.
+    fn run(frame_secs: f32, ticks: usize) -> Vec<String> {
+        let maze: Maze = LEVEL.iter().map(|r| r.chars().collect()).collect();
+        let spawn = Player { pos: Vector2::new(150.0, 150.0), a: 0.6, fov: 1.0, vel: Vector2::zero() };
+        let mut world = World::new(&maze, 100, spawn, None);
+        let mut events = EventQueue::new();
+        let mut timestep = FixedTimestep::new();
+        let mut states = Vec::new();
+        while states.len() < ticks {
+            for _ in 0..timestep.advance(frame_secs) {
+                let result = world.tick(&maze, 100, input_at(states.len()), TICK_SECS, &mut events);
+                let npcs: Vec<_> = world.npcs.iter().map(|n| (n.pos, n.state)).collect();
+                let coins: Vec<bool> = world.coins.iter().map(|c| c.collected).collect();
+                let fired: Vec<GameEvent> = events.drain().collect();
+                states.push(format!("{:?} {:?} {:?} {:?} {:?}", world.player, npcs, coins, fired, result));
+                if states.len() == ticks {
+                    break;
+                }
+            }
+        }
+        states
+    }
+
+    #[test]
+    fn same_inputs_give_the_same_ticks_at_any_render_rate() {
+        let ticks = 240;
+        let reference = run(TICK_SECS, ticks);
+        assert_eq!(reference.len(), ticks);
+        for fps in [30.0, 45.0, 144.0, 240.0] {
+            let states = run(1.0 / fps, ticks);
+            for (i, (a, b)) in reference.iter().zip(&states).enumerate() {
+                assert_eq!(a, b, "tick {} differs at {} FPS", i, fps);
+            }
+        }
+    }
+
+    #[test]
+    fn a_tick_that_ends_the_level_says_so() {
+        let maze: Maze = ["+---+", "|  G|", "+---+"].iter().map(|r| r.chars().collect()).collect();
+        let spawn = Player { pos: Vector2::new(150.0, 150.0), a: 0.0, fov: 1.0, vel: Vector2::zero() };
+        let mut world = World::new(&maze, 100, spawn, None);
+        let mut events = EventQueue::new();
+        let walk = MoveInput { forward: 1.0, strafe: 0.0 };
+        let result = (0..120).map(|_| world.tick(&maze, 100, walk, TICK_SECS, &mut events)).find(TickResult::level_over);
+        assert_eq!(result, Some(TickResult { escaped: Some((3, 1)), died: false }));
+        let fired: Vec<GameEvent> = events.drain().collect();
+        assert!(fired.contains(&GameEvent::PlayerEscaped((3, 1))));
+        assert!(fired.iter().any(|e| matches!(e, GameEvent::DoorOpened { .. })));
+    }
+
+    #[test]
+    fn picked_up_coins_leave_a_short_pop() {
+        let maze: Maze = ["+---+", "|C  |", "+---+"].iter().map(|r| r.chars().collect()).collect();
+        let spawn = Player { pos: Vector2::new(250.0, 150.0), a: 0.0, fov: 1.0, vel: Vector2::zero() };
+        let mut world = World::new(&maze, 100, spawn, None);
+        let mut events = EventQueue::new();
+        world.tick(&maze, 100, MoveInput::default(), TICK_SECS, &mut events);
+        assert!(world.coin_pops.is_empty());
+        world.player.pos = Vector2::new(160.0, 150.0);
+        world.tick(&maze, 100, MoveInput::default(), TICK_SECS, &mut events);
+        assert_eq!(world.coin_pops.len(), 1);
+        assert_eq!(world.coin_pops[0].pos, world.coins[0].pos);
+        let ticks = (sprite::COIN_POP_SECS / TICK_SECS).ceil() as usize;
+        for _ in 0..ticks {
+            world.tick(&maze, 100, MoveInput::default(), TICK_SECS, &mut events);
+        }
+        assert!(world.coin_pops.is_empty());
+    }
+}